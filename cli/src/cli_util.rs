@@ -36,6 +36,7 @@ use indexmap::{IndexMap, IndexSet};
 use itertools::Itertools;
 use jj_lib::backend::{ChangeId, CommitId, MergedTreeId, TreeValue};
 use jj_lib::commit::Commit;
+use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::fileset::FilesetExpression;
 use jj_lib::git_backend::GitBackend;
 use jj_lib::gitignore::{GitIgnoreError, GitIgnoreFile};
@@ -65,7 +66,8 @@ use jj_lib::str_util::StringPattern;
 use jj_lib::transaction::Transaction;
 use jj_lib::view::View;
 use jj_lib::working_copy::{
-    CheckoutStats, LockedWorkingCopy, SnapshotOptions, WorkingCopy, WorkingCopyFactory,
+    CheckoutOptions, CheckoutStats, LockedWorkingCopy, SnapshotOptions, WorkingCopy,
+    WorkingCopyFactory,
 };
 use jj_lib::workspace::{
     default_working_copy_factories, LockedWorkspace, WorkingCopyFactories, Workspace,
@@ -78,7 +80,7 @@ use tracing_chrome::ChromeLayerBuilder;
 use tracing_subscriber::prelude::*;
 
 use crate::command_error::{
-    cli_error, config_error_with_message, handle_command_result, internal_error,
+    cli_error, config_error, config_error_with_message, handle_command_result, internal_error,
     internal_error_with_message, user_error, user_error_with_hint, user_error_with_message,
     CommandError,
 };
@@ -98,6 +100,7 @@ use crate::revset_util::RevsetExpressionEvaluator;
 use crate::template_builder::TemplateLanguage;
 use crate::template_parser::TemplateAliasesMap;
 use crate::templater::{PropertyPlaceholder, TemplateRenderer};
+use crate::time_util::datetime_from_timestamp;
 use crate::ui::{ColorChoice, Ui};
 use crate::{revset_util, template_builder, text_util};
 
@@ -489,6 +492,7 @@ pub struct WorkspaceCommandHelper {
     may_update_working_copy: bool,
     working_copy_shared_with_git: bool,
     path_converter: RepoPathUiConverter,
+    conflict_marker_style: ConflictMarkerStyle,
 }
 
 impl WorkspaceCommandHelper {
@@ -511,6 +515,7 @@ impl WorkspaceCommandHelper {
             cwd: command.cwd.clone(),
             base: workspace.workspace_root().clone(),
         };
+        let conflict_marker_style = settings.conflict_marker_style()?;
         let helper = Self {
             string_args: command.string_args.clone(),
             global_args: command.global_args.clone(),
@@ -525,6 +530,7 @@ impl WorkspaceCommandHelper {
             may_update_working_copy,
             working_copy_shared_with_git,
             path_converter,
+            conflict_marker_style,
         };
         // Parse commit_summary template (and short-prefixes revset) early to
         // report error before starting mutable operation.
@@ -803,9 +809,27 @@ impl WorkspaceCommandHelper {
         Ok(git_ignores)
     }
 
+    /// The configured style of conflict markers in materialized files.
+    pub fn conflict_marker_style(&self) -> ConflictMarkerStyle {
+        self.conflict_marker_style
+    }
+
+    /// Options for checking out a tree in the working copy.
+    pub fn checkout_options(&self) -> CheckoutOptions {
+        CheckoutOptions {
+            conflict_marker_style: self.conflict_marker_style,
+            submodule_store: Some(self.repo().submodule_store().clone()),
+        }
+    }
+
     /// Creates textual diff renderer of the specified `formats`.
     pub fn diff_renderer(&self, formats: Vec<DiffFormat>) -> DiffRenderer<'_> {
-        DiffRenderer::new(self.repo().as_ref(), &self.path_converter, formats)
+        DiffRenderer::new(
+            self.repo().as_ref(),
+            &self.path_converter,
+            self.conflict_marker_style,
+            formats,
+        )
     }
 
     /// Loads textual diff renderer from the settings and command arguments.
@@ -991,9 +1015,20 @@ impl WorkspaceCommandHelper {
             path_converter: &self.path_converter,
             workspace_id: self.workspace_id(),
         };
+        // Relative dates are resolved against the configured commit timestamp
+        // if any, so that the results are reproducible.
+        let date_pattern_context = match self
+            .settings
+            .commit_timestamp()
+            .and_then(|timestamp| datetime_from_timestamp(&timestamp).ok())
+        {
+            Some(now) => now.into(),
+            None => chrono::Local::now().into(),
+        };
         RevsetParseContext::new(
             &self.revset_aliases_map,
             self.settings.user_email(),
+            date_pattern_context,
             &self.revset_extensions,
             Some(workspace_context),
         )
@@ -1230,6 +1265,8 @@ See https://github.com/martinvonz/jj/blob/main/docs/working-copy.md#stale-workin
             fsmonitor_settings: self.settings.fsmonitor_settings()?,
             progress: progress.as_ref().map(|x| x as _),
             max_new_file_size: self.settings.max_new_file_size()?,
+            conflict_marker_style: self.conflict_marker_style,
+            submodule_store: Some(self.user_repo.repo.submodule_store().clone()),
         })?;
         drop(progress);
         if new_tree_id != *wc_commit.tree_id() {
@@ -1270,11 +1307,13 @@ See https://github.com/martinvonz/jj/blob/main/docs/working-copy.md#stale-workin
         new_commit: &Commit,
     ) -> Result<(), CommandError> {
         assert!(self.may_update_working_copy);
+        let checkout_options = self.checkout_options();
         let stats = update_working_copy(
             &self.user_repo.repo,
             &mut self.workspace,
             maybe_old_commit,
             new_commit,
+            &checkout_options,
         )?;
         if Some(new_commit) != maybe_old_commit {
             if let Some(mut formatter) = ui.status_formatter() {
@@ -1662,6 +1701,17 @@ impl WorkspaceCommandTransaction<'_> {
         template.format(commit, formatter)
     }
 
+    /// Creates textual diff renderer of the specified `formats` which can look
+    /// up commits in this transaction.
+    pub fn diff_renderer(&self, formats: Vec<DiffFormat>) -> DiffRenderer<'_> {
+        DiffRenderer::new(
+            self.tx.repo(),
+            &self.helper.path_converter,
+            self.helper.conflict_marker_style,
+            formats,
+        )
+    }
+
     pub fn finish(self, ui: &mut Ui, description: impl Into<String>) -> Result<(), CommandError> {
         self.helper.finish_transaction(ui, self.tx, description)
     }
@@ -1732,6 +1782,7 @@ jj git init --colocate",
         WorkspaceLoadError::StoreLoadError(StoreLoadError::Signing(
             err @ SignInitError::UnknownBackend(_),
         )) => user_error(err),
+        WorkspaceLoadError::StoreLoadError(StoreLoadError::MergeDrivers(err)) => config_error(err),
         WorkspaceLoadError::StoreLoadError(err) => internal_error(err),
         WorkspaceLoadError::WorkingCopyState(err) => internal_error(err),
         WorkspaceLoadError::NonUnicodePath | WorkspaceLoadError::Path(_) => user_error(err),
@@ -2018,13 +2069,19 @@ pub fn update_working_copy(
     workspace: &mut Workspace,
     old_commit: Option<&Commit>,
     new_commit: &Commit,
+    options: &CheckoutOptions,
 ) -> Result<Option<CheckoutStats>, CommandError> {
     let old_tree_id = old_commit.map(|commit| commit.tree_id().clone());
     let stats = if Some(new_commit.tree_id()) != old_tree_id.as_ref() {
         // TODO: CheckoutError::ConcurrentCheckout should probably just result in a
         // warning for most commands (but be an error for the checkout command)
         let stats = workspace
-            .check_out(repo.op_id().clone(), old_tree_id.as_ref(), new_commit)
+            .check_out(
+                repo.op_id().clone(),
+                old_tree_id.as_ref(),
+                new_commit,
+                options,
+            )
             .map_err(|err| {
                 internal_error_with_message(
                     format!("Failed to check out commit {}", new_commit.id().hex()),
@@ -2127,7 +2184,12 @@ pub fn get_new_config_file_path(
         ConfigSource::User => {
             new_config_path()?.ok_or_else(|| user_error("No repo config path found to edit"))?
         }
-        ConfigSource::Repo => command.workspace_loader()?.repo_path().join("config.toml"),
+        ConfigSource::Repo => command
+            .layered_configs
+            .repo_config_path(command.workspace_loader()?.repo_path()),
+        ConfigSource::Workspace => command
+            .layered_configs
+            .workspace_config_path(command.workspace_loader()?.workspace_root()),
         _ => {
             return Err(user_error(format!(
                 "Can't get path for config source {config_source:?}"
@@ -2828,14 +2890,17 @@ impl CliRunner {
             .map_err(|err| map_workspace_load_error(err, None));
         layered_configs.read_user_config()?;
         let mut repo_config_path = None;
+        let mut workspace_config_path = None;
         if let Ok(loader) = &maybe_cwd_workspace_loader {
-            layered_configs.read_repo_config(loader.repo_path())?;
+            layered_configs.read_workspace_configs(loader.workspace_root(), loader.repo_path())?;
             repo_config_path = Some(layered_configs.repo_config_path(loader.repo_path()));
+            workspace_config_path =
+                Some(layered_configs.workspace_config_path(loader.workspace_root()));
         }
         let config = layered_configs.merge();
         ui.reset(&config).map_err(|e| {
             let user_config_path = layered_configs.user_config_path().unwrap_or(None);
-            let paths = [repo_config_path, user_config_path]
+            let paths = [workspace_config_path, repo_config_path, user_config_path]
                 .into_iter()
                 .flatten()
                 .map(|path| format!("- {}", path.display()))
@@ -2860,7 +2925,7 @@ impl CliRunner {
             // Invalid -R path is an error. No need to proceed.
             let loader = WorkspaceLoader::init(&cwd.join(path))
                 .map_err(|err| map_workspace_load_error(err, Some(path)))?;
-            layered_configs.read_repo_config(loader.repo_path())?;
+            layered_configs.read_workspace_configs(loader.workspace_root(), loader.repo_path())?;
             Ok(loader)
         } else {
             maybe_cwd_workspace_loader
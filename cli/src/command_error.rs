@@ -32,6 +32,7 @@ use jj_lib::revset::{
 };
 use jj_lib::signing::SignInitError;
 use jj_lib::str_util::StringPatternParseError;
+use jj_lib::submodule_store::SubmoduleStoreError;
 use jj_lib::working_copy::{ResetError, SnapshotError, WorkingCopyStateError};
 use jj_lib::workspace::WorkspaceInitError;
 use thiserror::Error;
@@ -274,6 +275,7 @@ impl From<WorkspaceInitError> for CommandError {
             }
             WorkspaceInitError::SignInit(err @ SignInitError::UnknownBackend(_)) => user_error(err),
             WorkspaceInitError::SignInit(err) => internal_error(err),
+            WorkspaceInitError::MergeDrivers(err) => config_error(err),
         }
     }
 }
@@ -523,6 +525,15 @@ impl From<GitConfigParseError> for CommandError {
     }
 }
 
+impl From<SubmoduleStoreError> for CommandError {
+    fn from(err: SubmoduleStoreError) -> Self {
+        match err {
+            SubmoduleStoreError::Other(_) => internal_error_with_message("Submodule error", err),
+            _ => user_error(err),
+        }
+    }
+}
+
 impl From<WorkingCopyStateError> for CommandError {
     fn from(err: WorkingCopyStateError) -> Self {
         internal_error_with_message("Failed to access working copy state", err)
@@ -630,9 +641,9 @@ fn revset_resolution_error_hint(err: &RevsetResolutionError) -> Option<String> {
 fn string_pattern_parse_error_hint(err: &StringPatternParseError) -> Option<String> {
     match err {
         StringPatternParseError::InvalidKind(_) => {
-            Some("Try prefixing with one of `exact:`, `glob:` or `substring:`".into())
+            Some("Try prefixing with one of `exact:`, `glob:`, `regex:`, or `substring:`".into())
         }
-        StringPatternParseError::GlobPattern(_) => None,
+        StringPatternParseError::GlobPattern(_) | StringPatternParseError::Regex(_) => None,
     }
 }
 
@@ -19,7 +19,7 @@ use tracing::instrument;
 use crate::cli_util::CommandHelper;
 use crate::command_error::{user_error, CommandError};
 use crate::description_util::{
-    description_template_for_commit, edit_description, join_message_paragraphs,
+    add_trailers, description_template_for_commit, edit_description, join_message_paragraphs,
 };
 use crate::ui::Ui;
 
@@ -101,6 +101,20 @@ new working-copy commit.
     } else {
         edit_description(tx.base_repo(), &template, command.settings())?
     };
+    // The transaction is only used to build the commit the trailers are
+    // generated for.
+    let mut trailers_tx = tx.base_repo().start_transaction(command.settings());
+    let commit_builder = trailers_tx
+        .mut_repo()
+        .rewrite_commit(command.settings(), &commit)
+        .set_tree_id(tree_id.clone())
+        .set_description(description);
+    let description = add_trailers(
+        command.settings(),
+        tx.base_workspace_helper(),
+        &commit_builder,
+        &[],
+    )?;
 
     let new_commit = tx
         .mut_repo()
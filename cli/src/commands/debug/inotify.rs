@@ -0,0 +1,94 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+#[cfg(all(feature = "inotify", target_os = "linux"))]
+use std::io::Write as _;
+
+use clap::Subcommand;
+#[cfg(all(feature = "inotify", target_os = "linux"))]
+use jj_lib::fsmonitor::inotify::Daemon;
+
+use crate::cli_util::CommandHelper;
+#[cfg(not(all(feature = "inotify", target_os = "linux")))]
+use crate::command_error::user_error;
+#[cfg(all(feature = "inotify", target_os = "linux"))]
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum DebugInotifyCommand {
+    /// Run the inotify filesystem monitor for the current workspace
+    ///
+    /// The daemon runs in the foreground until it's killed. Set
+    /// `core.fsmonitor="inotify"` to query it when snapshotting the working
+    /// copy.
+    Daemon,
+    /// Print the current clock of the inotify daemon for the workspace
+    QueryClock,
+    /// Print the files changed since the working copy was last snapshotted
+    ///
+    /// Prints `None` if the daemon doesn't know which files changed, in which
+    /// case the next snapshot scans the whole working copy.
+    QueryChangedFiles,
+}
+
+#[cfg(all(feature = "inotify", target_os = "linux"))]
+pub fn cmd_debug_inotify(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &DebugInotifyCommand,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+    let wc = super::check_local_disk_wc(workspace_command.working_copy().as_any())?;
+    match subcommand {
+        DebugInotifyCommand::Daemon => {
+            let daemon = Daemon::start(
+                workspace_command.workspace_root(),
+                &wc.inotify_socket_path(),
+            )
+            .map_err(|err| user_error_with_message("Failed to start the inotify daemon", err))?;
+            writeln!(
+                ui.status(),
+                "Watching {} for changes",
+                workspace_command.workspace_root().display()
+            )?;
+            daemon
+                .serve()
+                .map_err(|err| user_error_with_message("The inotify daemon failed", err))?;
+        }
+        DebugInotifyCommand::QueryClock => {
+            let (clock, _changed_files) = wc.query_inotify()?;
+            writeln!(ui.stdout(), "Clock: {clock}")?;
+        }
+        DebugInotifyCommand::QueryChangedFiles => {
+            let (_clock, changed_files) = wc.query_inotify()?;
+            writeln!(ui.stdout(), "Changed files: {changed_files:?}")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(all(feature = "inotify", target_os = "linux")))]
+pub fn cmd_debug_inotify(
+    _ui: &mut Ui,
+    _command: &CommandHelper,
+    _subcommand: &DebugInotifyCommand,
+) -> Result<(), CommandError> {
+    Err(user_error(
+        "Cannot run the inotify daemon because jj was not compiled with the `inotify` feature or \
+         isn't running on Linux",
+    ))
+}
@@ -14,6 +14,7 @@
 
 pub mod fileset;
 pub mod index;
+pub mod inotify;
 pub mod local_working_copy;
 pub mod operation;
 pub mod reindex;
@@ -32,6 +33,7 @@ use jj_lib::local_working_copy::LocalWorkingCopy;
 
 use self::fileset::{cmd_debug_fileset, DebugFilesetArgs};
 use self::index::{cmd_debug_index, DebugIndexArgs};
+use self::inotify::{cmd_debug_inotify, DebugInotifyCommand};
 use self::local_working_copy::{cmd_debug_local_working_copy, DebugLocalWorkingCopyArgs};
 use self::operation::{cmd_debug_operation, DebugOperationArgs};
 use self::reindex::{cmd_debug_reindex, DebugReindexArgs};
@@ -51,6 +53,8 @@ use crate::ui::Ui;
 pub enum DebugCommand {
     Fileset(DebugFilesetArgs),
     Index(DebugIndexArgs),
+    #[command(subcommand)]
+    Inotify(DebugInotifyCommand),
     LocalWorkingCopy(DebugLocalWorkingCopyArgs),
     #[command(visible_alias = "view")]
     Operation(DebugOperationArgs),
@@ -72,6 +76,7 @@ pub fn cmd_debug(
     match subcommand {
         DebugCommand::Fileset(args) => cmd_debug_fileset(ui, command, args),
         DebugCommand::Index(args) => cmd_debug_index(ui, command, args),
+        DebugCommand::Inotify(args) => cmd_debug_inotify(ui, command, args),
         DebugCommand::LocalWorkingCopy(args) => cmd_debug_local_working_copy(ui, command, args),
         DebugCommand::Operation(args) => cmd_debug_operation(ui, command, args),
         DebugCommand::Reindex(args) => cmd_debug_reindex(ui, command, args),
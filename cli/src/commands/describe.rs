@@ -12,15 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::io::{self, Read, Write};
 
+use itertools::Itertools;
+use jj_lib::commit::{Commit, CommitIteratorExt};
 use jj_lib::object_id::ObjectId;
+use jj_lib::trailer::Trailer;
 use tracing::instrument;
 
 use crate::cli_util::{CommandHelper, RevisionArg};
 use crate::command_error::CommandError;
 use crate::description_util::{
-    description_template_for_describe, edit_description, join_message_paragraphs,
+    add_trailers, description_template_for_describe, edit_description, edit_multiple_descriptions,
+    join_message_paragraphs,
 };
 use crate::ui::Ui;
 
@@ -28,16 +33,23 @@ use crate::ui::Ui;
 ///
 /// Starts an editor to let you edit the description of a change. The editor
 /// will be $EDITOR, or `pico` if that's not defined (`Notepad` on Windows).
+///
+/// If multiple revisions are given, the descriptions of all of them are
+/// edited in a single editor buffer. Each description is preceded by a
+/// `JJ: describe <change id> -------` line, which must be kept intact.
 #[derive(clap::Args, Clone, Debug)]
 #[command(visible_aliases = &["desc"])]
 pub(crate) struct DescribeArgs {
-    /// The revision whose description to edit
+    /// The revision(s) whose description to edit
     #[arg(default_value = "@")]
-    revision: RevisionArg,
+    revisions: Vec<RevisionArg>,
     /// Ignored (but lets you pass `-r` for consistency with other commands)
-    #[arg(short = 'r', hide = true)]
-    unused_revision: bool,
+    #[arg(short = 'r', hide = true, action = clap::ArgAction::Count)]
+    unused_revision: u8,
     /// The change description to use (don't open editor)
+    ///
+    /// If multiple revisions are given, the same description is used for all
+    /// of them.
     #[arg(long = "message", short, value_name = "MESSAGE")]
     message_paragraphs: Vec<String>,
     /// Read the change description from stdin
@@ -58,6 +70,17 @@ pub(crate) struct DescribeArgs {
     /// $ JJ_USER='Foo Bar' JJ_EMAIL=foo@bar.com jj describe --reset-author
     #[arg(long)]
     reset_author: bool,
+    /// Add a trailer to the description
+    ///
+    /// The trailer is given as `KEY: VALUE`, e.g.
+    /// `--add-trailer "Reviewed-by: Foo Bar <foo@bar.com>"`. It's appended to
+    /// the last paragraph of the description unless already present.
+    #[arg(
+        long,
+        value_name = "TRAILER",
+        value_parser = |s: &str| Trailer::parse(s).ok_or("expected KEY: VALUE"),
+    )]
+    add_trailer: Vec<Trailer>,
 }
 
 #[instrument(skip_all)]
@@ -67,35 +90,114 @@ pub(crate) fn cmd_describe(
     args: &DescribeArgs,
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
-    let commit = workspace_command.resolve_single_rev(&args.revision)?;
-    workspace_command.check_rewritable([commit.id()])?;
-    let description = if args.stdin {
+    let mut commits: Vec<Commit> = workspace_command
+        .parse_union_revsets(&args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    if commits.is_empty() {
+        writeln!(ui.status(), "No revisions to describe.")?;
+        return Ok(());
+    }
+    // Present the commits in topological order (parents first) in the editor.
+    commits.reverse();
+    workspace_command.check_rewritable(commits.iter().ids())?;
+
+    let shared_description = if args.stdin {
         let mut buffer = String::new();
         io::stdin().read_to_string(&mut buffer).unwrap();
-        buffer
+        Some(buffer)
     } else if !args.message_paragraphs.is_empty() {
-        join_message_paragraphs(&args.message_paragraphs)
-    } else if args.no_edit {
-        commit.description().to_owned()
+        Some(join_message_paragraphs(&args.message_paragraphs))
     } else {
-        let template =
-            description_template_for_describe(ui, command.settings(), &workspace_command, &commit)?;
-        edit_description(workspace_command.repo(), &template, command.settings())?
+        None
     };
-    if description == *commit.description() && !args.reset_author {
-        writeln!(ui.status(), "Nothing changed.")?;
+    let descriptions = if let Some(description) = shared_description {
+        vec![description; commits.len()]
+    } else if args.no_edit {
+        commits
+            .iter()
+            .map(|commit| commit.description().to_owned())
+            .collect()
+    } else if let [commit] = commits.as_slice() {
+        let template =
+            description_template_for_describe(ui, command.settings(), &workspace_command, commit)?;
+        vec![edit_description(
+            workspace_command.repo(),
+            &template,
+            command.settings(),
+        )?]
     } else {
-        let mut tx = workspace_command.start_transaction();
-        let mut commit_builder = tx
+        edit_multiple_descriptions(ui, command.settings(), &workspace_command, &commits)?
+    };
+
+    let mut changed_commits = vec![];
+    for (commit, description) in commits.iter().zip(descriptions) {
+        // The transaction is only used to build the commit the trailers are
+        // generated for.
+        let mut trailers_tx = workspace_command
+            .repo()
+            .start_transaction(command.settings());
+        let commit_builder = trailers_tx
             .mut_repo()
-            .rewrite_commit(command.settings(), &commit)
+            .rewrite_commit(command.settings(), commit)
             .set_description(description);
-        if args.reset_author {
-            let new_author = commit_builder.committer().clone();
-            commit_builder = commit_builder.set_author(new_author);
+        let description = add_trailers(
+            command.settings(),
+            &workspace_command,
+            &commit_builder,
+            &args.add_trailer,
+        )?;
+        if description != *commit.description() || args.reset_author {
+            changed_commits.push((commit, description));
         }
-        commit_builder.write()?;
-        tx.finish(ui, format!("describe commit {}", commit.id().hex()))?;
     }
+    let Some((first_commit, _)) = changed_commits.first() else {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    };
+    let tx_description = match changed_commits.len() {
+        1 => format!("describe commit {}", first_commit.id().hex()),
+        n => format!(
+            "describe commit {} and {} more",
+            first_commit.id().hex(),
+            n - 1
+        ),
+    };
+
+    let mut tx = workspace_command.start_transaction();
+    let roots = changed_commits
+        .iter()
+        .map(|(commit, _)| commit.id().clone())
+        .collect();
+    let mut new_descriptions: HashMap<_, _> = changed_commits
+        .into_iter()
+        .map(|(commit, description)| (commit.id().clone(), description))
+        .collect();
+    let mut num_described = 0;
+    let mut num_rebased = 0;
+    tx.mut_repo()
+        .transform_descendants(command.settings(), roots, |rewriter| {
+            let old_commit_id = rewriter.old_commit().id().clone();
+            let mut commit_builder = rewriter.rebase(command.settings())?;
+            if let Some(description) = new_descriptions.remove(&old_commit_id) {
+                commit_builder = commit_builder.set_description(description);
+                if args.reset_author {
+                    let new_author = commit_builder.committer().clone();
+                    commit_builder = commit_builder.set_author(new_author);
+                }
+                num_described += 1;
+            } else {
+                num_rebased += 1;
+            }
+            commit_builder.write()?;
+            Ok(())
+        })?;
+    if num_described > 1 {
+        writeln!(ui.status(), "Updated {num_described} commits")?;
+    }
+    if num_rebased > 0 {
+        writeln!(ui.status(), "Rebased {num_rebased} descendant commits")?;
+    }
+    tx.finish(ui, tx_description)?;
     Ok(())
 }
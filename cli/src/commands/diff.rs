@@ -12,11 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use jj_lib::copies::CopyRecords;
+use jj_lib::repo::Repo;
 use tracing::instrument;
 
 use crate::cli_util::{print_unmatched_explicit_paths, CommandHelper, RevisionArg};
 use crate::command_error::CommandError;
-use crate::diff_util::DiffFormatArgs;
+use crate::diff_util::{get_copy_records, DiffFormatArgs};
 use crate::ui::Ui;
 
 /// Compare file contents between two revisions
@@ -59,8 +61,10 @@ pub(crate) fn cmd_diff(
     args: &DiffArgs,
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
     let from_tree;
     let to_tree;
+    let copy_records;
     if args.from.is_some() || args.to.is_some() {
         let from =
             workspace_command.resolve_single_rev(args.from.as_ref().unwrap_or(&RevisionArg::AT))?;
@@ -68,11 +72,16 @@ pub(crate) fn cmd_diff(
         let to =
             workspace_command.resolve_single_rev(args.to.as_ref().unwrap_or(&RevisionArg::AT))?;
         to_tree = to.tree()?;
+        copy_records = get_copy_records(repo.store(), from.id(), to.id())?;
     } else {
         let commit = workspace_command
             .resolve_single_rev(args.revision.as_ref().unwrap_or(&RevisionArg::AT))?;
-        from_tree = commit.parent_tree(workspace_command.repo().as_ref())?;
-        to_tree = commit.tree()?
+        from_tree = commit.parent_tree(repo.as_ref())?;
+        to_tree = commit.tree()?;
+        copy_records = match commit.parent_ids() {
+            [parent_id] => get_copy_records(repo.store(), parent_id, commit.id())?,
+            _ => CopyRecords::default(),
+        };
     }
     let fileset_expression = workspace_command.parse_file_patterns(&args.paths)?;
     let matcher = fileset_expression.to_matcher();
@@ -84,6 +93,7 @@ pub(crate) fn cmd_diff(
         &from_tree,
         &to_tree,
         matcher.as_ref(),
+        &copy_records,
     )?;
     print_unmatched_explicit_paths(
         ui,
@@ -13,11 +13,14 @@
 // limitations under the License.
 
 use std::io::Write;
+use std::rc::Rc;
 
-use indexmap::IndexMap;
+use itertools::Itertools;
 use jj_lib::backend::CommitId;
-use jj_lib::commit::Commit;
-use jj_lib::repo::Repo;
+use jj_lib::commit::CommitIteratorExt;
+use jj_lib::repo::{ReadonlyRepo, Repo};
+use jj_lib::revset::RevsetExpression;
+use jj_lib::rewrite::{duplicate_commits, duplicate_commits_onto_parents};
 use tracing::instrument;
 
 use crate::cli_util::{short_commit_hash, CommandHelper, RevisionArg};
@@ -25,6 +28,11 @@ use crate::command_error::{user_error, CommandError};
 use crate::ui::Ui;
 
 /// Create a new change with the same content as an existing one
+///
+/// By default, the duplicated commits are created on top of the parents of the
+/// original commits. With `--destination`, `--insert-after` or
+/// `--insert-before`, they are created at the given location instead, with the
+/// topology among the duplicated commits preserved.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct DuplicateArgs {
     /// The revision(s) to duplicate
@@ -33,6 +41,28 @@ pub(crate) struct DuplicateArgs {
     /// Ignored (but lets you pass `-r` for consistency with other commands)
     #[arg(short = 'r', hide = true, action = clap::ArgAction::Count)]
     unused_revision: u8,
+    /// The revision(s) to duplicate onto (can be repeated to create a merge
+    /// commit)
+    #[arg(long, short)]
+    destination: Vec<RevisionArg>,
+    /// The revision(s) to insert after (can be repeated to create a merge
+    /// commit)
+    #[arg(
+        long,
+        short = 'A',
+        visible_alias = "after",
+        conflicts_with = "destination"
+    )]
+    insert_after: Vec<RevisionArg>,
+    /// The revision(s) to insert before (can be repeated to create a merge
+    /// commit)
+    #[arg(
+        long,
+        short = 'B',
+        visible_alias = "before",
+        conflicts_with = "destination"
+    )]
+    insert_before: Vec<RevisionArg>,
 }
 
 #[instrument(skip_all)]
@@ -53,37 +83,123 @@ pub(crate) fn cmd_duplicate(
     if to_duplicate.last() == Some(workspace_command.repo().store().root_commit_id()) {
         return Err(user_error("Cannot duplicate the root commit"));
     }
-    let mut duplicated_old_to_new: IndexMap<&CommitId, Commit> = IndexMap::new();
 
-    let mut tx = workspace_command.start_transaction();
-    let base_repo = tx.base_repo().clone();
-    let store = base_repo.store();
-    let mut_repo = tx.mut_repo();
-
-    for original_commit_id in to_duplicate.iter().rev() {
-        // Topological order ensures that any parents of `original_commit` are
-        // either not in `to_duplicate` or were already duplicated.
-        let original_commit = store.get_commit(original_commit_id)?;
-        let new_parents = original_commit
-            .parent_ids()
+    let location = if !args.destination.is_empty() {
+        let parent_commit_ids = workspace_command
+            .resolve_some_revsets_default_single(&args.destination)?
+            .iter()
+            .ids()
+            .cloned()
+            .collect_vec();
+        Some((parent_commit_ids, vec![]))
+    } else if !args.insert_after.is_empty() && !args.insert_before.is_empty() {
+        let parent_commit_ids = workspace_command
+            .resolve_some_revsets_default_single(&args.insert_after)?
+            .iter()
+            .ids()
+            .cloned()
+            .collect_vec();
+        let children_commit_ids = workspace_command
+            .resolve_some_revsets_default_single(&args.insert_before)?
             .iter()
-            .map(|id| duplicated_old_to_new.get(id).map_or(id, |c| c.id()).clone())
-            .collect();
-        let new_commit = mut_repo
-            .rewrite_commit(command.settings(), &original_commit)
-            .generate_new_change_id()
-            .set_parents(new_parents)
-            .write()?;
-        duplicated_old_to_new.insert(original_commit_id, new_commit);
+            .ids()
+            .cloned()
+            .collect_vec();
+        ensure_no_commit_loop(
+            workspace_command.repo(),
+            &RevsetExpression::commits(children_commit_ids.clone()),
+            &RevsetExpression::commits(parent_commit_ids.clone()),
+        )?;
+        Some((parent_commit_ids, children_commit_ids))
+    } else if !args.insert_after.is_empty() {
+        let parent_commit_ids = workspace_command
+            .resolve_some_revsets_default_single(&args.insert_after)?
+            .iter()
+            .ids()
+            .cloned()
+            .collect_vec();
+        let parents_expression = RevsetExpression::commits(parent_commit_ids.clone());
+        // Exclude children that are ancestors of the new parents.
+        let children_commit_ids = parents_expression
+            .children()
+            .minus(&parents_expression.ancestors())
+            .evaluate_programmatic(workspace_command.repo().as_ref())?
+            .iter()
+            .collect_vec();
+        Some((parent_commit_ids, children_commit_ids))
+    } else if !args.insert_before.is_empty() {
+        let children_commits = workspace_command
+            .resolve_some_revsets_default_single(&args.insert_before)?
+            .into_iter()
+            .collect_vec();
+        // Manually collect the parent commit IDs to preserve the order of parents.
+        let parent_commit_ids = children_commits
+            .iter()
+            .flat_map(|commit| commit.parent_ids())
+            .unique()
+            .cloned()
+            .collect_vec();
+        let children_commit_ids = children_commits.iter().ids().cloned().collect_vec();
+        Some((parent_commit_ids, children_commit_ids))
+    } else {
+        None
+    };
+    if let Some((_, children_commit_ids)) = &location {
+        workspace_command.check_rewritable(children_commit_ids)?;
     }
 
+    let mut tx = workspace_command.start_transaction();
+    let (duplicated_commits, num_rebased) =
+        if let Some((parent_commit_ids, children_commit_ids)) = &location {
+            let stats = duplicate_commits(
+                command.settings(),
+                tx.mut_repo(),
+                &to_duplicate,
+                parent_commit_ids,
+                children_commit_ids,
+            )?;
+            (stats.duplicated_commits, stats.num_rebased)
+        } else {
+            let duplicated_commits =
+                duplicate_commits_onto_parents(command.settings(), tx.mut_repo(), &to_duplicate)?;
+            (duplicated_commits, 0)
+        };
+
     if let Some(mut formatter) = ui.status_formatter() {
-        for (old_id, new_commit) in &duplicated_old_to_new {
+        for (old_id, new_commit) in &duplicated_commits {
             write!(formatter, "Duplicated {} as ", short_commit_hash(old_id))?;
             tx.write_commit_summary(formatter.as_mut(), new_commit)?;
             writeln!(formatter)?;
         }
+        if num_rebased > 0 {
+            writeln!(
+                formatter,
+                "Rebased {num_rebased} commits onto duplicated commits"
+            )?;
+        }
     }
     tx.finish(ui, format!("duplicate {} commit(s)", to_duplicate.len()))?;
     Ok(())
 }
+
+/// Ensure that there is no possible cycle between the potential children and
+/// parents of the duplicated commits.
+fn ensure_no_commit_loop(
+    repo: &ReadonlyRepo,
+    children_expression: &Rc<RevsetExpression>,
+    parents_expression: &Rc<RevsetExpression>,
+) -> Result<(), CommandError> {
+    if let Some(commit_id) = children_expression
+        .dag_range_to(parents_expression)
+        .evaluate_programmatic(repo)?
+        .iter()
+        .next()
+    {
+        return Err(user_error(format!(
+            "Refusing to create a loop: commit {} would be both an ancestor and a descendant of \
+             the duplicated commits",
+            short_commit_hash(&commit_id),
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,119 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::io::Write as _;
+
+use jj_lib::annotate::{get_annotation_for_file, AnnotateResults};
+use jj_lib::backend::TreeValue;
+use jj_lib::commit::Commit;
+use jj_lib::repo::Repo;
+use tracing::instrument;
+
+use crate::cli_util::{CommandHelper, RevisionArg};
+use crate::command_error::{user_error, CommandError};
+use crate::templater::TemplateRenderer;
+use crate::ui::Ui;
+
+/// Show the source change for each line of the target file.
+///
+/// Annotates a revision line by line. Each line includes the source change that
+/// introduced the associated line. A path to the desired file must be provided.
+/// The per-line prefix for each line can be customized via
+/// template with the `templates.annotate_commit_summary` config variable.
+///
+/// Conflicted files are annotated with their conflict markers materialized.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct FileAnnotateArgs {
+    /// The file to annotate
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    path: String,
+    /// The revision to annotate the file in
+    #[arg(long, short, default_value = "@")]
+    revision: RevisionArg,
+    /// Render the prefix of each line using the given template
+    ///
+    /// All 0-argument methods of the `Commit` type are available as keywords.
+    ///
+    /// For the syntax, see https://github.com/martinvonz/jj/blob/main/docs/templates.md
+    #[arg(long, short = 'T')]
+    template: Option<String>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_file_annotate(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &FileAnnotateArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let starting_commit = workspace_command.resolve_single_rev(&args.revision)?;
+    let file_path = workspace_command.parse_file_path(&args.path)?;
+    let file_value = starting_commit.tree()?.path_value(&file_path)?;
+    let ui_path = workspace_command.format_file_path(&file_path);
+    if file_value.is_absent() {
+        return Err(user_error(format!("No such path: {ui_path}")));
+    }
+    if file_value.is_tree()
+        || file_value
+            .iter()
+            .flatten()
+            .any(|value| !matches!(value, TreeValue::File { .. }))
+    {
+        return Err(user_error(format!(
+            "Path exists but is not a regular file: {ui_path}"
+        )));
+    }
+
+    let template_text = match &args.template {
+        Some(value) => value.to_owned(),
+        None => command
+            .settings()
+            .config()
+            .get_string("templates.annotate_commit_summary")?,
+    };
+    let template = workspace_command.parse_commit_template(&template_text)?;
+
+    let annotations = get_annotation_for_file(&starting_commit, &file_path)?;
+
+    ui.request_pager();
+    render_annotations(repo.as_ref(), ui, &template, &annotations)?;
+    Ok(())
+}
+
+fn render_annotations(
+    repo: &dyn Repo,
+    ui: &mut Ui,
+    template_render: &TemplateRenderer<Commit>,
+    results: &AnnotateResults,
+) -> Result<(), CommandError> {
+    let mut formatter = ui.stdout_formatter();
+    let mut commits: HashMap<_, Commit> = HashMap::new();
+    for (commit_id, line) in &results.file_annotations {
+        let commit = match commits.get(commit_id) {
+            Some(commit) => commit,
+            None => {
+                let commit = repo.store().get_commit(commit_id)?;
+                commits.entry(commit_id.clone()).or_insert(commit)
+            }
+        };
+        template_render.format(commit, formatter.as_mut())?;
+        formatter.write_all(line)?;
+        if !line.ends_with(b"\n") {
+            writeln!(formatter)?;
+        }
+    }
+    Ok(())
+}
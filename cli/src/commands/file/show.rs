@@ -118,7 +118,13 @@ fn write_tree_entries<P: AsRef<RepoPath>>(
     let repo = workspace_command.repo();
     for (path, result) in entries {
         let value = result?;
-        let materialized = materialize_tree_value(repo.store(), path.as_ref(), value).block_on()?;
+        let materialized = materialize_tree_value(
+            repo.store(),
+            path.as_ref(),
+            value,
+            workspace_command.conflict_marker_style(),
+        )
+        .block_on()?;
         match materialized {
             MaterializedTreeValue::Absent => panic!("absent values should be excluded"),
             MaterializedTreeValue::AccessDenied(err) => {
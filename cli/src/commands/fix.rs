@@ -13,25 +13,33 @@
 // limitations under the License.
 
 use std::collections::{HashMap, HashSet};
+use std::io;
 use std::io::Write;
-use std::process::Stdio;
+use std::path::Path;
+use std::process::{ExitStatus, Stdio};
 use std::sync::mpsc::channel;
 
 use futures::StreamExt;
 use itertools::Itertools;
 use jj_lib::backend::{BackendError, BackendResult, CommitId, FileId, TreeValue};
+use jj_lib::fileset::{self, FilesetExpression};
+use jj_lib::matchers::{EverythingMatcher, Matcher};
 use jj_lib::merged_tree::MergedTreeBuilder;
 use jj_lib::repo::Repo;
-use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::repo_path::{RepoPath, RepoPathBuf, RepoPathUiConverter};
 use jj_lib::revset::{RevsetExpression, RevsetIteratorExt};
+use jj_lib::settings::ConfigResultExt as _;
 use jj_lib::store::Store;
 use pollster::FutureExt;
 use rayon::iter::IntoParallelIterator;
 use rayon::prelude::ParallelIterator;
+use thiserror::Error;
 use tracing::instrument;
 
 use crate::cli_util::{CommandHelper, RevisionArg};
-use crate::command_error::{config_error_with_message, CommandError};
+use crate::command_error::{
+    config_error, config_error_with_message, user_error_with_hint, CommandError,
+};
 use crate::config::CommandNameAndArgs;
 use crate::ui::Ui;
 
@@ -48,20 +56,43 @@ use crate::ui::Ui;
 /// Files with existing conflicts will be updated on all sides of the conflict,
 /// which can potentially increase or decrease the number of conflict markers.
 ///
-/// The external tool must accept the current file content on standard input,
+/// The external tools must accept the current file content on standard input,
 /// and return the updated file content on standard output. The output will not
-/// be used unless the tool exits with a successful exit code. Output on
-/// standard error will be passed through to the terminal.
+/// be used unless the tool exits with a successful exit code, and a warning is
+/// printed for each file the tool failed on. Output on standard error will be
+/// passed through to the terminal.
 ///
-/// The configuration schema is expected to change in the future. For now, it
-/// defines a single command that will affect all changed files in the specified
-/// revisions. For example, to format some Rust code changed in the working copy
-/// revision, you could write this configuration:
+/// Tools are defined in a table where the keys are arbitrary identifiers and
+/// the values have the following properties:
+///  - `command`: The arguments used to run the tool. The first argument is the
+///    path to an executable file. Arguments can contain the substring `$path`,
+///    which will be replaced with the repo-relative path of the file being
+///    fixed.
+///  - `patterns`: Determines which files the tool will affect. If this list is
+///    empty, no files will be affected by the tool. If there are multiple
+///    patterns, the tool is applied only once to each file in the union of the
+///    patterns. Patterns are filesets relative to the workspace root.
 ///
-/// [fix]
-/// tool-command = ["rustfmt", "--emit", "stdout"]
+/// For example, the following configuration defines how two code formatters
+/// (`clang-format` and `black`) will apply to three different file extensions
+/// (.cc, .h, and .py):
 ///
-/// And then run the command `jj fix -s @`.
+/// [fix.tools.clang-format]
+/// command = ["/usr/bin/clang-format", "--assume-filename=$path"]
+/// patterns = ["glob:'**/*.cc'",
+///             "glob:'**/*.h'"]
+///
+/// [fix.tools.black]
+/// command = ["/usr/bin/black", "-", "--stdin-filename=$path"]
+/// patterns = ["glob:'**/*.py'"]
+///
+/// Execution order of tools that affect the same file is deterministic: tools
+/// are applied in the lexicographic order of their names, and each tool
+/// receives the output of the previous one. `--tool` can be used to run only
+/// some of the configured tools.
+///
+/// The legacy `fix.tool-command` setting defines a tool that affects all
+/// changed files. It runs before any of the tools in `fix.tools`.
 #[derive(clap::Args, Clone, Debug)]
 #[command(verbatim_doc_comment)]
 pub(crate) struct FixArgs {
@@ -73,6 +104,9 @@ pub(crate) struct FixArgs {
     /// Fix only these paths
     #[arg(value_hint = clap::ValueHint::AnyPath)]
     paths: Vec<String>,
+    /// Run only the tools with these names, as configured in `fix.tools`
+    #[arg(long, value_name = "NAME")]
+    tool: Vec<String>,
 }
 
 #[instrument(skip_all)]
@@ -95,6 +129,7 @@ pub(crate) fn cmd_fix(
     let matcher = workspace_command
         .parse_file_patterns(&args.paths)?
         .to_matcher();
+    let tools_config = get_tools_config(command, workspace_command.workspace_root(), &args.tool)?;
 
     let mut tx = workspace_command.start_transaction();
 
@@ -142,13 +177,14 @@ pub(crate) fn cmd_fix(
                 // Deleted files have no file content to fix, and they have no terms in `after`,
                 // so we don't add any tool inputs for them. Conflicted files produce one tool
                 // input for each side of the conflict.
+                // Files that aren't matched by any of the tools are left as they are.
+                if !tools_config.matches(&repo_path) {
+                    continue;
+                }
                 for term in after.into_iter().flatten() {
                     // We currently only support fixing the content of normal files, so we skip
                     // directories and symlinks, and we ignore the executable bit.
                     if let TreeValue::File { id, executable: _ } = term {
-                        // TODO: Consider filename arguments and tool configuration instead of
-                        // passing every changed file into the tool. Otherwise, the tool has to
-                        // be modified to implement that kind of stuff.
                         let tool_input = ToolInput {
                             file_id: id.clone(),
                             repo_path: repo_path.clone(),
@@ -165,18 +201,25 @@ pub(crate) fn cmd_fix(
         commit_paths.insert(commit.id().clone(), paths);
     }
 
-    // Run the configured tool on all of the chosen inputs.
-    // TODO: Support configuration of multiple tools and which files they affect.
-    let tool_command: CommandNameAndArgs = command
-        .settings()
-        .config()
-        .get("fix.tool-command")
-        .map_err(|err| config_error_with_message("Invalid `fix.tool-command`", err))?;
-    let fixed_file_ids = fix_file_ids(
+    // Run the configured tools on all of the chosen inputs.
+    let (fixed_file_ids, failures) = fix_file_ids(
         tx.repo().store().as_ref(),
-        &tool_command,
+        &tools_config,
         &unique_tool_inputs,
     )?;
+    for failure in failures
+        .iter()
+        .sorted_by_key(|failure| (&failure.tool_input.repo_path, failure.tool_name))
+    {
+        writeln!(
+            ui.warning_default(),
+            "Fix tool '{}' failed on {}: {}",
+            failure.tool_name,
+            tx.base_workspace_helper()
+                .format_file_path(&failure.tool_input.repo_path),
+            failure.error
+        )?;
+    }
 
     // Substitute the fixed file IDs into all of the affected commits. Currently,
     // fixes cannot delete or rename files, change the executable bit, or modify
@@ -253,42 +296,77 @@ struct ToolInput {
 
 /// Applies `run_tool()` to the inputs and stores the resulting file content.
 ///
+/// Each input is passed through all of the tools that match its path, in the
+/// order they appear in `tools_config`, with each tool receiving the output of
+/// the previous one.
+///
 /// Returns a map describing the subset of `tool_inputs` that resulted in
-/// changed file content. Failures when handling an input will cause it to be
-/// omitted from the return value, which is indistinguishable from succeeding
-/// with no changes.
-/// TODO: Better error handling so we can tell the user what went wrong with
-/// each failed input.
+/// changed file content, and the tools that failed. A tool that fails when
+/// handling an input is skipped, and the next tool receives the previous
+/// content.
 fn fix_file_ids<'a>(
     store: &Store,
-    tool_command: &CommandNameAndArgs,
+    tools_config: &'a ToolsConfig,
     tool_inputs: &'a HashSet<ToolInput>,
-) -> BackendResult<HashMap<&'a ToolInput, FileId>> {
+) -> BackendResult<(HashMap<&'a ToolInput, FileId>, Vec<ToolFailure<'a>>)> {
     let (updates_tx, updates_rx) = channel();
+    let (failures_tx, failures_rx) = channel();
     // TODO: Switch to futures, or document the decision not to. We don't need
     // threads unless the threads will be doing more than waiting for pipes.
     tool_inputs.into_par_iter().try_for_each_init(
-        || updates_tx.clone(),
-        |updates_tx, tool_input| -> Result<(), BackendError> {
+        || (updates_tx.clone(), failures_tx.clone()),
+        |(updates_tx, failures_tx), tool_input| -> Result<(), BackendError> {
             let mut read = store.read_file(&tool_input.repo_path, &tool_input.file_id)?;
             let mut old_content = vec![];
             read.read_to_end(&mut old_content).unwrap();
-            if let Ok(new_content) = run_tool(tool_command, tool_input, &old_content) {
-                if new_content != *old_content {
-                    let new_file_id =
-                        store.write_file(&tool_input.repo_path, &mut new_content.as_slice())?;
-                    updates_tx.send((tool_input, new_file_id)).unwrap();
+            let mut new_content = old_content.clone();
+            for tool in tools_config.tools_for_path(&tool_input.repo_path) {
+                match run_tool(&tool.command, tool_input, &new_content) {
+                    Ok(content) => new_content = content,
+                    Err(error) => failures_tx
+                        .send(ToolFailure {
+                            tool_name: &tool.name,
+                            tool_input,
+                            error,
+                        })
+                        .unwrap(),
                 }
             }
+            if new_content != old_content {
+                let new_file_id =
+                    store.write_file(&tool_input.repo_path, &mut new_content.as_slice())?;
+                updates_tx.send((tool_input, new_file_id)).unwrap();
+            }
             Ok(())
         },
     )?;
     drop(updates_tx);
+    drop(failures_tx);
     let mut result = HashMap::new();
     while let Ok((tool_input, new_file_id)) = updates_rx.recv() {
         result.insert(tool_input, new_file_id);
     }
-    Ok(result)
+    Ok((result, failures_rx.iter().collect()))
+}
+
+/// Error from running a tool on a file.
+#[derive(Debug, Error)]
+enum ToolError {
+    #[error("Failed to execute '{tool_binary}'")]
+    FailedToExecute {
+        tool_binary: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Tool exited with {0}")]
+    ExitStatus(ExitStatus),
+}
+
+/// A tool that failed when handling an input.
+struct ToolFailure<'a> {
+    tool_name: &'a str,
+    tool_input: &'a ToolInput,
+    error: ToolError,
 }
 
 /// Runs the `tool_command` to fix the given file content.
@@ -297,15 +375,20 @@ fn fix_file_ids<'a>(
 /// this is not verified.
 ///
 /// Returns the new file content, whose value will be the same as `old_content`
-/// unless the command introduced changes. Returns `None` if there were any
-/// failures when starting, stopping, or communicating with the subprocess.
+/// unless the command introduced changes. Returns an error if there were any
+/// failures when starting, stopping, or communicating with the subprocess, or
+/// if it exited with a non-zero status.
 fn run_tool(
     tool_command: &CommandNameAndArgs,
     tool_input: &ToolInput,
     old_content: &[u8],
-) -> Result<Vec<u8>, ()> {
+) -> Result<Vec<u8>, ToolError> {
     // TODO: Pipe stderr so we can tell the user which commit, file, and tool it is
     // associated with.
+    let to_err = |source| ToolError::FailedToExecute {
+        tool_binary: tool_command.split_name().into_owned(),
+        source,
+    };
     let mut vars: HashMap<&str, &str> = HashMap::new();
     vars.insert("path", tool_input.repo_path.as_internal_file_string());
     let mut child = tool_command
@@ -313,18 +396,123 @@ fn run_tool(
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .spawn()
-        .or(Err(()))?;
+        .map_err(to_err)?;
     let mut stdin = child.stdin.take().unwrap();
     let output = std::thread::scope(|s| {
         s.spawn(move || {
             stdin.write_all(old_content).ok();
         });
-        Some(child.wait_with_output().or(Err(())))
+        child.wait_with_output()
     })
-    .unwrap()?;
+    .map_err(to_err)?;
     if output.status.success() {
         Ok(output.stdout)
     } else {
-        Err(())
+        Err(ToolError::ExitStatus(output.status))
+    }
+}
+
+/// A tool that can be used to fix files, as configured in `fix.tools`.
+struct ToolConfig {
+    /// The name of the tool, used in messages.
+    name: String,
+    /// The command that will be run to fix a matching file.
+    command: CommandNameAndArgs,
+    /// The matcher that determines if this tool matches a file.
+    matcher: Box<dyn Matcher>,
+}
+
+/// The tools that `jj fix` will run, in the order they are applied.
+struct ToolsConfig {
+    tools: Vec<ToolConfig>,
+}
+
+impl ToolsConfig {
+    /// Returns true if any of the tools should be run on the file at `path`.
+    fn matches(&self, path: &RepoPath) -> bool {
+        self.tools.iter().any(|tool| tool.matcher.matches(path))
+    }
+
+    /// Iterates over the tools that should be run on the file at `path`.
+    fn tools_for_path<'a>(&'a self, path: &'a RepoPath) -> impl Iterator<Item = &'a ToolConfig> {
+        self.tools
+            .iter()
+            .filter(move |tool| tool.matcher.matches(path))
+    }
+}
+
+/// Simplifies deserialization of the config values while building a
+/// `ToolConfig`.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawToolConfig {
+    command: CommandNameAndArgs,
+    patterns: Vec<String>,
+}
+
+/// Parses the `fix.tools` config table, and the legacy `fix.tool-command`.
+///
+/// Only the tools named in `selected_tools` are returned, unless it's empty.
+/// Fails if the config is invalid, if no tools are configured, or if a
+/// selected tool doesn't exist.
+fn get_tools_config(
+    command: &CommandHelper,
+    workspace_root: &Path,
+    selected_tools: &[String],
+) -> Result<ToolsConfig, CommandError> {
+    let config = command.settings().config();
+    let mut tools: Vec<ToolConfig> = vec![];
+    if selected_tools.is_empty() {
+        if let Some(tool_command) = config
+            .get::<CommandNameAndArgs>("fix.tool-command")
+            .optional()
+            .map_err(|err| config_error_with_message("Invalid `fix.tool-command`", err))?
+        {
+            tools.push(ToolConfig {
+                name: "tool-command".to_owned(),
+                command: tool_command,
+                matcher: Box::new(EverythingMatcher),
+            });
+        }
+    }
+    let raw_tools = config
+        .get::<HashMap<String, RawToolConfig>>("fix.tools")
+        .optional()
+        .map_err(|err| config_error_with_message("Invalid `fix.tools`", err))?
+        .unwrap_or_default();
+    for name in selected_tools {
+        if !raw_tools.contains_key(name) {
+            return Err(user_error_with_hint(
+                format!("No such fix tool: {name}"),
+                format!("Configured tools: {}", raw_tools.keys().sorted().join(", ")),
+            ));
+        }
+    }
+    // Patterns are relative to the workspace root, not to the current directory.
+    let path_converter = RepoPathUiConverter::Fs {
+        cwd: workspace_root.to_owned(),
+        base: workspace_root.to_owned(),
+    };
+    for (name, raw_tool) in raw_tools.into_iter().sorted_by(|(a, _), (b, _)| a.cmp(b)) {
+        if !selected_tools.is_empty() && !selected_tools.contains(&name) {
+            continue;
+        }
+        let expressions: Vec<_> = raw_tool
+            .patterns
+            .iter()
+            .map(|pattern| fileset::parse(pattern, &path_converter))
+            .try_collect()
+            .map_err(|err| {
+                config_error_with_message(format!("Invalid `fix.tools.{name}.patterns`"), err)
+            })?;
+        tools.push(ToolConfig {
+            name,
+            command: raw_tool.command,
+            matcher: FilesetExpression::union_all(expressions).to_matcher(),
+        });
+    }
+    if tools.is_empty() {
+        return Err(config_error("No `fix.tools` are configured".to_owned()));
     }
+    Ok(ToolsConfig { tools })
 }
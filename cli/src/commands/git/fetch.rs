@@ -34,6 +34,8 @@ pub struct GitFetchArgs {
     ///
     /// By default, the specified name matches exactly. Use `glob:` prefix to
     /// expand `*` as a glob. The other wildcard characters aren't supported.
+    /// Other pattern kinds such as `regex:` are also accepted, and are matched
+    /// against the branch names listed by the remote.
     #[arg(long, short, default_value = "glob:*", value_parser = StringPattern::parse)]
     branch: Vec<StringPattern>,
     /// The remote to fetch from (only named remotes are supported, can be
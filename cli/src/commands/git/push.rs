@@ -18,6 +18,7 @@ use std::{fmt, io};
 
 use clap::ArgGroup;
 use itertools::Itertools;
+use jj_lib::backend::CommitId;
 use jj_lib::git::{self, GitBranchPushTargets, GitPushError};
 use jj_lib::object_id::ObjectId;
 use jj_lib::op_store::RefTarget;
@@ -45,7 +46,8 @@ use crate::ui::Ui;
 /// By default, pushes any branches pointing to
 /// `remote_branches(remote=<remote>)..@`. Use `--branch` to push specific
 /// branches. Use `--all` to push all branches. Use `--change` to generate
-/// branch names based on the change IDs of specific commits.
+/// branch names based on the change IDs of specific commits. Use `--tag` to
+/// push tags.
 ///
 /// Before the command actually moves, creates, or deletes a remote branch, it
 /// makes several [safety checks]. If there is a problem, you may need to run
@@ -59,7 +61,7 @@ use crate::ui::Ui;
 ///     https://martinvonz.github.io/jj/latest/branches/#conflicts
 
 #[derive(clap::Args, Clone, Debug)]
-#[command(group(ArgGroup::new("specific").args(&["branch", "change", "revisions"]).multiple(true)))]
+#[command(group(ArgGroup::new("specific").args(&["branch", "change", "revisions", "tag"]).multiple(true)))]
 #[command(group(ArgGroup::new("what").args(&["all", "deleted", "tracked"]).conflicts_with("specific")))]
 pub struct GitPushArgs {
     /// The remote to push to (only named remotes are supported)
@@ -99,6 +101,14 @@ pub struct GitPushArgs {
     /// repeated)
     #[arg(long, short)]
     change: Vec<RevisionArg>,
+    /// Push only this tag, or tags matching a pattern (can be repeated)
+    ///
+    /// Tags are never overwritten on the remote. By default, the specified
+    /// name matches exactly. Use `glob:` prefix to select tags by wildcard
+    /// pattern. For details, see
+    /// https://martinvonz.github.io/jj/latest/revsets#string-patterns.
+    #[arg(long, value_parser = StringPattern::parse)]
+    tag: Vec<StringPattern>,
     /// Only display what will change on the remote
     #[arg(long)]
     dry_run: bool,
@@ -203,8 +213,10 @@ pub fn cmd_git_push(
             }
         }
 
-        let use_default_revset =
-            args.branch.is_empty() && args.change.is_empty() && args.revisions.is_empty();
+        let use_default_revset = args.branch.is_empty()
+            && args.change.is_empty()
+            && args.revisions.is_empty()
+            && args.tag.is_empty();
         let branches_targeted = find_branches_targeted_by_revisions(
             ui,
             tx.base_workspace_helper(),
@@ -234,7 +246,8 @@ pub fn cmd_git_push(
             &remote
         );
     }
-    if branch_updates.is_empty() {
+    let tag_updates = find_tags_to_push(repo.view(), &args.tag)?;
+    if branch_updates.is_empty() && tag_updates.is_empty() {
         writeln!(ui.status(), "Nothing changed.")?;
         return Ok(());
     }
@@ -266,6 +279,7 @@ pub fn cmd_git_push(
     let new_heads = branch_updates
         .iter()
         .filter_map(|(_, update)| update.new_target.clone())
+        .chain(tag_updates.iter().map(|(_, target)| target.clone()))
         .collect_vec();
     let old_heads = repo
         .view()
@@ -312,7 +326,9 @@ pub fn cmd_git_push(
         }
     }
 
-    writeln!(ui.status(), "Branch changes to push to {}:", &remote)?;
+    if !branch_updates.is_empty() {
+        writeln!(ui.status(), "Branch changes to push to {}:", &remote)?;
+    }
     for (branch_name, update) in &branch_updates {
         match (&update.old_target, &update.new_target) {
             (Some(old_target), Some(new_target)) => {
@@ -355,6 +371,16 @@ pub fn cmd_git_push(
             }
         }
     }
+    if !tag_updates.is_empty() {
+        writeln!(ui.status(), "Tags to push to {}:", &remote)?;
+    }
+    for (tag_name, target) in &tag_updates {
+        writeln!(
+            ui.status(),
+            "  Add tag {tag_name} to {}",
+            short_commit_hash(target)
+        )?;
+    }
 
     if args.dry_run {
         writeln!(ui.status(), "Dry-run requested, not pushing.")?;
@@ -366,10 +392,18 @@ pub fn cmd_git_push(
     let mut sideband_progress_callback = |progress_message: &[u8]| {
         _ = writer.write(ui, progress_message);
     };
-    with_remote_git_callbacks(ui, Some(&mut sideband_progress_callback), |cb| {
-        git::push_branches(tx.mut_repo(), &git_repo, &remote, &targets, cb)
-    })
-    .map_err(|err| match err {
+    let mut push_result = Ok(());
+    if !targets.branch_updates.is_empty() {
+        push_result = with_remote_git_callbacks(ui, Some(&mut sideband_progress_callback), |cb| {
+            git::push_branches(tx.mut_repo(), &git_repo, &remote, &targets, cb)
+        });
+    }
+    if push_result.is_ok() && !tag_updates.is_empty() {
+        push_result = with_remote_git_callbacks(ui, Some(&mut sideband_progress_callback), |cb| {
+            git::push_tags(tx.repo(), &git_repo, &remote, &tag_updates, cb)
+        });
+    }
+    push_result.map_err(|err| match err {
         GitPushError::InternalGitError(err) => map_git_error(err),
         GitPushError::RefInUnexpectedLocation(refs) => user_error_with_hint(
             format!(
@@ -383,7 +417,10 @@ pub fn cmd_git_push(
         _ => user_error(err),
     })?;
     writer.flush(ui)?;
-    tx.finish(ui, tx_description)?;
+    // Pushed tags aren't recorded in the view
+    if !targets.branch_updates.is_empty() {
+        tx.finish(ui, tx_description)?;
+    }
     Ok(())
 }
 
@@ -538,6 +575,41 @@ fn find_branches_to_push<'a>(
     }
 }
 
+fn find_tags_to_push(
+    view: &View,
+    tag_patterns: &[StringPattern],
+) -> Result<Vec<(String, CommitId)>, CommandError> {
+    let mut tags = vec![];
+    let mut unmatched_patterns = vec![];
+    for pattern in tag_patterns {
+        let mut matches = view.tags_matching(pattern).peekable();
+        if matches.peek().is_none() {
+            unmatched_patterns.push(pattern);
+        }
+        for (tag_name, target) in matches {
+            let Some(id) = target.as_normal() else {
+                return Err(user_error_with_hint(
+                    format!("Tag {tag_name} is conflicted"),
+                    "Use `jj tag set` to fix it up.",
+                ));
+            };
+            tags.push((tag_name.to_owned(), id.clone()));
+        }
+    }
+    match &unmatched_patterns[..] {
+        [] => {
+            tags.sort_unstable();
+            tags.dedup();
+            Ok(tags)
+        }
+        [pattern] if pattern.is_exact() => Err(user_error(format!("No such tag: {pattern}"))),
+        patterns => Err(user_error(format!(
+            "No matching tags for patterns: {}",
+            patterns.iter().join(", ")
+        ))),
+    }
+}
+
 fn find_branches_targeted_by_revisions<'a>(
     ui: &Ui,
     workspace_command: &'a WorkspaceCommandHelper,
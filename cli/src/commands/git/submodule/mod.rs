@@ -0,0 +1,72 @@
+// Copyright 2020-2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod print_gitmodules;
+pub mod update;
+
+use std::collections::BTreeMap;
+
+use clap::Subcommand;
+use jj_lib::backend::TreeValue;
+use jj_lib::git::{parse_gitmodules, SubmoduleConfig};
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::repo_path::RepoPath;
+
+use self::print_gitmodules::{cmd_git_submodule_print_gitmodules, GitSubmodulePrintArgs};
+use self::update::{cmd_git_submodule_update, GitSubmoduleUpdateArgs};
+use crate::cli_util::CommandHelper;
+use crate::command_error::{user_error, CommandError};
+use crate::ui::Ui;
+
+/// Interact with Git submodules
+///
+/// Submodules are stored in the repo as pointers to commits in other Git
+/// repos. The repos themselves are kept in the repo's submodule store, and
+/// the pointed-to commits are checked out in the working copy. Moving the
+/// `HEAD` of a submodule checkout records a new pointer in the working-copy
+/// commit.
+#[derive(Subcommand, Clone, Debug)]
+pub enum GitSubmoduleCommand {
+    PrintGitmodules(GitSubmodulePrintArgs),
+    Update(GitSubmoduleUpdateArgs),
+}
+
+pub fn cmd_git_submodule(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &GitSubmoduleCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        GitSubmoduleCommand::PrintGitmodules(args) => {
+            cmd_git_submodule_print_gitmodules(ui, command, args)
+        }
+        GitSubmoduleCommand::Update(args) => cmd_git_submodule_update(ui, command, args),
+    }
+}
+
+/// Reads the submodule configs from the `.gitmodules` file in `tree`, or
+/// returns `None` if there's no such file.
+fn read_gitmodules(
+    tree: &MergedTree,
+) -> Result<Option<BTreeMap<String, SubmoduleConfig>>, CommandError> {
+    let gitmodules_path = RepoPath::from_internal_string(".gitmodules");
+    let mut gitmodules_file = match tree.path_value(gitmodules_path)?.into_resolved() {
+        Ok(None) => return Ok(None),
+        Ok(Some(TreeValue::File { id, .. })) => tree.store().read_file(gitmodules_path, &id)?,
+        _ => {
+            return Err(user_error(".gitmodules is not a file."));
+        }
+    };
+    Ok(Some(parse_gitmodules(&mut gitmodules_file)?))
+}
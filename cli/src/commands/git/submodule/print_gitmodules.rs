@@ -0,0 +1,53 @@
+// Copyright 2020-2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write;
+
+use super::read_gitmodules;
+use crate::cli_util::{CommandHelper, RevisionArg};
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Print the relevant contents from .gitmodules. For debugging purposes
+/// only.
+#[derive(clap::Args, Clone, Debug)]
+#[command(hide = true)]
+pub struct GitSubmodulePrintArgs {
+    /// Read .gitmodules from the given revision.
+    #[arg(long, short = 'r', default_value = "@")]
+    revisions: RevisionArg,
+}
+
+pub fn cmd_git_submodule_print_gitmodules(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitSubmodulePrintArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(&args.revisions)?;
+    let Some(submodules) = read_gitmodules(&commit.tree()?)? else {
+        writeln!(ui.status(), "No submodules!")?;
+        return Ok(());
+    };
+    for (name, submodule) in submodules {
+        writeln!(
+            ui.stdout(),
+            "name:{}\nurl:{}\npath:{}\n\n",
+            name,
+            submodule.url,
+            submodule.path
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write;
+
+use jj_lib::backend::TreeValue;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPathBuf;
+
+use super::read_gitmodules;
+use crate::cli_util::{short_commit_hash, CommandHelper};
+use crate::command_error::{user_error, CommandError};
+use crate::git_util::get_git_repo;
+use crate::ui::Ui;
+
+/// Fetch submodules and check them out in the working copy
+///
+/// Reads `.gitmodules` from the working-copy commit, fetches each submodule
+/// into the repo's submodule store, and checks out the commit the working-copy
+/// commit points to. Relative submodule URLs are resolved against the URL of
+/// the `origin` remote.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitSubmoduleUpdateArgs {
+    /// Only update submodules matching these paths
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+}
+
+pub fn cmd_git_submodule_update(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitSubmoduleUpdateArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let matcher = workspace_command
+        .parse_file_patterns(&args.paths)?
+        .to_matcher();
+    let repo = workspace_command.repo();
+    let wc_commit_id = workspace_command
+        .get_wc_commit_id()
+        .ok_or_else(|| user_error("This command requires a working copy"))?;
+    let wc_commit = repo.store().get_commit(wc_commit_id)?;
+    let tree = wc_commit.tree()?;
+    let Some(submodules) = read_gitmodules(&tree)? else {
+        writeln!(ui.status(), "No submodules!")?;
+        return Ok(());
+    };
+    let base_url = get_git_repo(repo.store())?
+        .find_remote("origin")
+        .ok()
+        .and_then(|remote| remote.url().map(|url| url.to_owned()))
+        .unwrap_or_else(|| workspace_command.workspace_root().display().to_string());
+    let submodule_store = repo.submodule_store();
+    for submodule in submodules.values() {
+        let path = RepoPathBuf::from_relative_path(&submodule.path)
+            .map_err(|err| user_error(format!("Invalid submodule path: {err}")))?;
+        if !matcher.matches(&path) {
+            continue;
+        }
+        let ui_path = workspace_command.format_file_path(&path);
+        let Ok(Some(TreeValue::GitSubmodule(id))) = tree.path_value(&path)?.into_resolved() else {
+            writeln!(
+                ui.warning_default(),
+                "Skipping {ui_path}: not a submodule in the working-copy commit"
+            )?;
+            continue;
+        };
+        let url = resolve_submodule_url(&base_url, &submodule.url);
+        writeln!(ui.status(), "Fetching submodule {ui_path} from {url}")?;
+        submodule_store.fetch(&path, &url)?;
+        let disk_path = path.to_fs_path(workspace_command.workspace_root());
+        std::fs::create_dir_all(&disk_path)?;
+        submodule_store.check_out(&path, &disk_path, &id)?;
+        writeln!(
+            ui.status(),
+            "Checked out submodule {ui_path} at {}",
+            short_commit_hash(&id)
+        )?;
+    }
+    Ok(())
+}
+
+/// Resolves a submodule URL like `../other.git` against the parent repo's URL,
+/// the same way `git submodule` does.
+fn resolve_submodule_url(base_url: &str, url: &str) -> String {
+    if !url.starts_with("./") && !url.starts_with("../") {
+        return url.to_owned();
+    }
+    let mut base = base_url.trim_end_matches('/').to_owned();
+    let mut rest = url;
+    loop {
+        if let Some(stripped) = rest.strip_prefix("./") {
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix("../") {
+            base.truncate(base.rfind(['/', ':']).unwrap_or(0));
+            rest = stripped;
+        } else {
+            break;
+        }
+    }
+    format!("{base}/{rest}")
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use clap::ArgGroup;
+use jj_lib::copies::CopyRecords;
 use jj_lib::rewrite::rebase_to_dest_parent;
 use tracing::instrument;
 
@@ -66,6 +67,7 @@ pub(crate) fn cmd_interdiff(
         &from_tree,
         &to_tree,
         matcher.as_ref(),
+        &CopyRecords::default(),
     )?;
     Ok(())
 }
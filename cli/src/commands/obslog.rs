@@ -14,6 +14,7 @@
 
 use itertools::Itertools;
 use jj_lib::commit::Commit;
+use jj_lib::copies::CopyRecords;
 use jj_lib::dag_walk::topo_order_reverse_ok;
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::repo::Repo;
@@ -179,6 +180,13 @@ fn show_predecessor_patch(
     };
     let predecessor_tree = rebase_to_dest_parent(repo, &predecessor, commit)?;
     let tree = commit.tree()?;
-    renderer.show_diff(ui, formatter, &predecessor_tree, &tree, &EverythingMatcher)?;
+    renderer.show_diff(
+        ui,
+        formatter,
+        &predecessor_tree,
+        &tree,
+        &EverythingMatcher,
+        &CopyRecords::default(),
+    )?;
     Ok(())
 }
@@ -0,0 +1,407 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeSet;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use itertools::Itertools as _;
+use jj_lib::backend::{ChangeId, CommitId};
+use jj_lib::commit::Commit;
+use jj_lib::copies::CopyRecords;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::op_store::{RefTarget, RemoteRef};
+use jj_lib::operation::Operation;
+use jj_lib::refs::{diff_named_ref_targets, diff_named_remote_refs};
+use jj_lib::repo::{ReadonlyRepo, Repo};
+use jj_lib::revset::{RevsetExpression, RevsetIteratorExt as _};
+use jj_lib::rewrite::rebase_to_dest_parent;
+use jj_lib::settings::UserSettings;
+use jj_lib::view::View;
+
+use crate::cli_util::{CommandHelper, WorkspaceCommandHelper, WorkspaceCommandTransaction};
+use crate::command_error::CommandError;
+use crate::diff_util::{self, DiffFormatArgs, DiffRenderer};
+use crate::formatter::Formatter;
+use crate::ui::Ui;
+
+/// Compare changes to the repository between two operations
+///
+/// By default, shows the changes made by the current operation, i.e. compares
+/// it with its parent operation(s).
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationDiffArgs {
+    /// Show repository changes in this operation, compared to its parent(s)
+    #[arg(long, visible_alias = "op")]
+    operation: Option<String>,
+    /// Show repository changes from this operation
+    #[arg(long, conflicts_with = "operation")]
+    from: Option<String>,
+    /// Show repository changes to this operation
+    #[arg(long, conflicts_with = "operation")]
+    to: Option<String>,
+    /// Show patch of modifications to changes
+    ///
+    /// If the previous version has different parents, it will be temporarily
+    /// rebased to the parents of the new version, so the diff is not
+    /// contaminated by unrelated changes.
+    #[arg(long, short = 'p')]
+    patch: bool,
+    #[command(flatten)]
+    diff_format: DiffFormatArgs,
+}
+
+pub fn cmd_op_diff(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationDiffArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+    let (from_ops, to_op) = if args.from.is_some() || args.to.is_some() {
+        let from_op = workspace_command.resolve_single_op(args.from.as_deref().unwrap_or("@"))?;
+        let to_op = workspace_command.resolve_single_op(args.to.as_deref().unwrap_or("@"))?;
+        (vec![from_op], to_op)
+    } else {
+        let op = workspace_command.resolve_single_op(args.operation.as_deref().unwrap_or("@"))?;
+        (parent_ops_or_self(&op)?, op)
+    };
+    drop(workspace_command);
+
+    let (mut workspace_command, from_repos) = load_repos(ui, command, &from_ops, &to_op)?;
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    show_op_diff(
+        ui,
+        formatter.as_mut(),
+        command,
+        &mut workspace_command,
+        &from_repos,
+        &args.diff_format,
+        args.patch,
+    )
+}
+
+/// Returns the parents of `op`, or `op` itself if it's the root operation.
+pub(super) fn parent_ops_or_self(op: &Operation) -> Result<Vec<Operation>, CommandError> {
+    let parent_ops: Vec<_> = op.parents().try_collect()?;
+    if parent_ops.is_empty() {
+        Ok(vec![op.clone()])
+    } else {
+        Ok(parent_ops)
+    }
+}
+
+/// Loads the repos at `from_ops`, and a workspace helper for the repo at
+/// `to_op`.
+pub(super) fn load_repos(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    from_ops: &[Operation],
+    to_op: &Operation,
+) -> Result<(WorkspaceCommandHelper, Vec<Arc<ReadonlyRepo>>), CommandError> {
+    let workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let from_repos: Vec<_> = from_ops
+        .iter()
+        .map(|op| repo_loader.load_at(op))
+        .try_collect()?;
+    let to_repo = repo_loader.load_at(to_op)?;
+    let workspace_command = command.for_loaded_repo(ui, workspace, to_repo)?;
+    Ok((workspace_command, from_repos))
+}
+
+/// Shows the changes to the repository from the merged `from_repos` to the
+/// repo of `workspace_command`.
+pub(super) fn show_op_diff(
+    ui: &Ui,
+    formatter: &mut dyn Formatter,
+    command: &CommandHelper,
+    workspace_command: &mut WorkspaceCommandHelper,
+    from_repos: &[Arc<ReadonlyRepo>],
+    diff_format: &DiffFormatArgs,
+    patch: bool,
+) -> Result<(), CommandError> {
+    let from_view = merge_views(command.settings(), from_repos)?;
+    let to_view = workspace_command.repo().view().clone();
+    let mut tx = workspace_command.start_transaction();
+    // Merge the index of the old repos so the commits that are no longer
+    // visible can still be looked up.
+    for from_repo in from_repos {
+        tx.mut_repo().merge_index(from_repo);
+    }
+    let formats = diff_util::diff_formats_for_log(command.settings(), diff_format, patch)?;
+    let diff_renderer = (!formats.is_empty()).then(|| tx.diff_renderer(formats));
+
+    show_changed_commits(
+        ui,
+        formatter,
+        &tx,
+        &from_view,
+        &to_view,
+        diff_renderer.as_ref(),
+    )?;
+    show_changed_working_copies(formatter, &tx, &from_view, &to_view)?;
+    show_changed_ref_targets(
+        formatter,
+        &tx,
+        "Changed local branches:",
+        diff_named_ref_targets(from_view.local_branches(), to_view.local_branches()),
+    )?;
+    show_changed_ref_targets(
+        formatter,
+        &tx,
+        "Changed tags:",
+        diff_named_ref_targets(
+            from_view
+                .tags()
+                .iter()
+                .map(|(name, target)| (name.as_str(), target)),
+            to_view
+                .tags()
+                .iter()
+                .map(|(name, target)| (name.as_str(), target)),
+        ),
+    )?;
+    show_changed_remote_refs(
+        formatter,
+        &tx,
+        diff_named_remote_refs(
+            from_view.all_remote_branches(),
+            to_view.all_remote_branches(),
+        ),
+    )?;
+    Ok(())
+}
+
+/// Merges the views of `repos`, which are the parents of an operation.
+fn merge_views(settings: &UserSettings, repos: &[Arc<ReadonlyRepo>]) -> Result<View, CommandError> {
+    let (first_repo, other_repos) = repos.split_first().expect("must have at least one repo");
+    if other_repos.is_empty() {
+        return Ok(first_repo.view().clone());
+    }
+    let mut tx = first_repo.start_transaction(settings);
+    for other_repo in other_repos {
+        tx.merge_operation(other_repo.operation().clone())?;
+    }
+    Ok(tx.repo().view().clone())
+}
+
+fn show_changed_commits(
+    ui: &Ui,
+    formatter: &mut dyn Formatter,
+    tx: &WorkspaceCommandTransaction,
+    from_view: &View,
+    to_view: &View,
+    diff_renderer: Option<&DiffRenderer>,
+) -> Result<(), CommandError> {
+    let repo = tx.repo();
+    let from_heads = RevsetExpression::commits(from_view.heads().iter().cloned().collect());
+    let to_heads = RevsetExpression::commits(to_view.heads().iter().cloned().collect());
+    let get_commits = |expr: Rc<RevsetExpression>| -> Result<Vec<Commit>, CommandError> {
+        let commits = expr
+            .evaluate_programmatic(repo)?
+            .iter()
+            .commits(repo.store())
+            .try_collect()?;
+        Ok(commits)
+    };
+    let added_commits = get_commits(from_heads.range(&to_heads))?;
+    let removed_commits = get_commits(to_heads.range(&from_heads))?;
+    if added_commits.is_empty() && removed_commits.is_empty() {
+        return Ok(());
+    }
+
+    // Group the commits by change id, so a rewritten change is shown with its
+    // new commits followed by its old commits.
+    let mut changes: IndexMap<&ChangeId, (Vec<&Commit>, Vec<&Commit>)> = IndexMap::new();
+    for commit in &added_commits {
+        changes
+            .entry(commit.change_id())
+            .or_default()
+            .0
+            .push(commit);
+    }
+    for commit in &removed_commits {
+        changes
+            .entry(commit.change_id())
+            .or_default()
+            .1
+            .push(commit);
+    }
+
+    writeln!(formatter, "Changed commits:")?;
+    for (added, removed) in changes.values() {
+        for commit in added {
+            write!(formatter, "+ ")?;
+            tx.write_commit_summary(formatter, commit)?;
+            writeln!(formatter)?;
+        }
+        for commit in removed {
+            write!(formatter, "- ")?;
+            tx.write_commit_summary(formatter, commit)?;
+            writeln!(formatter)?;
+        }
+        if let Some(diff_renderer) = diff_renderer {
+            match (added.as_slice(), removed.as_slice()) {
+                ([new_commit], [old_commit]) => {
+                    let old_tree = rebase_to_dest_parent(repo, old_commit, new_commit)?;
+                    diff_renderer.show_diff(
+                        ui,
+                        formatter,
+                        &old_tree,
+                        &new_commit.tree()?,
+                        &EverythingMatcher,
+                        &CopyRecords::default(),
+                    )?;
+                }
+                (added, _) => {
+                    for commit in added {
+                        diff_renderer.show_patch(ui, formatter, commit, &EverythingMatcher)?;
+                    }
+                }
+            }
+        }
+    }
+    writeln!(formatter)?;
+    Ok(())
+}
+
+fn show_changed_working_copies(
+    formatter: &mut dyn Formatter,
+    tx: &WorkspaceCommandTransaction,
+    from_view: &View,
+    to_view: &View,
+) -> Result<(), CommandError> {
+    let workspace_ids: BTreeSet<_> = from_view
+        .wc_commit_ids()
+        .keys()
+        .chain(to_view.wc_commit_ids().keys())
+        .collect();
+    let changed_workspace_ids = workspace_ids
+        .into_iter()
+        .filter(|id| from_view.get_wc_commit_id(id) != to_view.get_wc_commit_id(id))
+        .collect_vec();
+    if changed_workspace_ids.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(formatter, "Changed working copy commits:")?;
+    for workspace_id in changed_workspace_ids {
+        writeln!(formatter, "{}@:", workspace_id.as_str())?;
+        write_optional_commit_id(formatter, tx, "+", to_view.get_wc_commit_id(workspace_id))?;
+        write_optional_commit_id(formatter, tx, "-", from_view.get_wc_commit_id(workspace_id))?;
+    }
+    writeln!(formatter)?;
+    Ok(())
+}
+
+fn show_changed_ref_targets<'a>(
+    formatter: &mut dyn Formatter,
+    tx: &WorkspaceCommandTransaction,
+    heading: &str,
+    changed_refs: impl Iterator<Item = (&'a str, (&'a RefTarget, &'a RefTarget))>,
+) -> Result<(), CommandError> {
+    let changed_refs = changed_refs.collect_vec();
+    if changed_refs.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(formatter, "{heading}")?;
+    for (name, (from_target, to_target)) in changed_refs {
+        writeln!(formatter, "{name}:")?;
+        write_ref_target(formatter, tx, "+", to_target)?;
+        write_ref_target(formatter, tx, "-", from_target)?;
+    }
+    writeln!(formatter)?;
+    Ok(())
+}
+
+fn show_changed_remote_refs<'a>(
+    formatter: &mut dyn Formatter,
+    tx: &WorkspaceCommandTransaction,
+    changed_refs: impl Iterator<Item = ((&'a str, &'a str), (&'a RemoteRef, &'a RemoteRef))>,
+) -> Result<(), CommandError> {
+    let changed_refs = changed_refs.collect_vec();
+    if changed_refs.is_empty() {
+        return Ok(());
+    }
+
+    let tracking_label = |remote_ref: &RemoteRef| {
+        if remote_ref.is_tracking() {
+            "tracked"
+        } else {
+            "untracked"
+        }
+    };
+    writeln!(formatter, "Changed remote branches:")?;
+    for ((name, remote_name), (from_ref, to_ref)) in changed_refs {
+        writeln!(formatter, "{name}@{remote_name}:")?;
+        if from_ref.target == to_ref.target {
+            // Only the tracking state changed
+            writeln!(formatter, "+ {}", tracking_label(to_ref))?;
+            writeln!(formatter, "- {}", tracking_label(from_ref))?;
+        } else {
+            write_ref_target(formatter, tx, "+", &to_ref.target)?;
+            write_ref_target(formatter, tx, "-", &from_ref.target)?;
+        }
+    }
+    writeln!(formatter)?;
+    Ok(())
+}
+
+fn write_ref_target(
+    formatter: &mut dyn Formatter,
+    tx: &WorkspaceCommandTransaction,
+    prefix: &str,
+    target: &RefTarget,
+) -> Result<(), CommandError> {
+    if target.has_conflict() {
+        // Only the added terms of a conflicted ref are shown
+        for id in target.added_ids() {
+            write!(formatter, "{prefix} (conflicted) ")?;
+            write_commit_id(formatter, tx, id)?;
+        }
+        Ok(())
+    } else {
+        write_optional_commit_id(formatter, tx, prefix, target.as_normal())
+    }
+}
+
+fn write_optional_commit_id(
+    formatter: &mut dyn Formatter,
+    tx: &WorkspaceCommandTransaction,
+    prefix: &str,
+    id: Option<&CommitId>,
+) -> Result<(), CommandError> {
+    write!(formatter, "{prefix} ")?;
+    match id {
+        Some(id) => write_commit_id(formatter, tx, id),
+        None => {
+            writeln!(formatter, "(absent)")?;
+            Ok(())
+        }
+    }
+}
+
+fn write_commit_id(
+    formatter: &mut dyn Formatter,
+    tx: &WorkspaceCommandTransaction,
+    id: &CommitId,
+) -> Result<(), CommandError> {
+    let commit = tx.repo().store().get_commit(id)?;
+    tx.write_commit_summary(formatter, &commit)?;
+    writeln!(formatter)?;
+    Ok(())
+}
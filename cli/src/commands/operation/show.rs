@@ -0,0 +1,84 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::diff::{load_repos, parent_ops_or_self, show_op_diff};
+use crate::cli_util::{CommandHelper, LogContentFormat};
+use crate::command_error::CommandError;
+use crate::diff_util::DiffFormatArgs;
+use crate::operation_templater::OperationTemplateLanguage;
+use crate::ui::Ui;
+
+/// Show changes to the repository in an operation
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationShowArgs {
+    /// Show repository changes in this operation, compared to its parent(s)
+    #[arg(default_value = "@")]
+    operation: String,
+    /// Show patch of modifications to changes
+    ///
+    /// If the previous version has different parents, it will be temporarily
+    /// rebased to the parents of the new version, so the diff is not
+    /// contaminated by unrelated changes.
+    #[arg(long, short = 'p')]
+    patch: bool,
+    #[command(flatten)]
+    diff_format: DiffFormatArgs,
+}
+
+pub fn cmd_op_show(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationShowArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+    let op = workspace_command.resolve_single_op(&args.operation)?;
+    let parent_ops = parent_ops_or_self(&op)?;
+    let repo_loader = workspace_command.repo().loader();
+    let with_content_format = LogContentFormat::new(ui, command.settings())?;
+
+    let template;
+    {
+        let language = OperationTemplateLanguage::new(
+            repo_loader.op_store().root_operation_id(),
+            Some(op.id()),
+            command.operation_template_extensions(),
+        );
+        let text = command.settings().config().get_string("templates.op_log")?;
+        template = command
+            .parse_template(
+                ui,
+                &language,
+                &text,
+                OperationTemplateLanguage::wrap_operation,
+            )?
+            .labeled("op_log");
+    }
+    drop(workspace_command);
+
+    let (mut workspace_command, parent_repos) = load_repos(ui, command, &parent_ops, &op)?;
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    let formatter = formatter.as_mut();
+    with_content_format.write(formatter, |formatter| template.format(&op, formatter))?;
+    writeln!(formatter)?;
+    show_op_diff(
+        ui,
+        formatter,
+        command,
+        &mut workspace_command,
+        &parent_repos,
+        &args.diff_format,
+        args.patch,
+    )
+}
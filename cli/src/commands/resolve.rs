@@ -24,8 +24,10 @@ use crate::ui::Ui;
 
 /// Resolve a conflicted file with an external merge tool
 ///
-/// Only conflicts that can be resolved with a 3-way merge are supported. See
-/// docs for merge tool configuration instructions.
+/// Only conflicts between regular files are supported. A conflict with more
+/// than two sides is resolved as a sequence of 3-way merges, each one merging
+/// the result of the previous merge with the next side. See docs for merge tool
+/// configuration instructions.
 ///
 /// Note that conflicts can also be resolved without using this command. You may
 /// edit the conflict markers in the conflicted file directly with a text
@@ -14,17 +14,50 @@
 
 //! This file contains the internal implementation of `run`.
 
+use std::collections::HashMap;
+use std::io;
+use std::io::Write as _;
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+
 use itertools::Itertools as _;
+use jj_lib::backend::{CommitId, MergedTreeId};
+use jj_lib::commit::{Commit, CommitIteratorExt as _};
+use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::fsmonitor::FsmonitorSettings;
+use jj_lib::gitignore::GitIgnoreFile;
+use jj_lib::local_working_copy::LocalWorkingCopy;
+use jj_lib::op_store::{OperationId, WorkspaceId};
+use jj_lib::repo::Repo as _;
+use jj_lib::store::Store;
+use jj_lib::working_copy::{CheckoutOptions, SnapshotOptions, WorkingCopy as _};
+use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
+use tracing::instrument;
 
-use crate::cli_util::{CommandHelper, RevisionArg};
-use crate::command_error::{user_error, CommandError};
+use crate::cli_util::{short_change_hash, CommandHelper, RevisionArg};
+use crate::command_error::{internal_error_with_message, user_error, CommandError};
+use crate::formatter::Formatter;
 use crate::ui::Ui;
 
-/// Run a command across a set of revisions.
+/// Run a command across a set of revisions
+///
+/// Each revision is checked out into its own scratch working copy in the
+/// `.jj/run` directory, and the command is run there with the working copy as
+/// its current directory. Up to `--jobs` revisions are processed in parallel.
+/// The output of the command is printed as soon as it finishes, with every
+/// line prefixed by the change id of the revision it was run against.
 ///
+/// If the command modifies files in the working copy, the revision is
+/// rewritten to include those changes, and its descendants are rebased on top
+/// of it. Revisions for which the command fails are left unchanged.
 ///
-/// All recorded state will be persisted in the `.jj` directory, so occasionally
-/// a `jj run --clean` is needed to clean up disk space.
+/// There is one scratch working copy per job, in `.jj/run/<index>`. They are
+/// not removed after the command finishes, but reused by later invocations,
+/// so only the files that differ have to be written. To reclaim their disk
+/// space, `.jj/run` can be deleted whenever `jj run` isn't running; the
+/// scratch working copies are recreated as needed.
 ///
 /// # Example
 ///
@@ -48,22 +81,267 @@ pub struct RunArgs {
     jobs: Option<usize>,
 }
 
+/// The result of running the command against a single commit.
+struct RunOutput {
+    commit: Commit,
+    status: ExitStatus,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    /// The tree of the scratch working copy after the command finished.
+    new_tree_id: MergedTreeId,
+}
+
+#[instrument(skip_all)]
 pub fn cmd_run(ui: &mut Ui, command: &CommandHelper, args: &RunArgs) -> Result<(), CommandError> {
-    let workspace_command = command.workspace_helper(ui)?;
-    let _resolved_commits: Vec<_> = workspace_command
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let resolved_commits: Vec<_> = workspace_command
         .parse_union_revsets(&args.revisions)?
         .evaluate_to_commits()?
         .try_collect()?;
+    workspace_command.check_rewritable(resolved_commits.iter().ids())?;
     // Jobs are resolved in this order:
     // 1. Commandline argument iff > 0.
     // 2. the amount of cores available.
     // 3. a single job, if all of the above fails.
-    let _jobs = match args.jobs {
+    let jobs = match args.jobs {
         Some(0) => return Err(user_error("must pass at least one job")),
         Some(jobs) => Some(jobs),
         None => std::thread::available_parallelism().map(|t| t.into()).ok(),
     }
     // Fallback to a single user-visible job.
-    .unwrap_or(1usize);
-    Err(user_error("This is a stub, do not use"))
+    .unwrap_or(1usize)
+    .min(resolved_commits.len().max(1));
+
+    let base_ignores = workspace_command.base_ignores()?;
+    let max_new_file_size = command.settings().max_new_file_size()?;
+    let conflict_marker_style = command.settings().conflict_marker_style()?;
+    let store = workspace_command.repo().store().clone();
+    let operation_id = workspace_command.repo().op_id().clone();
+    let run_dir = workspace_command.workspace_root().join(".jj").join("run");
+    let working_copies: Vec<_> = (0..jobs)
+        .map(|index| {
+            load_or_init_working_copy(&store, &run_dir.join(index.to_string()), &operation_id)
+                .map(Mutex::new)
+        })
+        .try_collect()?;
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .map_err(|err| internal_error_with_message("Failed to start worker threads", err))?;
+    let (output_tx, output_rx) = channel();
+    let mut new_tree_ids: HashMap<CommitId, MergedTreeId> = HashMap::new();
+    let mut num_failed = 0;
+    std::thread::scope(|s| -> Result<(), CommandError> {
+        s.spawn(|| {
+            pool.install(|| {
+                resolved_commits
+                    .par_iter()
+                    .for_each_with(output_tx, |output_tx, commit| {
+                        // Each worker thread owns the scratch working copy with the same index,
+                        // so no two commands ever run in the same directory.
+                        let index = rayon::current_thread_index().unwrap();
+                        let result = run_on_commit(
+                            &working_copies[index].lock().unwrap(),
+                            commit,
+                            &args.shell_command,
+                            &base_ignores,
+                            max_new_file_size,
+                            conflict_marker_style,
+                            &operation_id,
+                        );
+                        // The receiver is only gone if we already gave up on an error.
+                        output_tx.send(result).ok();
+                    });
+            });
+        });
+        // Stream the results as they come in. The loop ends once all workers
+        // are done and the senders have been dropped.
+        for result in output_rx {
+            let output = result?;
+            write_output(ui, &output)?;
+            if output.status.success() {
+                new_tree_ids.insert(output.commit.id().clone(), output.new_tree_id);
+            } else {
+                num_failed += 1;
+            }
+        }
+        Ok(())
+    })?;
+
+    let mut tx = workspace_command.start_transaction();
+    let mut num_rewritten = 0;
+    tx.mut_repo().transform_descendants(
+        command.settings(),
+        resolved_commits.iter().ids().cloned().collect_vec(),
+        |rewriter| {
+            let old_commit = rewriter.old_commit().clone();
+            match new_tree_ids.get(old_commit.id()) {
+                Some(new_tree_id) if new_tree_id != old_commit.tree_id() => {
+                    // Apply the changes made by the command on top of the rebased
+                    // commit, in case one of its ancestors was rewritten too.
+                    let builder = rewriter.rebase(command.settings())?;
+                    let store = old_commit.store();
+                    let rebased_tree = store.get_root_tree(builder.tree_id())?;
+                    let old_tree = old_commit.tree()?;
+                    let new_tree = store.get_root_tree(new_tree_id)?;
+                    let merged_tree = rebased_tree.merge(&old_tree, &new_tree)?;
+                    builder.set_tree_id(merged_tree.id()).write()?;
+                    num_rewritten += 1;
+                }
+                _ => {
+                    if rewriter.parents_changed() {
+                        rewriter.rebase(command.settings())?.write()?;
+                    }
+                }
+            }
+            Ok(())
+        },
+    )?;
+    writeln!(
+        ui.status(),
+        "Rewrote {num_rewritten} commits of {} checked.",
+        resolved_commits.len()
+    )?;
+    tx.finish(ui, format!("run command '{}'", args.shell_command))?;
+    if num_failed > 0 {
+        return Err(user_error(format!(
+            "Command failed on {num_failed} of {} revisions",
+            resolved_commits.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Loads the scratch working copy in `dir`, creating it if it doesn't exist
+/// yet.
+fn load_or_init_working_copy(
+    store: &Arc<Store>,
+    dir: &Path,
+    operation_id: &OperationId,
+) -> Result<LocalWorkingCopy, CommandError> {
+    let working_copy_path = dir.join("working_copy");
+    let state_path = dir.join("state");
+    if state_path.join("checkout").is_file() {
+        return Ok(LocalWorkingCopy::load(
+            store.clone(),
+            working_copy_path,
+            state_path,
+        ));
+    }
+    std::fs::create_dir_all(&working_copy_path)?;
+    std::fs::create_dir_all(&state_path)?;
+    let working_copy = LocalWorkingCopy::init(
+        store.clone(),
+        working_copy_path,
+        state_path,
+        operation_id.clone(),
+        WorkspaceId::new(format!("run-{}", dir_name(dir))),
+    )?;
+    Ok(working_copy)
+}
+
+fn dir_name(dir: &Path) -> String {
+    dir.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Checks out `commit` into the scratch working copy, runs the shell command
+/// in it, and snapshots the result.
+fn run_on_commit(
+    working_copy: &LocalWorkingCopy,
+    commit: &Commit,
+    shell_command: &str,
+    base_ignores: &Arc<GitIgnoreFile>,
+    max_new_file_size: u64,
+    conflict_marker_style: ConflictMarkerStyle,
+    operation_id: &OperationId,
+) -> Result<RunOutput, CommandError> {
+    let mut locked_wc = working_copy.start_mutation()?;
+    // Pick up anything a previous run may have left behind, so that checking
+    // out the commit on top of it restores every tracked file.
+    locked_wc.snapshot(SnapshotOptions {
+        base_ignores: base_ignores.clone(),
+        fsmonitor_settings: FsmonitorSettings::None,
+        progress: None,
+        max_new_file_size,
+        conflict_marker_style,
+        submodule_store: None,
+    })?;
+    locked_wc
+        .check_out(
+            commit,
+            &CheckoutOptions {
+                conflict_marker_style,
+                submodule_store: None,
+            },
+        )
+        .map_err(|err| internal_error_with_message("Failed to check out commit", err))?;
+    let output = shell_command_for(shell_command)
+        .current_dir(working_copy.path())
+        .output()
+        .map_err(|err| user_error(format!("Failed to run '{shell_command}': {err}")))?;
+    let new_tree_id = locked_wc.snapshot(SnapshotOptions {
+        base_ignores: base_ignores.clone(),
+        fsmonitor_settings: FsmonitorSettings::None,
+        progress: None,
+        max_new_file_size,
+        conflict_marker_style,
+        submodule_store: None,
+    })?;
+    locked_wc.finish(operation_id.clone())?;
+    Ok(RunOutput {
+        commit: commit.clone(),
+        status: output.status,
+        stdout: output.stdout,
+        stderr: output.stderr,
+        new_tree_id,
+    })
+}
+
+#[cfg(unix)]
+fn shell_command_for(shell_command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(shell_command);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell_command_for(shell_command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/c").arg(shell_command);
+    cmd
+}
+
+/// Writes the output of the command, labelling every line with the change id
+/// of the commit it was run against.
+fn write_output(ui: &Ui, output: &RunOutput) -> Result<(), CommandError> {
+    let change_id = short_change_hash(output.commit.change_id());
+    write_labelled_lines(ui.stdout_formatter().as_mut(), &change_id, &output.stdout)?;
+    write_labelled_lines(ui.stderr_formatter().as_mut(), &change_id, &output.stderr)?;
+    if !output.status.success() {
+        writeln!(
+            ui.warning_default(),
+            "Command failed on {change_id} with {}",
+            output.status
+        )?;
+    }
+    Ok(())
+}
+
+fn write_labelled_lines(
+    formatter: &mut dyn Formatter,
+    change_id: &str,
+    content: &[u8],
+) -> io::Result<()> {
+    for line in content.split_inclusive(|b| *b == b'\n') {
+        formatter.with_label("change_id", |formatter| write!(formatter, "{change_id}"))?;
+        write!(formatter, ": ")?;
+        formatter.write_all(line)?;
+        if !line.ends_with(b"\n") {
+            writeln!(formatter)?;
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,103 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::io::Write;
+
+use itertools::Itertools as _;
+use jj_lib::commit::{Commit, CommitIteratorExt};
+use jj_lib::repo::Repo;
+use jj_lib::signing::SignBehavior;
+use tracing::instrument;
+
+use crate::cli_util::{CommandHelper, RevisionArg};
+use crate::command_error::{user_error_with_hint, CommandError};
+use crate::ui::Ui;
+
+/// Cryptographically sign revisions
+///
+/// Rewrites the revisions with a signature made by the configured signing
+/// backend, regardless of who authored them or whether they were already
+/// signed. Descendants of the signed revisions are rebased onto them.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct SignArgs {
+    /// The revision(s) to sign
+    #[arg(long, short, default_value = "@")]
+    revisions: Vec<RevisionArg>,
+    /// The key used for signing (defaults to `signing.key`)
+    #[arg(long)]
+    key: Option<String>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_sign(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &SignArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    if !workspace_command.repo().store().signer().can_sign() {
+        return Err(user_error_with_hint(
+            "Cannot sign commits because no signing backend is configured",
+            "Set `signing.backend` to enable signing.",
+        ));
+    }
+    let to_sign: Vec<Commit> = workspace_command
+        .parse_union_revsets(&args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    if to_sign.is_empty() {
+        writeln!(ui.status(), "No revisions to sign.")?;
+        return Ok(());
+    }
+    workspace_command.check_rewritable(to_sign.iter().ids())?;
+
+    let mut tx = workspace_command.start_transaction();
+    let to_sign_ids: HashSet<_> = to_sign.iter().ids().collect();
+    let mut signed_commits = vec![];
+    let mut num_rebased = 0;
+    tx.mut_repo().transform_descendants(
+        command.settings(),
+        to_sign.iter().ids().cloned().collect_vec(),
+        |rewriter| {
+            if to_sign_ids.contains(rewriter.old_commit().id()) {
+                let mut builder = rewriter
+                    .reparent(command.settings())?
+                    .set_sign_behavior(SignBehavior::Force);
+                if let Some(key) = &args.key {
+                    builder = builder.set_sign_key(Some(key.clone()));
+                }
+                let new_commit = builder.write()?;
+                signed_commits.push(new_commit);
+            } else if rewriter.parents_changed() {
+                rewriter.reparent(command.settings())?.write()?;
+                num_rebased += 1;
+            }
+            Ok(())
+        },
+    )?;
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(formatter, "Signed {} commits:", signed_commits.len())?;
+        for commit in &signed_commits {
+            write!(formatter, "  ")?;
+            tx.write_commit_summary(formatter.as_mut(), commit)?;
+            writeln!(formatter)?;
+        }
+        if num_rebased > 0 {
+            writeln!(formatter, "Rebased {num_rebased} descendant commits")?;
+        }
+    }
+    tx.finish(ui, format!("sign {} commits", signed_commits.len()))
+}
@@ -12,14 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
+
 use itertools::Itertools;
+use jj_lib::commit::Commit;
+use jj_lib::copies::CopyRecords;
+use jj_lib::matchers::Matcher;
 use jj_lib::repo::Repo;
 use jj_lib::revset::{RevsetExpression, RevsetFilterPredicate};
+use serde_json::json;
 use tracing::instrument;
 
-use crate::cli_util::{print_conflicted_paths, CommandHelper};
-use crate::command_error::CommandError;
-use crate::diff_util::DiffFormat;
+use crate::cli_util::{print_conflicted_paths, CommandHelper, WorkspaceCommandHelper};
+use crate::command_error::{internal_error, CommandError};
+use crate::commit_templater::CommitJsonSerializer;
+use crate::diff_util::{diff_summary_to_json, get_copy_records, DiffFormat};
 use crate::revset_util;
 use crate::ui::Ui;
 
@@ -37,6 +44,14 @@ pub(crate) struct StatusArgs {
     /// Restrict the status display to these paths
     #[arg(value_hint = clap::ValueHint::AnyPath)]
     paths: Vec<String>,
+    /// Print the status as a single line of JSON
+    ///
+    /// The object has `working_copy` and `parents` fields with the commits
+    /// serialized like the `json()` template function, a `changes` field with
+    /// the changed files, a `conflicts` field with the conflicted paths, and
+    /// `conflicted_branches` and `conflicted_remote_branches` fields.
+    #[arg(long)]
+    json: bool,
 }
 
 #[instrument(skip_all)]
@@ -54,6 +69,11 @@ pub(crate) fn cmd_status(
     let matcher = workspace_command
         .parse_file_patterns(&args.paths)?
         .to_matcher();
+    if args.json {
+        let value = status_to_json(&workspace_command, maybe_wc_commit.as_ref(), &matcher)?;
+        writeln!(ui.stdout(), "{value}")?;
+        return Ok(());
+    }
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     let formatter = formatter.as_mut();
@@ -65,8 +85,12 @@ pub(crate) fn cmd_status(
             writeln!(formatter, "The working copy is clean")?;
         } else {
             writeln!(formatter, "Working copy changes:")?;
+            let copy_records = match wc_commit.parent_ids() {
+                [parent_id] => get_copy_records(repo.store(), parent_id, wc_commit.id())?,
+                _ => CopyRecords::default(),
+            };
             let diff_renderer = workspace_command.diff_renderer(vec![DiffFormat::Summary]);
-            diff_renderer.show_diff(ui, formatter, &parent_tree, &tree, &matcher)?;
+            diff_renderer.show_diff(ui, formatter, &parent_tree, &tree, &matcher, &copy_records)?;
         }
 
         // TODO: Conflicts should also be filtered by the `matcher`. See the related
@@ -156,3 +180,59 @@ pub(crate) fn cmd_status(
 
     Ok(())
 }
+
+fn status_to_json(
+    workspace_command: &WorkspaceCommandHelper,
+    maybe_wc_commit: Option<&Commit>,
+    matcher: &dyn Matcher,
+) -> Result<serde_json::Value, CommandError> {
+    let repo = workspace_command.repo();
+    let serializer = CommitJsonSerializer::new(repo.as_ref());
+    let commit_to_json = |commit: &Commit| -> Result<serde_json::Value, CommandError> {
+        serializer
+            .to_json(commit)
+            .map_err(|err| internal_error(err.0))
+    };
+    let mut value = json!({
+        "working_copy": null,
+        "parents": [],
+        "changes": [],
+        "conflicts": [],
+    });
+    if let Some(wc_commit) = maybe_wc_commit {
+        let parent_tree = wc_commit.parent_tree(repo.as_ref())?;
+        let tree = wc_commit.tree()?;
+        let copy_records = match wc_commit.parent_ids() {
+            [parent_id] => get_copy_records(repo.store(), parent_id, wc_commit.id())?,
+            _ => CopyRecords::default(),
+        };
+        let tree_diff = parent_tree.diff_stream_with_copies(&tree, matcher, &copy_records);
+        value["working_copy"] = commit_to_json(wc_commit)?;
+        value["parents"] = wc_commit
+            .parents()
+            .map(|parent| commit_to_json(&parent?))
+            .try_collect::<_, Vec<_>, _>()?
+            .into();
+        value["changes"] = diff_summary_to_json(tree_diff)?;
+        value["conflicts"] = tree
+            .conflicts()
+            .map(|(path, _)| path.as_internal_file_string().to_owned())
+            .collect_vec()
+            .into();
+    }
+    value["conflicted_branches"] = repo
+        .view()
+        .local_branches()
+        .filter(|(_, target)| target.has_conflict())
+        .map(|(branch_name, _)| branch_name)
+        .collect_vec()
+        .into();
+    value["conflicted_remote_branches"] = repo
+        .view()
+        .all_remote_branches()
+        .filter(|(_, remote_ref)| remote_ref.target.has_conflict())
+        .map(|((branch_name, remote_name), _)| format!("{branch_name}@{remote_name}"))
+        .collect_vec()
+        .into();
+    Ok(value)
+}
@@ -12,18 +12,89 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use clap::builder::NonEmptyStringValueParser;
+use itertools::Itertools as _;
+use jj_lib::backend::SigningFn;
+use jj_lib::commit::Commit;
+use jj_lib::git::{self, GitTagError};
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::RefTarget;
+use jj_lib::repo::Repo as _;
 use jj_lib::str_util::StringPattern;
+use jj_lib::view::View;
 
-use crate::cli_util::CommandHelper;
-use crate::command_error::CommandError;
+use crate::cli_util::{CommandHelper, RevisionArg, WorkspaceCommandTransaction};
+use crate::command_error::{internal_error, user_error, user_error_with_hint, CommandError};
 use crate::commit_templater::{CommitTemplateLanguage, RefName};
+use crate::git_util::get_git_repo;
 use crate::ui::Ui;
 
 /// Manage tags.
 #[derive(clap::Subcommand, Clone, Debug)]
 pub enum TagCommand {
+    #[command(visible_alias("c"))]
+    Create(TagCreateArgs),
+    #[command(visible_alias("d"))]
+    Delete(TagDeleteArgs),
     #[command(visible_alias("l"))]
     List(TagListArgs),
+    #[command(visible_alias("s"))]
+    Set(TagSetArgs),
+}
+
+/// Create a new tag
+///
+/// Without `--message`, a lightweight tag is created. With `--message`, an
+/// annotated tag object is written to the backing Git repo.
+#[derive(clap::Args, Clone, Debug)]
+pub struct TagCreateArgs {
+    /// The tag's target revision
+    #[arg(long, short)]
+    revision: Option<RevisionArg>,
+
+    /// Create an annotated tag with the given message
+    #[arg(long, short)]
+    message: Option<String>,
+
+    /// Sign the annotated tag using the configured signing backend
+    #[arg(long, requires = "message")]
+    sign: bool,
+
+    /// The tags to create
+    #[arg(required = true, value_parser = NonEmptyStringValueParser::new())]
+    names: Vec<String>,
+}
+
+/// Delete existing tags
+#[derive(clap::Args, Clone, Debug)]
+pub struct TagDeleteArgs {
+    /// The tags to delete
+    ///
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// select tags by wildcard pattern. For details, see
+    /// https://github.com/martinvonz/jj/blob/main/docs/revsets.md#string-patterns.
+    #[arg(required = true, value_parser = StringPattern::parse)]
+    names: Vec<StringPattern>,
+}
+
+/// Create or update a tag to point to a certain commit
+#[derive(clap::Args, Clone, Debug)]
+pub struct TagSetArgs {
+    /// The tag's target revision
+    #[arg(long, short)]
+    revision: Option<RevisionArg>,
+
+    /// Create an annotated tag with the given message
+    #[arg(long, short)]
+    message: Option<String>,
+
+    /// Sign the annotated tag using the configured signing backend
+    #[arg(long, requires = "message")]
+    sign: bool,
+
+    /// The tags to update
+    #[arg(required = true, value_parser = NonEmptyStringValueParser::new())]
+    names: Vec<String>,
 }
 
 /// List tags.
@@ -51,8 +122,74 @@ pub fn cmd_tag(
     subcommand: &TagCommand,
 ) -> Result<(), CommandError> {
     match subcommand {
+        TagCommand::Create(args) => cmd_tag_create(ui, command, args),
+        TagCommand::Delete(args) => cmd_tag_delete(ui, command, args),
         TagCommand::List(args) => cmd_tag_list(ui, command, args),
+        TagCommand::Set(args) => cmd_tag_set(ui, command, args),
+    }
+}
+
+fn cmd_tag_create(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TagCreateArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let target_commit =
+        workspace_command.resolve_single_rev(args.revision.as_ref().unwrap_or(&RevisionArg::AT))?;
+    let view = workspace_command.repo().view();
+    for name in &args.names {
+        if view.get_tag(name).is_present() {
+            return Err(user_error_with_hint(
+                format!("Tag already exists: {name}"),
+                "Use `jj tag set` to update it.",
+            ));
+        }
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    set_tags(
+        command,
+        &mut tx,
+        &args.names,
+        &target_commit,
+        args.message.as_deref(),
+        args.sign,
+    )?;
+    tx.finish(
+        ui,
+        format!(
+            "create tag {names} pointing to commit {id}",
+            names = args.names.join(", "),
+            id = target_commit.id().hex()
+        ),
+    )?;
+    Ok(())
+}
+
+fn cmd_tag_delete(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TagDeleteArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo().clone();
+    let matched_tags = find_tags(repo.view(), &args.names)?;
+    let mut tx = workspace_command.start_transaction();
+    for (name, _) in &matched_tags {
+        tx.mut_repo().set_tag_target(name, RefTarget::absent());
     }
+    tx.finish(
+        ui,
+        format!(
+            "delete tag {}",
+            matched_tags.iter().map(|(name, _)| name).join(", ")
+        ),
+    )?;
+    if matched_tags.len() > 1 {
+        writeln!(ui.status(), "Deleted {} tags.", matched_tags.len())?;
+    }
+    Ok(())
 }
 
 fn cmd_tag_list(
@@ -88,3 +225,125 @@ fn cmd_tag_list(
 
     Ok(())
 }
+
+fn cmd_tag_set(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TagSetArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let target_commit =
+        workspace_command.resolve_single_rev(args.revision.as_ref().unwrap_or(&RevisionArg::AT))?;
+    let mut tx = workspace_command.start_transaction();
+    set_tags(
+        command,
+        &mut tx,
+        &args.names,
+        &target_commit,
+        args.message.as_deref(),
+        args.sign,
+    )?;
+    tx.finish(
+        ui,
+        format!(
+            "point tag {names} to commit {id}",
+            names = args.names.join(", "),
+            id = target_commit.id().hex()
+        ),
+    )?;
+    Ok(())
+}
+
+/// Points the tags to `target_commit`, creating annotated tag objects if a
+/// `message` is given.
+fn set_tags(
+    command: &CommandHelper,
+    tx: &mut WorkspaceCommandTransaction,
+    names: &[String],
+    target_commit: &Commit,
+    message: Option<&str>,
+    sign: bool,
+) -> Result<(), CommandError> {
+    let Some(message) = message else {
+        for name in names {
+            tx.mut_repo()
+                .set_tag_target(name, RefTarget::normal(target_commit.id().clone()));
+        }
+        return Ok(());
+    };
+
+    let store = tx.base_repo().store().clone();
+    if target_commit.id() == store.root_commit_id() {
+        return Err(user_error(
+            "Cannot create an annotated tag for the root commit",
+        ));
+    }
+    if sign && !store.signer().can_sign() {
+        return Err(user_error_with_hint(
+            "Cannot sign the tag because no signing backend is configured",
+            "Set `signing.backend` to enable signing.",
+        ));
+    }
+    let git_repo = get_git_repo(&store)?;
+    let settings = command.settings();
+    let tagger = settings.signature();
+    let sign_key = settings.sign_settings().key;
+    let mut sign_fn = |data: &[u8]| store.signer().sign(data, sign_key.as_deref());
+    let mut tags = vec![];
+    for name in names {
+        let sign_with = sign.then_some(&mut sign_fn as &mut SigningFn);
+        let tag = git::create_annotated_tag(
+            tx.mut_repo(),
+            &git_repo,
+            name,
+            target_commit.id(),
+            &tagger,
+            message,
+            sign_with,
+        )
+        .map_err(|err| match err {
+            GitTagError::InternalGitError(err) => internal_error(err),
+            _ => user_error(err),
+        })?;
+        tags.push(tag);
+    }
+    // The Git refs are updated only once all the tag objects are written.
+    let failed_tags = git::export_annotated_tags(tx.mut_repo(), &tags)?;
+    if !failed_tags.is_empty() {
+        return Err(user_error(format!(
+            "Failed to export tags: {}",
+            failed_tags
+                .iter()
+                .map(|failed| format!("{} ({})", failed.name, failed.reason))
+                .join(", ")
+        )));
+    }
+    Ok(())
+}
+
+fn find_tags<'a>(
+    view: &'a View,
+    name_patterns: &[StringPattern],
+) -> Result<Vec<(&'a str, &'a RefTarget)>, CommandError> {
+    let mut matching_tags = vec![];
+    let mut unmatched_patterns = vec![];
+    for pattern in name_patterns {
+        let mut matches = view.tags_matching(pattern).peekable();
+        if matches.peek().is_none() {
+            unmatched_patterns.push(pattern);
+        }
+        matching_tags.extend(matches);
+    }
+    match &unmatched_patterns[..] {
+        [] => {
+            matching_tags.sort_unstable_by_key(|(name, _)| *name);
+            matching_tags.dedup_by_key(|(name, _)| *name);
+            Ok(matching_tags)
+        }
+        [pattern] if pattern.is_exact() => Err(user_error(format!("No such tag: {pattern}"))),
+        patterns => Err(user_error(format!(
+            "No matching tags for patterns: {}",
+            patterns.iter().join(", ")
+        ))),
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::io::Write;
+
+use itertools::Itertools as _;
+use jj_lib::commit::{Commit, CommitIteratorExt};
+use jj_lib::signing::SignBehavior;
+use tracing::instrument;
+
+use crate::cli_util::{CommandHelper, RevisionArg};
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Drop the cryptographic signatures of revisions
+///
+/// Rewrites the signed revisions without their signatures. Revisions that
+/// aren't signed are left alone. Descendants of the rewritten revisions are
+/// rebased onto them.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct UnsignArgs {
+    /// The revision(s) to unsign
+    #[arg(long, short, default_value = "@")]
+    revisions: Vec<RevisionArg>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_unsign(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &UnsignArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let to_unsign: Vec<Commit> = workspace_command
+        .parse_union_revsets(&args.revisions)?
+        .evaluate_to_commits()?
+        .filter_ok(|commit| commit.is_signed())
+        .try_collect()?;
+    if to_unsign.is_empty() {
+        writeln!(ui.status(), "No signed revisions to unsign.")?;
+        return Ok(());
+    }
+    workspace_command.check_rewritable(to_unsign.iter().ids())?;
+
+    let mut tx = workspace_command.start_transaction();
+    let to_unsign_ids: HashSet<_> = to_unsign.iter().ids().collect();
+    let mut unsigned_commits = vec![];
+    let mut num_rebased = 0;
+    tx.mut_repo().transform_descendants(
+        command.settings(),
+        to_unsign.iter().ids().cloned().collect_vec(),
+        |rewriter| {
+            if to_unsign_ids.contains(rewriter.old_commit().id()) {
+                let new_commit = rewriter
+                    .reparent(command.settings())?
+                    .set_sign_behavior(SignBehavior::Drop)
+                    .write()?;
+                unsigned_commits.push(new_commit);
+            } else if rewriter.parents_changed() {
+                rewriter.reparent(command.settings())?.write()?;
+                num_rebased += 1;
+            }
+            Ok(())
+        },
+    )?;
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(formatter, "Unsigned {} commits:", unsigned_commits.len())?;
+        for commit in &unsigned_commits {
+            write!(formatter, "  ")?;
+            tx.write_commit_summary(formatter.as_mut(), commit)?;
+            writeln!(formatter)?;
+        }
+        if num_rebased > 0 {
+            writeln!(formatter, "Rebased {num_rebased} descendant commits")?;
+        }
+    }
+    tx.finish(ui, format!("unsign {} commits", unsigned_commits.len()))
+}
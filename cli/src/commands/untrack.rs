@@ -72,6 +72,8 @@ pub(crate) fn cmd_untrack(
         fsmonitor_settings: command.settings().fsmonitor_settings()?,
         progress: None,
         max_new_file_size: command.settings().max_new_file_size()?,
+        conflict_marker_style: command.settings().conflict_marker_style()?,
+        submodule_store: Some(tx.base_repo().submodule_store().clone()),
     })?;
     if wc_tree_id != *new_commit.tree_id() {
         let wc_tree = store.get_root_tree(&wc_tree_id)?;
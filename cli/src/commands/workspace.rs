@@ -175,6 +175,7 @@ fn cmd_workspace_add(
 
     // Copy sparse patterns from workspace where the command was run
     let mut new_workspace_command = WorkspaceCommandHelper::new(ui, command, new_workspace, repo)?;
+    let checkout_options = new_workspace_command.checkout_options();
     let (mut locked_ws, _wc_commit) = new_workspace_command.start_working_copy_mutation()?;
     let sparse_patterns = old_workspace_command
         .working_copy()
@@ -182,7 +183,7 @@ fn cmd_workspace_add(
         .to_vec();
     locked_ws
         .locked_wc()
-        .set_sparse_patterns(sparse_patterns)
+        .set_sparse_patterns(sparse_patterns, &checkout_options)
         .map_err(|err| internal_error_with_message("Failed to set sparse patterns", err))?;
     let operation_id = locked_ws.locked_wc().old_operation_id().clone();
     locked_ws.finish(operation_id)?;
@@ -402,6 +403,7 @@ fn cmd_workspace_update_stale(
     let mut workspace_command = command.workspace_helper_no_snapshot(ui)?;
 
     let repo = workspace_command.repo().clone();
+    let checkout_options = workspace_command.checkout_options();
     let (mut locked_ws, desired_wc_commit) =
         workspace_command.unchecked_start_working_copy_mutation()?;
     match check_stale_working_copy(locked_ws.locked_wc(), &desired_wc_commit, &repo)? {
@@ -419,7 +421,7 @@ fn cmd_workspace_update_stale(
             }
             let stats = locked_ws
                 .locked_wc()
-                .check_out(&desired_wc_commit)
+                .check_out(&desired_wc_commit, &checkout_options)
                 .map_err(|err| {
                     internal_error_with_message(
                         format!(
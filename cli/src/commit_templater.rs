@@ -29,7 +29,10 @@ use jj_lib::object_id::ObjectId as _;
 use jj_lib::op_store::{RefTarget, RemoteRef, WorkspaceId};
 use jj_lib::repo::Repo;
 use jj_lib::revset::{self, Revset, RevsetExpression, RevsetModifier, RevsetParseContext};
+use jj_lib::signing::{SigStatus, Verification};
+use jj_lib::trailer::{parse_description_trailers, Trailer};
 use once_cell::unsync::OnceCell;
+use serde_json::json;
 
 use crate::template_builder::{
     self, merge_fn_map, BuildContext, CoreTemplateBuildFnTable, CoreTemplatePropertyKind,
@@ -175,6 +178,65 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
                 let build = template_parser::lookup_method(type_name, table, function)?;
                 build(self, build_ctx, property, function)
             }
+            CommitTemplatePropertyKind::CryptographicSignatureOpt(property) => {
+                let type_name = "CryptographicSignature";
+                let table = &self.build_fn_table.cryptographic_signature_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                let inner_property = property.try_unwrap(type_name);
+                build(self, build_ctx, Box::new(inner_property), function)
+            }
+            CommitTemplatePropertyKind::Trailer(property) => {
+                let table = &self.build_fn_table.trailer_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::TrailerList(property) => {
+                // TODO: migrate to table?
+                template_builder::build_formattable_list_method(
+                    self,
+                    build_ctx,
+                    property,
+                    function,
+                    Self::wrap_trailer,
+                )
+            }
+        }
+    }
+
+    fn try_into_json(
+        &self,
+        property: Self::Property,
+    ) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'repo>> {
+        let serializer = || CommitJsonSerializer {
+            repo: self.repo,
+            branches_index: self.keyword_cache.branches_index(self.repo).clone(),
+            tags_index: self.keyword_cache.tags_index(self.repo).clone(),
+        };
+        match property {
+            CommitTemplatePropertyKind::Commit(property) => {
+                let serializer = serializer();
+                Some(Box::new(
+                    property.and_then(move |commit| serializer.to_json(&commit)),
+                ))
+            }
+            CommitTemplatePropertyKind::CommitOpt(property) => {
+                let serializer = serializer();
+                Some(Box::new(property.and_then(move |opt| {
+                    let value = opt.map(|commit| serializer.to_json(&commit)).transpose()?;
+                    Ok(value.unwrap_or_default())
+                })))
+            }
+            CommitTemplatePropertyKind::CommitList(property) => {
+                let serializer = serializer();
+                Some(Box::new(property.and_then(move |commits| {
+                    let values: Vec<_> = commits
+                        .iter()
+                        .map(|commit| serializer.to_json(commit))
+                        .try_collect()?;
+                    Ok(values.into())
+                })))
+            }
+            _ => property.try_into_json(),
         }
     }
 }
@@ -245,6 +307,24 @@ impl<'repo> CommitTemplateLanguage<'repo> {
     ) -> CommitTemplatePropertyKind<'repo> {
         CommitTemplatePropertyKind::ShortestIdPrefix(Box::new(property))
     }
+
+    pub fn wrap_cryptographic_signature_opt(
+        property: impl TemplateProperty<Output = Option<CryptographicSignature>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::CryptographicSignatureOpt(Box::new(property))
+    }
+
+    pub fn wrap_trailer(
+        property: impl TemplateProperty<Output = Trailer> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::Trailer(Box::new(property))
+    }
+
+    pub fn wrap_trailer_list(
+        property: impl TemplateProperty<Output = Vec<Trailer>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::TrailerList(Box::new(property))
+    }
 }
 
 pub enum CommitTemplatePropertyKind<'repo> {
@@ -257,6 +337,11 @@ pub enum CommitTemplatePropertyKind<'repo> {
     RefNameList(Box<dyn TemplateProperty<Output = Vec<Rc<RefName>>> + 'repo>),
     CommitOrChangeId(Box<dyn TemplateProperty<Output = CommitOrChangeId> + 'repo>),
     ShortestIdPrefix(Box<dyn TemplateProperty<Output = ShortestIdPrefix> + 'repo>),
+    CryptographicSignatureOpt(
+        Box<dyn TemplateProperty<Output = Option<CryptographicSignature>> + 'repo>,
+    ),
+    Trailer(Box<dyn TemplateProperty<Output = Trailer> + 'repo>),
+    TrailerList(Box<dyn TemplateProperty<Output = Vec<Trailer>> + 'repo>),
 }
 
 impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
@@ -271,6 +356,11 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::RefNameList(_) => "List<RefName>",
             CommitTemplatePropertyKind::CommitOrChangeId(_) => "CommitOrChangeId",
             CommitTemplatePropertyKind::ShortestIdPrefix(_) => "ShortestIdPrefix",
+            CommitTemplatePropertyKind::CryptographicSignatureOpt(_) => {
+                "Option<CryptographicSignature>"
+            }
+            CommitTemplatePropertyKind::Trailer(_) => "Trailer",
+            CommitTemplatePropertyKind::TrailerList(_) => "List<Trailer>",
         }
     }
 
@@ -293,6 +383,13 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             }
             CommitTemplatePropertyKind::CommitOrChangeId(_) => None,
             CommitTemplatePropertyKind::ShortestIdPrefix(_) => None,
+            CommitTemplatePropertyKind::CryptographicSignatureOpt(property) => {
+                Some(Box::new(property.map(|opt| opt.is_some())))
+            }
+            CommitTemplatePropertyKind::Trailer(_) => None,
+            CommitTemplatePropertyKind::TrailerList(property) => {
+                Some(Box::new(property.map(|l| !l.is_empty())))
+            }
         }
     }
 
@@ -328,6 +425,58 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::ShortestIdPrefix(property) => {
                 Some(property.into_template())
             }
+            CommitTemplatePropertyKind::CryptographicSignatureOpt(_) => None,
+            CommitTemplatePropertyKind::Trailer(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::TrailerList(property) => Some(property.into_template()),
+        }
+    }
+
+    fn try_into_json(
+        self,
+    ) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'repo>> {
+        match self {
+            CommitTemplatePropertyKind::Core(property) => property.try_into_json(),
+            // Commits are serialized by CommitTemplateLanguage::try_into_json(),
+            // which can look up the refs pointing to them.
+            CommitTemplatePropertyKind::Commit(_) => None,
+            CommitTemplatePropertyKind::CommitOpt(_) => None,
+            CommitTemplatePropertyKind::CommitList(_) => None,
+            CommitTemplatePropertyKind::RefName(property) => {
+                Some(Box::new(property.map(|ref_name| ref_name.to_json())))
+            }
+            CommitTemplatePropertyKind::RefNameOpt(property) => {
+                Some(Box::new(property.map(|opt| {
+                    opt.map_or(serde_json::Value::Null, |ref_name| ref_name.to_json())
+                })))
+            }
+            CommitTemplatePropertyKind::RefNameList(property) => {
+                Some(Box::new(property.map(|ref_names| {
+                    ref_names
+                        .iter()
+                        .map(|ref_name| ref_name.to_json())
+                        .collect()
+                })))
+            }
+            CommitTemplatePropertyKind::CommitOrChangeId(property) => {
+                Some(Box::new(property.map(|id| id.hex().into())))
+            }
+            CommitTemplatePropertyKind::ShortestIdPrefix(property) => {
+                Some(Box::new(property.map(|id| {
+                    json!({
+                        "prefix": id.prefix,
+                        "rest": id.rest,
+                    })
+                })))
+            }
+            CommitTemplatePropertyKind::CryptographicSignatureOpt(_) => None,
+            CommitTemplatePropertyKind::Trailer(property) => {
+                Some(Box::new(property.map(|trailer| trailer_to_json(&trailer))))
+            }
+            CommitTemplatePropertyKind::TrailerList(property) => {
+                Some(Box::new(property.map(|trailers| {
+                    trailers.iter().map(trailer_to_json).collect()
+                })))
+            }
         }
     }
 }
@@ -343,6 +492,9 @@ pub struct CommitTemplateBuildFnTable<'repo> {
     pub ref_name_methods: CommitTemplateBuildMethodFnMap<'repo, Rc<RefName>>,
     pub commit_or_change_id_methods: CommitTemplateBuildMethodFnMap<'repo, CommitOrChangeId>,
     pub shortest_id_prefix_methods: CommitTemplateBuildMethodFnMap<'repo, ShortestIdPrefix>,
+    pub cryptographic_signature_methods:
+        CommitTemplateBuildMethodFnMap<'repo, CryptographicSignature>,
+    pub trailer_methods: CommitTemplateBuildMethodFnMap<'repo, Trailer>,
 }
 
 impl<'repo> CommitTemplateBuildFnTable<'repo> {
@@ -354,6 +506,8 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             ref_name_methods: builtin_ref_name_methods(),
             commit_or_change_id_methods: builtin_commit_or_change_id_methods(),
             shortest_id_prefix_methods: builtin_shortest_id_prefix_methods(),
+            cryptographic_signature_methods: builtin_cryptographic_signature_methods(),
+            trailer_methods: builtin_trailer_methods(),
         }
     }
 
@@ -364,6 +518,8 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             ref_name_methods: HashMap::new(),
             commit_or_change_id_methods: HashMap::new(),
             shortest_id_prefix_methods: HashMap::new(),
+            cryptographic_signature_methods: HashMap::new(),
+            trailer_methods: HashMap::new(),
         }
     }
 
@@ -374,6 +530,8 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             ref_name_methods,
             commit_or_change_id_methods,
             shortest_id_prefix_methods,
+            cryptographic_signature_methods,
+            trailer_methods,
         } = extension;
 
         self.core.merge(core);
@@ -387,6 +545,11 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             &mut self.shortest_id_prefix_methods,
             shortest_id_prefix_methods,
         );
+        merge_fn_map(
+            &mut self.cryptographic_signature_methods,
+            cryptographic_signature_methods,
+        );
+        merge_fn_map(&mut self.trailer_methods, trailer_methods);
     }
 }
 
@@ -441,6 +604,15 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             Ok(L::wrap_string(out_property))
         },
     );
+    map.insert(
+        "trailers",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property =
+                self_property.map(|commit| parse_description_trailers(commit.description()));
+            Ok(L::wrap_trailer_list(out_property))
+        },
+    );
     map.insert(
         "change_id",
         |_language, _build_ctx, self_property, function| {
@@ -484,6 +656,18 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             Ok(L::wrap_signature(out_property))
         },
     );
+    map.insert(
+        "signature",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|commit| {
+                commit
+                    .is_signed()
+                    .then(|| CryptographicSignature::new(commit))
+            });
+            Ok(L::wrap_cryptographic_signature_opt(out_property))
+        },
+    );
     map.insert("mine", |language, _build_ctx, self_property, function| {
         function.expect_no_arguments()?;
         let user_email = language.revset_parse_context.user_email().to_owned();
@@ -654,6 +838,56 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
     map
 }
 
+/// Serializes commits together with the refs and working copies pointing to
+/// them, like the `json()` template function.
+pub struct CommitJsonSerializer<'repo> {
+    repo: &'repo dyn Repo,
+    branches_index: Rc<RefNamesIndex>,
+    tags_index: Rc<RefNamesIndex>,
+}
+
+impl<'repo> CommitJsonSerializer<'repo> {
+    pub fn new(repo: &'repo dyn Repo) -> Self {
+        CommitJsonSerializer {
+            repo,
+            branches_index: Rc::new(build_branches_index(repo)),
+            tags_index: Rc::new(build_ref_names_index(repo.view().tags())),
+        }
+    }
+
+    pub fn to_json(&self, commit: &Commit) -> Result<serde_json::Value, TemplatePropertyError> {
+        let change_id = CommitOrChangeId::Change(commit.change_id().clone());
+        let ref_names_to_json = |ref_names: &[Rc<RefName>]| -> Vec<serde_json::Value> {
+            ref_names
+                .iter()
+                .map(|ref_name| ref_name.to_json())
+                .collect()
+        };
+        let working_copies = self
+            .repo
+            .view()
+            .wc_commit_ids()
+            .iter()
+            .filter(|(_, wc_commit_id)| *wc_commit_id == commit.id())
+            .map(|(workspace_id, _)| workspace_id.as_str())
+            .sorted()
+            .collect_vec();
+        Ok(json!({
+            "commit_id": commit.id().hex(),
+            "parents": commit.parent_ids().iter().map(|id| id.hex()).collect_vec(),
+            "change_id": change_id.hex(),
+            "description": commit.description(),
+            "author": template_builder::signature_to_json(commit.author())?,
+            "committer": template_builder::signature_to_json(commit.committer())?,
+            "branches": ref_names_to_json(self.branches_index.get(commit.id())),
+            "tags": ref_names_to_json(self.tags_index.get(commit.id())),
+            "working_copies": working_copies,
+            "empty": commit.is_empty(self.repo)?,
+            "conflict": commit.has_conflict()?,
+        }))
+    }
+}
+
 // TODO: return Vec<String>
 fn extract_working_copies(repo: &dyn Repo, commit: &Commit) -> String {
     let wc_commit_ids = repo.view().wc_commit_ids();
@@ -847,6 +1081,24 @@ impl RefName {
             .map_or(false, |tracking| tracking.target.is_present())
     }
 
+    /// Serializes this ref as an object with the `name`, `remote` (if any),
+    /// `target` and `tracking_target` (if tracked) fields. Targets are lists
+    /// of commit ids, which contain more than one id (or `null` for absent
+    /// sides) if the ref is conflicted.
+    fn to_json(&self) -> serde_json::Value {
+        let mut value = json!({
+            "name": self.name,
+            "target": ref_target_to_json(&self.target),
+        });
+        if let Some(remote) = &self.remote {
+            value["remote"] = remote.as_str().into();
+        }
+        if let Some(tracking) = &self.tracking_ref {
+            value["tracking_target"] = ref_target_to_json(&tracking.target);
+        }
+        value
+    }
+
     /// Number of commits ahead of the tracking local ref.
     fn tracking_ahead_count(&self, repo: &dyn Repo) -> Result<SizeHint, TemplatePropertyError> {
         let Some(tracking) = &self.tracking_ref else {
@@ -878,6 +1130,15 @@ impl RefName {
     }
 }
 
+fn ref_target_to_json(target: &RefTarget) -> serde_json::Value {
+    target
+        .as_merge()
+        .iter()
+        .map(|id| id.as_ref().map(|id| id.hex()))
+        .collect_vec()
+        .into()
+}
+
 // If wrapping with Rc<T> becomes common, add generic impl for Rc<T>.
 impl Template for Rc<RefName> {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
@@ -1212,3 +1473,98 @@ fn builtin_shortest_id_prefix_methods<'repo>(
     });
     map
 }
+
+/// Signature of a commit, which is verified lazily.
+pub struct CryptographicSignature {
+    commit: Commit,
+}
+
+impl CryptographicSignature {
+    fn new(commit: Commit) -> Self {
+        CryptographicSignature { commit }
+    }
+
+    fn status(&self) -> SigStatus {
+        // A signed commit always has a status
+        self.commit.signature_status().unwrap_or(SigStatus::Unknown)
+    }
+
+    fn verification(&self) -> Result<Option<Verification>, TemplatePropertyError> {
+        Ok(self.commit.verification()?)
+    }
+}
+
+fn builtin_cryptographic_signature_methods<'repo>(
+) -> CommitTemplateBuildMethodFnMap<'repo, CryptographicSignature> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<CryptographicSignature>::new();
+    map.insert(
+        "status",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|sig| sig.status().to_string());
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert("key", |_language, _build_ctx, self_property, function| {
+        function.expect_no_arguments()?;
+        let out_property = self_property.and_then(|sig| {
+            let verification = sig.verification()?;
+            Ok(verification.and_then(|v| v.key).unwrap_or_default())
+        });
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert(
+        "display",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.and_then(|sig| {
+                let verification = sig.verification()?;
+                Ok(verification.and_then(|v| v.display).unwrap_or_default())
+            });
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map
+}
+
+impl Template for Trailer {
+    fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
+        write!(formatter.labeled("key"), "{}", self.key)?;
+        write!(formatter, ": ")?;
+        write!(formatter.labeled("value"), "{}", self.value)
+    }
+}
+
+impl Template for Vec<Trailer> {
+    fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
+        templater::format_joined(formatter, self, "\n")
+    }
+}
+
+fn trailer_to_json(trailer: &Trailer) -> serde_json::Value {
+    json!({
+        "key": trailer.key,
+        "value": trailer.value,
+    })
+}
+
+fn builtin_trailer_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Trailer> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<Trailer>::new();
+    map.insert("key", |_language, _build_ctx, self_property, function| {
+        function.expect_no_arguments()?;
+        let out_property = self_property.map(|trailer| trailer.key);
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert("value", |_language, _build_ctx, self_property, function| {
+        function.expect_no_arguments()?;
+        let out_property = self_property.map(|trailer| trailer.value);
+        Ok(L::wrap_string(out_property))
+    });
+    map
+}
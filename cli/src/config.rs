@@ -22,6 +22,8 @@ use std::{env, fmt, slice};
 use config::Source;
 use itertools::Itertools;
 use jj_lib::settings::ConfigResultExt as _;
+use jj_lib::str_util::StringPattern;
+use once_cell::unsync::OnceCell;
 use regex::{Captures, Regex};
 use thiserror::Error;
 use tracing::instrument;
@@ -176,6 +178,7 @@ pub enum ConfigSource {
     // TODO: Track explicit file paths, especially for when user config is a dir.
     User,
     Repo,
+    Workspace,
     CommandArg,
 }
 
@@ -194,17 +197,22 @@ pub struct AnnotatedValue {
 /// 2. Base environment variables
 /// 3. [User config](https://github.com/martinvonz/jj/blob/main/docs/config.md#configuration)
 /// 4. Repo config `.jj/repo/config.toml`
-/// 5. TODO: Workspace config `.jj/config.toml`
+/// 5. Workspace config `.jj/workspace-config.toml`
 /// 6. Override environment variables
 /// 7. Command-line arguments `--config-toml`
+///
+/// The `[[--scope]]` tables of the user, repo, and workspace configs are
+/// merged into their config if the conditions match the current workspace.
 #[derive(Clone, Debug)]
 pub struct LayeredConfigs {
     default: config::Config,
     env_base: config::Config,
     user: Option<config::Config>,
     repo: Option<config::Config>,
+    workspace: Option<config::Config>,
     env_overrides: config::Config,
     arg_overrides: Option<config::Config>,
+    scope_context: ConfigScopeContext,
 }
 
 impl LayeredConfigs {
@@ -215,15 +223,19 @@ impl LayeredConfigs {
             env_base: env_base(),
             user: None,
             repo: None,
+            workspace: None,
             env_overrides: env_overrides(),
             arg_overrides: None,
+            scope_context: ConfigScopeContext::new(None),
         }
     }
 
-    #[instrument]
+    #[instrument(skip(self))]
     pub fn read_user_config(&mut self) -> Result<(), ConfigError> {
         self.user = existing_config_path()?
             .map(|path| read_config_path(&path))
+            .transpose()?
+            .map(|config| resolve_config_scopes(config, &self.scope_context))
             .transpose()?;
         Ok(())
     }
@@ -232,9 +244,20 @@ impl LayeredConfigs {
         existing_config_path()
     }
 
-    #[instrument]
-    pub fn read_repo_config(&mut self, repo_path: &Path) -> Result<(), ConfigError> {
-        self.repo = Some(read_config_file(&self.repo_config_path(repo_path))?);
+    /// Reads the repo and workspace configs of the workspace. The scopes of
+    /// the user config are re-evaluated against the workspace.
+    #[instrument(skip(self))]
+    pub fn read_workspace_configs(
+        &mut self,
+        workspace_root: &Path,
+        repo_path: &Path,
+    ) -> Result<(), ConfigError> {
+        self.scope_context = ConfigScopeContext::new(Some((workspace_root, repo_path)));
+        self.read_user_config()?;
+        let repo = read_config_file(&self.repo_config_path(repo_path))?;
+        self.repo = Some(resolve_config_scopes(repo, &self.scope_context)?);
+        let workspace = read_config_file(&self.workspace_config_path(workspace_root))?;
+        self.workspace = Some(resolve_config_scopes(workspace, &self.scope_context)?);
         Ok(())
     }
 
@@ -242,6 +265,10 @@ impl LayeredConfigs {
         repo_path.join("config.toml")
     }
 
+    pub fn workspace_config_path(&self, workspace_root: &Path) -> PathBuf {
+        workspace_root.join(".jj").join("workspace-config.toml")
+    }
+
     pub fn parse_config_args(&mut self, toml_strs: &[String]) -> Result<(), ConfigError> {
         let config = toml_strs
             .iter()
@@ -271,6 +298,7 @@ impl LayeredConfigs {
             (ConfigSource::Env, Some(&self.env_base)),
             (ConfigSource::User, self.user.as_ref()),
             (ConfigSource::Repo, self.repo.as_ref()),
+            (ConfigSource::Workspace, self.workspace.as_ref()),
             (ConfigSource::Env, Some(&self.env_overrides)),
             (ConfigSource::CommandArg, self.arg_overrides.as_ref()),
         ];
@@ -325,6 +353,171 @@ impl LayeredConfigs {
     }
 }
 
+/// Table key of the conditional config sections.
+const SCOPE_KEY: &str = "--scope";
+/// Key of the conditions table within a conditional config section.
+const SCOPE_CONDITIONS_KEY: &str = "--when";
+
+/// Information about the current workspace against which the `[[--scope]]`
+/// conditions are evaluated.
+#[derive(Clone, Debug)]
+struct ConfigScopeContext {
+    workspace_root: Option<PathBuf>,
+    repo_path: Option<PathBuf>,
+    hostname: Option<String>,
+    // Loaded on demand since it requires opening the Git repository.
+    remote_urls: OnceCell<Vec<String>>,
+}
+
+impl ConfigScopeContext {
+    fn new(workspace: Option<(&Path, &Path)>) -> Self {
+        let (workspace_root, repo_path) = workspace.unzip();
+        ConfigScopeContext {
+            workspace_root: workspace_root.map(Path::to_owned),
+            repo_path: repo_path.map(Path::to_owned),
+            hostname: whoami::fallible::hostname().ok(),
+            remote_urls: OnceCell::new(),
+        }
+    }
+
+    fn remote_urls(&self) -> &[String] {
+        self.remote_urls.get_or_init(|| {
+            self.repo_path
+                .as_deref()
+                .map(read_git_remote_urls)
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// Conditions of a `[[--scope]]` table. Each non-empty list must have a
+/// pattern matching the workspace.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigScopeConditions {
+    /// Glob patterns of the workspace root or one of its parent directories.
+    repositories: Vec<String>,
+    /// Glob patterns of the URL of any Git remote.
+    remotes: Vec<String>,
+    /// Glob patterns of the host name.
+    hostnames: Vec<String>,
+}
+
+impl ConfigScopeConditions {
+    fn matches(&self, context: &ConfigScopeContext) -> Result<bool, config::ConfigError> {
+        if !self.repositories.is_empty() {
+            let Some(workspace_root) = &context.workspace_root else {
+                return Ok(false);
+            };
+            let paths = workspace_root
+                .ancestors()
+                .filter_map(|path| path.to_str())
+                .collect_vec();
+            let patterns = self.repositories.iter().map(|pattern| {
+                match pattern.strip_prefix("~/").zip(dirs::home_dir()) {
+                    Some((rest, home_dir)) => home_dir.join(rest).to_string_lossy().into_owned(),
+                    None => pattern.clone(),
+                }
+            });
+            if !matches_any_glob(patterns, &paths)? {
+                return Ok(false);
+            }
+        }
+        if !self.remotes.is_empty() {
+            let urls = context
+                .remote_urls()
+                .iter()
+                .map(String::as_str)
+                .collect_vec();
+            if !matches_any_glob(self.remotes.iter().cloned(), &urls)? {
+                return Ok(false);
+            }
+        }
+        if !self.hostnames.is_empty() {
+            let hostnames = context.hostname.as_slice().iter().map(String::as_str);
+            if !matches_any_glob(self.hostnames.iter().cloned(), &hostnames.collect_vec())? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn matches_any_glob(
+    patterns: impl IntoIterator<Item = String>,
+    haystacks: &[&str],
+) -> Result<bool, config::ConfigError> {
+    for pattern in patterns {
+        let pattern = StringPattern::glob(&pattern).map_err(|err| {
+            config::ConfigError::Message(format!("Invalid pattern in {SCOPE_KEY}: {err}"))
+        })?;
+        if haystacks.iter().any(|haystack| pattern.matches(haystack)) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Config source backed by a table of values.
+#[derive(Clone, Debug)]
+struct ConfigTable(config::Map<String, config::Value>);
+
+impl config::Source for ConfigTable {
+    fn clone_into_box(&self) -> Box<dyn config::Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<config::Map<String, config::Value>, config::ConfigError> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Merges the `[[--scope]]` tables of the `config` whose conditions match the
+/// `context`, in order. The `[[--scope]]` tables are removed from the result.
+fn resolve_config_scopes(
+    config: config::Config,
+    context: &ConfigScopeContext,
+) -> Result<config::Config, config::ConfigError> {
+    let mut table = config.collect()?;
+    let Some(scopes) = table.remove(SCOPE_KEY) else {
+        return Ok(config);
+    };
+    let mut builder = config::Config::builder().add_source(ConfigTable(table));
+    for scope in scopes.into_array()? {
+        let mut scope_table = scope.into_table()?;
+        let conditions: ConfigScopeConditions = scope_table
+            .remove(SCOPE_CONDITIONS_KEY)
+            .map(|value| value.try_deserialize())
+            .transpose()?
+            .unwrap_or_default();
+        if conditions.matches(context)? {
+            builder = builder.add_source(ConfigTable(scope_table));
+        }
+    }
+    builder.build()
+}
+
+/// Returns the URLs of the remotes of the Git repository backing the repo at
+/// `repo_path`, if any.
+fn read_git_remote_urls(repo_path: &Path) -> Vec<String> {
+    let store_path = repo_path.join("store");
+    let Ok(git_target) = std::fs::read_to_string(store_path.join("git_target")) else {
+        return vec![];
+    };
+    let Ok(git_repo) = git2::Repository::open(store_path.join(git_target.trim())) else {
+        return vec![];
+    };
+    let Ok(remote_names) = git_repo.remotes() else {
+        return vec![];
+    };
+    remote_names
+        .iter()
+        .flatten()
+        .filter_map(|name| git_repo.find_remote(name).ok())
+        .filter_map(|remote| remote.url().map(str::to_owned))
+        .collect()
+}
+
 enum ConfigPath {
     /// Existing config file path.
     Existing(PathBuf),
@@ -880,8 +1073,10 @@ mod tests {
             env_base: empty_config.to_owned(),
             user: None,
             repo: None,
+            workspace: None,
             env_overrides: empty_config,
             arg_overrides: None,
+            scope_context: ConfigScopeContext::new(None),
         };
         assert_eq!(
             layered_configs
@@ -911,8 +1106,10 @@ mod tests {
             env_base: env_base_config,
             user: None,
             repo: Some(repo_config),
+            workspace: None,
             env_overrides: empty_config,
             arg_overrides: None,
+            scope_context: ConfigScopeContext::new(None),
         };
         // Note: "email" is alphabetized, before "name" from same layer.
         insta::assert_debug_snapshot!(
@@ -1034,8 +1231,10 @@ mod tests {
             env_base: empty_config.to_owned(),
             user: Some(user_config),
             repo: Some(repo_config),
+            workspace: None,
             env_overrides: empty_config,
             arg_overrides: None,
+            scope_context: ConfigScopeContext::new(None),
         };
         insta::assert_debug_snapshot!(
             layered_configs
@@ -1108,6 +1307,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resolve_config_scopes() {
+        let config = config::Config::builder()
+            .add_source(config::File::from_str(
+                r#"
+                user.email = "personal@example.com"
+                user.name = "Test User"
+
+                [[--scope]]
+                --when.repositories = ["/home/test/work"]
+                user.email = "work@example.com"
+
+                [[--scope]]
+                --when.remotes = ["*github.com/oss/*"]
+                --when.hostnames = ["laptop*"]
+                revset-aliases."trunk()" = "main@origin"
+
+                [[--scope]]
+                --when.hostnames = ["desktop"]
+                user.name = "Desktop User"
+                "#,
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap();
+        let context = |workspace_root: Option<&str>, remote_url: &str| ConfigScopeContext {
+            workspace_root: workspace_root.map(PathBuf::from),
+            repo_path: None,
+            hostname: Some("laptop1".to_owned()),
+            remote_urls: OnceCell::with_value(vec![remote_url.to_owned()]),
+        };
+        let resolve = |context: &ConfigScopeContext| {
+            let config = resolve_config_scopes(config.clone(), context).unwrap();
+            let table = config::ValueKind::Table(config.collect().unwrap());
+            to_toml_value(&config::Value::new(None, table))
+                .unwrap()
+                .to_string()
+        };
+
+        insta::assert_snapshot!(resolve(&context(None, "")), @r###"{ user = { email = "personal@example.com", name = "Test User" } }"###);
+        insta::assert_snapshot!(
+            resolve(&context(Some("/home/test/work/repo"), "https://github.com/oss/repo")),
+            @r###"{ revset-aliases = { "trunk()" = "main@origin" }, user = { email = "work@example.com", name = "Test User" } }"###);
+        insta::assert_snapshot!(
+            resolve(&context(Some("/home/test/workspace"), "https://github.com/oss/repo")),
+            @r###"{ revset-aliases = { "trunk()" = "main@origin" }, user = { email = "personal@example.com", name = "Test User" } }"###);
+
+        // Unknown conditions are rejected
+        let config = config::Config::builder()
+            .add_source(config::File::from_str(
+                r#"
+                [[--scope]]
+                --when.unknown = ["value"]
+                "#,
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap();
+        assert!(resolve_config_scopes(config, &context(None, "")).is_err());
+    }
+
     #[test]
     fn test_config_path_home_dir_existing() -> anyhow::Result<()> {
         TestCase {
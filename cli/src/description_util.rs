@@ -1,12 +1,17 @@
+use std::collections::HashMap;
+
 use itertools::Itertools;
 use jj_lib::commit::Commit;
+use jj_lib::commit_builder::CommitBuilder;
+use jj_lib::copies::CopyRecords;
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::settings::UserSettings;
+use jj_lib::trailer::{append_trailers, Trailer};
 
-use crate::cli_util::{edit_temp_file, WorkspaceCommandHelper};
-use crate::command_error::CommandError;
+use crate::cli_util::{edit_temp_file, short_change_hash, WorkspaceCommandHelper};
+use crate::command_error::{user_error, user_error_with_hint, CommandError};
 use crate::diff_util::DiffFormat;
 use crate::formatter::PlainTextFormatter;
 use crate::text_util;
@@ -40,6 +45,102 @@ JJ: Lines starting with "JJ: " (like this one) will be removed.
     Ok(text_util::complete_newline(description.trim_matches('\n')))
 }
 
+/// Edits the descriptions of the `commits` in a single editor buffer.
+///
+/// Each description is preceded by a `JJ: describe <change id> -------` line.
+/// Returns the new descriptions in the same order as the `commits`.
+pub fn edit_multiple_descriptions(
+    ui: &Ui,
+    settings: &UserSettings,
+    workspace_command: &WorkspaceCommandHelper,
+    commits: &[Commit],
+) -> Result<Vec<String>, CommandError> {
+    let mut change_hashes: Vec<String> = Vec::with_capacity(commits.len());
+    let mut content = r#"JJ: Enter or edit commit descriptions after the `JJ: describe` lines.
+JJ: The text you enter will be lost if the `JJ: describe` lines are changed.
+
+"#
+    .to_owned();
+    for commit in commits {
+        let change_hash = short_change_hash(commit.change_id());
+        if change_hashes.contains(&change_hash) {
+            return Err(user_error_with_hint(
+                format!("Cannot describe divergent commits of change {change_hash} at once"),
+                "Describe the divergent commits one at a time.",
+            ));
+        }
+        let template = description_template_for_describe(ui, settings, workspace_command, commit)?;
+        content.push_str(&format!("JJ: describe {change_hash} -------\n"));
+        content.push_str(&text_util::complete_newline(template));
+        content.push('\n');
+        change_hashes.push(change_hash);
+    }
+    content.push_str("JJ: Lines starting with \"JJ: \" (like this one) will be removed.\n");
+
+    let content = edit_temp_file(
+        "description",
+        ".jjdescription",
+        workspace_command.repo().repo_path(),
+        &content,
+        settings,
+    )?;
+
+    let mut descriptions: HashMap<&str, String> = HashMap::new();
+    for (change_hash, description) in parse_bulk_edit_message(&content)? {
+        if !change_hashes.iter().any(|hash| hash == change_hash) {
+            return Err(user_error(format!(
+                "Unexpected change {change_hash} in the edited descriptions"
+            )));
+        }
+        if descriptions.insert(change_hash, description).is_some() {
+            return Err(user_error(format!(
+                "Change {change_hash} is described more than once"
+            )));
+        }
+    }
+    change_hashes
+        .iter()
+        .map(|change_hash| {
+            descriptions.remove(change_hash.as_str()).ok_or_else(|| {
+                user_error(format!(
+                    "Missing description for change {change_hash} in the edited descriptions"
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Splits the buffer edited by `edit_multiple_descriptions()` into pairs of
+/// change id and description.
+fn parse_bulk_edit_message(content: &str) -> Result<Vec<(&str, String)>, CommandError> {
+    let mut sections: Vec<(&str, Vec<&str>)> = vec![];
+    for line in content.lines() {
+        if let Some(change_hash) = line
+            .strip_prefix("JJ: describe ")
+            .and_then(|rest| rest.strip_suffix(" -------"))
+        {
+            sections.push((change_hash.trim(), vec![]));
+        } else if line.starts_with("JJ: ") {
+            continue;
+        } else if let Some((_, lines)) = sections.last_mut() {
+            lines.push(line);
+        } else if !line.trim().is_empty() {
+            return Err(user_error(format!(
+                "Found the following line without a `JJ: describe` header: {line}"
+            )));
+        }
+    }
+    Ok(sections
+        .into_iter()
+        .map(|(change_hash, lines)| {
+            // Normalize line ending, remove leading and trailing blank lines.
+            let description = lines.join("\n");
+            let description = text_util::complete_newline(description.trim_matches('\n'));
+            (change_hash, description)
+        })
+        .collect())
+}
+
 /// Combines the descriptions from the input commits. If only one is non-empty,
 /// then that one is used. Otherwise we concatenate the messages and ask the
 /// user to edit the result in their editor.
@@ -90,6 +191,43 @@ pub fn join_message_paragraphs(paragraphs: &[String]) -> String {
         .join("\n")
 }
 
+/// Appends the `extra_trailers` and the trailers generated by the
+/// `templates.commit_trailers` template to the description of the
+/// `commit_builder`.
+///
+/// The template is evaluated against the commit to be written by the
+/// `commit_builder`. It's skipped if the description is empty.
+pub fn add_trailers(
+    settings: &UserSettings,
+    workspace_command: &WorkspaceCommandHelper,
+    commit_builder: &CommitBuilder,
+    extra_trailers: &[Trailer],
+) -> Result<String, CommandError> {
+    let description = commit_builder.description();
+    let mut trailers = extra_trailers.to_vec();
+    let template_text = settings.config().get_string("templates.commit_trailers")?;
+    if !template_text.is_empty() && !description.trim().is_empty() {
+        let template = workspace_command.parse_commit_template(&template_text)?;
+        let new_commit = commit_builder.write_hidden()?;
+        let mut output = Vec::new();
+        template
+            .format(&new_commit, &mut PlainTextFormatter::new(&mut output))
+            .expect("write() to PlainTextFormatter should never fail");
+        let output = String::from_utf8(output).expect("template output should be utf-8 bytes");
+        for line in output.lines().filter(|line| !line.trim().is_empty()) {
+            let trailer = Trailer::parse(line).ok_or_else(|| {
+                user_error_with_hint(
+                    format!("Invalid trailer: {line}"),
+                    "The templates.commit_trailers template should produce lines of `KEY: \
+                     VALUE`.",
+                )
+            })?;
+            trailers.push(trailer);
+        }
+    }
+    Ok(append_trailers(description, &trailers))
+}
+
 pub fn description_template_for_describe(
     ui: &Ui,
     settings: &UserSettings,
@@ -133,6 +271,7 @@ pub fn description_template_for_commit(
         from_tree,
         to_tree,
         &EverythingMatcher,
+        &CopyRecords::default(),
     )?;
     let mut template_chunks = Vec::new();
     if !intro.is_empty() {
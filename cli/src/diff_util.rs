@@ -19,9 +19,10 @@ use std::ops::Range;
 
 use futures::{try_join, Stream, StreamExt};
 use itertools::Itertools;
-use jj_lib::backend::{BackendError, BackendResult, TreeValue};
+use jj_lib::backend::{BackendError, BackendResult, CommitId, TreeValue};
 use jj_lib::commit::Commit;
-use jj_lib::conflicts::{materialize_tree_value, MaterializedTreeValue};
+use jj_lib::conflicts::{materialize_tree_value, ConflictMarkerStyle, MaterializedTreeValue};
+use jj_lib::copies::{CopiesTreeDiffEntry, CopiesTreeDiffStream, CopyOperation, CopyRecords};
 use jj_lib::diff::{Diff, DiffHunk};
 use jj_lib::files::DiffLine;
 use jj_lib::matchers::Matcher;
@@ -217,6 +218,7 @@ pub enum DiffRenderError {
 pub struct DiffRenderer<'a> {
     repo: &'a dyn Repo,
     path_converter: &'a RepoPathUiConverter,
+    conflict_marker_style: ConflictMarkerStyle,
     formats: Vec<DiffFormat>,
 }
 
@@ -224,12 +226,14 @@ impl<'a> DiffRenderer<'a> {
     pub fn new(
         repo: &'a dyn Repo,
         path_converter: &'a RepoPathUiConverter,
+        conflict_marker_style: ConflictMarkerStyle,
         formats: Vec<DiffFormat>,
     ) -> Self {
         DiffRenderer {
             repo,
             formats,
             path_converter,
+            conflict_marker_style,
         }
     }
 
@@ -241,23 +245,35 @@ impl<'a> DiffRenderer<'a> {
         from_tree: &MergedTree,
         to_tree: &MergedTree,
         matcher: &dyn Matcher,
+        copy_records: &CopyRecords,
     ) -> Result<(), DiffRenderError> {
         let repo = self.repo;
         let path_converter = self.path_converter;
+        let conflict_marker_style = self.conflict_marker_style;
         for format in &self.formats {
             match format {
                 DiffFormat::Summary => {
-                    let tree_diff = from_tree.diff_stream(to_tree, matcher);
+                    let tree_diff =
+                        from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
                     show_diff_summary(formatter, tree_diff, path_converter)?;
                 }
                 DiffFormat::Stat => {
-                    let tree_diff = from_tree.diff_stream(to_tree, matcher);
+                    let tree_diff =
+                        from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
                     // TODO: In graph log, graph width should be subtracted
                     let width = usize::from(ui.term_width().unwrap_or(80));
-                    show_diff_stat(repo, formatter, tree_diff, path_converter, width)?;
+                    show_diff_stat(
+                        repo,
+                        formatter,
+                        tree_diff,
+                        path_converter,
+                        conflict_marker_style,
+                        width,
+                    )?;
                 }
                 DiffFormat::Types => {
-                    let tree_diff = from_tree.diff_stream(to_tree, matcher);
+                    let tree_diff =
+                        from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
                     show_types(formatter, tree_diff, path_converter)?;
                 }
                 DiffFormat::NameOnly => {
@@ -265,12 +281,21 @@ impl<'a> DiffRenderer<'a> {
                     show_names(formatter, tree_diff, path_converter)?;
                 }
                 DiffFormat::Git { context } => {
-                    let tree_diff = from_tree.diff_stream(to_tree, matcher);
-                    show_git_diff(repo, formatter, *context, tree_diff)?;
+                    let tree_diff =
+                        from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
+                    show_git_diff(repo, formatter, *context, tree_diff, conflict_marker_style)?;
                 }
                 DiffFormat::ColorWords { context } => {
-                    let tree_diff = from_tree.diff_stream(to_tree, matcher);
-                    show_color_words_diff(repo, formatter, *context, tree_diff, path_converter)?;
+                    let tree_diff =
+                        from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
+                    show_color_words_diff(
+                        repo,
+                        formatter,
+                        *context,
+                        tree_diff,
+                        path_converter,
+                        conflict_marker_style,
+                    )?;
                 }
                 DiffFormat::Tool(tool) => {
                     merge_tools::generate_diff(
@@ -280,6 +305,7 @@ impl<'a> DiffRenderer<'a> {
                         to_tree,
                         matcher,
                         tool,
+                        conflict_marker_style,
                     )
                     .map_err(DiffRenderError::DiffGenerate)?;
                 }
@@ -298,10 +324,30 @@ impl<'a> DiffRenderer<'a> {
     ) -> Result<(), DiffRenderError> {
         let from_tree = commit.parent_tree(self.repo)?;
         let to_tree = commit.tree()?;
-        self.show_diff(ui, formatter, &from_tree, &to_tree, matcher)
+        let copy_records = match commit.parent_ids() {
+            [parent_id] => get_copy_records(self.repo.store(), parent_id, commit.id())?,
+            _ => CopyRecords::default(),
+        };
+        self.show_diff(ui, formatter, &from_tree, &to_tree, matcher, &copy_records)
     }
 }
 
+/// Collects the copies and renames between the `root` and `head` commits.
+pub fn get_copy_records(
+    store: &Store,
+    root: &CommitId,
+    head: &CommitId,
+) -> BackendResult<CopyRecords> {
+    let mut copy_records = CopyRecords::default();
+    copy_records.add_records(
+        store
+            .get_copy_records(None, root, head)?
+            .collect::<Vec<_>>()
+            .block_on(),
+    )?;
+    Ok(copy_records)
+}
+
 fn show_color_words_diff_hunks(
     left: &[u8],
     right: &[u8],
@@ -518,14 +564,15 @@ pub fn show_color_words_diff(
     repo: &dyn Repo,
     formatter: &mut dyn Formatter,
     num_context_lines: usize,
-    tree_diff: TreeDiffStream,
+    tree_diff: CopiesTreeDiffStream,
     path_converter: &RepoPathUiConverter,
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<(), DiffRenderError> {
     formatter.push_label("diff")?;
-    let mut diff_stream = materialized_diff_stream(repo.store(), tree_diff);
+    let mut diff_stream = materialized_diff_stream(repo.store(), tree_diff, conflict_marker_style);
     async {
-        while let Some((path, diff)) = diff_stream.next().await {
-            let ui_path = path_converter.format_file_path(&path);
+        while let Some((entry, diff)) = diff_stream.next().await {
+            let ui_path = entry.format_ui_path(path_converter);
             let (left_value, right_value) = diff?;
 
             match (&left_value, &right_value) {
@@ -546,7 +593,7 @@ pub fn show_color_words_diff(
                     formatter.labeled("header"),
                     "Added {description} {ui_path}:"
                 )?;
-                let right_content = diff_content(&path, right_value)?;
+                let right_content = diff_content(&entry.target, right_value)?;
                 if right_content.is_empty() {
                     writeln!(formatter.labeled("empty"), "    (empty)")?;
                 } else if right_content.is_binary {
@@ -571,7 +618,15 @@ pub fn show_color_words_diff(
                             ..
                         },
                     ) => {
-                        if *left_executable && *right_executable {
+                        if let (Some(copy_operation), true) =
+                            (entry.copy_operation, left_executable == right_executable)
+                        {
+                            let operation = match copy_operation {
+                                CopyOperation::Rename => "Renamed",
+                                CopyOperation::Copy => "Copied",
+                            };
+                            format!("{operation} {}", basic_diff_file_type(&right_value))
+                        } else if *left_executable && *right_executable {
                             "Modified executable file".to_string()
                         } else if *left_executable {
                             "Executable file became non-executable at".to_string()
@@ -595,6 +650,10 @@ pub fn show_color_words_diff(
                         MaterializedTreeValue::Symlink { .. },
                         MaterializedTreeValue::Symlink { .. },
                     ) => "Symlink target changed at".to_string(),
+                    (
+                        MaterializedTreeValue::GitSubmodule(_),
+                        MaterializedTreeValue::GitSubmodule(_),
+                    ) => "Modified Git submodule".to_string(),
                     (_, _) => {
                         let left_type = basic_diff_file_type(&left_value);
                         let right_type = basic_diff_file_type(&right_value);
@@ -607,8 +666,8 @@ pub fn show_color_words_diff(
                         )
                     }
                 };
-                let left_content = diff_content(&path, left_value)?;
-                let right_content = diff_content(&path, right_value)?;
+                let left_content = diff_content(&entry.source, left_value)?;
+                let right_content = diff_content(&entry.target, right_value)?;
                 writeln!(formatter.labeled("header"), "{description} {ui_path}:")?;
                 if left_content.is_binary || right_content.is_binary {
                     writeln!(formatter.labeled("binary"), "    (binary)")?;
@@ -626,7 +685,7 @@ pub fn show_color_words_diff(
                     formatter.labeled("header"),
                     "Removed {description} {ui_path}:"
                 )?;
-                let left_content = diff_content(&path, left_value)?;
+                let left_content = diff_content(&entry.source, left_value)?;
                 if left_content.is_empty() {
                     writeln!(formatter.labeled("empty"), "    (empty)")?;
                 } else if left_content.is_binary {
@@ -692,10 +751,9 @@ fn git_diff_part(
             contents = target.into_bytes();
         }
         MaterializedTreeValue::GitSubmodule(id) => {
-            // TODO: What should we actually do here?
-            mode = "040000".to_string();
+            mode = "160000".to_string();
+            contents = format!("Subproject commit {}\n", id.hex()).into_bytes();
             hash = id.hex();
-            contents = vec![];
         }
         MaterializedTreeValue::Conflict {
             id: _,
@@ -859,26 +917,58 @@ fn show_unified_diff_hunks(
     Ok(())
 }
 
+/// Source and target paths of a diff entry, and how the target was created
+/// from the source if they differ.
+struct CopiesDiffPaths {
+    source: RepoPathBuf,
+    target: RepoPathBuf,
+    copy_operation: Option<CopyOperation>,
+}
+
+impl CopiesDiffPaths {
+    fn format_ui_path(&self, path_converter: &RepoPathUiConverter) -> String {
+        if self.copy_operation.is_some() {
+            path_converter.format_copied_path(&self.source, &self.target)
+        } else {
+            path_converter.format_file_path(&self.target)
+        }
+    }
+}
+
 fn materialized_diff_stream<'a>(
     store: &'a Store,
-    tree_diff: TreeDiffStream<'a>,
+    tree_diff: CopiesTreeDiffStream<'a>,
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> impl Stream<
     Item = (
-        RepoPathBuf,
+        CopiesDiffPaths,
         BackendResult<(MaterializedTreeValue, MaterializedTreeValue)>,
     ),
 > + 'a {
     tree_diff
-        .map(|(path, diff)| async {
-            match diff {
-                Err(err) => (path, Err(err)),
+        .map(move |entry| async move {
+            let CopiesTreeDiffEntry {
+                source,
+                target,
+                copy_operation,
+                value,
+            } = entry;
+            let values = match value {
+                Err(err) => Err(err),
                 Ok((before, after)) => {
-                    let before_future = materialize_tree_value(store, &path, before);
-                    let after_future = materialize_tree_value(store, &path, after);
-                    let values = try_join!(before_future, after_future);
-                    (path, values)
+                    let before_future =
+                        materialize_tree_value(store, &source, before, conflict_marker_style);
+                    let after_future =
+                        materialize_tree_value(store, &target, after, conflict_marker_style);
+                    try_join!(before_future, after_future)
                 }
-            }
+            };
+            let paths = CopiesDiffPaths {
+                source,
+                target,
+                copy_operation,
+            };
+            (paths, values)
         })
         .buffered((store.concurrency() / 2).max(1))
 }
@@ -887,17 +977,20 @@ pub fn show_git_diff(
     repo: &dyn Repo,
     formatter: &mut dyn Formatter,
     num_context_lines: usize,
-    tree_diff: TreeDiffStream,
+    tree_diff: CopiesTreeDiffStream,
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<(), DiffRenderError> {
     formatter.push_label("diff")?;
 
-    let mut diff_stream = materialized_diff_stream(repo.store(), tree_diff);
+    let mut diff_stream = materialized_diff_stream(repo.store(), tree_diff, conflict_marker_style);
     async {
-        while let Some((path, diff)) = diff_stream.next().await {
+        while let Some((entry, diff)) = diff_stream.next().await {
+            let path = &entry.target;
             let path_string = path.as_internal_file_string();
+            let source_string = entry.source.as_internal_file_string();
             let (left_value, right_value) = diff?;
             if left_value.is_absent() {
-                let right_part = git_diff_part(&path, right_value)?;
+                let right_part = git_diff_part(path, right_value)?;
                 formatter.with_label("file_header", |formatter| {
                     writeln!(formatter, "diff --git a/{path_string} b/{path_string}")?;
                     writeln!(formatter, "new file mode {}", &right_part.mode)?;
@@ -907,13 +1000,26 @@ pub fn show_git_diff(
                 })?;
                 show_unified_diff_hunks(formatter, &[], &right_part.content, num_context_lines)?;
             } else if right_value.is_present() {
-                let left_part = git_diff_part(&path, left_value)?;
-                let right_part = git_diff_part(&path, right_value)?;
+                let left_part = git_diff_part(&entry.source, left_value)?;
+                let right_part = git_diff_part(path, right_value)?;
                 formatter.with_label("file_header", |formatter| {
-                    writeln!(formatter, "diff --git a/{path_string} b/{path_string}")?;
+                    writeln!(formatter, "diff --git a/{source_string} b/{path_string}")?;
                     if left_part.mode != right_part.mode {
                         writeln!(formatter, "old mode {}", &left_part.mode)?;
                         writeln!(formatter, "new mode {}", &right_part.mode)?;
+                    }
+                    match entry.copy_operation {
+                        Some(CopyOperation::Rename) => {
+                            writeln!(formatter, "rename from {source_string}")?;
+                            writeln!(formatter, "rename to {path_string}")?;
+                        }
+                        Some(CopyOperation::Copy) => {
+                            writeln!(formatter, "copy from {source_string}")?;
+                            writeln!(formatter, "copy to {path_string}")?;
+                        }
+                        None => {}
+                    }
+                    if left_part.mode != right_part.mode {
                         if left_part.hash != right_part.hash {
                             writeln!(formatter, "index {}...{}", &left_part.hash, right_part.hash)?;
                         }
@@ -925,7 +1031,7 @@ pub fn show_git_diff(
                         )?;
                     }
                     if left_part.content != right_part.content {
-                        writeln!(formatter, "--- a/{path_string}")?;
+                        writeln!(formatter, "--- a/{source_string}")?;
                         writeln!(formatter, "+++ b/{path_string}")?;
                     }
                     Ok(())
@@ -937,7 +1043,7 @@ pub fn show_git_diff(
                     num_context_lines,
                 )?;
             } else {
-                let left_part = git_diff_part(&path, left_value)?;
+                let left_part = git_diff_part(path, left_value)?;
                 formatter.with_label("file_header", |formatter| {
                     writeln!(formatter, "diff --git a/{path_string} b/{path_string}")?;
                     writeln!(formatter, "deleted file mode {}", &left_part.mode)?;
@@ -958,20 +1064,31 @@ pub fn show_git_diff(
 #[instrument(skip_all)]
 pub fn show_diff_summary(
     formatter: &mut dyn Formatter,
-    mut tree_diff: TreeDiffStream,
+    mut tree_diff: CopiesTreeDiffStream,
     path_converter: &RepoPathUiConverter,
 ) -> io::Result<()> {
     formatter.with_label("diff", |formatter| -> io::Result<()> {
         async {
-            while let Some((repo_path, diff)) = tree_diff.next().await {
-                let (before, after) = diff.unwrap();
-                let ui_path = path_converter.format_file_path(&repo_path);
+            while let Some(entry) = tree_diff.next().await {
+                let (before, after) = entry.value.unwrap();
+                if let Some(copy_operation) = entry.copy_operation {
+                    let ui_path = path_converter.format_copied_path(&entry.source, &entry.target);
+                    match copy_operation {
+                        CopyOperation::Rename => {
+                            writeln!(formatter.labeled("renamed"), "R {ui_path}")?;
+                        }
+                        CopyOperation::Copy => {
+                            writeln!(formatter.labeled("copied"), "C {ui_path}")?;
+                        }
+                    }
+                    continue;
+                }
+                let ui_path = path_converter.format_file_path(&entry.target);
                 if before.is_present() && after.is_present() {
                     writeln!(formatter.labeled("modified"), "M {ui_path}")?;
                 } else if before.is_absent() {
                     writeln!(formatter.labeled("added"), "A {ui_path}")?;
                 } else {
-                    // `R` could be interpreted as "renamed"
                     writeln!(formatter.labeled("removed"), "D {ui_path}")?;
                 }
             }
@@ -981,6 +1098,45 @@ pub fn show_diff_summary(
     })
 }
 
+/// Serializes the entries of `tree_diff` as a list of objects with `status`
+/// (`"modified"`, `"added"`, `"removed"`, `"copied"` or `"renamed"`) and
+/// `path` fields. Copied and renamed files also have a `source` field. The
+/// paths are relative to the workspace root.
+pub fn diff_summary_to_json(
+    mut tree_diff: CopiesTreeDiffStream,
+) -> BackendResult<serde_json::Value> {
+    async {
+        let mut entries = vec![];
+        while let Some(entry) = tree_diff.next().await {
+            let (before, after) = entry.value?;
+            let target = entry.target.as_internal_file_string();
+            let value = match entry.copy_operation {
+                Some(copy_operation) => {
+                    let status = match copy_operation {
+                        CopyOperation::Rename => "renamed",
+                        CopyOperation::Copy => "copied",
+                    };
+                    let source = entry.source.as_internal_file_string();
+                    serde_json::json!({ "status": status, "path": target, "source": source })
+                }
+                None => {
+                    let status = if before.is_present() && after.is_present() {
+                        "modified"
+                    } else if before.is_absent() {
+                        "added"
+                    } else {
+                        "removed"
+                    };
+                    serde_json::json!({ "status": status, "path": target })
+                }
+            };
+            entries.push(value);
+        }
+        Ok(entries.into())
+    }
+    .block_on()
+}
+
 struct DiffStat {
     path: String,
     added: usize,
@@ -1021,21 +1177,22 @@ fn get_diff_stat(
 pub fn show_diff_stat(
     repo: &dyn Repo,
     formatter: &mut dyn Formatter,
-    tree_diff: TreeDiffStream,
+    tree_diff: CopiesTreeDiffStream,
     path_converter: &RepoPathUiConverter,
+    conflict_marker_style: ConflictMarkerStyle,
     display_width: usize,
 ) -> Result<(), DiffRenderError> {
     let mut stats: Vec<DiffStat> = vec![];
     let mut max_path_width = 0;
     let mut max_diffs = 0;
 
-    let mut diff_stream = materialized_diff_stream(repo.store(), tree_diff);
+    let mut diff_stream = materialized_diff_stream(repo.store(), tree_diff, conflict_marker_style);
     async {
-        while let Some((repo_path, diff)) = diff_stream.next().await {
+        while let Some((entry, diff)) = diff_stream.next().await {
             let (left, right) = diff?;
-            let path = path_converter.format_file_path(&repo_path);
-            let left_content = diff_content(&repo_path, left)?;
-            let right_content = diff_content(&repo_path, right)?;
+            let path = entry.format_ui_path(path_converter);
+            let left_content = diff_content(&entry.source, left)?;
+            let right_content = diff_content(&entry.target, right)?;
             max_path_width = max(max_path_width, path.width());
             let stat = get_diff_stat(path, &left_content, &right_content);
             max_diffs = max(max_diffs, stat.added + stat.removed);
@@ -1097,19 +1254,24 @@ pub fn show_diff_stat(
 
 pub fn show_types(
     formatter: &mut dyn Formatter,
-    mut tree_diff: TreeDiffStream,
+    mut tree_diff: CopiesTreeDiffStream,
     path_converter: &RepoPathUiConverter,
 ) -> io::Result<()> {
     formatter.with_label("diff", |formatter| {
         async {
-            while let Some((repo_path, diff)) = tree_diff.next().await {
-                let (before, after) = diff.unwrap();
+            while let Some(entry) = tree_diff.next().await {
+                let (before, after) = entry.value.unwrap();
+                let ui_path = if entry.copy_operation.is_some() {
+                    path_converter.format_copied_path(&entry.source, &entry.target)
+                } else {
+                    path_converter.format_file_path(&entry.target)
+                };
                 writeln!(
                     formatter.labeled("modified"),
                     "{}{} {}",
                     diff_summary_char(&before),
                     diff_summary_char(&after),
-                    path_converter.format_file_path(&repo_path)
+                    ui_path
                 )?;
             }
             Ok(())
@@ -32,6 +32,12 @@ pub trait Formatter: Write {
     fn push_label(&mut self, label: &str) -> io::Result<()>;
 
     fn pop_label(&mut self) -> io::Result<()>;
+
+    /// Starts a hyperlink to `url`. Formatters which don't support hyperlinks
+    /// write the text as is.
+    fn push_hyperlink(&mut self, url: &str) -> io::Result<()>;
+
+    fn pop_hyperlink(&mut self) -> io::Result<()>;
 }
 
 impl dyn Formatter + '_ {
@@ -207,6 +213,14 @@ impl<W: Write> Formatter for PlainTextFormatter<W> {
     fn pop_label(&mut self) -> io::Result<()> {
         Ok(())
     }
+
+    fn push_hyperlink(&mut self, _url: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        Ok(())
+    }
 }
 
 pub struct SanitizingFormatter<W> {
@@ -242,6 +256,14 @@ impl<W: Write> Formatter for SanitizingFormatter<W> {
     fn pop_label(&mut self) -> io::Result<()> {
         Ok(())
     }
+
+    fn push_hyperlink(&mut self, _url: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -272,6 +294,11 @@ pub struct ColorFormatter<W: Write> {
     cached_styles: HashMap<Vec<String>, Style>,
     /// The style we last wrote to the output.
     current_style: Style,
+    /// The stack of currently applied hyperlink URLs. The innermost one is
+    /// the desired hyperlink.
+    hyperlinks: Vec<String>,
+    /// The hyperlink we last wrote to the output.
+    current_hyperlink: Option<String>,
 }
 
 impl<W: Write> ColorFormatter<W> {
@@ -283,6 +310,8 @@ impl<W: Write> ColorFormatter<W> {
             labels: vec![],
             cached_styles: HashMap::new(),
             current_style: Style::default(),
+            hyperlinks: vec![],
+            current_hyperlink: None,
         }
     }
 
@@ -372,6 +401,17 @@ impl<W: Write> ColorFormatter<W> {
         }
         Ok(())
     }
+
+    fn write_new_hyperlink(&mut self) -> io::Result<()> {
+        let new_hyperlink = self.hyperlinks.last();
+        if new_hyperlink != self.current_hyperlink.as_ref() {
+            // OSC 8 escape sequence. An empty URL ends the hyperlink.
+            let url = new_hyperlink.map_or("", String::as_str);
+            write!(self.output, "\x1b]8;;{url}\x1b\\")?;
+            self.current_hyperlink = new_hyperlink.cloned();
+        }
+        Ok(())
+    }
 }
 
 fn rules_from_config(config: &config::Config) -> Result<Rules, config::ConfigError> {
@@ -492,6 +532,7 @@ impl<W: Write> Write for ColorFormatter<W> {
         for line in data.split_inclusive(|b| *b == b'\n') {
             if line.ends_with(b"\n") {
                 self.write_new_style()?;
+                self.write_new_hyperlink()?;
                 write_line_exclusive(
                     &mut self.output,
                     &line[..line.len() - 1],
@@ -499,11 +540,15 @@ impl<W: Write> Write for ColorFormatter<W> {
                     self.debug,
                 )?;
                 let labels = mem::take(&mut self.labels);
+                let hyperlinks = mem::take(&mut self.hyperlinks);
+                self.write_new_hyperlink()?;
                 self.write_new_style()?;
                 self.output.write_all(b"\n")?;
                 self.labels = labels;
+                self.hyperlinks = hyperlinks;
             } else {
                 self.write_new_style()?;
+                self.write_new_hyperlink()?;
                 write_line_exclusive(&mut self.output, line, &self.labels, self.debug)?;
             }
         }
@@ -550,6 +595,21 @@ impl<W: Write> Formatter for ColorFormatter<W> {
         }
         Ok(())
     }
+
+    fn push_hyperlink(&mut self, url: &str) -> io::Result<()> {
+        // Control characters would terminate the escape sequence early.
+        let url = url.chars().filter(|c| !c.is_control()).collect();
+        self.hyperlinks.push(url);
+        Ok(())
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        self.hyperlinks.pop();
+        if self.hyperlinks.is_empty() {
+            self.write_new_hyperlink()?
+        }
+        Ok(())
+    }
 }
 
 impl<W: Write> Drop for ColorFormatter<W> {
@@ -558,10 +618,13 @@ impl<W: Write> Drop for ColorFormatter<W> {
         // because of an error), let's still try to reset any currently active style.
         self.labels.clear();
         self.write_new_style().ok();
+        self.hyperlinks.clear();
+        self.write_new_hyperlink().ok();
     }
 }
 
-/// Like buffered formatter, but records `push`/`pop_label()` calls.
+/// Like buffered formatter, but records `push`/`pop_label()` and
+/// `push`/`pop_hyperlink()` calls.
 ///
 /// This allows you to manipulate the recorded data without losing labels.
 /// The recorded data and labels can be written to another formatter. If
@@ -578,6 +641,8 @@ pub struct FormatRecorder {
 enum LabelOp {
     PushLabel(String),
     PopLabel,
+    PushHyperlink(String),
+    PopHyperlink,
 }
 
 impl FormatRecorder {
@@ -617,6 +682,8 @@ impl FormatRecorder {
             match op {
                 LabelOp::PushLabel(label) => formatter.push_label(label)?,
                 LabelOp::PopLabel => formatter.pop_label()?,
+                LabelOp::PushHyperlink(url) => formatter.push_hyperlink(url)?,
+                LabelOp::PopHyperlink => formatter.pop_hyperlink()?,
             }
         }
         flush_data(formatter, self.data.len())
@@ -648,6 +715,16 @@ impl Formatter for FormatRecorder {
         self.push_label_op(LabelOp::PopLabel);
         Ok(())
     }
+
+    fn push_hyperlink(&mut self, url: &str) -> io::Result<()> {
+        self.push_label_op(LabelOp::PushHyperlink(url.to_owned()));
+        Ok(())
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        self.push_label_op(LabelOp::PopHyperlink);
+        Ok(())
+    }
 }
 
 fn write_sanitized(output: &mut impl Write, buf: &[u8]) -> Result<(), Error> {
@@ -820,6 +897,45 @@ mod tests {
         insta::assert_snapshot!(String::from_utf8(output).unwrap(), @" before [38;5;2m inside [39m after ");
     }
 
+    #[test]
+    fn test_color_formatter_hyperlink() {
+        // Test that a hyperlink is ended at the end of each line and after the
+        // linked text, and that control characters are removed from the URL.
+        let config = config_from_string(
+            r#"
+        colors.inside = "green"
+        "#,
+        );
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = ColorFormatter::for_config(&mut output, &config, false).unwrap();
+        write!(formatter, " before ").unwrap();
+        formatter
+            .push_hyperlink("https://example.com/\x1b\x07")
+            .unwrap();
+        formatter.push_label("inside").unwrap();
+        write!(formatter, " first \n second ").unwrap();
+        formatter.pop_label().unwrap();
+        formatter.pop_hyperlink().unwrap();
+        write!(formatter, " after ").unwrap();
+        drop(formatter);
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r###"
+         before [38;5;2m]8;;https://example.com/\ first ]8;;\[39m
+        [38;5;2m]8;;https://example.com/\ second [39m]8;;\ after 
+        "###);
+    }
+
+    #[test]
+    fn test_plaintext_formatter_hyperlink() {
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = PlainTextFormatter::new(&mut output);
+        write!(formatter, " before ").unwrap();
+        formatter.push_hyperlink("https://example.com/").unwrap();
+        write!(formatter, " inside ").unwrap();
+        formatter.pop_hyperlink().unwrap();
+        write!(formatter, " after ").unwrap();
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @" before  inside  after ");
+    }
+
     #[test]
     fn test_color_formatter_attributes() {
         // Test that each attribute of the style can be set and that they can be
@@ -1237,4 +1353,21 @@ mod tests {
             String::from_utf8(output).unwrap(),
             @"<< outer1 >>[38;5;1m<< inner1  inner2 >>[39m<< outer2 >>");
     }
+
+    #[test]
+    fn test_format_recorder_hyperlink() {
+        let mut recorder = FormatRecorder::new();
+        write!(recorder, " outer ").unwrap();
+        recorder.push_hyperlink("https://example.com/").unwrap();
+        write!(recorder, " inner ").unwrap();
+        recorder.pop_hyperlink().unwrap();
+
+        // Replayed output should be linked.
+        let config = config_from_string(r#" colors.inner = "red" "#);
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = ColorFormatter::for_config(&mut output, &config, false).unwrap();
+        recorder.replay(&mut formatter).unwrap();
+        drop(formatter);
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r###" outer ]8;;https://example.com/\ inner ]8;;\"###);
+    }
 }
@@ -159,6 +159,13 @@ impl<'a, C: 'a> IntoTemplateProperty<'a> for GenericTemplatePropertyKind<'a, C>
             GenericTemplatePropertyKind::Self_(_) => None,
         }
     }
+
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+        match self {
+            GenericTemplatePropertyKind::Core(property) => property.try_into_json(),
+            GenericTemplatePropertyKind::Self_(_) => None,
+        }
+    }
 }
 
 /// Function that translates keyword (or 0-ary method call node of the self type
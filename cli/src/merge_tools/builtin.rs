@@ -5,7 +5,7 @@ use std::sync::Arc;
 use futures::{StreamExt, TryFutureExt, TryStreamExt};
 use itertools::Itertools;
 use jj_lib::backend::{BackendError, BackendResult, FileId, MergedTreeId, TreeValue};
-use jj_lib::conflicts::{materialize_tree_value, MaterializedTreeValue};
+use jj_lib::conflicts::{materialize_tree_value, ConflictMarkerStyle, MaterializedTreeValue};
 use jj_lib::diff::{find_line_ranges, Diff, DiffHunk};
 use jj_lib::files::{self, ContentHunk, MergeResult};
 use jj_lib::matchers::Matcher;
@@ -127,9 +127,10 @@ fn read_file_contents(
     store: &Store,
     tree: &MergedTree,
     path: &RepoPath,
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<FileInfo, BuiltinToolError> {
     let value = tree.path_value(path)?;
-    let materialized_value = materialize_tree_value(store, path, value)
+    let materialized_value = materialize_tree_value(store, path, value, conflict_marker_style)
         .map_err(BuiltinToolError::BackendError)
         .block_on()?;
     match materialized_value {
@@ -286,11 +287,13 @@ pub fn make_diff_files(
     left_tree: &MergedTree,
     right_tree: &MergedTree,
     changed_files: &[RepoPathBuf],
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<Vec<scm_record::File<'static>>, BuiltinToolError> {
     let mut files = Vec::new();
     for changed_path in changed_files {
-        let left_info = read_file_contents(store, left_tree, changed_path)?;
-        let right_info = read_file_contents(store, right_tree, changed_path)?;
+        let left_info = read_file_contents(store, left_tree, changed_path, conflict_marker_style)?;
+        let right_info =
+            read_file_contents(store, right_tree, changed_path, conflict_marker_style)?;
         let mut sections = Vec::new();
 
         if should_render_mode_section(&left_info, &right_info) {
@@ -495,6 +498,7 @@ pub fn edit_diff_builtin(
     left_tree: &MergedTree,
     right_tree: &MergedTree,
     matcher: &dyn Matcher,
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<MergedTreeId, BuiltinToolError> {
     let store = left_tree.store().clone();
     let changed_files: Vec<_> = left_tree
@@ -502,7 +506,13 @@ pub fn edit_diff_builtin(
         .map(|(path, diff)| diff.map(|_| path))
         .try_collect()
         .block_on()?;
-    let files = make_diff_files(&store, left_tree, right_tree, &changed_files)?;
+    let files = make_diff_files(
+        &store,
+        left_tree,
+        right_tree,
+        &changed_files,
+        conflict_marker_style,
+    )?;
     let mut input = scm_record::helpers::CrosstermInput;
     let recorder = scm_record::Recorder::new(
         scm_record::RecordState {
@@ -669,7 +679,14 @@ mod tests {
             changed_path.to_owned(),
             added_path.to_owned(),
         ];
-        let files = make_diff_files(store, &left_tree, &right_tree, &changed_files).unwrap();
+        let files = make_diff_files(
+            store,
+            &left_tree,
+            &right_tree,
+            &changed_files,
+            ConflictMarkerStyle::Diff,
+        )
+        .unwrap();
         insta::assert_debug_snapshot!(files, @r###"
         [
             File {
@@ -799,7 +816,14 @@ mod tests {
         let right_tree = testutils::create_tree(&test_repo.repo, &[(added_empty_file_path, "")]);
 
         let changed_files = vec![added_empty_file_path.to_owned()];
-        let files = make_diff_files(store, &left_tree, &right_tree, &changed_files).unwrap();
+        let files = make_diff_files(
+            store,
+            &left_tree,
+            &right_tree,
+            &changed_files,
+            ConflictMarkerStyle::Diff,
+        )
+        .unwrap();
         insta::assert_debug_snapshot!(files, @r###"
         [
             File {
@@ -863,7 +887,14 @@ mod tests {
         let right_tree = testutils::create_tree(&test_repo.repo, &[]);
 
         let changed_files = vec![added_empty_file_path.to_owned()];
-        let files = make_diff_files(store, &left_tree, &right_tree, &changed_files).unwrap();
+        let files = make_diff_files(
+            store,
+            &left_tree,
+            &right_tree,
+            &changed_files,
+            ConflictMarkerStyle::Diff,
+        )
+        .unwrap();
         insta::assert_debug_snapshot!(files, @r###"
         [
             File {
@@ -928,7 +959,14 @@ mod tests {
             testutils::create_tree(&test_repo.repo, &[(empty_file_path, "modified\n")]);
 
         let changed_files = vec![empty_file_path.to_owned()];
-        let files = make_diff_files(store, &left_tree, &right_tree, &changed_files).unwrap();
+        let files = make_diff_files(
+            store,
+            &left_tree,
+            &right_tree,
+            &changed_files,
+            ConflictMarkerStyle::Diff,
+        )
+        .unwrap();
         insta::assert_debug_snapshot!(files, @r###"
         [
             File {
@@ -1070,4 +1108,55 @@ mod tests {
         ]
         "###);
     }
+
+    #[test]
+    fn test_make_merge_sections_multi_sided() {
+        let merge = Merge::from_removes_adds(
+            [b"base 1\nbase 2\n".as_slice(), b"base 1\nbase 2\n"],
+            [
+                b"a 1\nbase 2\n".as_slice(),
+                b"b 1\nbase 2\n",
+                b"c 1\nbase 2\n",
+            ],
+        );
+        let sections = make_merge_sections(files::merge(&merge)).unwrap();
+        insta::assert_debug_snapshot!(sections, @r###"
+        [
+            Changed {
+                lines: [
+                    SectionChangedLine {
+                        is_checked: false,
+                        change_type: Added,
+                        line: "a 1\n",
+                    },
+                    SectionChangedLine {
+                        is_checked: false,
+                        change_type: Removed,
+                        line: "base 1\n",
+                    },
+                    SectionChangedLine {
+                        is_checked: false,
+                        change_type: Added,
+                        line: "b 1\n",
+                    },
+                    SectionChangedLine {
+                        is_checked: false,
+                        change_type: Removed,
+                        line: "base 1\n",
+                    },
+                    SectionChangedLine {
+                        is_checked: false,
+                        change_type: Added,
+                        line: "c 1\n",
+                    },
+                ],
+            },
+            Unchanged {
+                lines: [
+                    "base 2\n",
+                ],
+            },
+        ]
+        "###);
+    }
 }
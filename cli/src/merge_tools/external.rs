@@ -1,16 +1,20 @@
 use std::collections::HashMap;
 use std::io::{self, Write};
+use std::iter::zip;
 use std::process::{Command, ExitStatus, Stdio};
 use std::sync::Arc;
 
 use itertools::Itertools;
 use jj_lib::backend::{FileId, MergedTreeId, TreeValue};
-use jj_lib::conflicts::{self, materialize_merge_result};
+use jj_lib::conflicts::{
+    self, extract_as_single_hunk, materialize_merge_result, ConflictMarkerStyle,
+};
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::{Merge, MergedTreeValue};
 use jj_lib::merged_tree::{MergedTree, MergedTreeBuilder};
 use jj_lib::repo_path::RepoPath;
+use jj_lib::store::Store;
 use pollster::FutureExt;
 use thiserror::Error;
 
@@ -45,9 +49,10 @@ pub struct ExternalMergeTool {
     /// merge tool is done, any remaining conflict markers in the
     /// file parsed and taken to mean that the conflict was only partially
     /// resolved.
-    // TODO: Instead of a boolean, this could denote the flavor of conflict markers to put in
-    // the file (`jj` or `diff3` for example).
     pub merge_tool_edits_conflict_markers: bool,
+    /// If provided, overrides the `ui.conflict-marker-style` setting for the
+    /// conflict markers written to the `$output` file.
+    pub conflict_marker_style: Option<ConflictMarkerStyle>,
 }
 
 impl Default for ExternalMergeTool {
@@ -63,6 +68,7 @@ impl Default for ExternalMergeTool {
             edit_args: ["$left", "$right"].map(ToOwned::to_owned).to_vec(),
             merge_args: vec![],
             merge_tool_edits_conflict_markers: false,
+            conflict_marker_style: None,
         }
     }
 }
@@ -128,10 +134,108 @@ pub fn run_mergetool_external(
     repo_path: &RepoPath,
     conflict: MergedTreeValue,
     tree: &MergedTree,
+    default_conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<MergedTreeId, ConflictResolveError> {
+    let conflict_marker_style = editor
+        .conflict_marker_style
+        .unwrap_or(default_conflict_marker_style);
+    let new_file_ids = if content.num_sides() == 2 {
+        run_mergetool_external_3way(
+            editor,
+            &file_merge,
+            content,
+            repo_path,
+            tree.store(),
+            conflict_marker_style,
+        )?
+    } else {
+        run_mergetool_external_pairwise(
+            editor,
+            &file_merge.simplify(),
+            repo_path,
+            tree.store(),
+            conflict_marker_style,
+        )?
+    };
+    // Keep the executable bit if all sides agree on it, or take it from the
+    // first side, which the merged content is based on.
+    let executable = conflict.to_executable_merge().is_some_and(|executable| {
+        executable
+            .resolve_trivial()
+            .copied()
+            .unwrap_or(*executable.first())
+    });
+    let new_tree_value = match new_file_ids.into_resolved() {
+        Ok(new_file_id) => Merge::normal(TreeValue::File {
+            id: new_file_id.unwrap(),
+            executable,
+        }),
+        Err(new_file_ids) if new_file_ids.iter().len() == conflict.iter().len() => {
+            conflict.with_new_file_ids(&new_file_ids)
+        }
+        // A partially resolved N-sided conflict has fewer sides than the original.
+        Err(new_file_ids) => {
+            new_file_ids.map(|file_id| file_id.clone().map(|id| TreeValue::File { id, executable }))
+        }
+    };
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    tree_builder.set_or_remove(repo_path.to_owned(), new_tree_value);
+    let new_tree = tree_builder.write_tree(tree.store())?;
+    Ok(new_tree)
+}
+
+/// Resolves a conflict with more than 2 sides as a sequence of 3-way merges.
+/// The result of each merge becomes the left side of the next one. If the tool
+/// leaves one of the merges unresolved, the sides that haven't been merged yet
+/// are kept in the returned conflict.
+fn run_mergetool_external_pairwise(
+    editor: &ExternalMergeTool,
+    file_merge: &Merge<Option<FileId>>,
+    repo_path: &RepoPath,
+    store: &Store,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<Merge<Option<FileId>>, ConflictResolveError> {
+    let mut merged = file_merge.first().clone();
+    for (i, (base, right)) in zip(file_merge.removes(), file_merge.adds().skip(1)).enumerate() {
+        let step_merge = Merge::from_removes_adds([base.clone()], [merged, right.clone()]);
+        let content = extract_as_single_hunk(&step_merge, store, repo_path).block_on()?;
+        let new_file_ids = run_mergetool_external_3way(
+            editor,
+            &step_merge,
+            content,
+            repo_path,
+            store,
+            conflict_marker_style,
+        )?;
+        match new_file_ids.into_resolved() {
+            Ok(new_file_id) => merged = new_file_id,
+            Err(new_file_ids) => {
+                let removes = new_file_ids
+                    .removes()
+                    .chain(file_merge.removes().skip(i + 1))
+                    .cloned();
+                let adds = new_file_ids
+                    .adds()
+                    .chain(file_merge.adds().skip(i + 2))
+                    .cloned();
+                return Ok(Merge::from_removes_adds(removes, adds));
+            }
+        }
+    }
+    Ok(Merge::resolved(merged))
+}
+
+fn run_mergetool_external_3way(
+    editor: &ExternalMergeTool,
+    file_merge: &Merge<Option<FileId>>,
+    content: Merge<jj_lib::files::ContentHunk>,
+    repo_path: &RepoPath,
+    store: &Store,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<Merge<Option<FileId>>, ConflictResolveError> {
     let initial_output_content: Vec<u8> = if editor.merge_tool_edits_conflict_markers {
         let mut materialized_conflict = vec![];
-        materialize_merge_result(&content, &mut materialized_conflict)
+        materialize_merge_result(&content, conflict_marker_style, &mut materialized_conflict)
             .expect("Writing to an in-memory buffer should never fail");
         materialized_conflict
     } else {
@@ -194,29 +298,18 @@ pub fn run_mergetool_external(
 
     let new_file_ids = if editor.merge_tool_edits_conflict_markers {
         conflicts::update_from_content(
-            &file_merge,
-            tree.store(),
+            file_merge,
+            store,
             repo_path,
             output_file_contents.as_slice(),
+            conflict_marker_style,
         )
         .block_on()?
     } else {
-        let new_file_id = tree
-            .store()
-            .write_file(repo_path, &mut output_file_contents.as_slice())?;
+        let new_file_id = store.write_file(repo_path, &mut output_file_contents.as_slice())?;
         Merge::normal(new_file_id)
     };
-    let new_tree_value = match new_file_ids.into_resolved() {
-        Ok(new_file_id) => Merge::normal(TreeValue::File {
-            id: new_file_id.unwrap(),
-            executable: false,
-        }),
-        Err(new_file_ids) => conflict.with_new_file_ids(&new_file_ids),
-    };
-    let mut tree_builder = MergedTreeBuilder::new(tree.id());
-    tree_builder.set_or_remove(repo_path.to_owned(), new_tree_value);
-    let new_tree = tree_builder.write_tree(tree.store())?;
-    Ok(new_tree)
+    Ok(new_file_ids)
 }
 
 pub fn edit_diff_external(
@@ -226,6 +319,7 @@ pub fn edit_diff_external(
     matcher: &dyn Matcher,
     instructions: Option<&str>,
     base_ignores: Arc<GitIgnoreFile>,
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<MergedTreeId, DiffEditError> {
     let got_output_field = find_all_variables(&editor.edit_args).contains(&"output");
     let store = left_tree.store();
@@ -236,6 +330,7 @@ pub fn edit_diff_external(
         matcher,
         got_output_field.then_some(DiffSide::Right),
         instructions,
+        conflict_marker_style,
     )?;
 
     let patterns = diffedit_wc.working_copies.to_command_variables();
@@ -254,7 +349,7 @@ pub fn edit_diff_external(
         }));
     }
 
-    diffedit_wc.snapshot_results(base_ignores)
+    diffedit_wc.snapshot_results(base_ignores, conflict_marker_style)
 }
 
 /// Generates textual diff by the specified `tool`, and writes into `writer`.
@@ -265,9 +360,20 @@ pub fn generate_diff(
     right_tree: &MergedTree,
     matcher: &dyn Matcher,
     tool: &ExternalMergeTool,
+    default_conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<(), DiffGenerateError> {
+    let conflict_marker_style = tool
+        .conflict_marker_style
+        .unwrap_or(default_conflict_marker_style);
     let store = left_tree.store();
-    let diff_wc = check_out_trees(store, left_tree, right_tree, matcher, None)?;
+    let diff_wc = check_out_trees(
+        store,
+        left_tree,
+        right_tree,
+        matcher,
+        None,
+        conflict_marker_style,
+    )?;
     set_readonly_recursively(diff_wc.left_working_copy_path())
         .map_err(ExternalToolError::SetUpDir)?;
     set_readonly_recursively(diff_wc.right_working_copy_path())
@@ -20,7 +20,7 @@ use std::sync::Arc;
 
 use config::ConfigError;
 use jj_lib::backend::MergedTreeId;
-use jj_lib::conflicts::extract_as_single_hunk;
+use jj_lib::conflicts::{extract_as_single_hunk, ConflictMarkerStyle};
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::matchers::Matcher;
 use jj_lib::merged_tree::MergedTree;
@@ -76,8 +76,6 @@ pub enum ConflictResolveError {
          supported. Conflict summary for {0:?}:\n{1}"
     )]
     NotNormalFiles(RepoPathBuf, String),
-    #[error("The conflict at {path:?} has {sides} sides. At most 2 sides are supported.")]
-    ConflictTooComplicated { path: RepoPathBuf, sides: usize },
     #[error(
         "The output file is either unchanged or empty after the editor quit (run with --debug to \
          see the exact invocation)."
@@ -169,6 +167,7 @@ pub struct DiffEditor {
     tool: MergeTool,
     base_ignores: Arc<GitIgnoreFile>,
     use_instructions: bool,
+    conflict_marker_style: ConflictMarkerStyle,
 }
 
 impl DiffEditor {
@@ -209,6 +208,7 @@ impl DiffEditor {
             tool,
             base_ignores,
             use_instructions: settings.config().get_bool("ui.diff-instructions")?,
+            conflict_marker_style: settings.conflict_marker_style()?,
         })
     }
 
@@ -222,7 +222,10 @@ impl DiffEditor {
     ) -> Result<MergedTreeId, DiffEditError> {
         match &self.tool {
             MergeTool::Builtin => {
-                Ok(edit_diff_builtin(left_tree, right_tree, matcher).map_err(Box::new)?)
+                Ok(
+                    edit_diff_builtin(left_tree, right_tree, matcher, self.conflict_marker_style)
+                        .map_err(Box::new)?,
+                )
             }
             MergeTool::External(editor) => {
                 let instructions = self.use_instructions.then_some(instructions).flatten();
@@ -233,6 +236,7 @@ impl DiffEditor {
                     matcher,
                     instructions,
                     self.base_ignores.clone(),
+                    self.conflict_marker_style,
                 )
             }
         }
@@ -243,6 +247,7 @@ impl DiffEditor {
 #[derive(Clone, Debug)]
 pub struct MergeEditor {
     tool: MergeTool,
+    conflict_marker_style: ConflictMarkerStyle,
 }
 
 impl MergeEditor {
@@ -251,7 +256,7 @@ impl MergeEditor {
     pub fn with_name(name: &str, settings: &UserSettings) -> Result<Self, MergeToolConfigError> {
         let tool = get_tool_config(settings, name)?
             .unwrap_or_else(|| MergeTool::external(ExternalMergeTool::with_program(name)));
-        Self::new_inner(name, tool)
+        Self::new_inner(name, tool, settings)
     }
 
     /// Loads the default 3-way merge editor from the settings.
@@ -263,16 +268,23 @@ impl MergeEditor {
             None
         }
         .unwrap_or_else(|| MergeTool::external(ExternalMergeTool::with_merge_args(&args)));
-        Self::new_inner(&args, tool)
+        Self::new_inner(&args, tool, settings)
     }
 
-    fn new_inner(name: impl ToString, tool: MergeTool) -> Result<Self, MergeToolConfigError> {
+    fn new_inner(
+        name: impl ToString,
+        tool: MergeTool,
+        settings: &UserSettings,
+    ) -> Result<Self, MergeToolConfigError> {
         if matches!(&tool, MergeTool::External(mergetool) if mergetool.merge_args.is_empty()) {
             return Err(MergeToolConfigError::MergeArgsNotConfigured {
                 tool_name: name.to_string(),
             });
         }
-        Ok(MergeEditor { tool })
+        Ok(MergeEditor {
+            tool,
+            conflict_marker_style: settings.conflict_marker_style()?,
+        })
     }
 
     /// Starts a merge editor for the specified file.
@@ -297,13 +309,6 @@ impl MergeEditor {
             )
         })?;
         let simplified_file_merge = file_merge.clone().simplify();
-        // We only support conflicts with 2 sides (3-way conflicts)
-        if simplified_file_merge.num_sides() > 2 {
-            return Err(ConflictResolveError::ConflictTooComplicated {
-                path: repo_path.to_owned(),
-                sides: simplified_file_merge.num_sides(),
-            });
-        };
         let content =
             extract_as_single_hunk(&simplified_file_merge, tree.store(), repo_path).block_on()?;
 
@@ -313,7 +318,13 @@ impl MergeEditor {
                 Ok(tree_id)
             }
             MergeTool::External(editor) => external::run_mergetool_external(
-                editor, file_merge, content, repo_path, conflict, tree,
+                editor,
+                file_merge,
+                content,
+                repo_path,
+                conflict,
+                tree,
+                self.conflict_marker_style,
             ),
         }
     }
@@ -357,6 +368,7 @@ mod tests {
                 ],
                 merge_args: [],
                 merge_tool_edits_conflict_markers: false,
+                conflict_marker_style: None,
             },
         )
         "###);
@@ -383,6 +395,7 @@ mod tests {
                 ],
                 merge_args: [],
                 merge_tool_edits_conflict_markers: false,
+                conflict_marker_style: None,
             },
         )
         "###);
@@ -416,6 +429,7 @@ mod tests {
                 ],
                 merge_args: [],
                 merge_tool_edits_conflict_markers: false,
+                conflict_marker_style: None,
             },
         )
         "###);
@@ -438,6 +452,7 @@ mod tests {
                 ],
                 merge_args: [],
                 merge_tool_edits_conflict_markers: false,
+                conflict_marker_style: None,
             },
         )
         "###);
@@ -459,6 +474,7 @@ mod tests {
                 ],
                 merge_args: [],
                 merge_tool_edits_conflict_markers: false,
+                conflict_marker_style: None,
             },
         )
         "###);
@@ -486,6 +502,7 @@ mod tests {
                 ],
                 merge_args: [],
                 merge_tool_edits_conflict_markers: false,
+                conflict_marker_style: None,
             },
         )
         "###);
@@ -511,6 +528,7 @@ mod tests {
                 ],
                 merge_args: [],
                 merge_tool_edits_conflict_markers: false,
+                conflict_marker_style: None,
             },
         )
         "###);
@@ -530,6 +548,7 @@ mod tests {
                 ],
                 merge_args: [],
                 merge_tool_edits_conflict_markers: false,
+                conflict_marker_style: None,
             },
         )
         "###);
@@ -580,6 +599,7 @@ mod tests {
                     "$output",
                 ],
                 merge_tool_edits_conflict_markers: false,
+                conflict_marker_style: None,
             },
         )
         "###);
@@ -625,6 +645,7 @@ mod tests {
                     "$output",
                 ],
                 merge_tool_edits_conflict_markers: false,
+                conflict_marker_style: None,
             },
         )
         "###);
@@ -652,6 +673,7 @@ mod tests {
                     "$output",
                 ],
                 merge_tool_edits_conflict_markers: false,
+                conflict_marker_style: None,
             },
         )
         "###);
@@ -682,6 +704,7 @@ mod tests {
                     "$output",
                 ],
                 merge_tool_edits_conflict_markers: false,
+                conflict_marker_style: None,
             },
         )
         "###);
@@ -21,6 +21,7 @@ use jj_lib::extensions_map::ExtensionsMap;
 use jj_lib::object_id::ObjectId;
 use jj_lib::op_store::OperationId;
 use jj_lib::operation::Operation;
+use serde_json::json;
 
 use crate::template_builder::{
     self, merge_fn_map, BuildContext, CoreTemplateBuildFnTable, CoreTemplatePropertyKind,
@@ -29,7 +30,7 @@ use crate::template_builder::{
 use crate::template_parser::{self, FunctionCallNode, TemplateParseResult};
 use crate::templater::{
     PlainTextFormattedProperty, Template, TemplateFormatter, TemplateProperty,
-    TemplatePropertyExt as _, TimestampRange,
+    TemplatePropertyError, TemplatePropertyExt as _, TimestampRange,
 };
 
 pub trait OperationTemplateLanguageExtension {
@@ -177,6 +178,37 @@ impl IntoTemplateProperty<'static> for OperationTemplatePropertyKind {
             OperationTemplatePropertyKind::OperationId(property) => Some(property.into_template()),
         }
     }
+
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value>>> {
+        match self {
+            OperationTemplatePropertyKind::Core(property) => property.try_into_json(),
+            OperationTemplatePropertyKind::Operation(property) => {
+                Some(Box::new(property.and_then(|op| operation_to_json(&op))))
+            }
+            OperationTemplatePropertyKind::OperationId(property) => {
+                Some(Box::new(property.map(|id| id.hex().into())))
+            }
+        }
+    }
+}
+
+/// Serializes `op` as an object with the id, parent ids and metadata of the
+/// operation.
+fn operation_to_json(op: &Operation) -> Result<serde_json::Value, TemplatePropertyError> {
+    let metadata = op.metadata();
+    Ok(json!({
+        "id": op.id().hex(),
+        "parents": op.parent_ids().iter().map(|id| id.hex()).collect_vec(),
+        "time": {
+            "start": template_builder::timestamp_to_json(&metadata.start_time)?,
+            "end": template_builder::timestamp_to_json(&metadata.end_time)?,
+        },
+        "description": metadata.description,
+        "hostname": metadata.hostname,
+        "username": metadata.username,
+        "is_snapshot": metadata.is_snapshot,
+        "tags": metadata.tags,
+    }))
 }
 
 /// Table of functions that translate method call node of self type `T`.
@@ -17,16 +17,17 @@ use std::collections::HashMap;
 use itertools::Itertools as _;
 use jj_lib::backend::{Signature, Timestamp};
 use jj_lib::dsl_util::AliasExpandError as _;
+use serde_json::json;
 
 use crate::template_parser::{
     self, BinaryOp, ExpressionKind, ExpressionNode, FunctionCallNode, TemplateAliasesMap,
     TemplateParseError, TemplateParseErrorKind, TemplateParseResult, UnaryOp,
 };
 use crate::templater::{
-    CoalesceTemplate, ConcatTemplate, ConditionalTemplate, LabelTemplate, ListPropertyTemplate,
-    ListTemplate, Literal, PlainTextFormattedProperty, PropertyPlaceholder, ReformatTemplate,
-    SeparateTemplate, SizeHint, Template, TemplateProperty, TemplatePropertyError,
-    TemplatePropertyExt as _, TemplateRenderer, TimestampRange,
+    CoalesceTemplate, ConcatTemplate, ConditionalTemplate, HyperlinkTemplate, LabelTemplate,
+    ListPropertyTemplate, ListTemplate, Literal, PlainTextFormattedProperty, PropertyPlaceholder,
+    ReformatTemplate, SeparateTemplate, SizeHint, Template, TemplateProperty,
+    TemplatePropertyError, TemplatePropertyExt as _, TemplateRenderer, TimestampRange,
 };
 use crate::{text_util, time_util};
 
@@ -69,6 +70,18 @@ pub trait TemplateLanguage<'a> {
         property: Self::Property,
         function: &FunctionCallNode,
     ) -> TemplateParseResult<Self::Property>;
+
+    /// Converts the given `property` to a JSON value if its type is
+    /// serializable.
+    ///
+    /// This can be overridden to serialize types which need the language
+    /// context, such as the refs pointing to a commit.
+    fn try_into_json(
+        &self,
+        property: Self::Property,
+    ) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+        property.try_into_json()
+    }
 }
 
 /// Implements `TemplateLanguage::wrap_<type>()` functions.
@@ -133,6 +146,14 @@ pub trait IntoTemplateProperty<'a> {
 
     fn try_into_plain_text(self) -> Option<Box<dyn TemplateProperty<Output = String> + 'a>>;
     fn try_into_template(self) -> Option<Box<dyn Template + 'a>>;
+
+    /// Converts the property to a JSON value if its type is serializable.
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>>
+    where
+        Self: Sized,
+    {
+        None
+    }
 }
 
 pub enum CoreTemplatePropertyKind<'a> {
@@ -237,6 +258,65 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
             CoreTemplatePropertyKind::ListTemplate(template) => Some(template.into_template()),
         }
     }
+
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+        match self {
+            CoreTemplatePropertyKind::String(property) => {
+                Some(Box::new(property.map(serde_json::Value::from)))
+            }
+            CoreTemplatePropertyKind::StringList(property) => {
+                Some(Box::new(property.map(serde_json::Value::from)))
+            }
+            CoreTemplatePropertyKind::Boolean(property) => {
+                Some(Box::new(property.map(serde_json::Value::from)))
+            }
+            CoreTemplatePropertyKind::Integer(property) => {
+                Some(Box::new(property.map(serde_json::Value::from)))
+            }
+            CoreTemplatePropertyKind::IntegerOpt(property) => {
+                Some(Box::new(property.map(serde_json::Value::from)))
+            }
+            CoreTemplatePropertyKind::Signature(property) => Some(Box::new(
+                property.and_then(|sig| Ok(signature_to_json(&sig)?)),
+            )),
+            CoreTemplatePropertyKind::SizeHint(property) => Some(Box::new(
+                property.map(|(lower, upper)| json!([lower, upper])),
+            )),
+            CoreTemplatePropertyKind::Timestamp(property) => Some(Box::new(
+                property.and_then(|time| Ok(timestamp_to_json(&time)?)),
+            )),
+            CoreTemplatePropertyKind::TimestampRange(property) => {
+                Some(Box::new(property.and_then(|range| {
+                    Ok(json!({
+                        "start": timestamp_to_json(&range.start)?,
+                        "end": timestamp_to_json(&range.end)?,
+                    }))
+                })))
+            }
+            CoreTemplatePropertyKind::Template(_) => None,
+            CoreTemplatePropertyKind::ListTemplate(_) => None,
+        }
+    }
+}
+
+/// Serializes `timestamp` as an RFC 3339 date-time string.
+pub fn timestamp_to_json(
+    timestamp: &Timestamp,
+) -> Result<serde_json::Value, time_util::TimestampOutOfRange> {
+    let datetime = time_util::datetime_from_timestamp(timestamp)?;
+    Ok(datetime.to_rfc3339().into())
+}
+
+/// Serializes `signature` as an object with `name`, `email` and `timestamp`
+/// fields.
+pub fn signature_to_json(
+    signature: &Signature,
+) -> Result<serde_json::Value, time_util::TimestampOutOfRange> {
+    Ok(json!({
+        "name": signature.name,
+        "email": signature.email,
+        "timestamp": timestamp_to_json(&signature.timestamp)?,
+    }))
 }
 
 /// Function that translates global function call node.
@@ -986,6 +1066,12 @@ fn builtin_functions<'a, L: TemplateLanguage<'a> + ?Sized>() -> TemplateBuildFun
         });
         Ok(L::wrap_template(Box::new(template)))
     });
+    map.insert("json", |language, build_ctx, function| {
+        let [value_node] = function.expect_exact_arguments()?;
+        let value = expect_json_expression(language, build_ctx, value_node)?;
+        let out_property = value.map(|value| value.to_string());
+        Ok(L::wrap_string(out_property))
+    });
     map.insert("label", |language, build_ctx, function| {
         let [label_node, content_node] = function.expect_exact_arguments()?;
         let label_property = expect_plain_text_expression(language, build_ctx, label_node)?;
@@ -996,6 +1082,14 @@ fn builtin_functions<'a, L: TemplateLanguage<'a> + ?Sized>() -> TemplateBuildFun
             content, labels,
         ))))
     });
+    map.insert("hyperlink", |language, build_ctx, function| {
+        let [url_node, content_node] = function.expect_exact_arguments()?;
+        let url = expect_plain_text_expression(language, build_ctx, url_node)?;
+        let content = expect_template_expression(language, build_ctx, content_node)?;
+        Ok(L::wrap_template(Box::new(HyperlinkTemplate::new(
+            content, url,
+        ))))
+    });
     map.insert("if", |language, build_ctx, function| {
         let ([condition_node, true_node], [false_node]) = function.expect_arguments()?;
         let condition = expect_boolean_expression(language, build_ctx, condition_node)?;
@@ -1221,6 +1315,16 @@ pub fn expect_template_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
     })
 }
 
+pub fn expect_json_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
+    language: &L,
+    build_ctx: &BuildContext<L::Property>,
+    node: &ExpressionNode,
+) -> TemplateParseResult<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+    expect_expression_of_type(language, build_ctx, node, "Serialize", |expression| {
+        language.try_into_json(expression.property)
+    })
+}
+
 fn expect_expression_of_type<'a, L: TemplateLanguage<'a> + ?Sized, T>(
     language: &L,
     build_ctx: &BuildContext<L::Property>,
@@ -2125,6 +2229,37 @@ mod tests {
         "###);
     }
 
+    #[test]
+    fn test_json_function() {
+        let mut env = TestTemplateEnv::new();
+        env.add_keyword("author", || {
+            L::wrap_signature(Literal(new_signature("Test User", "test.user@example.com")))
+        });
+        env.add_keyword("size_hint", || L::wrap_size_hint(Literal((5, None))));
+        env.add_keyword("time_range", || {
+            L::wrap_timestamp_range(Literal(TimestampRange {
+                start: new_timestamp(0, 60),
+                end: new_timestamp(1_500, 60),
+            }))
+        });
+        env.add_keyword("bad_string", || L::wrap_string(new_error_property("Bad")));
+
+        insta::assert_snapshot!(env.render_ok(r#"json("\"quoted\"\n")"#), @r###""\"quoted\"\n""###);
+        insta::assert_snapshot!(env.render_ok(r#"json("a\nb".lines())"#), @r###"["a","b"]"###);
+        insta::assert_snapshot!(env.render_ok(r#"json(author)"#), @r###"{"email":"test.user@example.com","name":"Test User","timestamp":"1970-01-01T00:00:00+00:00"}"###);
+        insta::assert_snapshot!(env.render_ok(r#"json(size_hint)"#), @"[5,null]");
+        insta::assert_snapshot!(env.render_ok(r#"json(time_range)"#), @r###"{"end":"1970-01-01T01:00:01.500+01:00","start":"1970-01-01T01:00:00+01:00"}"###);
+        insta::assert_snapshot!(env.render_ok(r#"json(bad_string)"#), @"<Error: Bad>");
+        insta::assert_snapshot!(env.parse_err(r#"json(label("x", "y"))"#), @r###"
+         --> 1:6
+          |
+        1 | json(label("x", "y"))
+          |      ^-------------^
+          |
+          = Expected expression of type "Serialize", but actual type is "Template"
+        "###);
+    }
+
     #[test]
     fn test_label_function() {
         let mut env = TestTemplateEnv::new();
@@ -2148,6 +2283,20 @@ mod tests {
             @"[38;5;1mtext[39m");
     }
 
+    #[test]
+    fn test_hyperlink_function() {
+        let mut env = TestTemplateEnv::new();
+        env.add_keyword("bad_string", || L::wrap_string(new_error_property("Bad")));
+        env.add_color("error", crossterm::style::Color::DarkRed);
+
+        insta::assert_snapshot!(
+            env.render_ok(r#"hyperlink("https://example.com/", label("error", "text"))"#),
+            @r###"[38;5;1m]8;;https://example.com/\text[39m]8;;\"###);
+        insta::assert_snapshot!(
+            env.render_ok(r#"hyperlink(bad_string, "text")"#),
+            @"[38;5;1m<Error: Bad>[39m");
+    }
+
     #[test]
     fn test_coalesce_function() {
         let mut env = TestTemplateEnv::new();
@@ -177,6 +177,38 @@ where
     }
 }
 
+pub struct HyperlinkTemplate<T, P> {
+    content: T,
+    url: P,
+}
+
+impl<T, P> HyperlinkTemplate<T, P> {
+    pub fn new(content: T, url: P) -> Self
+    where
+        T: Template,
+        P: TemplateProperty<Output = String>,
+    {
+        HyperlinkTemplate { content, url }
+    }
+}
+
+impl<T, P> Template for HyperlinkTemplate<T, P>
+where
+    T: Template,
+    P: TemplateProperty<Output = String>,
+{
+    fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
+        match self.url.extract() {
+            Ok(url) => {
+                formatter.push_hyperlink(&url)?;
+                self.content.format(formatter)?;
+                formatter.pop_hyperlink()
+            }
+            Err(err) => formatter.handle_error(err),
+        }
+    }
+}
+
 /// Renders contents in order, and returns the first non-empty output.
 pub struct CoalesceTemplate<T>(pub Vec<T>);
 
@@ -705,6 +737,14 @@ impl<'a> TemplateFormatter<'a> {
         self.formatter.pop_label()
     }
 
+    pub fn push_hyperlink(&mut self, url: &str) -> io::Result<()> {
+        self.formatter.push_hyperlink(url)
+    }
+
+    pub fn pop_hyperlink(&mut self) -> io::Result<()> {
+        self.formatter.pop_hyperlink()
+    }
+
     pub fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> io::Result<()> {
         self.formatter.write_fmt(args)
     }
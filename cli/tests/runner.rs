@@ -9,6 +9,7 @@ fn test_no_forgotten_test_files() {
 }
 
 mod test_abandon_command;
+mod test_absorb_command;
 mod test_acls;
 mod test_advance_branches;
 mod test_alias;
@@ -25,6 +26,7 @@ mod test_diff_command;
 mod test_diffedit_command;
 mod test_duplicate_command;
 mod test_edit_command;
+mod test_file_annotate_command;
 mod test_file_chmod_command;
 mod test_file_print_command;
 mod test_fix_command;
@@ -55,8 +57,10 @@ mod test_resolve_command;
 mod test_restore_command;
 mod test_revset_output;
 mod test_root;
+mod test_run_command;
 mod test_shell_completion;
 mod test_show_command;
+mod test_sign_command;
 mod test_sparse_command;
 mod test_split_command;
 mod test_squash_command;
@@ -0,0 +1,253 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_absorb_simple() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "1"]);
+    std::fs::write(repo_path.join("file1"), "1a\n1b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "2"]);
+    std::fs::write(repo_path.join("file1"), "1a\n1b\n2a\n2b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+
+    // Modify lines from both commits, and insert lines around the ones from
+    // the first commit
+    std::fs::write(repo_path.join("file1"), "1X\n1A\n1b\n1Y\n2a\n2B\n").unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["absorb"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Absorbed changes into these revisions:
+      qpvuntsm e9d75e5e 1
+      kkmpptxz 670332d0 2
+    Working copy now at: zsuskuln 10b4487a (no description set)
+    Parent commit      : kkmpptxz 670332d0 2
+    "###);
+    insta::assert_snapshot!(get_diffs(&test_env, &repo_path, "all()"), @r###"
+    @  zsuskuln 10b4487a (no description set)
+    │  diff --git a/file1 b/file1
+    │  index b358830429...6e23f6e8f3 100644
+    │  --- a/file1
+    │  +++ b/file1
+    │  @@ -1,5 +1,6 @@
+    │   1X
+    │   1A
+    │   1b
+    │  +1Y
+    │   2a
+    │   2B
+    ◉  kkmpptxz 670332d0 2
+    │  diff --git a/file1 b/file1
+    │  index ed237b5112...b358830429 100644
+    │  --- a/file1
+    │  +++ b/file1
+    │  @@ -1,3 +1,5 @@
+    │   1X
+    │   1A
+    │   1b
+    │  +2a
+    │  +2B
+    ◉  qpvuntsm e9d75e5e 1
+    │  diff --git a/file1 b/file1
+    │  new file mode 100644
+    │  index 0000000000..ed237b5112
+    │  --- /dev/null
+    │  +++ b/file1
+    │  @@ -1,0 +1,3 @@
+    │  +1X
+    │  +1A
+    │  +1b
+    ◉  zzzzzzzz 00000000 (empty) (no description set)
+    "###);
+
+    // Nothing left to absorb
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["absorb"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+}
+
+#[test]
+fn test_absorb_ambiguous_and_skipped() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "1"]);
+    std::fs::write(repo_path.join("file1"), "1a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "2"]);
+    std::fs::write(repo_path.join("file1"), "1a\n2a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "3"]);
+
+    // Replacing lines from both commits with a different number of lines is
+    // ambiguous, and new files can't be absorbed
+    std::fs::write(repo_path.join("file1"), "new\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "new\n").unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["absorb"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Warning: Skipping file2: Added files can't be absorbed
+    Nothing changed.
+    "###);
+    insta::assert_snapshot!(get_diffs(&test_env, &repo_path, "@"), @r###"
+    @  zsuskuln 7975a4fe 3
+    │  diff --git a/file1 b/file1
+    ~  index adb8bf2c04...3e757656cf 100644
+       --- a/file1
+       +++ b/file1
+       @@ -1,2 +1,1 @@
+       -1a
+       -2a
+       +new
+       diff --git a/file2 b/file2
+       new file mode 100644
+       index 0000000000..3e757656cf
+       --- /dev/null
+       +++ b/file2
+       @@ -1,0 +1,1 @@
+       +new
+    "###);
+}
+
+#[test]
+fn test_absorb_into_and_paths() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "1"]);
+    std::fs::write(repo_path.join("file1"), "1a\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "1a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "2"]);
+    std::fs::write(repo_path.join("file1"), "1a\n2a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "3"]);
+    std::fs::write(repo_path.join("file1"), "1A\n2A\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "1A\n").unwrap();
+
+    // Only absorb into the second commit
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["absorb", "--into", "description(2)"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Absorbed changes into these revisions:
+      kkmpptxz bd201289 2
+    Working copy now at: zsuskuln 6e536149 3
+    Parent commit      : kkmpptxz bd201289 2
+    "###);
+
+    // Only absorb changes to file2
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["absorb", "file2"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Absorbed changes into these revisions:
+      qpvuntsm 28bdf313 1
+    Rebased 1 descendant commits
+    Working copy now at: zsuskuln abc4fb3d 3
+    Parent commit      : kkmpptxz c2ab1ec9 2
+    "###);
+    insta::assert_snapshot!(get_diffs(&test_env, &repo_path, "all()"), @r###"
+    @  zsuskuln abc4fb3d 3
+    │  diff --git a/file1 b/file1
+    │  index b0d775db18...0cae4e9091 100644
+    │  --- a/file1
+    │  +++ b/file1
+    │  @@ -1,2 +1,2 @@
+    │  -1a
+    │  +1A
+    │   2A
+    ◉  kkmpptxz c2ab1ec9 2
+    │  diff --git a/file1 b/file1
+    │  index a8994dc188...b0d775db18 100644
+    │  --- a/file1
+    │  +++ b/file1
+    │  @@ -1,1 +1,2 @@
+    │   1a
+    │  +2A
+    ◉  qpvuntsm 28bdf313 1
+    │  diff --git a/file1 b/file1
+    │  new file mode 100644
+    │  index 0000000000..a8994dc188
+    │  --- /dev/null
+    │  +++ b/file1
+    │  @@ -1,0 +1,1 @@
+    │  +1a
+    │  diff --git a/file2 b/file2
+    │  new file mode 100644
+    │  index 0000000000..268de3f3ec
+    │  --- /dev/null
+    │  +++ b/file2
+    │  @@ -1,0 +1,1 @@
+    │  +1A
+    ◉  zzzzzzzz 00000000 (empty) (no description set)
+    "###);
+
+    // Immutable commits are not considered as destinations
+    test_env.add_config(r#"revset-aliases."immutable_heads()" = "description(1)""#);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["absorb"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+}
+
+#[test]
+fn test_absorb_abandons_empty_source() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "1"]);
+    std::fs::write(repo_path.join("file1"), "1a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(repo_path.join("file1"), "1A\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "child"]);
+
+    // The source has no description, so it's abandoned once all its changes
+    // are absorbed
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["absorb", "--from", "@-"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Absorbed changes into these revisions:
+      qpvuntsm 1a0527e4 1
+    Rebased 1 descendant commits
+    Working copy now at: zsuskuln 85bffa68 (empty) child
+    Parent commit      : qpvuntsm 1a0527e4 1
+    "###);
+    insta::assert_snapshot!(get_diffs(&test_env, &repo_path, "all()"), @r###"
+    @  zsuskuln 85bffa68 (empty) child
+    ◉  qpvuntsm 1a0527e4 1
+    │  diff --git a/file1 b/file1
+    │  new file mode 100644
+    │  index 0000000000..268de3f3ec
+    │  --- /dev/null
+    │  +++ b/file1
+    │  @@ -1,0 +1,1 @@
+    │  +1A
+    ◉  zzzzzzzz 00000000 (empty) (no description set)
+    "###);
+}
+
+fn get_diffs(test_env: &TestEnvironment, repo_path: &Path, revisions: &str) -> String {
+    let template = r#"format_commit_summary_with_refs(self, "") ++ "\n""#;
+    test_env.jj_cmd_success(
+        repo_path,
+        &["log", "-r", revisions, "-T", template, "--git"],
+    )
+}
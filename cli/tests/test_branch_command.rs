@@ -561,7 +561,7 @@ fn test_branch_delete_glob() {
     error: invalid value 'whatever:branch' for '<NAMES>...': Invalid string pattern kind "whatever:"
 
     For more information, try '--help'.
-    Hint: Try prefixing with one of `exact:`, `glob:` or `substring:`
+    Hint: Try prefixing with one of `exact:`, `glob:`, `regex:`, or `substring:`
     "###);
 }
 
@@ -1328,6 +1328,70 @@ fn test_branch_list() {
     insta::assert_snapshot!(stderr, @r###"
     Hint: Branches marked as deleted will be *deleted permanently* on the remote on the next `jj git push`. Use `jj branch forget` to prevent this.
     "###);
+
+    let (stdout, _stderr) = test_env.jj_cmd_ok(
+        &local_path,
+        &["branch", "list", "--all-remotes", "-T", "json"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    {"name":"local-only","target":["4e887f78eb623acad70de4be659dc036e118f6e5"]}
+    {"name":"remote-delete","target":[null]}
+    {"name":"remote-delete","remote":"origin","target":["203e60eb354463395f342935a715fedb8fdb8b3b"],"tracking_target":[null]}
+    {"name":"remote-sync","target":["c761c7ea2e2235c50fc0da3cfc2e82b05d1086c0"]}
+    {"name":"remote-sync","remote":"origin","target":["c761c7ea2e2235c50fc0da3cfc2e82b05d1086c0"],"tracking_target":["c761c7ea2e2235c50fc0da3cfc2e82b05d1086c0"]}
+    {"name":"remote-unsync","target":["4e887f78eb623acad70de4be659dc036e118f6e5"]}
+    {"name":"remote-unsync","remote":"origin","target":["38ef8af7134173e4682b5aea2822b14a98549e59"],"tracking_target":["4e887f78eb623acad70de4be659dc036e118f6e5"]}
+    {"name":"remote-untrack","remote":"origin","target":["71a16b0505cdf2d3c3b91975a7c739be1d761db2"]}
+    "###);
+}
+
+#[test]
+fn test_branch_list_regex_and_case_insensitive() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=commit"]);
+    for branch in ["release-1", "release-12a", "Release-3", "feature"] {
+        test_env.jj_cmd_ok(&repo_path, &["branch", "create", branch]);
+    }
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["branch", "list", r"regex:^release-\d+$"]);
+    insta::assert_snapshot!(stdout, @r###"
+    release-1: qpvuntsm 8da1cfc8 (empty) commit
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["branch", "list", r"regex-i:^release-\d+$"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Release-3: qpvuntsm 8da1cfc8 (empty) commit
+    release-1: qpvuntsm 8da1cfc8 (empty) commit
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["branch", "list", "glob-i:RELEASE-*"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Release-3: qpvuntsm 8da1cfc8 (empty) commit
+    release-1: qpvuntsm 8da1cfc8 (empty) commit
+    release-12a: qpvuntsm 8da1cfc8 (empty) commit
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-T=branches",
+            "-r=branches(substring-i:LEASE-1)",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"Release-3 feature release-1 release-12a");
+
+    // Malformed regex
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["branch", "list", "regex:("]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: invalid value 'regex:(' for '[NAMES]...': regex parse error:
+        (
+        ^
+    error: unclosed group
+
+    For more information, try '--help'.
+    "###);
 }
 
 #[test]
@@ -23,7 +23,7 @@ fn test_commit_with_description_from_cli() {
     let workspace_path = test_env.env_root().join("repo");
 
     // Description applies to the current working-copy (not the new one)
-    test_env.jj_cmd_ok(&workspace_path, &["commit", "-m=first"]);
+    test_env.jj_cmd_ok(&workspace_path, &["commit", "-mfirst"]);
     insta::assert_snapshot!(get_log_output(&test_env, &workspace_path), @r###"
     @  e8ea92a8b6b3
     ◉  fa15625b4a98 first
@@ -155,6 +155,24 @@ JJ: Lines starting with "JJ: " (like this one) will be removed.
     );
 }
 
+#[test]
+fn test_commit_with_trailers() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    test_env.add_config(r#"templates.commit_trailers = 'format_signed_off_by_trailer(self)'"#);
+    let workspace_path = test_env.env_root().join("repo");
+
+    std::fs::write(workspace_path.join("file1"), "foo\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["commit", "-mfirst"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &workspace_path), @r###"
+    @  f9379e273948
+    ◉  75796af5dbe7 first
+    │
+    │  Signed-off-by: Test User <test.user@example.com>
+    ◉  000000000000
+    "###);
+}
+
 #[test]
 fn test_commit_without_working_copy() {
     let test_env = TestEnvironment::default();
@@ -162,7 +180,7 @@ fn test_commit_without_working_copy() {
     let workspace_path = test_env.env_root().join("repo");
 
     test_env.jj_cmd_ok(&workspace_path, &["workspace", "forget"]);
-    let stderr = test_env.jj_cmd_failure(&workspace_path, &["commit", "-m=first"]);
+    let stderr = test_env.jj_cmd_failure(&workspace_path, &["commit", "-mfirst"]);
     insta::assert_snapshot!(stderr, @r###"
     Error: This command requires a working copy
     "###);
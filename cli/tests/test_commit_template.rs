@@ -429,6 +429,37 @@ fn test_log_builtin_templates_colored_debug() {
     "###);
 }
 
+#[test]
+fn test_log_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first\n\nbody"]);
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "main"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "second"]);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-T", "json"]);
+    insta::assert_snapshot!(stdout, @r###"
+    {"author":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:10+07:00"},"branches":[],"change_id":"zsuskulnrvyrovkzqrwmxqlsskqntxvp","commit_id":"232bd97bef2be4d974c9c4f9b7eb1d59c3638f30","committer":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:10+07:00"},"conflict":false,"description":"second\n","empty":true,"parents":["08f6e9e8f9f60ef5a9e358b48db7c1ae87f05fd0"],"tags":[],"working_copies":["default"]}
+    {"author":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:08+07:00"},"branches":[{"name":"main","target":["08f6e9e8f9f60ef5a9e358b48db7c1ae87f05fd0"]}],"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","commit_id":"08f6e9e8f9f60ef5a9e358b48db7c1ae87f05fd0","committer":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:08+07:00"},"conflict":false,"description":"first\n\nbody\n","empty":true,"parents":["0000000000000000000000000000000000000000"],"tags":[],"working_copies":[]}
+    {"author":{"email":"","name":"","timestamp":"1970-01-01T00:00:00+00:00"},"branches":[],"change_id":"zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz","commit_id":"0000000000000000000000000000000000000000","committer":{"email":"","name":"","timestamp":"1970-01-01T00:00:00+00:00"},"conflict":false,"description":"","empty":true,"parents":[],"tags":[],"working_copies":[]}
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r@-",
+            "-T",
+            r#"json(branches) ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    [{"name":"main","target":["08f6e9e8f9f60ef5a9e358b48db7c1ae87f05fd0"]}]
+    "###);
+}
+
 #[test]
 fn test_log_obslog_divergence() {
     let test_env = TestEnvironment::default();
@@ -850,7 +881,7 @@ fn test_log_contained_in() {
       |
       = Invalid string pattern
     3: Invalid string pattern kind "x:"
-    Hint: Try prefixing with one of `exact:`, `glob:` or `substring:`
+    Hint: Try prefixing with one of `exact:`, `glob:`, `regex:`, or `substring:`
     "###);
 
     let stderr = test_env.jj_cmd_failure(
@@ -870,3 +901,148 @@ fn test_log_contained_in() {
     Hint: Did you mean "main"?
     "###);
 }
+
+#[test]
+fn test_log_signature() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(
+        r#"
+    signing.backend = "test"
+    signing.key = "impeccable"
+    "#,
+    );
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["describe", "-mfirst", "--config-toml=signing.sign-all=true"],
+    );
+    test_env.jj_cmd_ok(&repo_path, &["new", "-msecond"]);
+
+    let template = r#"
+    separate(" ",
+      description.first_line(),
+      if(signature,
+        "signed:" ++ signature.status() ++ " key:" ++ signature.key(),
+        "unsigned"),
+    ) ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-r::@", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  second unsigned
+    ◉  first signed:good key:impeccable
+    ◉  unsigned
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "-r~signed() & ~root()",
+            "-T",
+            r#"description.first_line() ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @  second
+    │
+    ~
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "-rsignature_status(good)",
+            "-T",
+            r#"description.first_line() ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    ◉  first
+    │
+    ~
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-rsignature_status(nice)"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse revset: Expected expression of type signature status
+    Caused by:  --> 1:18
+      |
+    1 | signature_status(nice)
+      |                  ^--^
+      |
+      = Expected expression of type signature status
+    "###);
+}
+
+#[test]
+fn test_log_trailers() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "describe",
+            "-mfirst",
+            "-mbody",
+            "-mChange-Id: I1234\nSigned-off-by: Test User\n  <test.user@example.com>",
+        ],
+    );
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["new", "-msecond\n\nKey: value\n\nnot trailers"],
+    );
+
+    let template = r#"
+    separate(" ",
+      description.first_line(),
+      if(trailers,
+        trailers.map(|t| "[" ++ t.key() ++ "=" ++ t.value() ++ "]"),
+        "no trailers"),
+    ) ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-r::@", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  second no trailers
+    ◉  first [Change-Id=I1234] [Signed-off-by=Test User <test.user@example.com>]
+    ◉  no trailers
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r@-", "-T", r#"trailers ++ "\n""#],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    Change-Id: I1234
+    Signed-off-by: Test User <test.user@example.com>
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r@-",
+            "-T",
+            r#"json(trailers) ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    [{"key":"Change-Id","value":"I1234"},{"key":"Signed-off-by","value":"Test User <test.user@example.com>"}]
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            r#"-rtrailer(signed-off-by, glob:"*example.com*")"#,
+            "-T",
+            r#"description.first_line() ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    ◉  first
+    │
+    ~
+    "###);
+}
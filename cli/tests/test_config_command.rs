@@ -419,6 +419,145 @@ fn test_config_layer_workspace() {
     insta::assert_snapshot!(stdout, @r###"
     ui.editor = "main-repo"
     "###);
+
+    // Workspace
+    test_env.jj_cmd_ok(
+        &secondary_path,
+        &[
+            "config",
+            "set",
+            "--workspace",
+            config_key,
+            "secondary-workspace",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(&main_path, &["config", "list", config_key]);
+    insta::assert_snapshot!(stdout, @r###"
+    ui.editor = "main-repo"
+    "###);
+    let stdout = test_env.jj_cmd_success(&secondary_path, &["config", "list", config_key]);
+    insta::assert_snapshot!(stdout, @r###"
+    ui.editor = "secondary-workspace"
+    "###);
+    let stdout = test_env.jj_cmd_success(&secondary_path, &["config", "list", "--workspace"]);
+    insta::assert_snapshot!(stdout, @r###"
+    ui.editor = "secondary-workspace"
+    "###);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(secondary_path.join(".jj/workspace-config.toml")).unwrap(),
+        @r###"
+    [ui]
+    editor = "secondary-workspace"
+    "###);
+}
+
+#[test]
+fn test_config_conditional_scopes() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "work"]);
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "personal"]);
+    let env_root = test_env.env_root().canonicalize().unwrap();
+    let work_path = env_root.join("work");
+    let personal_path = env_root.join("personal");
+    test_env.jj_cmd_ok(
+        &personal_path,
+        &[
+            "git",
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/personal/repo.git",
+        ],
+    );
+
+    test_env.add_config(&format!(
+        r#"
+        test-key = "default"
+
+        [[--scope]]
+        --when.repositories = [{work_path:?}]
+        test-key = "work"
+
+        [[--scope]]
+        --when.remotes = ["*github.com/personal/*"]
+        revset-aliases."trunk()" = "main@origin"
+
+        [[--scope]]
+        --when.repositories = [{work_path:?}]
+        --when.hostnames = ["no-such-host"]
+        test-key = "host"
+        "#,
+        work_path = work_path.to_str().unwrap(),
+    ));
+
+    // The repository path matches the workspace root or its parents
+    let stdout = test_env.jj_cmd_success(&work_path, &["config", "list", "test-key"]);
+    insta::assert_snapshot!(stdout, @r###"
+    test-key = "work"
+    "###);
+    std::fs::create_dir(work_path.join("subdir")).unwrap();
+    let stdout =
+        test_env.jj_cmd_success(&work_path.join("subdir"), &["config", "list", "test-key"]);
+    insta::assert_snapshot!(stdout, @r###"
+    test-key = "work"
+    "###);
+    let stdout = test_env.jj_cmd_success(&personal_path, &["config", "list", "test-key"]);
+    insta::assert_snapshot!(stdout, @r###"
+    test-key = "default"
+    "###);
+    let stdout = test_env.jj_cmd_success(&env_root, &["config", "list", "test-key"]);
+    insta::assert_snapshot!(stdout, @r###"
+    test-key = "default"
+    "###);
+
+    // The scopes are evaluated against the repository specified by -R
+    let stdout = test_env.jj_cmd_success(&env_root, &["config", "list", "test-key", "-R", "work"]);
+    insta::assert_snapshot!(stdout, @r###"
+    test-key = "work"
+    "###);
+
+    // Remote URLs of the backing Git repository
+    let stdout = test_env.jj_cmd_success(&personal_path, &["config", "list", "revset-aliases"]);
+    insta::assert_snapshot!(stdout, @r###"
+    revset-aliases."trunk()" = "main@origin"
+    "###);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&work_path, &["config", "list", "revset-aliases"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Warning: No matching config key for revset-aliases
+    "###);
+
+    // Scopes are also evaluated in the repo config
+    std::fs::write(
+        work_path.join(".jj/repo/config.toml"),
+        r#"
+        [[--scope]]
+        --when.remotes = ["*"]
+        test-key = "remote"
+        [[--scope]]
+        test-key = "repo"
+        "#,
+    )
+    .unwrap();
+    let stdout = test_env.jj_cmd_success(&work_path, &["config", "list", "test-key"]);
+    insta::assert_snapshot!(stdout, @r###"
+    test-key = "repo"
+    "###);
+
+    // Invalid conditions
+    std::fs::write(
+        work_path.join(".jj/repo/config.toml"),
+        r#"
+        [[--scope]]
+        --when.branches = ["main"]
+        "#,
+    )
+    .unwrap();
+    let stderr = test_env.jj_cmd_failure(&work_path, &["config", "list", "test-key"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Config error: unknown field `branches`, expected one of `repositories`, `remotes`, `hostnames`
+    For help, see https://github.com/martinvonz/jj/blob/main/docs/config.md.
+    "###);
 }
 
 #[test]
@@ -427,11 +566,11 @@ fn test_config_set_bad_opts() {
     let stderr = test_env.jj_cmd_cli_error(test_env.env_root(), &["config", "set"]);
     insta::assert_snapshot!(stderr, @r###"
     error: the following required arguments were not provided:
-      <--user|--repo>
+      <--user|--repo|--workspace>
       <NAME>
       <VALUE>
 
-    Usage: jj config set <--user|--repo> <NAME> <VALUE>
+    Usage: jj config set <--user|--repo|--workspace> <NAME> <VALUE>
 
     For more information, try '--help'.
     "###);
@@ -608,9 +747,9 @@ fn test_config_edit_missing_opt() {
     let stderr = test_env.jj_cmd_cli_error(test_env.env_root(), &["config", "edit"]);
     insta::assert_snapshot!(stderr, @r###"
     error: the following required arguments were not provided:
-      <--user|--repo>
+      <--user|--repo|--workspace>
 
-    Usage: jj config edit <--user|--repo>
+    Usage: jj config edit <--user|--repo|--workspace>
 
     For more information, try '--help'.
     "###);
@@ -664,6 +803,12 @@ fn test_config_path() {
       $TEST_ENV/repo/.jj/repo/config.toml
       "###
     );
+    assert_snapshot!(
+      test_env.jj_cmd_success(&repo_path, &["config", "path", "--workspace"]),
+      @r###"
+    $TEST_ENV/repo/.jj/workspace-config.toml
+    "###
+    );
 }
 
 #[test]
@@ -129,7 +129,7 @@ fn test_debug_reindex() {
     );
     let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_path, &["debug", "reindex"]);
     assert_snapshot!(stdout, @"");
-    insta::assert_snapshot!(stderr, @r###"
+    assert_snapshot!(stderr, @r###"
     Finished indexing 4 commits.
     "###);
     let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "index"]);
@@ -241,6 +241,122 @@ fn test_debug_operation_id() {
     );
 }
 
+#[cfg(all(feature = "inotify", target_os = "linux"))]
+#[test]
+fn test_debug_inotify() {
+    use std::process::{Child, Command, Stdio};
+    use std::time::Duration;
+
+    struct KillOnDrop(Child);
+
+    impl Drop for KillOnDrop {
+        fn drop(&mut self) {
+            self.0.kill().ok();
+            self.0.wait().ok();
+        }
+    }
+
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+    test_env.add_config(r#"core.fsmonitor = "inotify""#);
+    std::fs::write(workspace_path.join(".gitignore"), "ignored/\n").unwrap();
+    std::fs::create_dir(workspace_path.join("ignored")).unwrap();
+
+    // Not using `jj_cmd()` so that polling doesn't change the command numbers
+    let jj = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin("jj"));
+        cmd.current_dir(&workspace_path)
+            .args(args)
+            .env_clear()
+            .env("HOME", test_env.home_dir())
+            .env("JJ_CONFIG", test_env.config_path())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        cmd
+    };
+    let _daemon = KillOnDrop(jj(&["debug", "inotify", "daemon"]).spawn().unwrap());
+    let mut attempts = 0;
+    while !jj(&["debug", "inotify", "query-clock"])
+        .status()
+        .unwrap()
+        .success()
+    {
+        attempts += 1;
+        assert!(attempts < 100, "the inotify daemon didn't start");
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    // The first snapshot crawls the working copy. Ignored directories are always
+    // reported as changed.
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["status"]);
+    assert_snapshot!(stdout, @r###"
+    Working copy changes:
+    A .gitignore
+    Working copy : qpvuntsm 320dc9cd (no description set)
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    "###);
+    std::fs::write(workspace_path.join("file"), "contents").unwrap();
+    std::fs::create_dir(workspace_path.join("dir")).unwrap();
+    std::fs::write(workspace_path.join("ignored").join("file"), "contents").unwrap();
+    std::fs::write(workspace_path.join(".jj").join("file"), "contents").unwrap();
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &["debug", "inotify", "query-changed-files"],
+    );
+    assert_snapshot!(stdout, @r###"
+    Changed files: Some(["dir", "file", "ignored"])
+    "###);
+
+    // Files in newly created directories are watched
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["status"]);
+    assert_snapshot!(stdout, @r###"
+    Working copy changes:
+    A .gitignore
+    A file
+    Working copy : qpvuntsm eedb0239 (no description set)
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    "###);
+    std::fs::write(workspace_path.join("dir").join("file"), "contents").unwrap();
+    std::fs::write(workspace_path.join("file"), "modified").unwrap();
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &["debug", "inotify", "query-changed-files"],
+    );
+    assert_snapshot!(stdout, @r###"
+    Changed files: Some(["dir/file", "file", "ignored"])
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["status"]);
+    assert_snapshot!(stdout, @r###"
+    Working copy changes:
+    A .gitignore
+    A dir/file
+    A file
+    Working copy : qpvuntsm 235b40af (no description set)
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    "###);
+
+    // Directories which are no longer ignored are watched
+    std::fs::write(workspace_path.join(".gitignore"), "").unwrap();
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &["debug", "inotify", "query-changed-files"],
+    );
+    assert_snapshot!(stdout, @r###"
+    Changed files: Some([".gitignore", "ignored"])
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["status"]);
+    assert_snapshot!(stdout, @r###"
+    Working copy changes:
+    A .gitignore
+    A dir/file
+    A file
+    A ignored/file
+    Working copy : qpvuntsm f24fc143 (no description set)
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    "###);
+}
+
 fn filter_index_stats(text: &str) -> String {
     let regex = Regex::new(r"    Name: [0-9a-z]+").unwrap();
     regex.replace_all(text, "    Name: [hash]").to_string()
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::path::Path;
+
 use crate::common::{get_stderr_string, TestEnvironment};
 
 #[test]
@@ -171,6 +173,179 @@ fn test_describe() {
     assert!(get_stderr_string(&assert).contains("bad-jj-editor-from-jj-editor-env"));
 }
 
+#[test]
+fn test_describe_multiple_commits() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let edit_script = test_env.set_up_fake_editor();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  kkmpptxzrspx test.user@example.com
+    ◉  rlvkpnrzqnoo test.user@example.com
+    ◉  qpvuntsmwlqt test.user@example.com
+    ◉  zzzzzzzzzzzz
+    "###);
+
+    // Initial buffer contains a section for each commit in topological order
+    std::fs::write(&edit_script, "dump editor0").unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["describe", "-r", "@--::"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor0")).unwrap(), @r###"
+    JJ: Enter or edit commit descriptions after the `JJ: describe` lines.
+    JJ: The text you enter will be lost if the `JJ: describe` lines are changed.
+
+    JJ: describe qpvuntsmwlqt -------
+
+    JJ: describe rlvkpnrzqnoo -------
+
+    JJ: describe kkmpptxzrspx -------
+
+    JJ: Lines starting with "JJ: " (like this one) will be removed.
+    "###);
+
+    // Set the descriptions in editor
+    std::fs::write(
+        &edit_script,
+        r#"write
+JJ: describe qpvuntsmwlqt -------
+first
+
+JJ: describe kkmpptxzrspx -------
+third
+"#,
+    )
+    .unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["describe", "-r", "@--::"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Missing description for change rlvkpnrzqnoo in the edited descriptions
+    "###);
+    std::fs::write(
+        &edit_script,
+        r#"write
+JJ: describe qpvuntsmwlqt -------
+first
+
+JJ: describe rlvkpnrzqnoo -------
+second
+line 2
+
+JJ: describe kkmpptxzrspx -------
+third
+"#,
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["describe", "-r", "@--::"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Updated 3 commits
+    Working copy now at: kkmpptxz 642c4362 (empty) third
+    Parent commit      : rlvkpnrz 409e20a4 (empty) second
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  kkmpptxzrspx test.user@example.com third
+    ◉  rlvkpnrzqnoo test.user@example.com second
+    │  line 2
+    ◉  qpvuntsmwlqt test.user@example.com first
+    ◉  zzzzzzzzzzzz
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "-n1", "-Tdescription"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22 and 2 more
+    "###);
+
+    // Unexpected and duplicated sections are rejected
+    std::fs::write(
+        &edit_script,
+        r#"write
+JJ: describe qpvuntsmwlqt -------
+first
+JJ: describe rlvkpnrzqnoo -------
+second
+JJ: describe rlvkpnrzqnoo -------
+second again
+"#,
+    )
+    .unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["describe", "-r", "@--", "-r", "@-"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Change rlvkpnrzqnoo is described more than once
+    "###);
+    std::fs::write(
+        &edit_script,
+        r#"write
+JJ: describe qpvuntsmwlqt -------
+first
+JJ: describe kkmpptxzrspx -------
+third
+"#,
+    )
+    .unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["describe", "-r", "@--", "-r", "@-"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Unexpected change kkmpptxzrspx in the edited descriptions
+    "###);
+    std::fs::write(&edit_script, "write\nno header\n").unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["describe", "-r", "@--", "-r", "@-"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Found the following line without a `JJ: describe` header: no header
+    "###);
+
+    // Unchanged descriptions are left alone, descendants are rebased
+    std::fs::write(
+        &edit_script,
+        r#"write
+JJ: describe qpvuntsmwlqt -------
+first
+
+JJ: describe rlvkpnrzqnoo -------
+second (edited)
+"#,
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["describe", "-r", "@--", "-r", "@-"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Rebased 1 descendant commits
+    Working copy now at: kkmpptxz 054819d5 (empty) third
+    Parent commit      : rlvkpnrz 2f040cec (empty) second (edited)
+    "###);
+
+    // The same message and author are applied to all commits
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "describe",
+            "-r",
+            "@--::@-",
+            "-m",
+            "shared",
+            "--reset-author",
+            "--config-toml",
+            r#"user.email = "ove.ridder@example.com""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Updated 2 commits
+    Rebased 1 descendant commits
+    Working copy now at: kkmpptxz 2f924021 (empty) third
+    Parent commit      : rlvkpnrz feb5d691 (empty) shared
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  kkmpptxzrspx test.user@example.com third
+    ◉  rlvkpnrzqnoo ove.ridder@example.com shared
+    ◉  qpvuntsmwlqt ove.ridder@example.com shared
+    ◉  zzzzzzzzzzzz
+    "###);
+}
+
 #[test]
 fn test_multiple_message_args() {
     let test_env = TestEnvironment::default();
@@ -323,3 +498,136 @@ fn test_describe_author() {
     ~
     "###);
 }
+
+#[test]
+fn test_describe_trailers() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let get_description = || {
+        test_env.jj_cmd_success(
+            &repo_path,
+            &["log", "--no-graph", "-r@", "-T", "description"],
+        )
+    };
+
+    // Add trailers given on the command line
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "describe",
+            "-m",
+            "subject",
+            "--add-trailer",
+            "Reviewed-by: Foo Bar <foo@bar.com>",
+            "--add-trailer=Change-Id:I1234",
+        ],
+    );
+    insta::assert_snapshot!(get_description(), @r###"
+    subject
+
+    Reviewed-by: Foo Bar <foo@bar.com>
+    Change-Id: I1234
+    "###);
+
+    // Existing trailers aren't duplicated
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["describe", "--no-edit", "--add-trailer", "Change-Id: I1234"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+
+    let stderr =
+        test_env.jj_cmd_cli_error(&repo_path, &["describe", "--add-trailer", "not a trailer"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: invalid value 'not a trailer' for '--add-trailer <TRAILER>': expected KEY: VALUE
+
+    For more information, try '--help'.
+    "###);
+
+    // Add trailers generated by the template
+    test_env.add_config(r#"templates.commit_trailers = 'format_signed_off_by_trailer(self)'"#);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "describe",
+            "--config-toml",
+            r#"user.name = "Ove Ridder"
+            user.email = "ove.ridder@example.com""#,
+            "--no-edit",
+        ],
+    );
+    insta::assert_snapshot!(get_description(), @r###"
+    subject
+
+    Reviewed-by: Foo Bar <foo@bar.com>
+    Change-Id: I1234
+    Signed-off-by: Ove Ridder <ove.ridder@example.com>
+    "###);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "describe",
+            "--config-toml",
+            r#"user.name = "Ove Ridder"
+            user.email = "ove.ridder@example.com""#,
+            "--no-edit",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+
+    // The template isn't applied to empty descriptions
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["describe", "-m", ""]);
+    insta::assert_snapshot!(stderr, @r###"
+    Working copy now at: qpvuntsm d0793f72 (empty) (no description set)
+    Parent commit      : zzzzzzzz 00000000 (empty) (no description set)
+    "###);
+    insta::assert_snapshot!(get_description(), @"");
+
+    // Trailers added to an empty description aren't duplicated
+    let add_trailer_args = [
+        "describe",
+        "--no-edit",
+        "--add-trailer",
+        "Change-Id: I1234",
+        "--config-toml",
+        "templates.commit_trailers = ''",
+    ];
+    test_env.jj_cmd_ok(&repo_path, &add_trailer_args);
+    insta::assert_snapshot!(get_description(), @r###"
+
+    Change-Id: I1234
+    "###);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &add_trailer_args);
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+    insta::assert_snapshot!(get_description(), @r###"
+
+    Change-Id: I1234
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &[
+            "describe",
+            "-m",
+            "subject",
+            "--config-toml",
+            r#"templates.commit_trailers = '"not a trailer"'"#,
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Invalid trailer: not a trailer
+    Hint: The templates.commit_trailers template should produce lines of `KEY: VALUE`.
+    "###);
+}
+
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    let template = r#"change_id.short() ++ " " ++ author.email() ++ " " ++ description"#;
+    test_env.jj_cmd_success(repo_path, &["log", "-T", template])
+}
@@ -31,63 +31,45 @@ fn test_diff_basic() {
 
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff"]);
     insta::assert_snapshot!(stdout, @r###"
-    Removed regular file file1:
-       1     : foo
     Modified regular file file2:
        1    1: foo
             2: bar
        2    3: baz quxquux
-    Added regular file file3:
-            1: foo
+    Renamed regular file {file1 => file3}:
     "###);
 
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--context=0"]);
     insta::assert_snapshot!(stdout, @r###"
-    Removed regular file file1:
-       1     : foo
     Modified regular file file2:
        1    1: foo
             2: bar
        2    3: baz quxquux
-    Added regular file file3:
-            1: foo
+    Renamed regular file {file1 => file3}:
     "###);
 
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--color=debug"]);
     insta::assert_snapshot!(stdout, @r###"
-    [38;5;3m<<diff header::Removed >><<diff header::regular file>><<diff header:: >><<diff header::file1>><<diff header:::>>[39m
-    [38;5;1m<<diff removed line_number:: >><<diff removed line_number:: >><<diff removed line_number:: >><<diff removed line_number::1>>[39m<<diff:: >><<diff::    : >>[4m[38;5;1m<<diff removed token::foo>>[24m[39m
     [38;5;3m<<diff header::Modified regular file>><<diff header:: >><<diff header::file2>><<diff header:::>>[39m
     [38;5;1m<<diff removed line_number:: >><<diff removed line_number:: >><<diff removed line_number:: >><<diff removed line_number::1>>[39m<<diff:: >>[38;5;2m<<diff added line_number:: >><<diff added line_number:: >><<diff added line_number:: >><<diff added line_number::1>>[39m<<diff::: >><<diff::foo>>
     <<diff::     >>[38;5;2m<<diff added line_number:: >><<diff added line_number:: >><<diff added line_number:: >><<diff added line_number::2>>[39m<<diff::: >>[4m[38;5;2m<<diff added token::bar>>[24m[39m
     [38;5;1m<<diff removed line_number:: >><<diff removed line_number:: >><<diff removed line_number:: >><<diff removed line_number::2>>[39m<<diff:: >>[38;5;2m<<diff added line_number:: >><<diff added line_number:: >><<diff added line_number:: >><<diff added line_number::3>>[39m<<diff::: >><<diff::baz >>[4m[38;5;1m<<diff removed token::qux>>[38;5;2m<<diff added token::quux>>[24m[39m<<diff::>>
-    [38;5;3m<<diff header::Added >><<diff header::regular file>><<diff header:: >><<diff header::file3>><<diff header:::>>[39m
-    <<diff::     >>[38;5;2m<<diff added line_number:: >><<diff added line_number:: >><<diff added line_number:: >><<diff added line_number::1>>[39m<<diff::: >>[4m[38;5;2m<<diff added token::foo>>[24m[39m
+    [38;5;3m<<diff header::Renamed regular file>><<diff header:: >><<diff header::{file1 => file3}>><<diff header:::>>[39m
     "###);
 
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s"]);
     insta::assert_snapshot!(stdout, @r###"
-    D file1
     M file2
-    A file3
+    R {file1 => file3}
     "###);
 
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--types"]);
     insta::assert_snapshot!(stdout, @r###"
-    F- file1
     FF file2
-    -F file3
+    FF {file1 => file3}
     "###);
 
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git"]);
     insta::assert_snapshot!(stdout, @r###"
-    diff --git a/file1 b/file1
-    deleted file mode 100644
-    index 257cc5642c..0000000000
-    --- a/file1
-    +++ /dev/null
-    @@ -1,1 +1,0 @@
-    -foo
     diff --git a/file2 b/file2
     index 523a4a9de8...485b56a572 100644
     --- a/file2
@@ -97,24 +79,13 @@ fn test_diff_basic() {
     -baz qux
     +bar
     +baz quux
-    diff --git a/file3 b/file3
-    new file mode 100644
-    index 0000000000..257cc5642c
-    --- /dev/null
-    +++ b/file3
-    @@ -1,0 +1,1 @@
-    +foo
+    diff --git a/file1 b/file3
+    rename from file1
+    rename to file3
     "###);
 
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git", "--context=0"]);
     insta::assert_snapshot!(stdout, @r###"
-    diff --git a/file1 b/file1
-    deleted file mode 100644
-    index 257cc5642c..0000000000
-    --- a/file1
-    +++ /dev/null
-    @@ -1,1 +1,0 @@
-    -foo
     diff --git a/file2 b/file2
     index 523a4a9de8...485b56a572 100644
     --- a/file2
@@ -123,24 +94,13 @@ fn test_diff_basic() {
     -baz qux
     +bar
     +baz quux
-    diff --git a/file3 b/file3
-    new file mode 100644
-    index 0000000000..257cc5642c
-    --- /dev/null
-    +++ b/file3
-    @@ -1,0 +1,1 @@
-    +foo
+    diff --git a/file1 b/file3
+    rename from file1
+    rename to file3
     "###);
 
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git", "--color=debug"]);
     insta::assert_snapshot!(stdout, @r###"
-    [1m<<diff file_header::diff --git a/>><<diff file_header::file1>><<diff file_header:: b/>><<diff file_header::file1>><<diff file_header::>>[0m
-    [1m<<diff file_header::deleted file mode >><<diff file_header::100644>><<diff file_header::>>[0m
-    [1m<<diff file_header::index >><<diff file_header::257cc5642c>><<diff file_header::..0000000000>>[0m
-    [1m<<diff file_header::--- a/>><<diff file_header::file1>><<diff file_header::>>[0m
-    [1m<<diff file_header::+++ /dev/null>>[0m
-    [38;5;6m<<diff hunk_header::@@ ->><<diff hunk_header::1>><<diff hunk_header::,>><<diff hunk_header::1>><<diff hunk_header:: +>><<diff hunk_header::1>><<diff hunk_header::,>><<diff hunk_header::0>><<diff hunk_header:: @@>>[39m
-    [38;5;1m<<diff removed::->><<diff removed::foo>>[39m
     [1m<<diff file_header::diff --git a/>><<diff file_header::file2>><<diff file_header:: b/>><<diff file_header::file2>><<diff file_header::>>[0m
     [1m<<diff file_header::index >><<diff file_header::523a4a9de8>><<diff file_header::...>><<diff file_header::485b56a572>><<diff file_header:: >><<diff file_header::100644>><<diff file_header::>>[0m
     [1m<<diff file_header::--- a/>><<diff file_header::file2>><<diff file_header::>>[0m
@@ -150,27 +110,15 @@ fn test_diff_basic() {
     [38;5;1m<<diff removed::->><<diff removed::baz qux>>[39m
     [38;5;2m<<diff added::+>><<diff added::bar>>[39m
     [38;5;2m<<diff added::+>><<diff added::baz quux>>[39m
-    [1m<<diff file_header::diff --git a/>><<diff file_header::file3>><<diff file_header:: b/>><<diff file_header::file3>><<diff file_header::>>[0m
-    [1m<<diff file_header::new file mode >><<diff file_header::100644>><<diff file_header::>>[0m
-    [1m<<diff file_header::index 0000000000..>><<diff file_header::257cc5642c>><<diff file_header::>>[0m
-    [1m<<diff file_header::--- /dev/null>>[0m
-    [1m<<diff file_header::+++ b/>><<diff file_header::file3>><<diff file_header::>>[0m
-    [38;5;6m<<diff hunk_header::@@ ->><<diff hunk_header::1>><<diff hunk_header::,>><<diff hunk_header::0>><<diff hunk_header:: +>><<diff hunk_header::1>><<diff hunk_header::,>><<diff hunk_header::1>><<diff hunk_header:: @@>>[39m
-    [38;5;2m<<diff added::+>><<diff added::foo>>[39m
+    [1m<<diff file_header::diff --git a/>><<diff file_header::file1>><<diff file_header:: b/>><<diff file_header::file3>><<diff file_header::>>[0m
+    [1m<<diff file_header::rename from >><<diff file_header::file1>><<diff file_header::>>[0m
+    [1m<<diff file_header::rename to >><<diff file_header::file3>><<diff file_header::>>[0m
     "###);
 
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "--git"]);
     insta::assert_snapshot!(stdout, @r###"
-    D file1
     M file2
-    A file3
-    diff --git a/file1 b/file1
-    deleted file mode 100644
-    index 257cc5642c..0000000000
-    --- a/file1
-    +++ /dev/null
-    @@ -1,1 +1,0 @@
-    -foo
+    R {file1 => file3}
     diff --git a/file2 b/file2
     index 523a4a9de8...485b56a572 100644
     --- a/file2
@@ -180,21 +128,16 @@ fn test_diff_basic() {
     -baz qux
     +bar
     +baz quux
-    diff --git a/file3 b/file3
-    new file mode 100644
-    index 0000000000..257cc5642c
-    --- /dev/null
-    +++ b/file3
-    @@ -1,0 +1,1 @@
-    +foo
+    diff --git a/file1 b/file3
+    rename from file1
+    rename to file3
     "###);
 
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--stat"]);
     insta::assert_snapshot!(stdout, @r###"
-    file1 | 1 -
-    file2 | 3 ++-
-    file3 | 1 +
-    3 files changed, 3 insertions(+), 2 deletions(-)
+    file2            | 3 ++-
+    {file1 => file3} | 0
+    2 files changed, 2 insertions(+), 1 deletion(-)
     "###);
 
     // Filter by glob pattern
@@ -226,9 +169,8 @@ fn test_diff_basic() {
         ],
     );
     insta::assert_snapshot!(stdout.replace('\\', "/"), @r###"
-    D repo/file1
     M repo/file2
-    A repo/file3
+    R repo/{file1 => file3}
     "###);
     insta::assert_snapshot!(stderr.replace('\\', "/"), @r###"
     Warning: No matching entries for paths: repo/x, repo/y/z
@@ -240,6 +182,110 @@ fn test_diff_basic() {
     insta::assert_snapshot!(stderr, @"");
 }
 
+#[test]
+fn test_diff_copies_and_renames() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "1\n2\n3\n4\n5\n6\n7\n8\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "a\nb\nc\nd\ne\nf\ng\nh\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::remove_file(repo_path.join("file1")).unwrap();
+    std::fs::create_dir(repo_path.join("dir")).unwrap();
+    std::fs::write(
+        repo_path.join("dir").join("renamed"),
+        "1\n2\n3\n4\n5\n6\n7\nchanged\n",
+    )
+    .unwrap();
+    std::fs::write(repo_path.join("file2"), "a\nb\nc\nd\ne\nf\ng\nh\ni\n").unwrap();
+    std::fs::write(repo_path.join("copy"), "a\nb\nc\nd\ne\nf\ng\nh\n").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout, @r###"
+    C {file2 => copy}
+    R {file1 => dir/renamed}
+    M file2
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file2 b/copy
+    copy from file2
+    copy to copy
+    diff --git a/file1 b/dir/renamed
+    rename from file1
+    rename to dir/renamed
+    index 535d2b01d3...37d7216358 100644
+    --- a/file1
+    +++ b/dir/renamed
+    @@ -5,4 +5,4 @@
+     5
+     6
+     7
+    -8
+    +changed
+    diff --git a/file2 b/file2
+    index 71ac1b5791...b236ae5a2d 100644
+    --- a/file2
+    +++ b/file2
+    @@ -6,3 +6,4 @@
+     f
+     g
+     h
+    +i
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Copied regular file {file2 => copy}:
+        ...
+    Renamed regular file {file1 => dir/renamed}:
+        ...
+       5    5: 5
+       6    6: 6
+       7    7: 7
+       8    8: 8changed
+    Modified regular file file2:
+        ...
+       6    6: f
+       7    7: g
+       8    8: h
+            9: i
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--stat"]);
+    insta::assert_snapshot!(stdout, @r###"
+    {file2 => copy}        | 0
+    {file1 => dir/renamed} | 2 +-
+    file2                  | 1 +
+    3 files changed, 2 insertions(+), 1 deletion(-)
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--types"]);
+    insta::assert_snapshot!(stdout, @r###"
+    FF {file2 => copy}
+    FF {file1 => dir/renamed}
+    FF file2
+    "###);
+
+    // The removal of the source is shown if the target isn't included
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "file1"]);
+    insta::assert_snapshot!(stdout, @r###"
+    D file1
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["status"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Working copy changes:
+    C {file2 => copy}
+    R {file1 => dir/renamed}
+    M file2
+    Working copy : rlvkpnrz e7518a69 (no description set)
+    Parent commit: qpvuntsm 91cf587a (no description set)
+    "###);
+}
+
 #[test]
 fn test_diff_empty() {
     let test_env = TestEnvironment::default();
@@ -1318,11 +1364,9 @@ fn test_diff_binary() {
 
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff"]);
     insta::assert_snapshot!(stdout, @r###"
-    Removed regular file file1.png:
-        (binary)
     Modified regular file file2.png:
         (binary)
-    Added regular file file3.png:
+    Renamed regular file {file1.png => file3.png}:
         (binary)
     Added regular file file4.png:
         (binary)
@@ -1330,10 +1374,71 @@ fn test_diff_binary() {
 
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--stat"]);
     insta::assert_snapshot!(stdout, @r###"
-    file1.png | 3 ---
-    file2.png | 5 ++---
-    file3.png | 3 +++
-    file4.png | 1 +
-    4 files changed, 6 insertions(+), 6 deletions(-)
+    file2.png                | 5 ++---
+    {file1.png => file3.png} | 2 +-
+    file4.png                | 1 +
+    3 files changed, 4 insertions(+), 4 deletions(-)
+    "###);
+}
+
+#[test]
+fn test_diff_merge_commit_with_merge_driver() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    // The driver fails if it's run
+    test_env.add_config(
+        r#"
+        [merge-drivers.lockfiles]
+        files = 'glob:"*.lock"'
+        command = ["jj-nonexistent-merge-driver", "%output"]
+        "#,
+    );
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "base"]);
+    std::fs::write(repo_path.join("deps.lock"), "base\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "left"]);
+    std::fs::write(repo_path.join("deps.lock"), "left\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(base)", "-m", "right"]);
+    std::fs::write(repo_path.join("deps.lock"), "right\n").unwrap();
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["new", "description(left)", "description(right)"],
+    );
+    std::fs::write(repo_path.join("deps.lock"), "merged\n").unwrap();
+
+    // Diffing the merge commit merges the parent trees without the driver
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @r###"
+    M deps.lock
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-r@", "-p", "-T", "''"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @
+    │  Resolved conflict in deps.lock:
+    ~     1    1: <<<<<<< Conflict 1 of 1merged
+          2     : %%%%%%% Changes from base to side #1
+          3     : -base
+          4     : +left
+          5     : +++++++ Contents of side #2
+          6     : right
+          7     : >>>>>>> Conflict 1 of 1 ends
+    "###);
+
+    // Rebasing runs the driver
+    let stderr = test_env.jj_cmd_internal_error(
+        &repo_path,
+        &[
+            "rebase",
+            "-r",
+            "description(right)",
+            "-d",
+            "description(left)",
+        ],
+    );
+    insta::assert_snapshot!(strip_last_line(&stderr), @r###"
+    Internal error: Unexpected error from backend
+    Caused by:
+    1: Failed to run merge driver 'lockfiles'
     "###);
 }
@@ -251,6 +251,178 @@ fn test_duplicate_many() {
 }
 
 // https://github.com/martinvonz/jj/issues/1050
+#[test]
+fn test_duplicate_destination() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a1", &[]);
+    create_commit(&test_env, &repo_path, "a2", &["a1"]);
+    create_commit(&test_env, &repo_path, "b1", &[]);
+    create_commit(&test_env, &repo_path, "c1", &[]);
+    // Test the setup
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  04412d875ed0   c1
+    │ ◉  224867cdd6ef   b1
+    ├─╯
+    │ ◉  47df67757a64   a2
+    │ ◉  9e85a474f005   a1
+    ├─╯
+    ◉  000000000000
+    "###);
+
+    // Duplicate a single commit onto another
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate", "a1", "-d", "c1"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Duplicated 9e85a474f005 as kpqxywon b1f8aedf a1
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ◉  b1f8aedfa110   a1
+    @  04412d875ed0   c1
+    │ ◉  224867cdd6ef   b1
+    ├─╯
+    │ ◉  47df67757a64   a2
+    │ ◉  9e85a474f005   a1
+    ├─╯
+    ◉  000000000000
+    "###);
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+
+    // Multiple destinations create a merge commit
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["duplicate", "a1", "-d", "b1", "-d", "c1"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Duplicated 9e85a474f005 as lylxulpl e7a5e6d8 a1
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ◉    e7a5e6d86f9d   a1
+    ├─╮
+    │ @  04412d875ed0   c1
+    ◉ │  224867cdd6ef   b1
+    ├─╯
+    │ ◉  47df67757a64   a2
+    │ ◉  9e85a474f005   a1
+    ├─╯
+    ◉  000000000000
+    "###);
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+
+    // The topology of the duplicated commits is preserved
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate", "a1", "a2", "-d", "c1"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Duplicated 9e85a474f005 as uyznsvlq 3a84e6b5 a1
+    Duplicated 47df67757a64 as uuqyqztp 66dfc9f9 a2
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ◉  66dfc9f9729b   a2
+    ◉  3a84e6b5aaaf   a1
+    @  04412d875ed0   c1
+    │ ◉  224867cdd6ef   b1
+    ├─╯
+    │ ◉  47df67757a64   a2
+    │ ◉  9e85a474f005   a1
+    ├─╯
+    ◉  000000000000
+    "###);
+}
+
+#[test]
+fn test_duplicate_insert_after_before() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a1", &[]);
+    create_commit(&test_env, &repo_path, "a2", &["a1"]);
+    create_commit(&test_env, &repo_path, "b1", &[]);
+    create_commit(&test_env, &repo_path, "b2", &["b1"]);
+    // Test the setup
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  fe7492e10bd8   b2
+    ◉  224867cdd6ef   b1
+    │ ◉  47df67757a64   a2
+    │ ◉  9e85a474f005   a1
+    ├─╯
+    ◉  000000000000
+    "###);
+
+    // Insert after a commit, rebasing its children onto the duplicate
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate", "b1", "-A", "a1"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Duplicated 224867cdd6ef as kpqxywon 90cb23cc b1
+    Rebased 1 commits onto duplicated commits
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ◉  cf550eae150b   a2
+    ◉  90cb23cc0e0b   b1
+    ◉  9e85a474f005   a1
+    │ @  fe7492e10bd8   b2
+    │ ◉  224867cdd6ef   b1
+    ├─╯
+    ◉  000000000000
+    "###);
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+
+    // Insert before a commit
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate", "b1", "b2", "-B", "a2"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Duplicated 224867cdd6ef as lylxulpl 4d00d300 b1
+    Duplicated fe7492e10bd8 as rsllmpnm bdf7b976 b2
+    Rebased 1 commits onto duplicated commits
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ◉  fa91fabee40d   a2
+    ◉  bdf7b97673b6   b2
+    ◉  4d00d300f2b1   b1
+    ◉  9e85a474f005   a1
+    │ @  fe7492e10bd8   b2
+    │ ◉  224867cdd6ef   b1
+    ├─╯
+    ◉  000000000000
+    "###);
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+
+    // Insert between commits
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["duplicate", "a1", "-A", "b1", "-B", "b2"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Duplicated 9e85a474f005 as uyznsvlq 85559ae9 a1
+    Rebased 1 commits onto duplicated commits
+    Working copy now at: vruxwmqv 656d7ac3 b2 | b2
+    Parent commit      : uyznsvlq 85559ae9 a1
+    Added 1 files, modified 0 files, removed 0 files
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  656d7ac35217   b2
+    ◉  85559ae99864   a1
+    ◉  224867cdd6ef   b1
+    │ ◉  47df67757a64   a2
+    │ ◉  9e85a474f005   a1
+    ├─╯
+    ◉  000000000000
+    "###);
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+
+    // Refuse to create a loop
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["duplicate", "b1", "-A", "a2", "-B", "a1"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Refusing to create a loop: commit 47df67757a64 would be both an ancestor and a descendant of the duplicated commits
+    "###);
+
+    // Can't insert before the root commit
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["duplicate", "b1", "-B", "root()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: The root commit 000000000000 is immutable
+    "###);
+}
+
 #[test]
 fn test_undo_after_duplicate() {
     let test_env = TestEnvironment::default();
@@ -47,8 +47,137 @@ fn test_fix_no_config() {
     let repo_path = test_env.env_root().join("repo");
     let stderr = test_env.jj_cmd_failure(&repo_path, &["fix", "-s", "@"]);
     insta::assert_snapshot!(stderr, @r###"
-    Config error: Invalid `fix.tool-command`
-    Caused by: configuration property "fix.tool-command" not found
+    Config error: No `fix.tools` are configured
+    For help, see https://github.com/martinvonz/jj/blob/main/docs/config.md.
+    "###);
+}
+
+/// Returns the path to the fake formatter, escaped for use in a TOML string.
+fn escaped_fake_formatter_path() -> String {
+    let formatter_path = assert_cmd::cargo::cargo_bin("fake-formatter");
+    assert!(formatter_path.is_file());
+    formatter_path.to_str().unwrap().replace('\\', r"\\")
+}
+
+#[test]
+fn test_config_multiple_tools() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let formatter_path = escaped_fake_formatter_path();
+    test_env.add_config(&format!(
+        r###"
+        [fix.tools.tool-1]
+        command = ["{formatter_path}", "--uppercase"]
+        patterns = ["foo"]
+
+        [fix.tools.tool-2]
+        command = ["{formatter_path}", "--reverse"]
+        patterns = ["bar"]
+        "###
+    ));
+
+    std::fs::write(repo_path.join("foo"), "Foo\n").unwrap();
+    std::fs::write(repo_path.join("bar"), "Bar\n").unwrap();
+    std::fs::write(repo_path.join("baz"), "Baz\n").unwrap();
+
+    let (_stdout, _stderr) = test_env.jj_cmd_ok(&repo_path, &["fix", "-s", "@"]);
+    let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "foo", "-r", "@"]);
+    insta::assert_snapshot!(content, @"FOO\n");
+    let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "bar", "-r", "@"]);
+    insta::assert_snapshot!(content, @"raB\n");
+    let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "baz", "-r", "@"]);
+    insta::assert_snapshot!(content, @"Baz\n");
+}
+
+#[test]
+fn test_config_multiple_tools_chained() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let formatter_path = escaped_fake_formatter_path();
+    // Tools are applied in the order of their names, regardless of the order
+    // they're defined in.
+    test_env.add_config(&format!(
+        r###"
+        [fix.tools.b-reverse]
+        command = ["{formatter_path}", "--reverse"]
+        patterns = ["glob:'*.txt'"]
+
+        [fix.tools.a-uppercase]
+        command = ["{formatter_path}", "--uppercase"]
+        patterns = ["foo.txt"]
+        "###
+    ));
+
+    std::fs::write(repo_path.join("foo.txt"), "Foo\n").unwrap();
+    std::fs::write(repo_path.join("bar.txt"), "Bar\n").unwrap();
+    std::fs::write(repo_path.join("baz"), "Baz\n").unwrap();
+
+    let (_stdout, _stderr) = test_env.jj_cmd_ok(&repo_path, &["fix", "-s", "@"]);
+    let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "foo.txt", "-r", "@"]);
+    insta::assert_snapshot!(content, @"OOF\n");
+    let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "bar.txt", "-r", "@"]);
+    insta::assert_snapshot!(content, @"raB\n");
+    let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "baz", "-r", "@"]);
+    insta::assert_snapshot!(content, @"Baz\n");
+}
+
+#[test]
+fn test_config_select_tool() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let formatter_path = escaped_fake_formatter_path();
+    test_env.add_config(&format!(
+        r###"
+        [fix.tools.uppercase]
+        command = ["{formatter_path}", "--uppercase"]
+        patterns = ["all()"]
+
+        [fix.tools.reverse]
+        command = ["{formatter_path}", "--reverse"]
+        patterns = ["all()"]
+        "###
+    ));
+
+    std::fs::write(repo_path.join("foo"), "Foo\n").unwrap();
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["fix", "-s", "@", "--tool", "missing"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No such fix tool: missing
+    Hint: Configured tools: reverse, uppercase
+    "###);
+
+    let (_stdout, _stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["fix", "-s", "@", "--tool", "reverse"]);
+    let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "foo", "-r", "@"]);
+    insta::assert_snapshot!(content, @"ooF\n");
+}
+
+#[test]
+fn test_config_invalid_patterns() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let formatter_path = escaped_fake_formatter_path();
+    test_env.add_config(&format!(
+        r###"
+        [fix.tools.my-tool]
+        command = ["{formatter_path}", "--uppercase"]
+        patterns = ["bad-function(foo)"]
+        "###
+    ));
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["fix", "-s", "@"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Config error: Invalid `fix.tools.my-tool.patterns`
+    Caused by:  --> 1:13
+      |
+    1 | bad-function(foo)
+      |             ^---
+      |
+      = expected <EOI>, `:`, `|`, `&`, or `~`
     For help, see https://github.com/martinvonz/jj/blob/main/docs/config.md.
     "###);
 }
@@ -380,6 +509,7 @@ fn test_failure() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["fix", "-s", "@"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
+    Warning: Fix tool 'tool-command' failed on file: Tool exited with exit status: 1
     Fixed 0 commits of 1 checked.
     Nothing changed.
     "###);
@@ -416,7 +546,8 @@ fn test_stderr_failure() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["fix", "-s", "@"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r###"
-    errorFixed 0 commits of 1 checked.
+    errorWarning: Fix tool 'tool-command' failed on file: Tool exited with exit status: 1
+    Fixed 0 commits of 1 checked.
     Nothing changed.
     "###);
     let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "file", "-r", "@"]);
@@ -429,12 +560,11 @@ fn test_missing_command() {
     test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
     let repo_path = test_env.env_root().join("repo");
     test_env.add_config(r#"fix.tool-command = ["this_executable_shouldnt_exist"]"#);
+    std::fs::write(repo_path.join("file"), "content").unwrap();
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["fix", "-s", "@"]);
     insta::assert_snapshot!(stdout, @"");
-    // TODO: We should display a warning about invalid tool configurations. When we
-    // support multiple tools, we should also keep going to see if any of the other
-    // executions succeed.
     insta::assert_snapshot!(stderr, @r###"
+    Warning: Fix tool 'tool-command' failed on file: Failed to execute 'this_executable_shouldnt_exist'
     Fixed 0 commits of 1 checked.
     Nothing changed.
     "###);
@@ -792,6 +792,119 @@ fn test_git_fetch_some_of_many_branches() {
     "###);
 }
 
+#[test]
+fn test_git_fetch_branches_by_regex() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("git.auto-local-branch = true");
+    test_env.add_config(r#"revset-aliases."immutable_heads()" = "none()""#);
+    let source_git_repo_path = test_env.env_root().join("source");
+    let _git_repo = git2::Repository::init(source_git_repo_path.clone()).unwrap();
+
+    // Clone an empty repo. The target repo is a normal `jj` repo, *not* colocated
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "clone", "source", "target"]);
+    let target_jj_repo_path = test_env.env_root().join("target");
+    create_colocated_repo_and_branches_from_trunk1(&test_env, &source_git_repo_path);
+
+    // Patterns which can't be expressed as a refspec glob are matched against
+    // the remote branch names
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &target_jj_repo_path,
+        &["git", "fetch", "--branch", r"regex:^a\d$"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    branch: a1@origin [new] tracked
+    branch: a2@origin [new] tracked
+    "###);
+    insta::assert_snapshot!(get_branch_output(&test_env, &target_jj_repo_path), @r###"
+    a1: nknoxmzm 359a9a02 descr_for_a1
+      @origin: nknoxmzm 359a9a02 descr_for_a1
+    a2: qkvnknrk decaa396 descr_for_a2
+      @origin: qkvnknrk decaa396 descr_for_a2
+    "###);
+
+    // Case-insensitive patterns are also matched against the remote branch names
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &target_jj_repo_path,
+        &["git", "fetch", "--branch", "exact-i:B"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    branch: b@origin [new] tracked
+    "###);
+    insta::assert_snapshot!(get_branch_output(&test_env, &target_jj_repo_path), @r###"
+    a1: nknoxmzm 359a9a02 descr_for_a1
+      @origin: nknoxmzm 359a9a02 descr_for_a1
+    a2: qkvnknrk decaa396 descr_for_a2
+      @origin: qkvnknrk decaa396 descr_for_a2
+    b: vpupmnsl c7d4bdcb descr_for_b
+      @origin: vpupmnsl c7d4bdcb descr_for_b
+    "###);
+}
+
+#[test]
+fn test_git_fetch_branches_by_regex_colocated() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("git.auto-local-branch = true");
+    let source_git_repo_path = test_env.env_root().join("source");
+    let _git_repo = git2::Repository::init(source_git_repo_path.clone()).unwrap();
+    create_colocated_repo_and_branches_from_trunk1(&test_env, &source_git_repo_path);
+
+    let target_jj_repo_path = test_env.env_root().join("target");
+    test_env.jj_cmd_ok(
+        test_env.env_root(),
+        &["git", "init", "--colocate", "target"],
+    );
+    let target_git_repo = git2::Repository::open(&target_jj_repo_path).unwrap();
+    test_env.jj_cmd_ok(
+        &target_jj_repo_path,
+        &["git", "remote", "add", "origin", "../source"],
+    );
+
+    // Only the matching branches are written to the Git repo, so the others
+    // aren't imported by the next command either
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &target_jj_repo_path,
+        &["git", "fetch", "--branch", r"regex:^a1$"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    branch: a1@origin [new] tracked
+    "###);
+    let git_ref_names = |git_repo: &git2::Repository| -> Vec<String> {
+        git_repo
+            .references_glob("refs/remotes/*")
+            .unwrap()
+            .map(|git_ref| git_ref.unwrap().name().unwrap().to_owned())
+            .collect()
+    };
+    insta::assert_debug_snapshot!(git_ref_names(&target_git_repo), @r###"
+    [
+        "refs/remotes/origin/a1",
+    ]
+    "###);
+    insta::assert_snapshot!(get_branch_output(&test_env, &target_jj_repo_path), @r###"
+    a1: pqvsntvk 595f93a0 descr_for_a1
+      @git: pqvsntvk 595f93a0 descr_for_a1
+      @origin: pqvsntvk 595f93a0 descr_for_a1
+    "###);
+
+    // Deleted remote branches matching the pattern are pruned
+    test_env.jj_cmd_ok(&source_git_repo_path, &["branch", "delete", "a1"]);
+    test_env.jj_cmd_ok(&source_git_repo_path, &["git", "export"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &target_jj_repo_path,
+        &["git", "fetch", "--branch", "exact-i:A1"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    branch: a1@origin [deleted] untracked
+    Abandoned 2 commits that are no longer reachable.
+    "###);
+    insta::assert_debug_snapshot!(git_ref_names(&target_git_repo), @"[]");
+    insta::assert_snapshot!(get_branch_output(&test_env, &target_jj_repo_path), @"");
+}
+
 // See `test_undo_restore_commands.rs` for fetch-undo-push and fetch-undo-fetch
 // of the same branches for various kinds of undo.
 #[test]
@@ -1254,6 +1254,74 @@ fn test_git_push_to_remote_named_git() {
     "###);
 }
 
+#[test]
+fn test_git_push_tags() {
+    let (test_env, workspace_root) = set_up();
+    let origin_git_repo = git2::Repository::open(
+        test_env
+            .env_root()
+            .join("origin")
+            .join(".jj")
+            .join("repo")
+            .join("store")
+            .join("git"),
+    )
+    .unwrap();
+    test_env.jj_cmd_ok(&workspace_root, &["tag", "create", "-rbranch1", "v1"]);
+    test_env.jj_cmd_ok(
+        &workspace_root,
+        &["tag", "create", "-rbranch2", "-mRelease 2", "v2"],
+    );
+
+    let stderr = test_env.jj_cmd_failure(&workspace_root, &["git", "push", "--tag=v3"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No such tag: v3
+    "###);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &workspace_root,
+        &["git", "push", "--tag=glob:v*", "--dry-run"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Tags to push to origin:
+      Add tag v1 to d13ecdbda2a2
+      Add tag v2 to 8476341eb395
+    Dry-run requested, not pushing.
+    "###);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push", "--tag=glob:v*"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Tags to push to origin:
+      Add tag v1 to d13ecdbda2a2
+      Add tag v2 to 8476341eb395
+    "###);
+    let v1 = origin_git_repo.find_reference("refs/tags/v1").unwrap();
+    insta::assert_snapshot!(v1.peel_to_commit().unwrap().summary().unwrap(), @"description 1");
+    // The annotated tag object is pushed as is
+    let v2 = origin_git_repo.find_reference("refs/tags/v2").unwrap();
+    insta::assert_snapshot!(v2.peel_to_tag().unwrap().message().unwrap(), @r###"
+    Release 2
+    "###);
+
+    // Pushing the same tag again is a no-op
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push", "--tag=v1"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Tags to push to origin:
+      Add tag v1 to d13ecdbda2a2
+    "###);
+
+    // Tags aren't overwritten on the remote
+    test_env.jj_cmd_ok(&workspace_root, &["tag", "set", "-rbranch2", "v1"]);
+    let stderr = test_env.jj_cmd_failure(&workspace_root, &["git", "push", "--tag=v1"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Tags to push to origin:
+      Add tag v1 to 8476341eb395
+    Error: Tags already exist on the remote with different targets
+    "###);
+}
+
 fn get_branch_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
     // --quiet to suppress deleted branches hint
     test_env.jj_cmd_success(repo_path, &["branch", "list", "--all-remotes", "--quiet"])
@@ -12,8 +12,63 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::path::Path;
+
 use crate::common::TestEnvironment;
 
+fn git_commit(
+    git_repo: &git2::Repository,
+    tree_oid: git2::Oid,
+    parents: &[git2::Oid],
+    message: &str,
+) -> git2::Oid {
+    let signature = git2::Signature::new(
+        "Someone",
+        "someone@example.com",
+        &git2::Time::new(1234567890, 60),
+    )
+    .unwrap();
+    let tree = git_repo.find_tree(tree_oid).unwrap();
+    let parents = parents
+        .iter()
+        .map(|oid| git_repo.find_commit(*oid).unwrap())
+        .collect::<Vec<_>>();
+    let parents = parents.iter().collect::<Vec<_>>();
+    git_repo
+        .commit(
+            Some("refs/heads/main"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap()
+}
+
+fn git_tree(git_repo: &git2::Repository, entries: &[(&str, git2::Oid, i32)]) -> git2::Oid {
+    let mut builder = git_repo.treebuilder(None).unwrap();
+    for (name, oid, mode) in entries {
+        builder.insert(name, *oid, *mode).unwrap();
+    }
+    builder.write().unwrap()
+}
+
+/// Creates a Git repo at `path` with two commits, each adding a file.
+fn init_submodule_origin(path: &Path) -> (git2::Oid, git2::Oid) {
+    let git_repo = git2::Repository::init(path).unwrap();
+    let blob_a = git_repo.blob(b"a\n").unwrap();
+    let blob_b = git_repo.blob(b"b\n").unwrap();
+    let tree1 = git_tree(&git_repo, &[("a", blob_a, 0o100644)]);
+    let commit1 = git_commit(&git_repo, tree1, &[], "add a");
+    let tree2 = git_tree(
+        &git_repo,
+        &[("a", blob_a, 0o100644), ("b", blob_b, 0o100644)],
+    );
+    let commit2 = git_commit(&git_repo, tree2, &[commit1], "add b");
+    (commit1, commit2)
+}
+
 #[test]
 fn test_gitsubmodule_print_gitmodules() {
     let test_env = TestEnvironment::default();
@@ -63,3 +118,81 @@ fn test_gitsubmodule_print_gitmodules() {
 	path:new
     "###);
 }
+
+#[test]
+fn test_gitsubmodule_update() {
+    let test_env = TestEnvironment::default();
+    let (sub_commit1, sub_commit2) = init_submodule_origin(&test_env.env_root().join("sub"));
+
+    // Create a parent repo pointing to the first commit of the submodule. The
+    // URL is relative to the parent repo.
+    let git_repo = git2::Repository::init(test_env.env_root().join("parent")).unwrap();
+    let gitmodules = git_repo
+        .blob(b"[submodule \"sub\"]\n\tpath = sub\n\turl = ../sub\n")
+        .unwrap();
+    let tree = git_tree(
+        &git_repo,
+        &[
+            (".gitmodules", gitmodules, 0o100644),
+            ("sub", sub_commit1, 0o160000),
+        ],
+    );
+    git_commit(&git_repo, tree, &[], "add submodule");
+    git_repo.set_head("refs/heads/main").unwrap();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "clone", "parent", "repo"]);
+    let workspace_root = test_env.env_root().join("repo");
+
+    // The submodule hasn't been fetched, so it's an empty directory
+    let sub_path = workspace_root.join("sub");
+    assert!(sub_path.is_dir());
+    assert!(!sub_path.join("a").exists());
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "update"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Fetching submodule sub from $TEST_ENV/sub
+    Checked out submodule sub at 8a2dd549331a
+    "###);
+    assert!(sub_path.join("a").exists());
+    assert!(!sub_path.join("b").exists());
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    // Moving HEAD of the submodule records a new pointer in the working copy
+    let sub_repo = git2::Repository::open(&sub_path).unwrap();
+    sub_repo.set_head_detached(sub_commit2).unwrap();
+    sub_repo
+        .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+        .unwrap();
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "--git"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/sub b/sub
+    index 8a2dd54933...feabac77e0 160000
+    --- a/sub
+    +++ b/sub
+    @@ -1,1 +1,1 @@
+    -Subproject commit 8a2dd549331a6ab3f2c1c0b7ba7f975c6b6f8ad4
+    +Subproject commit feabac77e049f5c8b8363140a91b5b23eb4f74ab
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @r###"
+    M sub
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Modified Git submodule sub:
+       1    1: Git submodule checked out at 8a2dd549331a6ab3f2c1c0b7ba7f975c6b6f8ad4feabac77e049f5c8b8363140a91b5b23eb4f74ab
+    "###);
+
+    // Checking out another commit checks out the submodule commit it points to
+    test_env.jj_cmd_ok(&workspace_root, &["describe", "-m", "bump sub"]);
+    test_env.jj_cmd_ok(&workspace_root, &["new", "main"]);
+    assert!(sub_path.join("a").exists());
+    assert!(!sub_path.join("b").exists());
+    test_env.jj_cmd_ok(&workspace_root, &["edit", "description(bump)"]);
+    assert!(sub_path.join("b").exists());
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @r###"
+    M sub
+    "###);
+}
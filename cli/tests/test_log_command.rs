@@ -53,6 +53,7 @@ fn test_log_with_no_template() {
     - commit_summary_separator
     - description_placeholder
     - email_placeholder
+    - json
     - name_placeholder
     "###);
 }
@@ -288,6 +288,7 @@ fn test_obslog_with_no_template() {
     - commit_summary_separator
     - description_placeholder
     - email_placeholder
+    - json
     - name_placeholder
     "###);
 }
@@ -149,6 +149,7 @@ fn test_op_log_with_no_template() {
     - commit_summary_separator
     - description_placeholder
     - email_placeholder
+    - json
     - name_placeholder
     "###);
 }
@@ -182,6 +183,20 @@ fn test_op_log_no_graph() {
     "###);
 }
 
+#[test]
+fn test_op_log_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "--no-graph", "-T", "json"]);
+    insta::assert_snapshot!(stdout, @r###"
+    {"description":"add workspace 'default'","hostname":"host.example.com","id":"b51416386f2685fd5493f2b20e8eec3c24a1776d9e1a7cb5ed7e30d2d9c88c0c1e1fe71b0b7358cba60de42533d1228ed9878f2f89817d892c803395ccf9fe92","is_snapshot":false,"parents":["9a7d829846af88a2f7a1e348fb46ff58729e49632bc9c6a052aec8501563cb0d10f4a4e6010ffde529f84a2b9b5b3a4c211a889106a41f6c076dfdacc79f6af7"],"tags":{},"time":{"end":"2001-02-03T04:05:07+07:00","start":"2001-02-03T04:05:07+07:00"},"username":"test-username"}
+    {"description":"initialize repo","hostname":"host.example.com","id":"9a7d829846af88a2f7a1e348fb46ff58729e49632bc9c6a052aec8501563cb0d10f4a4e6010ffde529f84a2b9b5b3a4c211a889106a41f6c076dfdacc79f6af7","is_snapshot":false,"parents":["00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"],"tags":{},"time":{"end":"2001-02-03T04:05:07+07:00","start":"2001-02-03T04:05:07+07:00"},"username":"test-username"}
+    {"description":"","hostname":"","id":"00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","is_snapshot":false,"parents":[],"tags":{},"time":{"end":"1970-01-01T00:00:00+00:00","start":"1970-01-01T00:00:00+00:00"},"username":""}
+    "###);
+}
+
 #[test]
 fn test_op_log_no_graph_null_terminated() {
     let test_env = TestEnvironment::default();
@@ -513,3 +528,175 @@ fn get_log_output(test_env: &TestEnvironment, repo_path: &Path, op_id: &str) ->
         &["log", "-T", "commit_id", "--at-op", op_id, "-r", "all()"],
     )
 }
+
+#[test]
+fn test_op_show() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "main"]);
+
+    // Showing the latest operation reports the new branch
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show"]);
+    insta::assert_snapshot!(stdout, @r###"
+    dfe764e85647 test-username@host.example.com 2001-02-03 04:05:09.000 +07:00 - 2001-02-03 04:05:09.000 +07:00
+    create branch main pointing to commit fa15625b4a986997697639dfc2844138900c79f2
+    args: jj branch create main
+
+    Changed local branches:
+    main:
+    + qpvuntsm fa15625b main | (empty) first
+    - (absent)
+
+    "###);
+
+    // Showing an older operation reports the rewritten commit
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show", "@-"]);
+    insta::assert_snapshot!(stdout, @r###"
+    550c7992bb0a test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22
+    args: jj describe -m first
+
+    Changed commits:
+    + qpvuntsm fa15625b (empty) first
+    - qpvuntsm hidden 230dd059 (empty) (no description set)
+
+    Changed working copy commits:
+    default@:
+    + qpvuntsm fa15625b (empty) first
+    - qpvuntsm hidden 230dd059 (empty) (no description set)
+
+    "###);
+
+    // With --patch, the inter-diff of the rewritten commit is shown
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "second"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show", "@-", "-p", "--git"]);
+    insta::assert_snapshot!(stdout, @r###"
+    83ef5fea0bf4 test-username@host.example.com 2001-02-03 04:05:12.000 +07:00 - 2001-02-03 04:05:12.000 +07:00
+    snapshot working copy
+    args: jj describe -m second
+
+    Changed commits:
+    + qpvuntsm b9c6feb5 main | first
+    - qpvuntsm hidden fa15625b (empty) first
+    diff --git a/file b/file
+    new file mode 100644
+    index 0000000000..7898192261
+    --- /dev/null
+    +++ b/file
+    @@ -1,0 +1,1 @@
+    +a
+
+    Changed working copy commits:
+    default@:
+    + qpvuntsm b9c6feb5 main | first
+    - qpvuntsm hidden fa15625b (empty) first
+
+    Changed local branches:
+    main:
+    + qpvuntsm b9c6feb5 main | first
+    - qpvuntsm hidden fa15625b (empty) first
+
+    "###);
+}
+
+#[test]
+fn test_op_diff() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "origin"]);
+    let origin_path = test_env.env_root().join("origin");
+    let origin_git_path = origin_path.join(".jj/repo/store/git");
+    test_env.jj_cmd_ok(&origin_path, &["describe", "-m", "origin 1"]);
+    test_env.jj_cmd_ok(&origin_path, &["branch", "create", "main"]);
+    test_env.jj_cmd_ok(&origin_path, &["new", "root()", "-m", "origin 2"]);
+    test_env.jj_cmd_ok(&origin_path, &["branch", "create", "feature"]);
+    test_env.jj_cmd_ok(&origin_path, &["git", "export"]);
+
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "remote",
+            "add",
+            "origin",
+            origin_git_path.to_str().unwrap(),
+        ],
+    );
+    test_env.jj_cmd_ok(&repo_path, &["git", "fetch"]);
+
+    // What did the fetch do?
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Changed commits:
+    + propsusp 03bad9ef feature@origin | (empty) origin 2
+    + pvmkwnsm 760d9539 main@origin | (empty) origin 1
+
+    Changed remote branches:
+    feature@origin:
+    + propsusp 03bad9ef feature@origin | (empty) origin 2
+    - (absent)
+    main@origin:
+    + pvmkwnsm 760d9539 main@origin | (empty) origin 1
+    - (absent)
+
+    "###);
+
+    // Move branches on the remote and fetch again
+    test_env.jj_cmd_ok(
+        &origin_path,
+        &["describe", "feature", "-m", "origin 2 rewritten"],
+    );
+    test_env.jj_cmd_ok(&origin_path, &["branch", "delete", "main"]);
+    test_env.jj_cmd_ok(&origin_path, &["git", "export"]);
+    test_env.jj_cmd_ok(&repo_path, &["branch", "track", "feature@origin"]);
+    test_env.jj_cmd_ok(&repo_path, &["git", "fetch"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff", "--op", "@"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Changed commits:
+    + vsslznuk 8ebc3e2f feature | (empty) origin 2 rewritten
+    - propsusp hidden 03bad9ef (empty) origin 2
+    - pvmkwnsm hidden 760d9539 (empty) origin 1
+
+    Changed local branches:
+    feature:
+    + vsslznuk 8ebc3e2f feature | (empty) origin 2 rewritten
+    - propsusp hidden 03bad9ef (empty) origin 2
+
+    Changed remote branches:
+    feature@origin:
+    + vsslznuk 8ebc3e2f feature | (empty) origin 2 rewritten
+    - propsusp hidden 03bad9ef (empty) origin 2
+    main@origin:
+    + (absent)
+    - pvmkwnsm hidden 760d9539 (empty) origin 1
+
+    "###);
+
+    // Compare two arbitrary operations
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["op", "diff", "--from", "@---", "--to", "@"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Changed commits:
+    + vsslznuk 8ebc3e2f feature | (empty) origin 2 rewritten
+
+    Changed local branches:
+    feature:
+    + vsslznuk 8ebc3e2f feature | (empty) origin 2 rewritten
+    - (absent)
+
+    Changed remote branches:
+    feature@origin:
+    + vsslznuk 8ebc3e2f feature | (empty) origin 2 rewritten
+    - (absent)
+
+    "###);
+
+    // No changes between an operation and itself
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff", "--from", "@", "--to", "@"]);
+    insta::assert_snapshot!(stdout, @"");
+}
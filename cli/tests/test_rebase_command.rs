@@ -2394,6 +2394,58 @@ fn test_rebase_skip_if_on_destination() {
     "###);
 }
 
+#[test]
+fn test_rebase_with_merge_drivers() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"
+        [merge-drivers.lockfiles]
+        files = 'glob:"*.lock"'
+        builtin = "theirs"
+        "#,
+    );
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "base"]);
+    std::fs::write(repo_path.join("deps.lock"), "base\n").unwrap();
+    std::fs::write(repo_path.join("file"), "base\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "left"]);
+    std::fs::write(repo_path.join("deps.lock"), "left\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(base)", "-m", "right"]);
+    std::fs::write(repo_path.join("deps.lock"), "right\n").unwrap();
+    std::fs::write(repo_path.join("file"), "right\n").unwrap();
+
+    // The lockfile is resolved by the merge driver, taking the rebased side
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["rebase", "-r", "@", "-d", "description(left)"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Rebased 1 commits onto destination
+    Working copy now at: zsuskuln 40a30cf8 right
+    Parent commit      : kkmpptxz 98d85285 left
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "deps.lock"]);
+    insta::assert_snapshot!(stdout, @r###"
+    right
+    "###);
+
+    // An invalid driver is reported as a config error
+    test_env.add_config(
+        r#"
+        [merge-drivers.invalid]
+        files = 'glob:"*.json"'
+        "#,
+    );
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Config error: Invalid merge driver 'invalid': Either builtin or command must be set
+    For help, see https://github.com/martinvonz/jj/blob/main/docs/config.md.
+    "###);
+}
+
 fn get_long_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
     let template = r#"description.first_line() ++ "  " ++ change_id.shortest(8) ++ "  " ++ commit_id.shortest(8)"#;
     test_env.jj_cmd_success(repo_path, &["log", "-T", template])
@@ -464,7 +464,7 @@ fn test_baseless_conflict_input_files() {
 
 #[test]
 fn test_too_many_parents() {
-    let test_env = TestEnvironment::default();
+    let mut test_env = TestEnvironment::default();
     test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
     let repo_path = test_env.env_root().join("repo");
 
@@ -483,12 +483,127 @@ fn test_too_many_parents() {
     file    [38;5;1m3-sided[38;5;3m conflict[39m
     "###);
 
-    let error = test_env.jj_cmd_failure(&repo_path, &["resolve"]);
-    insta::assert_snapshot!(error, @r###"
-    Hint: Using default editor ':builtin'; run `jj config set --user ui.merge-editor :builtin` to disable this message.
+    // The external tool resolves the conflict as a sequence of 3-way merges, the
+    // result of each one becoming the left side of the next
+    let editor_script = test_env.set_up_fake_editor();
+    std::fs::write(
+        &editor_script,
+        [
+            "dump editor0",
+            "write\nab\n",
+            "next invocation\n",
+            "dump editor1",
+            "write\nabc\n",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "resolve",
+            "--config-toml",
+            "merge-tools.fake-editor.merge-tool-edits-conflict-markers=true",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
     Resolving conflicts in: file
-    Error: Failed to resolve conflicts
-    Caused by: The conflict at "file" has 3 sides. At most 2 sides are supported.
+    Working copy now at: znkkpsqq c79466e5 conflict | conflict
+    Parent commit      : zsuskuln aa493daf a | a
+    Parent commit      : royxmykx db6a4daf b | b
+    Parent commit      : vruxwmqv a425542c c | c
+    Added 0 files, modified 1 files, removed 0 files
+    "###);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor0")).unwrap(), @r###"
+    <<<<<<< Conflict 1 of 1
+    %%%%%%% Changes from base to side #1
+    -base
+    +a
+    +++++++ Contents of side #2
+    b
+    >>>>>>> Conflict 1 of 1 ends
+    "###);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor1")).unwrap(), @r###"
+    <<<<<<< Conflict 1 of 1
+    +++++++ Contents of side #1
+    ab
+    %%%%%%% Changes from base to side #2
+    -base
+    +c
+    >>>>>>> Conflict 1 of 1 ends
+    "###);
+    insta::assert_snapshot!(std::fs::read_to_string(repo_path.join("file")).unwrap(), @r###"
+    abc
+    "###);
+
+    // If one of the 3-way merges is left unresolved, the remaining sides are kept
+    // in the conflict
+    test_env.jj_cmd_ok(&repo_path, &["new", "a", "b", "c"]);
+    std::fs::write(
+        &editor_script,
+        ["write\n<<<<<<<\n%%%%%%%\n-base\n+a\n+++++++\nb2\n>>>>>>>\n"].join("\0"),
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "resolve",
+            "--config-toml",
+            "merge-tools.fake-editor.merge-tool-edits-conflict-markers=true",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Resolving conflicts in: file
+    New conflicts appeared in these commits:
+      kxryzmor 9f60a716 (conflict) (no description set)
+    To resolve the conflicts, start by updating to it:
+      jj new kxryzmorwvtz
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you may want inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    Working copy now at: kxryzmor 9f60a716 (conflict) (no description set)
+    Parent commit      : zsuskuln aa493daf a | a
+    Parent commit      : royxmykx db6a4daf b | b
+    Parent commit      : vruxwmqv a425542c c | c
+    Added 0 files, modified 1 files, removed 0 files
+    There are unresolved conflicts at these paths:
+    file    3-sided conflict
+    "###);
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["resolve", "--list"]), @r###"
+    file    3-sided conflict
+    "###);
+    insta::assert_snapshot!(std::fs::read_to_string(repo_path.join("file")).unwrap(), @r###"
+    <<<<<<< Conflict 1 of 1
+    %%%%%%% Changes from base #1 to side #1
+    -base
+    +a
+    +++++++ Contents of side #2
+    b2
+    %%%%%%% Changes from base #2 to side #3
+    -base
+    +c
+    >>>>>>> Conflict 1 of 1 ends
+    "###);
+
+    // The executable bit of the sides is kept in the remaining conflict
+    for rev in ["base", "a", "b", "c"] {
+        test_env.jj_cmd_ok(&repo_path, &["file", "chmod", "x", "file", "-r", rev]);
+    }
+    test_env.jj_cmd_ok(&repo_path, &["new", "a", "b", "c"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "resolve",
+            "--config-toml",
+            "merge-tools.fake-editor.merge-tool-edits-conflict-markers=true",
+        ],
+    );
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["debug", "tree"]), @r###"
+    file: Ok(Conflicted([Some(File { id: FileId("78981922613b2afb6025042ff6bd878ac1994e85"), executable: true }), Some(File { id: FileId("df967b96a579e45a18b8251732d16804b2e56a55"), executable: true }), Some(File { id: FileId("e6bfff5c1d0f0ecd501552b43a1e13d8008abc31"), executable: true }), Some(File { id: FileId("df967b96a579e45a18b8251732d16804b2e56a55"), executable: true }), Some(File { id: FileId("f2ad6c76f0115a6ba5b00456a849810e7ec0af20"), executable: true })]))
     "###);
 }
 
@@ -981,3 +1096,89 @@ fn test_multiple_conflicts() {
     Error: No conflicts found at this revision
     "###);
 }
+
+#[test]
+fn test_conflict_marker_style_git() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(r#"ui.conflict-marker-style = "git""#);
+
+    create_commit(
+        &test_env,
+        &repo_path,
+        "base",
+        &[],
+        &[("file", "line 1\nbase\nline 3\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "a",
+        &["base"],
+        &[("file", "line 1\na\nline 3\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "b",
+        &["base"],
+        &[("file", "line 1\nb\nline 3\n")],
+    );
+    create_commit(&test_env, &repo_path, "conflict", &["a", "b"], &[]);
+    insta::assert_snapshot!(std::fs::read_to_string(repo_path.join("file")).unwrap(), @r###"
+    line 1
+    <<<<<<< Side #1 (Conflict 1 of 1)
+    a
+    ||||||| Base
+    base
+    =======
+    b
+    >>>>>>> Side #2 (Conflict 1 of 1 ends)
+    line 3
+    "###);
+
+    // Editing the conflict by hand keeps it unresolved as long as the Git-style
+    // markers are intact
+    std::fs::write(
+        repo_path.join("file"),
+        indoc! {"
+            line 1 edited
+            <<<<<<<
+            a
+            |||||||
+            base
+            =======
+            b edited
+            >>>>>>>
+            line 3
+        "},
+    )
+    .unwrap();
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["resolve", "--list"]), @r###"
+    file    2-sided conflict
+    "###);
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["diff", "--git"]), @r###"
+    diff --git a/file b/file
+    --- a/file
+    +++ b/file
+    @@ -1,9 +1,9 @@
+    -line 1
+    +line 1 edited
+     <<<<<<< Side #1 (Conflict 1 of 1)
+     a
+     ||||||| Base
+     base
+     =======
+    -b
+    +b edited
+     >>>>>>> Side #2 (Conflict 1 of 1 ends)
+     line 3
+    "###);
+
+    // Removing the markers resolves the conflict
+    std::fs::write(repo_path.join("file"), "line 1 edited\na and b\nline 3\n").unwrap();
+    insta::assert_snapshot!(test_env.jj_cmd_cli_error(&repo_path, &["resolve", "--list"]), @r###"
+    Error: No conflicts found at this revision
+    "###);
+}
@@ -189,7 +189,7 @@ fn test_bad_function_call() {
       |
       = Invalid string pattern
     2: Invalid string pattern kind "bad:"
-    Hint: Try prefixing with one of `exact:`, `glob:` or `substring:`
+    Hint: Try prefixing with one of `exact:`, `glob:`, `regex:`, or `substring:`
     "###);
 
     let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-r", "root()::whatever()"]);
@@ -250,6 +250,75 @@ fn test_bad_function_call() {
       |
       = expected <expression>
     "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-r", "author_date(foo)"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse revset: Invalid date pattern
+    Caused by:
+    1:  --> 1:13
+      |
+    1 | author_date(foo)
+      |             ^-^
+      |
+      = Invalid date pattern
+    2: Date pattern must specify 'after' or 'before' in "foo"
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["log", "-r", "committer_date(after:'not a date')"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse revset: Invalid date pattern
+    Caused by:
+    1:  --> 1:16
+      |
+    1 | committer_date(after:'not a date')
+      |                ^----------------^
+      |
+      = Invalid date pattern
+    2: expected unsupported identifier as position 0..3
+    "###);
+}
+
+#[test]
+fn test_date_patterns() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=first"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=second"]);
+
+    let template = r#"description.first_line() ++ " " ++ committer.timestamp() ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-T",
+            template,
+            "-r",
+            "committer_date(after:'2001-02-03T04:05:09+07:00')",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    second 2001-02-03 04:05:09.000 +07:00
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-T",
+            template,
+            "-r",
+            "committer_date(before:'2001-02-03T04:05:09+07:00') ~ root()",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    first 2001-02-03 04:05:08.000 +07:00
+     2001-02-03 04:05:07.000 +07:00
+    "###);
 }
 
 #[test]
@@ -290,7 +359,7 @@ fn test_function_name_hint() {
       | ^-----^
       |
       = Function "author_" doesn't exist
-    Hint: Did you mean "author", "my_author"?
+    Hint: Did you mean "author", "author_date", "my_author"?
     "###);
 
     insta::assert_snapshot!(evaluate_err("my_branches"), @r###"
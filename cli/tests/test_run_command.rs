@@ -0,0 +1,183 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+fn create_commit(test_env: &TestEnvironment, repo_path: &Path, name: &str, parents: &[&str]) {
+    if parents.is_empty() {
+        test_env.jj_cmd_ok(repo_path, &["new", "root()", "-m", name]);
+    } else {
+        let mut args = vec!["new", "-m", name];
+        args.extend(parents);
+        test_env.jj_cmd_ok(repo_path, &args);
+    }
+    std::fs::write(repo_path.join(name), format!("{name}\n")).unwrap();
+    test_env.jj_cmd_ok(repo_path, &["branch", "create", name]);
+}
+
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    let template = r#"commit_id.short() ++ "  " ++ description.first_line()"#;
+    test_env.jj_cmd_success(repo_path, &["log", "-T", template])
+}
+
+#[test]
+fn test_run_no_jobs() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["run", "true", "-j", "0"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: must pass at least one job
+    "###);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_output() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["run", "cat a b 2>/dev/null; true", "-r", "a|b", "-j", "1"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    zsuskulnrvyr: a
+    zsuskulnrvyr: b
+    rlvkpnrzqnoo: a
+    "###);
+    insta::assert_snapshot!(stderr, @r###"
+    Rewrote 0 commits of 2 checked.
+    Nothing changed.
+    "###);
+    // The command ran in a scratch working copy, not in the workspace.
+    assert!(repo_path.join(".jj").join("run").join("0").is_dir());
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  1394f625cbbd  b
+    ◉  2443ea76b0b1  a
+    ◉  000000000000
+    "###);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_rewrites_commits() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    create_commit(&test_env, &repo_path, "c", &["b"]);
+
+    // Modify the files in `a` and `b`. `c` is rebased on top of the rewritten `b`.
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "run",
+            "for f in *; do echo changed >> $f; done",
+            "-r",
+            "a|b",
+            "-j",
+            "1",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Rewrote 2 commits of 2 checked.
+    Working copy now at: royxmykx 2804a1e0 c | c
+    Parent commit      : zsuskuln 157bc515 b | b
+    Added 0 files, modified 2 files, removed 0 files
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  2804a1e0cfe8  c
+    ◉  157bc5158d7f  b
+    ◉  1ad00a5ac80f  a
+    ◉  000000000000
+    "###);
+    let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "a", "-r", "a"]);
+    insta::assert_snapshot!(content, @r###"
+    a
+    changed
+    "###);
+    // The change made to `a` in both `a` and `b` is only applied once.
+    let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "a", "-r", "c"]);
+    insta::assert_snapshot!(content, @r###"
+    a
+    changed
+    "###);
+    let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "b", "-r", "c"]);
+    insta::assert_snapshot!(content, @r###"
+    b
+    changed
+    "###);
+    let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "c", "-r", "c"]);
+    insta::assert_snapshot!(content, @r###"
+    c
+    "###);
+
+    // Running again reuses the scratch working copy.
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["run", "cat a", "-r", "c", "-j", "1"]);
+    insta::assert_snapshot!(stdout, @r###"
+    royxmykxtrkr: a
+    royxmykxtrkr: changed
+    "###);
+    insta::assert_snapshot!(stderr, @r###"
+    Rewrote 0 commits of 1 checked.
+    Nothing changed.
+    "###);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_failing_command() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commit(&test_env, &repo_path, "a", &[]);
+
+    // Changes made by a failing command are not recorded.
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["run", "echo changed > a; echo oops >&2; false", "-r", "a"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    rlvkpnrzqnoo: oops
+    Warning: Command failed on rlvkpnrzqnoo with exit status: 1
+    Rewrote 0 commits of 1 checked.
+    Nothing changed.
+    Error: Command failed on 1 of 1 revisions
+    "###);
+    let content = test_env.jj_cmd_success(&repo_path, &["file", "show", "a", "-r", "a"]);
+    insta::assert_snapshot!(content, @r###"
+    a
+    "###);
+}
+
+#[test]
+fn test_run_immutable() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["run", "true", "-r", "root()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: The root commit 000000000000 is immutable
+    "###);
+}
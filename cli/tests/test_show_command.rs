@@ -73,6 +73,7 @@ fn test_show_with_no_template() {
     - commit_summary_separator
     - description_placeholder
     - email_placeholder
+    - json
     - name_placeholder
     "###);
 }
@@ -0,0 +1,143 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    let template = r#"
+    separate(" ",
+      description.first_line(),
+      if(signature, "signed:" ++ signature.key(), "unsigned"),
+    ) ++ "\n"
+    "#;
+    test_env.jj_cmd_success(repo_path, &["log", "-T", template])
+}
+
+#[test]
+fn test_sign() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(
+        r#"
+    signing.backend = "test"
+    signing.key = "impeccable"
+    "#,
+    );
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-mone"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-mtwo"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-mthree"]);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["sign", "-r", "@--::@-"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Signed 2 commits:
+      qpvuntsm d9cf2915 (empty) one
+      kkmpptxz 649891f4 (empty) two
+    Rebased 1 descendant commits
+    Working copy now at: zsuskuln 51893b05 (empty) three
+    Parent commit      : kkmpptxz 649891f4 (empty) two
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  three unsigned
+    ◉  two signed:impeccable
+    ◉  one signed:impeccable
+    ◉  unsigned
+    "###);
+
+    // Re-sign with another key
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["sign", "-r", "description(two)", "--key=other"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Signed 1 commits:
+      kkmpptxz 332bd9a2 (empty) two
+    Rebased 1 descendant commits
+    Working copy now at: zsuskuln 498975ad (empty) three
+    Parent commit      : kkmpptxz 332bd9a2 (empty) two
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  three unsigned
+    ◉  two signed:other
+    ◉  one signed:impeccable
+    ◉  unsigned
+    "###);
+
+    // The root commit can't be rewritten
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["sign", "-r", "root()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: The root commit 000000000000 is immutable
+    "###);
+}
+
+#[test]
+fn test_sign_without_backend() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["sign"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Cannot sign commits because no signing backend is configured
+    Hint: Set `signing.backend` to enable signing.
+    "###);
+}
+
+#[test]
+fn test_unsign() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(
+        r#"
+    signing.backend = "test"
+    signing.key = "impeccable"
+    signing.sign-all = true
+    "#,
+    );
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-mone"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-mtwo"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-mthree"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  three signed:impeccable
+    ◉  two signed:impeccable
+    ◉  one signed:impeccable
+    ◉  unsigned
+    "###);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["unsign", "-r", "@-"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Unsigned 1 commits:
+      kkmpptxz ab6a4b40 (empty) two
+    Rebased 1 descendant commits
+    Working copy now at: zsuskuln 19760089 (empty) three
+    Parent commit      : kkmpptxz ab6a4b40 (empty) two
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  three signed:impeccable
+    ◉  two unsigned
+    ◉  one signed:impeccable
+    ◉  unsigned
+    "###);
+
+    // Unsigned commits are skipped
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["unsign", "-r", "@-"]);
+    insta::assert_snapshot!(stderr, @r###"
+    No signed revisions to unsign.
+    "###);
+}
@@ -224,3 +224,161 @@ fn test_status_simplify_conflict_sides() {
     Then run `jj squash` to move the resolution into the conflicted commit.
     "###);
 }
+
+#[test]
+fn test_status_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(
+        &test_env,
+        &repo_path,
+        "base",
+        &[],
+        &[("file1", "base\n"), ("file2", "base\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "left",
+        &["base"],
+        &[("file1", "left\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "right",
+        &["base"],
+        &[("file1", "right\n")],
+    );
+    create_commit(&test_env, &repo_path, "merge", &["left", "right"], &[]);
+    std::fs::rename(repo_path.join("file2"), repo_path.join("file3")).unwrap();
+    std::fs::write(repo_path.join("file4"), "new\n").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["status", "--json"]);
+    let value: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    insta::assert_snapshot!(serde_json::to_string_pretty(&value).unwrap(), @r###"
+    {
+      "changes": [
+        {
+          "path": "file2",
+          "status": "removed"
+        },
+        {
+          "path": "file3",
+          "status": "added"
+        },
+        {
+          "path": "file4",
+          "status": "added"
+        }
+      ],
+      "conflicted_branches": [],
+      "conflicted_remote_branches": [],
+      "conflicts": [
+        "file1"
+      ],
+      "parents": [
+        {
+          "author": {
+            "email": "test.user@example.com",
+            "name": "Test User",
+            "timestamp": "2001-02-03T04:05:10+07:00"
+          },
+          "branches": [
+            {
+              "name": "left",
+              "target": [
+                "9a28eaebd8404d699e5fc0bef379f459c66b7ce4"
+              ]
+            }
+          ],
+          "change_id": "zsuskulnrvyrovkzqrwmxqlsskqntxvp",
+          "commit_id": "9a28eaebd8404d699e5fc0bef379f459c66b7ce4",
+          "committer": {
+            "email": "test.user@example.com",
+            "name": "Test User",
+            "timestamp": "2001-02-03T04:05:11+07:00"
+          },
+          "conflict": false,
+          "description": "left\n",
+          "empty": false,
+          "parents": [
+            "9339a0bbd54396cd27a9df4d8d261f08bf3fc377"
+          ],
+          "tags": [],
+          "working_copies": []
+        },
+        {
+          "author": {
+            "email": "test.user@example.com",
+            "name": "Test User",
+            "timestamp": "2001-02-03T04:05:12+07:00"
+          },
+          "branches": [
+            {
+              "name": "right",
+              "target": [
+                "0208ce6229d0df130113981ca5b15464552c2cc9"
+              ]
+            }
+          ],
+          "change_id": "royxmykxtrkrqppotnrvutxlvrvqyxmy",
+          "commit_id": "0208ce6229d0df130113981ca5b15464552c2cc9",
+          "committer": {
+            "email": "test.user@example.com",
+            "name": "Test User",
+            "timestamp": "2001-02-03T04:05:13+07:00"
+          },
+          "conflict": false,
+          "description": "right\n",
+          "empty": false,
+          "parents": [
+            "9339a0bbd54396cd27a9df4d8d261f08bf3fc377"
+          ],
+          "tags": [],
+          "working_copies": []
+        }
+      ],
+      "working_copy": {
+        "author": {
+          "email": "test.user@example.com",
+          "name": "Test User",
+          "timestamp": "2001-02-03T04:05:14+07:00"
+        },
+        "branches": [
+          {
+            "name": "merge",
+            "target": [
+              "9f98d3460e9adc025e317364b804c4bc7a9bb42b"
+            ]
+          }
+        ],
+        "change_id": "vruxwmqvtpmxqkrrksmzyrvxysqqlsxp",
+        "commit_id": "9f98d3460e9adc025e317364b804c4bc7a9bb42b",
+        "committer": {
+          "email": "test.user@example.com",
+          "name": "Test User",
+          "timestamp": "2001-02-03T04:05:16+07:00"
+        },
+        "conflict": true,
+        "description": "merge\n",
+        "empty": false,
+        "parents": [
+          "9a28eaebd8404d699e5fc0bef379f459c66b7ce4",
+          "0208ce6229d0df130113981ca5b15464552c2cc9"
+        ],
+        "tags": [],
+        "working_copies": [
+          "default"
+        ]
+      }
+    }
+    "###);
+
+    // The changes are filtered by the paths
+    let stdout = test_env.jj_cmd_success(&repo_path, &["status", "--json", "file4"]);
+    let value: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    insta::assert_snapshot!(value["changes"], @r###"[{"path":"file4","status":"added"}]"###);
+}
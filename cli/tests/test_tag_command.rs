@@ -117,3 +117,106 @@ fn test_tag_list() {
     added_targets: commit2
     "###);
 }
+
+#[test]
+fn test_tag_create_delete_set() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "--colocate", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let git_repo = git2::Repository::open(&repo_path).unwrap();
+
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-mcommit1"]);
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-mcommit2"]);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["tag", "create", "-r@--", "v1"]);
+    insta::assert_snapshot!(stderr, @"");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["tag", "create", "v1"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Tag already exists: v1
+    Hint: Use `jj tag set` to update it.
+    "###);
+
+    // Tags are exported to the colocated Git repo
+    let git_tag = git_repo.find_reference("refs/tags/v1").unwrap();
+    insta::assert_snapshot!(git_tag.peel_to_commit().unwrap().summary().unwrap(), @"commit1");
+
+    test_env.jj_cmd_ok(&repo_path, &["tag", "set", "-r@-", "v1", "v2"]);
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["tag", "list"]), @r###"
+    v1: rlvkpnrz d0a19ea4 (empty) commit2
+    v2: rlvkpnrz d0a19ea4 (empty) commit2
+    "###);
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["tag", "delete", "glob:v*"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Deleted 2 tags.
+    "###);
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["tag", "list"]), @"");
+    assert!(git_repo.find_reference("refs/tags/v1").is_err());
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["tag", "delete", "v1"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No such tag: v1
+    "###);
+}
+
+#[test]
+fn test_tag_create_annotated() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "--colocate", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let git_repo = git2::Repository::open(&repo_path).unwrap();
+
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-mcommit1"]);
+    test_env.jj_cmd_ok(&repo_path, &["tag", "create", "-r@-", "-mRelease 1", "v1"]);
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["tag", "list"]), @r###"
+    v1: qpvuntsm caf975d0 (empty) commit1
+    "###);
+    let git_tag = git_repo
+        .find_reference("refs/tags/v1")
+        .unwrap()
+        .peel_to_tag()
+        .unwrap();
+    insta::assert_snapshot!(git_tag.message().unwrap(), @r###"
+    Release 1
+    "###);
+    insta::assert_snapshot!(git_tag.tagger().unwrap().to_string(), @"Test User <test.user@example.com>");
+
+    // The tag isn't re-imported or re-exported as a lightweight tag
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "-Tdescription", "-n2"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  create tag v1 pointing to commit caf975d0989a4e84d6d2fd1047ea03663244969c
+    ◉  commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22
+    "###);
+
+    // Moving the tag doesn't replace the tag object in Git, and the next import
+    // restores the tag from Git
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["tag", "set", "-r@", "v1"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Warning: The working-copy commit in workspace 'default' became immutable, so a new commit has been created on top of it.
+    Warning: Failed to export some branches:
+      v1: Annotated tag cannot be moved
+    Working copy now at: royxmykx 5aa73400 (empty) (no description set)
+    Parent commit      : rlvkpnrz c76d57e4 (empty) (no description set)
+    "###);
+    let git_ref = git_repo.find_reference("refs/tags/v1").unwrap();
+    assert_eq!(git_ref.peel_to_tag().unwrap().id(), git_tag.id());
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["tag", "list"]);
+    insta::assert_snapshot!(stdout, @r###"
+    v1: qpvuntsm caf975d0 (empty) commit1
+    "###);
+    insta::assert_snapshot!(stderr, @r###"
+    Done importing changes from the underlying Git repo.
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["tag", "create", "-mmsg", "-rroot()", "v2"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Cannot create an annotated tag for the root commit
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["tag", "create", "-mmsg", "--sign", "v2"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Cannot sign the tag because no signing backend is configured
+    Hint: Set `signing.backend` to enable signing.
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["tag", "create", "-mmsg", "bad..name"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Tag name is not allowed in Git: bad..name
+    "###);
+}
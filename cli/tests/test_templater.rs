@@ -129,6 +129,60 @@ fn test_templater_upper_lower() {
       render(r#""Hello".upper() ++ "Hello".lower()"#), @"HELLOhello");
 }
 
+#[test]
+fn test_templater_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first line\n\"quoted\"\n"]);
+    let render = |template| get_template_output(&test_env, &repo_path, "@", template);
+
+    insta::assert_snapshot!(render(r#"json("a\tb")"#), @r###""a\tb""###);
+    insta::assert_snapshot!(render(r#"json(42) ++ json(-1) ++ json(true)"#), @"42-1true");
+    insta::assert_snapshot!(render(r#"json(description)"#), @r###""first line\n\"quoted\"\n""###);
+    insta::assert_snapshot!(render(r#"json(description.lines())"#), @r###"["first line","\"quoted\""]"###);
+    insta::assert_snapshot!(render(r#"json(author)"#), @r###"{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:08+07:00"}"###);
+    insta::assert_snapshot!(render(r#"json(committer.timestamp())"#), @r###""2001-02-03T04:05:08+07:00""###);
+    insta::assert_snapshot!(render(r#"json(change_id.shortest(8))"#), @r###"{"prefix":"q","rest":"pvuntsm"}"###);
+    insta::assert_snapshot!(render(r#"json(parents)"#), @r###"[{"author":{"email":"","name":"","timestamp":"1970-01-01T00:00:00+00:00"},"branches":[],"change_id":"zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz","commit_id":"0000000000000000000000000000000000000000","committer":{"email":"","name":"","timestamp":"1970-01-01T00:00:00+00:00"},"conflict":false,"description":"","empty":true,"parents":[],"tags":[],"working_copies":[]}]"###);
+
+    // Templates can't be serialized
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &[
+            "log",
+            "-r@",
+            "-T",
+            r#"json(parents.map(|c| c.commit_id()))"#,
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template: Expected expression of type "Serialize", but actual type is "ListTemplate"
+    Caused by:  --> 1:6
+      |
+    1 | json(parents.map(|c| c.commit_id()))
+      |      ^----------------------------^
+      |
+      = Expected expression of type "Serialize", but actual type is "ListTemplate"
+    "###);
+}
+
+#[test]
+fn test_templater_hyperlink() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let template = r#"hyperlink("https://example.com/" ++ commit_id.short(), label("commit_id", commit_id.short()))"#;
+
+    // Links are only written if colors are enabled
+    insta::assert_snapshot!(
+        get_template_output(&test_env, &repo_path, "@", template),
+        @"230dd059e1b0");
+    insta::assert_snapshot!(
+        get_colored_template_output(&test_env, &repo_path, "@", template),
+        @r###"[38;5;4m]8;;https://example.com/230dd059e1b0\230dd059e1b0]8;;\[39m"###);
+}
+
 #[test]
 fn test_templater_alias() {
     let test_env = TestEnvironment::default();
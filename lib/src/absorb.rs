@@ -0,0 +1,303 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Algorithm to split changes in a single source commit into its most relevant
+//! ancestors, "absorbing" them into the commits that last modified the same
+//! lines.
+
+use std::collections::{HashMap, HashSet};
+use std::io::Read as _;
+use std::ops::Range;
+
+use itertools::Itertools as _;
+
+use crate::annotate::get_annotation_with_domain;
+use crate::backend::{BackendError, BackendResult, CommitId, FileId, TreeValue};
+use crate::commit::Commit;
+use crate::diff::{find_line_ranges, Diff, DiffHunk};
+use crate::matchers::Matcher;
+use crate::merge::{Merge, MergedTreeValue};
+use crate::merged_tree::{MergedTree, MergedTreeBuilder};
+use crate::repo::{MutableRepo, Repo};
+use crate::repo_path::{RepoPath, RepoPathBuf};
+use crate::rewrite::merge_commit_trees;
+use crate::settings::UserSettings;
+use crate::store::Store;
+
+/// The source commit to absorb changes from.
+#[derive(Clone, Debug)]
+pub struct AbsorbSource {
+    commit: Commit,
+    parents: Vec<Commit>,
+    parent_tree: MergedTree,
+}
+
+impl AbsorbSource {
+    /// Creates a source from a commit, comparing it against its parents.
+    pub fn from_commit(repo: &dyn Repo, commit: Commit) -> BackendResult<Self> {
+        let parents: Vec<_> = commit.parents().try_collect()?;
+        let parent_tree = merge_commit_trees(repo, &parents)?;
+        Ok(AbsorbSource {
+            commit,
+            parents,
+            parent_tree,
+        })
+    }
+
+    /// The source commit.
+    pub fn commit(&self) -> &Commit {
+        &self.commit
+    }
+}
+
+/// Changes selected to be absorbed into each destination commit.
+#[derive(Default)]
+pub struct SelectedTrees {
+    /// Trees to absorb into each destination commit. Each tree is the parent
+    /// tree of the source with the hunks selected for that destination
+    /// applied.
+    pub target_commits: HashMap<CommitId, MergedTreeBuilder>,
+    /// Changed paths which can't be absorbed, and the reason why.
+    pub skipped_paths: Vec<(RepoPathBuf, String)>,
+}
+
+/// Assigns each hunk of the changes in `source` to the commit in
+/// `destinations` which last modified the lines the hunk touches.
+///
+/// Hunks which touch lines from more than one commit are split line by line if
+/// they replace as many lines as they remove, and left in the source
+/// otherwise. Pure insertions are assigned to the commit which introduced the
+/// surrounding lines.
+pub fn split_hunks_to_trees(
+    repo: &dyn Repo,
+    source: &AbsorbSource,
+    destinations: &HashSet<CommitId>,
+    matcher: &dyn Matcher,
+) -> BackendResult<SelectedTrees> {
+    let store = repo.store();
+    let mut selected_trees = SelectedTrees::default();
+    let left_tree = &source.parent_tree;
+    let right_tree = source.commit.tree()?;
+    for (path, diff) in left_tree.diff(&right_tree, matcher) {
+        let (left_value, right_value) = diff?;
+        let (left_id, executable) = match to_file_value(&left_value) {
+            Ok(Some(value)) => value,
+            Ok(None) => {
+                let reason = "Added files can't be absorbed".to_owned();
+                selected_trees.skipped_paths.push((path, reason));
+                continue;
+            }
+            Err(reason) => {
+                selected_trees.skipped_paths.push((path, reason));
+                continue;
+            }
+        };
+        let right_id = match to_file_value(&right_value) {
+            Ok(Some((id, _executable))) => id,
+            Ok(None) => {
+                let reason = "Deleted files can't be absorbed".to_owned();
+                selected_trees.skipped_paths.push((path, reason));
+                continue;
+            }
+            Err(reason) => {
+                selected_trees.skipped_paths.push((path, reason));
+                continue;
+            }
+        };
+        let left_text = read_file(store, &path, &left_id)?;
+        let right_text = read_file(store, &path, &right_id)?;
+        let line_origins =
+            get_annotation_with_domain(&left_text, &source.parents, &path, destinations)?;
+        let left_lines = left_text.split_inclusive(|b| *b == b'\n').collect_vec();
+        let right_lines = right_text.split_inclusive(|b| *b == b'\n').collect_vec();
+        let hunks_by_commit = split_file_hunks(&line_origins, &left_text, &right_text);
+        for (commit_id, hunks) in hunks_by_commit {
+            let mut new_text = vec![];
+            let mut left_pos = 0;
+            for (left_range, right_range) in hunks {
+                new_text.extend(left_lines[left_pos..left_range.start].concat());
+                new_text.extend(right_lines[right_range].concat());
+                left_pos = left_range.end;
+            }
+            new_text.extend(left_lines[left_pos..].concat());
+            let id = store.write_file(&path, &mut new_text.as_slice())?;
+            selected_trees
+                .target_commits
+                .entry(commit_id)
+                .or_insert_with(|| MergedTreeBuilder::new(left_tree.id()))
+                .set_or_remove(
+                    path.clone(),
+                    Merge::normal(TreeValue::File { id, executable }),
+                );
+        }
+    }
+    Ok(selected_trees)
+}
+
+/// Returns the file id and executable bit of a resolved file, `None` if the
+/// path is absent, or the reason why it can't be absorbed otherwise.
+fn to_file_value(value: &MergedTreeValue) -> Result<Option<(FileId, bool)>, String> {
+    match value.as_resolved() {
+        Some(None) => Ok(None),
+        Some(Some(TreeValue::File { id, executable })) => Ok(Some((id.clone(), *executable))),
+        Some(Some(_)) => Err("Only regular files can be absorbed".to_owned()),
+        None => Err("Conflicted files can't be absorbed".to_owned()),
+    }
+}
+
+fn read_file(store: &Store, path: &RepoPath, id: &FileId) -> BackendResult<Vec<u8>> {
+    let mut content = vec![];
+    store
+        .read_file(path, id)?
+        .read_to_end(&mut content)
+        .map_err(|err| BackendError::ReadFile {
+            path: path.to_owned(),
+            id: id.clone(),
+            source: err.into(),
+        })?;
+    Ok(content)
+}
+
+/// Line ranges of a hunk in the left and the right text.
+type HunkRanges = (Range<usize>, Range<usize>);
+
+/// Returns the line ranges of the hunks to absorb into each commit, ordered by
+/// position.
+fn split_file_hunks(
+    line_origins: &[Option<CommitId>],
+    left_text: &[u8],
+    right_text: &[u8],
+) -> HashMap<CommitId, Vec<HunkRanges>> {
+    let mut hunks_by_commit: HashMap<CommitId, Vec<_>> = HashMap::new();
+    let mut add_hunk = |origin: Option<&CommitId>, left_range, right_range| {
+        if let Some(commit_id) = origin {
+            hunks_by_commit
+                .entry(commit_id.clone())
+                .or_default()
+                .push((left_range, right_range));
+        }
+    };
+    let count_lines = |text: &[u8]| text.split_inclusive(|b| *b == b'\n').count();
+    let mut left_pos = 0;
+    let mut right_pos = 0;
+    let diff = Diff::for_tokenizer(&[left_text, right_text], find_line_ranges);
+    for hunk in diff.hunks() {
+        match hunk {
+            DiffHunk::Matching(common) => {
+                let num_lines = count_lines(common);
+                left_pos += num_lines;
+                right_pos += num_lines;
+            }
+            DiffHunk::Different(slices) => {
+                let left_range = left_pos..left_pos + count_lines(slices[0]);
+                let right_range = right_pos..right_pos + count_lines(slices[1]);
+                left_pos = left_range.end;
+                right_pos = right_range.end;
+                if left_range.is_empty() {
+                    // An insertion belongs to the commit which introduced the
+                    // lines around it.
+                    let before = left_range
+                        .start
+                        .checked_sub(1)
+                        .map(|line| line_origins[line].as_ref());
+                    let after = line_origins.get(left_range.start).map(Option::as_ref);
+                    let origin = match (before, after) {
+                        (Some(before), Some(after)) => before.filter(|_| before == after),
+                        (Some(origin), None) | (None, Some(origin)) => origin,
+                        (None, None) => None,
+                    };
+                    add_hunk(origin, left_range, right_range);
+                } else if let Ok(origin) = line_origins[left_range.clone()].iter().all_equal_value()
+                {
+                    add_hunk(origin.as_ref(), left_range, right_range);
+                } else if left_range.len() == right_range.len() {
+                    // The hunk modifies lines from different commits. Since
+                    // each line is replaced by one line, they can be absorbed
+                    // separately.
+                    for (left_line, right_line) in left_range.zip(right_range) {
+                        add_hunk(
+                            line_origins[left_line].as_ref(),
+                            left_line..left_line + 1,
+                            right_line..right_line + 1,
+                        );
+                    }
+                }
+            }
+        }
+    }
+    hunks_by_commit
+}
+
+/// Result of absorbing changes into the destination commits.
+pub struct AbsorbStats {
+    /// The rewritten source commit, or `None` if it was abandoned because all
+    /// its changes were absorbed.
+    pub rewritten_source: Option<Commit>,
+    /// The rewritten destination commits.
+    pub rewritten_destinations: Vec<Commit>,
+    /// The number of other descendants which were rebased.
+    pub num_rebased: usize,
+}
+
+/// Merges the selected trees into the destination commits, and rebases their
+/// descendants. The source commit keeps its content, so the absorbed changes
+/// disappear from its diff. It's abandoned if it ends up empty and has no
+/// description.
+pub fn absorb_hunks(
+    repo: &mut MutableRepo,
+    settings: &UserSettings,
+    source: &AbsorbSource,
+    mut selected_trees: HashMap<CommitId, MergedTreeBuilder>,
+) -> BackendResult<AbsorbStats> {
+    let store = repo.store().clone();
+    let mut rewritten_source = None;
+    let mut rewritten_destinations = vec![];
+    let mut num_rebased = 0;
+    let roots = selected_trees.keys().cloned().collect_vec();
+    repo.transform_descendants(settings, roots, |mut rewriter| {
+        if rewriter.old_commit().id() == source.commit.id() {
+            let new_parents: Vec<_> = rewriter
+                .new_parents()
+                .iter()
+                .map(|id| store.get_commit(id))
+                .try_collect()?;
+            let new_parent_tree = merge_commit_trees(rewriter.mut_repo(), &new_parents)?;
+            if new_parent_tree.id() == *source.commit.tree_id()
+                && source.commit.description().is_empty()
+            {
+                rewriter.abandon();
+            } else {
+                rewritten_source = Some(rewriter.reparent(settings)?.write()?);
+            }
+            return Ok(());
+        }
+        let Some(tree_builder) = selected_trees.remove(rewriter.old_commit().id()) else {
+            rewriter.rebase(settings)?.write()?;
+            num_rebased += 1;
+            return Ok(());
+        };
+        let selected_tree = store.get_root_tree(&tree_builder.write_tree(&store)?)?;
+        let commit_builder = rewriter.rebase(settings)?;
+        let destination_tree = store.get_root_tree(commit_builder.tree_id())?;
+        let new_tree = destination_tree.merge(&source.parent_tree, &selected_tree)?;
+        let commit = commit_builder.set_tree_id(new_tree.id()).write()?;
+        rewritten_destinations.push(commit);
+        Ok(())
+    })?;
+    Ok(AbsorbStats {
+        rewritten_source,
+        rewritten_destinations,
+        num_rebased,
+    })
+}
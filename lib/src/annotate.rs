@@ -0,0 +1,296 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Methods that allow annotation (attribution and blame) for a file in a
+//! repository.
+//!
+//! TODO: Add support for different blame layers with a trait in the future.
+//! Like commit metadata and more.
+
+use std::collections::{HashMap, HashSet};
+use std::io::Read as _;
+
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
+
+use crate::backend::{BackendError, BackendResult, CommitId};
+use crate::commit::{Commit, CommitByCommitterTimestamp};
+use crate::conflicts::{materialize_tree_value, ConflictMarkerStyle, MaterializedTreeValue};
+use crate::dag_walk;
+use crate::diff::{find_line_ranges, Diff, DiffHunk};
+use crate::repo_path::RepoPath;
+
+/// Annotation results for a specific file.
+#[derive(Clone, Debug)]
+pub struct AnnotateResults {
+    /// An array of annotation results ordered by line. For each line, the
+    /// commit id is the id of the commit which introduced the line, and the
+    /// bytes are the content of the line, including the line terminator if
+    /// there is one.
+    pub file_annotations: Vec<(CommitId, Vec<u8>)>,
+}
+
+/// Lines of a file in some commit which haven't been attributed yet.
+struct PendingLines {
+    /// The content of the file in that commit.
+    content: Vec<u8>,
+    /// Maps line numbers in the starting commit to line numbers in `content`.
+    line_map: HashMap<usize, usize>,
+}
+
+/// Gets line-by-line annotations for the file at `file_path` in
+/// `starting_commit`.
+///
+/// Each line is attributed to the closest ancestor (or the starting commit
+/// itself) in which the line was added or last modified. When a line exists
+/// in more than one parent of a merge commit, it's attributed through the
+/// first of those parents. Conflicted files are annotated in their
+/// materialized form, including the conflict markers.
+///
+/// Returns an empty result if the path doesn't exist in `starting_commit`.
+pub fn get_annotation_for_file(
+    starting_commit: &Commit,
+    file_path: &RepoPath,
+) -> BackendResult<AnnotateResults> {
+    let original_content = get_file_contents(starting_commit, file_path)?.unwrap_or_default();
+    let original_lines = original_content
+        .split_inclusive(|b| *b == b'\n')
+        .collect_vec();
+    let mut pending: HashMap<CommitId, PendingLines> = HashMap::new();
+    pending.insert(
+        starting_commit.id().clone(),
+        PendingLines {
+            line_map: (0..original_lines.len()).map(|i| (i, i)).collect(),
+            content: original_content.clone(),
+        },
+    );
+    let line_origins = attribute_lines(
+        original_lines.len(),
+        pending,
+        vec![starting_commit.clone()],
+        file_path,
+        None,
+    )?;
+
+    let file_annotations = line_origins
+        .into_iter()
+        .zip(original_lines)
+        .map(|(commit_id, line)| (commit_id.unwrap(), line.to_vec()))
+        .collect();
+    Ok(AnnotateResults { file_annotations })
+}
+
+/// Attributes each line of `content` to the commit in `domain` in which the
+/// line was added or last modified, treating `content` as the content of the
+/// file at `file_path` in a commit with the given `parents`.
+///
+/// Lines that were introduced outside `domain`, or that don't exist in any of
+/// the parents, are attributed to `None`.
+pub fn get_annotation_with_domain(
+    content: &[u8],
+    parents: &[Commit],
+    file_path: &RepoPath,
+    domain: &HashSet<CommitId>,
+) -> BackendResult<Vec<Option<CommitId>>> {
+    let num_lines = content.split_inclusive(|b| *b == b'\n').count();
+    let mut unmatched_lines: HashSet<usize> = (0..num_lines).collect();
+    let mut pending: HashMap<CommitId, PendingLines> = HashMap::new();
+    let mut starting_commits = vec![];
+    for parent in parents {
+        let Some(parent_content) = get_file_contents(parent, file_path)? else {
+            continue;
+        };
+        let same_lines = find_same_lines(content, &parent_content);
+        let mut line_map = HashMap::new();
+        unmatched_lines.retain(|line| {
+            if let Some(&parent_line) = same_lines.get(line) {
+                line_map.insert(*line, parent_line);
+                false
+            } else {
+                true
+            }
+        });
+        if domain.contains(parent.id()) && !line_map.is_empty() {
+            pending.insert(
+                parent.id().clone(),
+                PendingLines {
+                    content: parent_content,
+                    line_map,
+                },
+            );
+            starting_commits.push(parent.clone());
+        }
+    }
+    attribute_lines(
+        num_lines,
+        pending,
+        starting_commits,
+        file_path,
+        Some(domain),
+    )
+}
+
+/// Walks the ancestors of `starting_commits` to find the commit each pending
+/// line was introduced in. If `domain` is specified, the walk doesn't leave
+/// it, and lines introduced outside of it are left unattributed.
+fn attribute_lines(
+    num_lines: usize,
+    mut pending: HashMap<CommitId, PendingLines>,
+    starting_commits: Vec<Commit>,
+    file_path: &RepoPath,
+    domain: Option<&HashSet<CommitId>>,
+) -> BackendResult<Vec<Option<CommitId>>> {
+    let in_domain = |id: &CommitId| domain.map_or(true, |domain| domain.contains(id));
+    let mut line_origins: Vec<Option<CommitId>> = vec![None; num_lines];
+    let mut num_unattributed: usize = pending.values().map(|lines| lines.line_map.len()).sum();
+
+    // Visit commits in reverse topological order, so that all the lines a
+    // commit received from its children are known by the time it's visited.
+    let commits = dag_walk::topo_order_reverse_lazy_ok(
+        starting_commits
+            .into_iter()
+            .map(|commit| Ok(CommitByCommitterTimestamp(commit))),
+        |CommitByCommitterTimestamp(commit)| commit.id().clone(),
+        |CommitByCommitterTimestamp(commit)| {
+            let parents: Vec<BackendResult<_>> = commit
+                .parent_ids()
+                .iter()
+                .filter(|id| in_domain(id))
+                .map(|id| {
+                    commit
+                        .store()
+                        .get_commit(id)
+                        .map(CommitByCommitterTimestamp)
+                })
+                .collect();
+            parents
+        },
+    );
+    for item in commits {
+        if num_unattributed == 0 {
+            break;
+        }
+        let CommitByCommitterTimestamp(commit) = item?;
+        let Some(PendingLines {
+            content,
+            mut line_map,
+        }) = pending.remove(commit.id())
+        else {
+            continue;
+        };
+        for parent in commit.parents() {
+            if line_map.is_empty() {
+                break;
+            }
+            let parent = parent?;
+            let Some(parent_content) = get_file_contents(&parent, file_path)? else {
+                continue;
+            };
+            let same_lines = find_same_lines(&content, &parent_content);
+            if !in_domain(parent.id()) {
+                // These lines were introduced outside the domain.
+                line_map.retain(|_, local_line| !same_lines.contains_key(local_line));
+                continue;
+            }
+            let parent_pending =
+                pending
+                    .entry(parent.id().clone())
+                    .or_insert_with(|| PendingLines {
+                        content: parent_content,
+                        line_map: HashMap::new(),
+                    });
+            line_map.retain(|&original_line, &mut local_line| {
+                if let Some(&parent_line) = same_lines.get(&local_line) {
+                    parent_pending.line_map.insert(original_line, parent_line);
+                    false
+                } else {
+                    true
+                }
+            });
+            if parent_pending.line_map.is_empty() {
+                pending.remove(parent.id());
+            }
+        }
+        // The remaining lines don't exist in any of the parents, so this commit
+        // introduced them.
+        for original_line in line_map.into_keys() {
+            line_origins[original_line] = Some(commit.id().clone());
+        }
+        num_unattributed = pending.values().map(|lines| lines.line_map.len()).sum();
+    }
+    // Lines that are still pending would only be left over if the walk ended
+    // early because of a missing commit. Attribute them to where they were
+    // last seen.
+    for (commit_id, PendingLines { line_map, .. }) in pending {
+        for original_line in line_map.into_keys() {
+            line_origins[original_line].get_or_insert_with(|| commit_id.clone());
+        }
+    }
+    Ok(line_origins)
+}
+
+/// Returns a map from line numbers in `current` to line numbers in `parent`
+/// for all the lines that are unchanged between them.
+fn find_same_lines(current: &[u8], parent: &[u8]) -> HashMap<usize, usize> {
+    let mut result = HashMap::new();
+    let mut current_line = 0;
+    let mut parent_line = 0;
+    let diff = Diff::for_tokenizer(&[current, parent], find_line_ranges);
+    for hunk in diff.hunks() {
+        match hunk {
+            DiffHunk::Matching(common) => {
+                for _ in common.split_inclusive(|b| *b == b'\n') {
+                    result.insert(current_line, parent_line);
+                    current_line += 1;
+                    parent_line += 1;
+                }
+            }
+            DiffHunk::Different(slices) => {
+                current_line += slices[0].split_inclusive(|b| *b == b'\n').count();
+                parent_line += slices[1].split_inclusive(|b| *b == b'\n').count();
+            }
+        }
+    }
+    result
+}
+
+/// Reads the content of the file at `file_path` in `commit`, materializing
+/// conflicts if the file is conflicted. Returns `None` if the path doesn't
+/// refer to a file.
+fn get_file_contents(commit: &Commit, file_path: &RepoPath) -> BackendResult<Option<Vec<u8>>> {
+    let tree = commit.tree()?;
+    let value = tree.path_value(file_path)?;
+    let materialized = materialize_tree_value(
+        commit.store(),
+        file_path,
+        value,
+        ConflictMarkerStyle::default(),
+    )
+    .block_on()?;
+    match materialized {
+        MaterializedTreeValue::File { id, mut reader, .. } => {
+            let mut content = vec![];
+            reader
+                .read_to_end(&mut content)
+                .map_err(|err| BackendError::ReadFile {
+                    path: file_path.to_owned(),
+                    id,
+                    source: err.into(),
+                })?;
+            Ok(Some(content))
+        }
+        MaterializedTreeValue::Conflict { contents, .. } => Ok(Some(contents)),
+        _ => Ok(None),
+    }
+}
@@ -21,6 +21,7 @@ use std::io::Read;
 use std::time::SystemTime;
 
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 use thiserror::Error;
 
 use crate::content_hash::ContentHash;
@@ -241,6 +242,22 @@ impl TreeValue {
     }
 }
 
+/// A copy or rename of a file between two commits, as reported by
+/// [`Backend::get_copy_records()`].
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct CopyRecord {
+    /// The destination of the copy.
+    pub target: RepoPathBuf,
+    /// The commit in which the target exists.
+    pub target_commit: CommitId,
+    /// The path the target was copied from.
+    pub source: RepoPathBuf,
+    /// The version of the source file the target was copied from.
+    pub source_file: FileId,
+    /// The commit in which the source exists.
+    pub source_commit: CommitId,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct TreeEntry<'a> {
     name: &'a RepoPathComponent,
@@ -416,6 +433,20 @@ pub trait Backend: Send + Sync + Debug {
         sign_with: Option<&mut SigningFn>,
     ) -> BackendResult<(CommitId, Commit)>;
 
+    /// Get the files that were copied or renamed between the trees of `root`
+    /// and `head`. If `paths` is `None`, all paths are included, otherwise
+    /// only records whose source or target is in `paths` are returned.
+    ///
+    /// Backends may record the copies from the parent when a commit is written
+    /// and infer them for other pairs of commits. Backends that don't know
+    /// about copies may return an empty stream.
+    fn get_copy_records(
+        &self,
+        paths: Option<&[RepoPathBuf]>,
+        root: &CommitId,
+        head: &CommitId,
+    ) -> BackendResult<BoxStream<'_, BackendResult<CopyRecord>>>;
+
     /// Perform garbage collection.
     ///
     /// All commits found in the `index` won't be removed. In addition to that,
@@ -25,7 +25,7 @@ use crate::backend::{self, BackendResult, ChangeId, CommitId, MergedTreeId, Sign
 use crate::merged_tree::MergedTree;
 use crate::repo::Repo;
 use crate::rewrite::merge_commit_trees;
-use crate::signing::{SignResult, Verification};
+use crate::signing::{SigStatus, SignError, SignResult, Verification};
 use crate::store::Store;
 
 #[derive(Clone)]
@@ -172,6 +172,17 @@ impl Commit {
             .map(|sig| self.store.signer().verify(&self.id, &sig.data, &sig.sig))
             .transpose()
     }
+
+    /// Verifies the signature and returns its status, or `None` if the commit
+    /// isn't signed. A malformed signature is reported as `Bad`, and a
+    /// signature which no backend could verify is reported as `Unknown`.
+    pub fn signature_status(&self) -> Option<SigStatus> {
+        match self.verification() {
+            Ok(verification) => verification.map(|verification| verification.status),
+            Err(SignError::InvalidSignatureFormat) => Some(SigStatus::Bad),
+            Err(SignError::Backend(_)) => Some(SigStatus::Unknown),
+        }
+    }
 }
 
 pub trait CommitIteratorExt<'c, I> {
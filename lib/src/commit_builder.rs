@@ -190,6 +190,15 @@ impl CommitBuilder<'_> {
         self
     }
 
+    /// Writes the commit to the store without adding it to the repo, so it can
+    /// be inspected before the actual commit is written. The commit isn't
+    /// signed.
+    pub fn write_hidden(&self) -> BackendResult<Commit> {
+        let mut commit = self.commit.clone();
+        commit.secure_sig = None;
+        self.mut_repo.store().write_commit(commit, None)
+    }
+
     pub fn write(mut self) -> BackendResult<Commit> {
         let sign_settings = &self.sign_settings;
         let store = self.mut_repo.store();
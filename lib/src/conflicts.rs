@@ -34,11 +34,15 @@ const CONFLICT_END_LINE: &[u8] = b">>>>>>>";
 const CONFLICT_DIFF_LINE: &[u8] = b"%%%%%%%";
 const CONFLICT_MINUS_LINE: &[u8] = b"-------";
 const CONFLICT_PLUS_LINE: &[u8] = b"+++++++";
+const CONFLICT_GIT_ANCESTOR_LINE: &[u8] = b"|||||||";
+const CONFLICT_GIT_SEPARATOR_LINE: &[u8] = b"=======";
 const CONFLICT_START_LINE_CHAR: u8 = CONFLICT_START_LINE[0];
 const CONFLICT_END_LINE_CHAR: u8 = CONFLICT_END_LINE[0];
 const CONFLICT_DIFF_LINE_CHAR: u8 = CONFLICT_DIFF_LINE[0];
 const CONFLICT_MINUS_LINE_CHAR: u8 = CONFLICT_MINUS_LINE[0];
 const CONFLICT_PLUS_LINE_CHAR: u8 = CONFLICT_PLUS_LINE[0];
+const CONFLICT_GIT_ANCESTOR_LINE_CHAR: u8 = CONFLICT_GIT_ANCESTOR_LINE[0];
+const CONFLICT_GIT_SEPARATOR_LINE_CHAR: u8 = CONFLICT_GIT_SEPARATOR_LINE[0];
 
 /// A conflict marker is one of the separators, optionally followed by a space
 /// and some text.
@@ -47,12 +51,27 @@ const CONFLICT_PLUS_LINE_CHAR: u8 = CONFLICT_PLUS_LINE[0];
 // markers inside the text of the conflicts.
 static CONFLICT_MARKER_REGEX: once_cell::sync::Lazy<Regex> = once_cell::sync::Lazy::new(|| {
     Regex::new(
-        r"(<{7}|>{7}|%{7}|\-{7}|\+{7})( .*)?
+        r"(<{7}|>{7}|%{7}|\-{7}|\+{7}|\|{7}|={7})( .*)?
 ",
     )
     .unwrap()
 });
 
+/// Describes how conflicts are written as conflict markers in files.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictMarkerStyle {
+    /// Write a snapshot of one side and a diff from each base to the next side
+    /// (`%%%%%%%` sections).
+    #[default]
+    Diff,
+    /// Write a snapshot of every side and base.
+    Snapshot,
+    /// Write conflicts like Git's "diff3" style. Conflicts with more than two
+    /// sides can't be represented this way, so they are written as snapshots.
+    Git,
+}
+
 fn write_diff_hunks(hunks: &[DiffHunk], file: &mut dyn Write) -> std::io::Result<()> {
     for hunk in hunks {
         match hunk {
@@ -153,8 +172,9 @@ pub async fn materialize_tree_value(
     store: &Store,
     path: &RepoPath,
     value: MergedTreeValue,
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> BackendResult<MaterializedTreeValue> {
-    match materialize_tree_value_no_access_denied(store, path, value).await {
+    match materialize_tree_value_no_access_denied(store, path, value, conflict_marker_style).await {
         Err(BackendError::ReadAccessDenied { source, .. }) => {
             Ok(MaterializedTreeValue::AccessDenied(source))
         }
@@ -166,6 +186,7 @@ async fn materialize_tree_value_no_access_denied(
     store: &Store,
     path: &RepoPath,
     value: MergedTreeValue,
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> BackendResult<MaterializedTreeValue> {
     match value.into_resolved() {
         Ok(None) => Ok(MaterializedTreeValue::Absent),
@@ -191,7 +212,7 @@ async fn materialize_tree_value_no_access_denied(
             if let Some(file_merge) = conflict.to_file_merge() {
                 let file_merge = file_merge.simplify();
                 let content = extract_as_single_hunk(&file_merge, store, path).await?;
-                materialize_merge_result(&content, &mut contents)
+                materialize_merge_result(&content, conflict_marker_style, &mut contents)
                     .expect("Failed to materialize conflict to in-memory buffer");
             } else {
                 // Unless all terms are regular files, we can't do much better than to try to
@@ -216,6 +237,7 @@ async fn materialize_tree_value_no_access_denied(
 
 pub fn materialize_merge_result(
     single_hunk: &Merge<ContentHunk>,
+    conflict_marker_style: ConflictMarkerStyle,
     output: &mut dyn Write,
 ) -> std::io::Result<()> {
     let slices = single_hunk.map(|content| content.0.as_slice());
@@ -235,85 +257,18 @@ pub fn materialize_merge_result(
                     output.write_all(&content.0)?;
                 } else {
                     conflict_index += 1;
-                    output.write_all(CONFLICT_START_LINE)?;
-                    output.write_all(
-                        format!(" Conflict {conflict_index} of {num_conflicts}\n").as_bytes(),
-                    )?;
-                    let mut add_index = 0;
-                    for (base_index, left) in hunk.removes().enumerate() {
-                        // The vast majority of conflicts one actually tries to
-                        // resolve manually have 1 base.
-                        let base_str = if hunk.removes().len() == 1 {
-                            "base".to_string()
-                        } else {
-                            format!("base #{}", base_index + 1)
-                        };
-
-                        let right1 = if let Some(right1) = hunk.get_add(add_index) {
-                            right1
-                        } else {
-                            // If we have no more positive terms, emit the remaining negative
-                            // terms as snapshots.
-                            output.write_all(CONFLICT_MINUS_LINE)?;
-                            output.write_all(format!(" Contents of {base_str}\n").as_bytes())?;
-                            output.write_all(&left.0)?;
-                            continue;
-                        };
-                        let diff1 = Diff::for_tokenizer(&[&left.0, &right1.0], find_line_ranges)
-                            .hunks()
-                            .collect_vec();
-                        // Check if the diff against the next positive term is better. Since
-                        // we want to preserve the order of the terms, we don't match against
-                        // any later positive terms.
-                        if let Some(right2) = hunk.get_add(add_index + 1) {
-                            let diff2 =
-                                Diff::for_tokenizer(&[&left.0, &right2.0], find_line_ranges)
-                                    .hunks()
-                                    .collect_vec();
-                            if diff_size(&diff2) < diff_size(&diff1) {
-                                // If the next positive term is a better match, emit
-                                // the current positive term as a snapshot and the next
-                                // positive term as a diff.
-                                output.write_all(CONFLICT_PLUS_LINE)?;
-                                output.write_all(
-                                    format!(" Contents of side #{}\n", add_index + 1).as_bytes(),
-                                )?;
-                                output.write_all(&right1.0)?;
-                                output.write_all(CONFLICT_DIFF_LINE)?;
-                                output.write_all(
-                                    format!(
-                                        " Changes from {base_str} to side #{}\n",
-                                        add_index + 2
-                                    )
-                                    .as_bytes(),
-                                )?;
-                                write_diff_hunks(&diff2, output)?;
-                                add_index += 2;
-                                continue;
-                            }
+                    let conflict_info = format!("Conflict {conflict_index} of {num_conflicts}");
+                    match conflict_marker_style {
+                        ConflictMarkerStyle::Git if hunk.num_sides() == 2 => {
+                            materialize_git_style_conflict(&hunk, &conflict_info, output)?;
+                        }
+                        ConflictMarkerStyle::Diff => {
+                            materialize_jj_style_conflict(&hunk, &conflict_info, true, output)?;
+                        }
+                        ConflictMarkerStyle::Snapshot | ConflictMarkerStyle::Git => {
+                            materialize_jj_style_conflict(&hunk, &conflict_info, false, output)?;
                         }
-
-                        output.write_all(CONFLICT_DIFF_LINE)?;
-                        output.write_all(
-                            format!(" Changes from {base_str} to side #{}\n", add_index + 1)
-                                .as_bytes(),
-                        )?;
-                        write_diff_hunks(&diff1, output)?;
-                        add_index += 1;
-                    }
-
-                    //  Emit the remaining positive terms as snapshots.
-                    for (add_index, slice) in hunk.adds().enumerate().skip(add_index) {
-                        output.write_all(CONFLICT_PLUS_LINE)?;
-                        output.write_all(
-                            format!(" Contents of side #{}\n", add_index + 1).as_bytes(),
-                        )?;
-                        output.write_all(&slice.0)?;
                     }
-                    output.write_all(CONFLICT_END_LINE)?;
-                    output.write_all(
-                        format!(" Conflict {conflict_index} of {num_conflicts} ends\n").as_bytes(),
-                    )?;
                 }
             }
         }
@@ -321,6 +276,133 @@ pub fn materialize_merge_result(
     Ok(())
 }
 
+fn materialize_git_style_conflict(
+    hunk: &Merge<ContentHunk>,
+    conflict_info: &str,
+    output: &mut dyn Write,
+) -> std::io::Result<()> {
+    let (Some(left), Some(base), Some(right)) =
+        (hunk.get_add(0), hunk.get_remove(0), hunk.get_add(1))
+    else {
+        panic!("Git-style conflict markers can only represent 2-sided conflicts");
+    };
+    output.write_all(CONFLICT_START_LINE)?;
+    output.write_all(format!(" Side #1 ({conflict_info})\n").as_bytes())?;
+    output.write_all(&left.0)?;
+    output.write_all(CONFLICT_GIT_ANCESTOR_LINE)?;
+    output.write_all(b" Base\n")?;
+    output.write_all(&base.0)?;
+    output.write_all(CONFLICT_GIT_SEPARATOR_LINE)?;
+    output.write_all(b"\n")?;
+    output.write_all(&right.0)?;
+    output.write_all(CONFLICT_END_LINE)?;
+    output.write_all(format!(" Side #2 ({conflict_info} ends)\n").as_bytes())?;
+    Ok(())
+}
+
+fn materialize_jj_style_conflict(
+    hunk: &Merge<ContentHunk>,
+    conflict_info: &str,
+    use_diffs: bool,
+    output: &mut dyn Write,
+) -> std::io::Result<()> {
+    // The vast majority of conflicts one actually tries to resolve manually have 1
+    // base.
+    let base_str = |base_index: usize| {
+        if hunk.removes().len() == 1 {
+            "base".to_string()
+        } else {
+            format!("base #{}", base_index + 1)
+        }
+    };
+    let write_side = |add_index: usize, output: &mut dyn Write| -> std::io::Result<()> {
+        output.write_all(CONFLICT_PLUS_LINE)?;
+        output.write_all(format!(" Contents of side #{}\n", add_index + 1).as_bytes())?;
+        output.write_all(&hunk.get_add(add_index).unwrap().0)
+    };
+
+    output.write_all(CONFLICT_START_LINE)?;
+    output.write_all(format!(" {conflict_info}\n").as_bytes())?;
+    let mut add_index = 0;
+    for (base_index, left) in hunk.removes().enumerate() {
+        if !use_diffs {
+            // Emit every term as a snapshot, starting with the first side.
+            if base_index == 0 {
+                write_side(add_index, output)?;
+                add_index += 1;
+            }
+            output.write_all(CONFLICT_MINUS_LINE)?;
+            output.write_all(format!(" Contents of {}\n", base_str(base_index)).as_bytes())?;
+            output.write_all(&left.0)?;
+            if add_index < hunk.adds().len() {
+                write_side(add_index, output)?;
+                add_index += 1;
+            }
+            continue;
+        }
+
+        let right1 = if let Some(right1) = hunk.get_add(add_index) {
+            right1
+        } else {
+            // If we have no more positive terms, emit the remaining negative
+            // terms as snapshots.
+            output.write_all(CONFLICT_MINUS_LINE)?;
+            output.write_all(format!(" Contents of {}\n", base_str(base_index)).as_bytes())?;
+            output.write_all(&left.0)?;
+            continue;
+        };
+        let diff1 = Diff::for_tokenizer(&[&left.0, &right1.0], find_line_ranges)
+            .hunks()
+            .collect_vec();
+        // Check if the diff against the next positive term is better. Since
+        // we want to preserve the order of the terms, we don't match against
+        // any later positive terms.
+        if let Some(right2) = hunk.get_add(add_index + 1) {
+            let diff2 = Diff::for_tokenizer(&[&left.0, &right2.0], find_line_ranges)
+                .hunks()
+                .collect_vec();
+            if diff_size(&diff2) < diff_size(&diff1) {
+                // If the next positive term is a better match, emit
+                // the current positive term as a snapshot and the next
+                // positive term as a diff.
+                write_side(add_index, output)?;
+                output.write_all(CONFLICT_DIFF_LINE)?;
+                output.write_all(
+                    format!(
+                        " Changes from {} to side #{}\n",
+                        base_str(base_index),
+                        add_index + 2
+                    )
+                    .as_bytes(),
+                )?;
+                write_diff_hunks(&diff2, output)?;
+                add_index += 2;
+                continue;
+            }
+        }
+
+        output.write_all(CONFLICT_DIFF_LINE)?;
+        output.write_all(
+            format!(
+                " Changes from {} to side #{}\n",
+                base_str(base_index),
+                add_index + 1
+            )
+            .as_bytes(),
+        )?;
+        write_diff_hunks(&diff1, output)?;
+        add_index += 1;
+    }
+
+    //  Emit the remaining positive terms as snapshots.
+    for add_index in add_index..hunk.adds().len() {
+        write_side(add_index, output)?;
+    }
+    output.write_all(CONFLICT_END_LINE)?;
+    output.write_all(format!(" {conflict_info} ends\n").as_bytes())?;
+    Ok(())
+}
+
 fn diff_size(hunks: &[DiffHunk]) -> usize {
     hunks
         .iter()
@@ -381,6 +463,24 @@ pub fn parse_conflict(input: &[u8], num_sides: usize) -> Option<Vec<Merge<Conten
 }
 
 fn parse_conflict_hunk(input: &[u8]) -> Merge<ContentHunk> {
+    // If the hunk starts with a jj-style marker, it was written in the "diff" or
+    // "snapshot" style. Otherwise, it may be a Git-style conflict.
+    let first_line = input.split_inclusive(|b| *b == b'\n').next();
+    let is_jj_style = first_line.is_some_and(|line| {
+        CONFLICT_MARKER_REGEX.is_match_at(line, 0)
+            && matches!(
+                line[0],
+                CONFLICT_DIFF_LINE_CHAR | CONFLICT_MINUS_LINE_CHAR | CONFLICT_PLUS_LINE_CHAR
+            )
+    });
+    if is_jj_style {
+        parse_jj_style_conflict_hunk(input)
+    } else {
+        parse_git_style_conflict_hunk(input)
+    }
+}
+
+fn parse_jj_style_conflict_hunk(input: &[u8]) -> Merge<ContentHunk> {
     enum State {
         Diff,
         Minus,
@@ -442,14 +542,57 @@ fn parse_conflict_hunk(input: &[u8]) -> Merge<ContentHunk> {
     Merge::from_removes_adds(removes, adds)
 }
 
+fn parse_git_style_conflict_hunk(input: &[u8]) -> Merge<ContentHunk> {
+    #[derive(PartialEq, Eq)]
+    enum State {
+        Left,
+        Base,
+        Right,
+    }
+    let mut state = State::Left;
+    let mut left = ContentHunk(vec![]);
+    let mut base = ContentHunk(vec![]);
+    let mut right = ContentHunk(vec![]);
+    for line in input.split_inclusive(|b| *b == b'\n') {
+        if CONFLICT_MARKER_REGEX.is_match_at(line, 0) {
+            match line[0] {
+                CONFLICT_GIT_ANCESTOR_LINE_CHAR if state == State::Left => {
+                    state = State::Base;
+                    continue;
+                }
+                CONFLICT_GIT_SEPARATOR_LINE_CHAR if state == State::Base => {
+                    state = State::Right;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        match state {
+            State::Left => left.0.extend_from_slice(line),
+            State::Base => base.0.extend_from_slice(line),
+            State::Right => right.0.extend_from_slice(line),
+        }
+    }
+
+    if state == State::Right {
+        Merge::from_vec(vec![left, base, right])
+    } else {
+        // Doesn't look like a conflict
+        Merge::resolved(ContentHunk(vec![]))
+    }
+}
+
 /// Parses conflict markers in `content` and returns an updated version of
 /// `file_ids` with the new contents. If no (valid) conflict markers remain, a
-/// single resolves `FileId` will be returned.
+/// single resolves `FileId` will be returned. Conflict markers of any style
+/// are recognized, but `conflict_marker_style` should be the style the
+/// conflict was materialized with, so unchanged content can be detected.
 pub async fn update_from_content(
     file_ids: &Merge<Option<FileId>>,
     store: &Store,
     path: &RepoPath,
     content: &[u8],
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> BackendResult<Merge<Option<FileId>>> {
     let simplified_file_ids = file_ids.clone().simplify();
     let simplified_file_ids = &simplified_file_ids;
@@ -461,7 +604,7 @@ pub async fn update_from_content(
     // copy.
     let mut old_content = Vec::with_capacity(content.len());
     let merge_hunk = extract_as_single_hunk(simplified_file_ids, store, path).await?;
-    materialize_merge_result(&merge_hunk, &mut old_content).unwrap();
+    materialize_merge_result(&merge_hunk, conflict_marker_style, &mut old_content).unwrap();
     if content == old_content {
         return Ok(file_ids.clone());
     }
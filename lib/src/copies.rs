@@ -0,0 +1,187 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Code for working with copies and renames.
+
+use std::collections::HashMap;
+
+use futures::stream::{BoxStream, StreamExt as _};
+
+use crate::backend::{BackendResult, CopyRecord};
+use crate::matchers::Matcher;
+use crate::merge::MergedTreeValue;
+use crate::merged_tree::{MergedTree, TreeDiffStream};
+use crate::repo_path::{RepoPath, RepoPathBuf};
+
+/// A collection of `CopyRecord`s, indexed by source and target path.
+#[derive(Default, Debug)]
+pub struct CopyRecords {
+    records: Vec<CopyRecord>,
+    // Maps from `source` or `target` to the index of the entry in `records`.
+    // Conflicts are resolved in favor of the first record for a source, and
+    // the last record for a target.
+    sources: HashMap<RepoPathBuf, usize>,
+    targets: HashMap<RepoPathBuf, usize>,
+}
+
+impl CopyRecords {
+    /// Adds the given records to `self`. Stops at the first error.
+    pub fn add_records(
+        &mut self,
+        copy_records: impl IntoIterator<Item = BackendResult<CopyRecord>>,
+    ) -> BackendResult<()> {
+        for record in copy_records {
+            let record = record?;
+            let index = self.records.len();
+            self.sources.entry(record.source.clone()).or_insert(index);
+            self.targets.insert(record.target.clone(), index);
+            self.records.push(record);
+        }
+        Ok(())
+    }
+
+    /// Returns true if there are no records.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns the record that copies or renames `source`, if any.
+    pub fn for_source(&self, source: &RepoPath) -> Option<&CopyRecord> {
+        self.sources.get(source).map(|&index| &self.records[index])
+    }
+
+    /// Returns the record that creates `target` from another file, if any.
+    pub fn for_target(&self, target: &RepoPath) -> Option<&CopyRecord> {
+        self.targets.get(target).map(|&index| &self.records[index])
+    }
+
+    /// Iterates over all the records in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &CopyRecord> {
+        self.records.iter()
+    }
+}
+
+/// Whether a file was copied or renamed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CopyOperation {
+    /// The source file still exists in the target tree, or another copy of it
+    /// is reported as the rename.
+    Copy,
+    /// The source file doesn't exist in the target tree.
+    Rename,
+}
+
+/// A diff entry which may refer to a file at a different path in the source
+/// tree.
+pub struct CopiesTreeDiffEntry {
+    /// The path of the file in the source tree. This is the same as `target`
+    /// unless the file was copied or renamed.
+    pub source: RepoPathBuf,
+    /// The path of the file in the target tree.
+    pub target: RepoPathBuf,
+    /// How the file was created from `source`, if it was copied or renamed.
+    pub copy_operation: Option<CopyOperation>,
+    /// The values at `source` in the source tree and at `target` in the target
+    /// tree.
+    pub value: BackendResult<(MergedTreeValue, MergedTreeValue)>,
+}
+
+/// Stream of differences between two trees, including copies and renames.
+pub type CopiesTreeDiffStream<'a> = BoxStream<'a, CopiesTreeDiffEntry>;
+
+/// Wraps `inner`, the diff between `source_tree` and `target_tree`, so that
+/// added files with a copy record are reported together with their source, and
+/// the removal of a renamed source is reported only as part of the rename.
+pub(crate) fn copies_tree_diff_stream<'a>(
+    inner: TreeDiffStream<'a>,
+    source_tree: MergedTree,
+    target_tree: MergedTree,
+    matcher: &'a dyn Matcher,
+    copy_records: &'a CopyRecords,
+) -> CopiesTreeDiffStream<'a> {
+    inner
+        .filter_map(move |(path, diff)| {
+            let entry = resolve_copies(
+                &source_tree,
+                &target_tree,
+                matcher,
+                copy_records,
+                path,
+                diff,
+            );
+            futures::future::ready(entry)
+        })
+        .boxed()
+}
+
+fn resolve_copies(
+    source_tree: &MergedTree,
+    target_tree: &MergedTree,
+    matcher: &dyn Matcher,
+    copy_records: &CopyRecords,
+    path: RepoPathBuf,
+    diff: BackendResult<(MergedTreeValue, MergedTreeValue)>,
+) -> Option<CopiesTreeDiffEntry> {
+    let plain_entry = |path: RepoPathBuf, value| CopiesTreeDiffEntry {
+        source: path.clone(),
+        target: path,
+        copy_operation: None,
+        value,
+    };
+    let Ok((before, after)) = &diff else {
+        return Some(plain_entry(path, diff));
+    };
+    if before.is_absent() {
+        if let Some(record) = copy_records.for_target(&path) {
+            let source_value = match source_tree.path_value(&record.source) {
+                Ok(value) => value,
+                Err(err) => return Some(plain_entry(path, Err(err))),
+            };
+            if source_value.is_present() {
+                // If a removed file has multiple targets, only the first one is
+                // reported as a rename.
+                let is_first_target = copy_records
+                    .for_source(&record.source)
+                    .is_some_and(|first| first.target == path);
+                let copy_operation = match target_tree.path_value(&record.source) {
+                    Ok(value) if value.is_absent() && is_first_target => CopyOperation::Rename,
+                    Ok(_) => CopyOperation::Copy,
+                    Err(err) => return Some(plain_entry(path, Err(err))),
+                };
+                return Some(CopiesTreeDiffEntry {
+                    source: record.source.clone(),
+                    target: path,
+                    copy_operation: Some(copy_operation),
+                    value: Ok((source_value, after.clone())),
+                });
+            }
+        }
+    } else if after.is_absent() {
+        // The removal of a renamed file is reported as part of the rename, but
+        // only if the target is included in the diff.
+        if let Some(record) = copy_records.for_source(&path) {
+            let target_added = matches!(
+                (
+                    source_tree.path_value(&record.target),
+                    target_tree.path_value(&record.target),
+                ),
+                (Ok(old), Ok(new)) if old.is_absent() && new.is_present()
+            );
+            if target_added && matcher.matches(&record.target) {
+                return None;
+            }
+        }
+    }
+    Some(plain_entry(path, diff))
+}
@@ -0,0 +1,335 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bloom filters of the paths changed by commits.
+
+#![allow(missing_docs)]
+
+use std::borrow::Cow;
+use std::cmp::max;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, Read as _};
+use std::iter;
+use std::path::{Path, PathBuf};
+
+use blake2::Blake2b512;
+use digest::Digest;
+use itertools::Itertools as _;
+
+use super::composite::CompositeIndex;
+use crate::backend::BackendResult;
+use crate::commit::Commit;
+use crate::fileset::{FilePattern, FilesetExpression};
+use crate::matchers::EverythingMatcher;
+use crate::repo_path::{RepoPath, RepoPathBuf};
+use crate::rewrite;
+
+/// Current format version of the changed-path table file.
+const CHANGED_PATH_TABLE_FILE_FORMAT_VERSION: u32 = 1;
+
+const NUM_HASHES: u64 = 7;
+const BITS_PER_PATH: usize = 10;
+const MIN_FILTER_SIZE: usize = 8;
+
+/// Bloom filter of the paths changed by a commit, and their ancestor
+/// directories.
+///
+/// The filter may report false positives, but never false negatives.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct ChangedPathFilter<'a> {
+    bits: Cow<'a, [u8]>,
+}
+
+impl ChangedPathFilter<'static> {
+    /// Builds filter containing the given `paths` and their ancestors.
+    pub fn from_paths<'a>(paths: impl IntoIterator<Item = &'a RepoPath>) -> Self {
+        let keys: HashSet<&RepoPath> = paths
+            .into_iter()
+            .flat_map(|path| iter::successors(Some(path), |path| path.parent()))
+            .collect();
+        let size = max(MIN_FILTER_SIZE, (keys.len() * BITS_PER_PATH).div_ceil(8));
+        let mut bits = vec![0; size];
+        for key in keys {
+            for bit in bit_positions(key, size * 8) {
+                bits[bit / 8] |= 1 << (bit % 8);
+            }
+        }
+        ChangedPathFilter { bits: bits.into() }
+    }
+
+    /// Builds filter of the paths changed by `commit` compared to the merged
+    /// parent trees.
+    pub fn from_commit(commit: &Commit, index: &CompositeIndex) -> BackendResult<Self> {
+        let parents: Vec<_> = commit.parents().try_collect()?;
+        let from_tree = rewrite::merge_commit_trees_without_repo(commit.store(), &index, &parents)?;
+        let to_tree = commit.tree()?;
+        let paths = from_tree
+            .diff(&to_tree, &EverythingMatcher)
+            .map(|(path, _)| path)
+            .collect_vec();
+        Ok(Self::from_paths(paths.iter().map(AsRef::as_ref)))
+    }
+}
+
+impl<'a> ChangedPathFilter<'a> {
+    pub fn from_bytes(bits: &'a [u8]) -> Self {
+        ChangedPathFilter { bits: bits.into() }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bits
+    }
+
+    pub fn borrowed(&self) -> ChangedPathFilter<'_> {
+        ChangedPathFilter::from_bytes(&self.bits)
+    }
+
+    pub fn into_owned(self) -> ChangedPathFilter<'static> {
+        ChangedPathFilter {
+            bits: self.bits.into_owned().into(),
+        }
+    }
+
+    /// Returns false if `path` was definitely not changed. The root path is
+    /// contained unless the commit changed nothing.
+    pub fn may_contain(&self, path: &RepoPath) -> bool {
+        bit_positions(path, self.bits.len() * 8)
+            .all(|bit| self.bits[bit / 8] & (1 << (bit % 8)) != 0)
+    }
+}
+
+fn bit_positions(path: &RepoPath, num_bits: usize) -> impl Iterator<Item = usize> {
+    // The hash has to be stable across versions, so don't use std::hash.
+    let hash = Blake2b512::digest(path.as_internal_file_string().as_bytes());
+    let h1 = u64::from_le_bytes(hash[0..8].try_into().unwrap());
+    let h2 = u64::from_le_bytes(hash[8..16].try_into().unwrap());
+    let num_bits = u64::try_from(num_bits).unwrap();
+    (0..NUM_HASHES).map(move |i| {
+        let bit = h1.wrapping_add(i.wrapping_mul(h2)) % num_bits;
+        usize::try_from(bit).unwrap()
+    })
+}
+
+/// Returns paths of which at least one must be in the filter of a commit that
+/// changes files matching the `expression`.
+///
+/// Every file pattern is anchored at a directory, so the paths matched by the
+/// `expression` are always under one of the returned paths.
+pub(super) fn filter_keys_for_fileset(expression: &FilesetExpression) -> Vec<RepoPathBuf> {
+    match expression {
+        FilesetExpression::None => vec![],
+        FilesetExpression::All => vec![RepoPathBuf::root()],
+        FilesetExpression::Pattern(pattern) => match pattern {
+            FilePattern::FilePath(path) | FilePattern::PrefixPath(path) => vec![path.clone()],
+            FilePattern::FileGlob { dir, .. } => vec![dir.clone()],
+        },
+        FilesetExpression::UnionAll(expressions) => expressions
+            .iter()
+            .flat_map(filter_keys_for_fileset)
+            .collect(),
+        FilesetExpression::Intersection(expression1, expression2) => {
+            let keys1 = filter_keys_for_fileset(expression1);
+            let keys2 = filter_keys_for_fileset(expression2);
+            if keys1.len() <= keys2.len() {
+                keys1
+            } else {
+                keys2
+            }
+        }
+        FilesetExpression::Difference(expression1, _) => filter_keys_for_fileset(expression1),
+    }
+}
+
+/// Changed-path filters of the commits in an index segment, stored next to
+/// the segment file.
+///
+/// File format:
+/// ```text
+/// u32: file format version
+/// u32: number of local commit entries
+/// for each entry, in the order of the segment file:
+///   u32: end offset of the filter data (same as the previous offset if the
+///        commit wasn't indexed)
+/// for each entry:
+///   <variable number of bytes>: Bloom filter
+/// ```
+pub(super) struct ChangedPathTable {
+    num_local_commits: u32,
+    data: Vec<u8>,
+}
+
+impl ChangedPathTable {
+    pub fn file_path(dir: &Path, segment_name: &str) -> PathBuf {
+        dir.join(format!("{segment_name}.changed_paths"))
+    }
+
+    /// Loads the table of the segment `segment_name`, or returns `None` if
+    /// the segment has no changed-path table.
+    pub fn load(dir: &Path, segment_name: &str) -> io::Result<Option<Self>> {
+        let mut file = match File::open(Self::file_path(dir, segment_name)) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let mut buf = vec![];
+        file.read_to_end(&mut buf)?;
+        Self::from_bytes(buf).map(Some)
+    }
+
+    pub fn from_bytes(mut buf: Vec<u8>) -> io::Result<Self> {
+        let invalid_data = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+        let read_u32 = |buf: &[u8], offset: usize| {
+            buf.get(offset..offset + 4)
+                .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+                .ok_or_else(|| invalid_data("unexpected end of changed-path table"))
+        };
+        if read_u32(&buf, 0)? != CHANGED_PATH_TABLE_FILE_FORMAT_VERSION {
+            return Err(invalid_data("unexpected changed-path table version"));
+        }
+        let num_local_commits = read_u32(&buf, 4)?;
+        buf.drain(..8);
+        let offsets_size = (num_local_commits as usize) * 4;
+        let last_offset = match num_local_commits {
+            0 => 0,
+            n => read_u32(&buf, (n as usize - 1) * 4)? as usize,
+        };
+        if buf.len() != offsets_size + last_offset {
+            return Err(invalid_data("unexpected changed-path table length"));
+        }
+        Ok(ChangedPathTable {
+            num_local_commits,
+            data: buf,
+        })
+    }
+
+    pub fn serialize<'a>(
+        filters: impl ExactSizeIterator<Item = Option<&'a ChangedPathFilter<'a>>>,
+    ) -> Vec<u8> {
+        let num_local_commits = u32::try_from(filters.len()).unwrap();
+        let mut offsets = vec![];
+        let mut data = vec![];
+        for filter in filters {
+            if let Some(filter) = filter {
+                data.extend_from_slice(filter.as_bytes());
+            }
+            offsets.extend(u32::try_from(data.len()).unwrap().to_le_bytes());
+        }
+        let mut buf = vec![];
+        buf.extend(CHANGED_PATH_TABLE_FILE_FORMAT_VERSION.to_le_bytes());
+        buf.extend(num_local_commits.to_le_bytes());
+        buf.extend(offsets);
+        buf.extend(data);
+        buf
+    }
+
+    pub fn num_local_commits(&self) -> u32 {
+        self.num_local_commits
+    }
+
+    pub fn filter(&self, local_pos: u32) -> Option<ChangedPathFilter<'_>> {
+        let offset_at = |pos: u32| {
+            let offset = (pos as usize) * 4;
+            u32::from_le_bytes(self.data[offset..][..4].try_into().unwrap()) as usize
+        };
+        let start = if local_pos == 0 {
+            0
+        } else {
+            offset_at(local_pos - 1)
+        };
+        let end = offset_at(local_pos);
+        let filters_base = (self.num_local_commits as usize) * 4;
+        (start < end).then(|| {
+            ChangedPathFilter::from_bytes(&self.data[filters_base + start..filters_base + end])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fileset::FilesetExpression;
+
+    fn repo_path(value: &str) -> &RepoPath {
+        RepoPath::from_internal_string(value)
+    }
+
+    #[test]
+    fn test_changed_path_filter() {
+        let filter = ChangedPathFilter::from_paths([repo_path("dir/sub/file"), repo_path("top")]);
+        assert!(filter.may_contain(RepoPath::root()));
+        assert!(filter.may_contain(repo_path("dir")));
+        assert!(filter.may_contain(repo_path("dir/sub")));
+        assert!(filter.may_contain(repo_path("dir/sub/file")));
+        assert!(filter.may_contain(repo_path("top")));
+        assert!(!filter.may_contain(repo_path("dir/other")));
+        assert!(!filter.may_contain(repo_path("other")));
+
+        let filter = ChangedPathFilter::from_paths([]);
+        assert!(!filter.may_contain(RepoPath::root()));
+    }
+
+    #[test]
+    fn test_filter_keys_for_fileset() {
+        let file = |value: &str| {
+            FilesetExpression::pattern(FilePattern::FilePath(repo_path(value).to_owned()))
+        };
+        let prefix = |value: &str| {
+            FilesetExpression::pattern(FilePattern::PrefixPath(repo_path(value).to_owned()))
+        };
+        let glob = |dir: &str, pattern: &str| {
+            FilesetExpression::pattern(FilePattern::FileGlob {
+                dir: repo_path(dir).to_owned(),
+                pattern: glob::Pattern::new(pattern).unwrap(),
+            })
+        };
+        let keys = |expression: &FilesetExpression| {
+            filter_keys_for_fileset(expression)
+                .iter()
+                .map(|path| path.as_internal_file_string().to_owned())
+                .collect_vec()
+        };
+
+        assert_eq!(keys(&FilesetExpression::none()), Vec::<String>::new());
+        assert_eq!(keys(&FilesetExpression::all()), [""]);
+        assert_eq!(keys(&file("a/b")), ["a/b"]);
+        assert_eq!(keys(&glob("a", "*.rs")), ["a"]);
+        assert_eq!(
+            keys(&FilesetExpression::union_all(vec![file("a"), prefix("b")])),
+            ["a", "b"]
+        );
+        assert_eq!(
+            keys(
+                &FilesetExpression::union_all(vec![file("a"), prefix("b")]).intersection(file("c"))
+            ),
+            ["c"]
+        );
+        assert_eq!(keys(&FilesetExpression::all().difference(file("c"))), [""]);
+    }
+
+    #[test]
+    fn test_changed_path_table_round_trip() {
+        let filter1 = ChangedPathFilter::from_paths([repo_path("a")]);
+        let filter3 = ChangedPathFilter::from_paths([repo_path("b"), repo_path("c")]);
+        let buf = ChangedPathTable::serialize([Some(&filter1), None, Some(&filter3)].into_iter());
+        let table = ChangedPathTable::from_bytes(buf.clone()).unwrap();
+        assert_eq!(table.num_local_commits(), 3);
+        assert_eq!(table.filter(0), Some(filter1.borrowed()));
+        assert_eq!(table.filter(1), None);
+        assert_eq!(table.filter(2), Some(filter3.borrowed()));
+
+        assert!(ChangedPathTable::from_bytes(buf[..buf.len() - 1].to_vec()).is_err());
+        assert!(ChangedPathTable::from_bytes(vec![]).is_err());
+    }
+}
@@ -22,6 +22,7 @@ use std::sync::{Arc, Mutex};
 use itertools::Itertools;
 use ref_cast::{ref_cast_custom, RefCastCustom};
 
+use super::changed_path::ChangedPathFilter;
 use super::entry::{
     IndexEntry, IndexPosition, IndexPositionByGeneration, LocalPosition, SmallIndexPositionsVec,
     SmallLocalPositionsVec,
@@ -75,6 +76,10 @@ pub(super) trait IndexSegment: Send + Sync {
     fn num_parents(&self, local_pos: LocalPosition) -> u32;
 
     fn parent_positions(&self, local_pos: LocalPosition) -> SmallIndexPositionsVec;
+
+    /// Returns the filter of the paths changed by the commit, or `None` if the
+    /// commit isn't indexed by changed paths.
+    fn changed_path_filter(&self, local_pos: LocalPosition) -> Option<ChangedPathFilter<'_>>;
 }
 
 pub(super) type DynIndexSegment = dyn IndexSegment;
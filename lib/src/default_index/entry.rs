@@ -19,6 +19,7 @@ use std::hash::{Hash, Hasher};
 
 use smallvec::SmallVec;
 
+use super::changed_path::ChangedPathFilter;
 use super::composite::{CompositeIndex, DynIndexSegment};
 use crate::backend::{ChangeId, CommitId};
 use crate::object_id::ObjectId;
@@ -110,6 +111,10 @@ impl<'a> IndexEntry<'a> {
         self.source.parent_positions(self.local_pos)
     }
 
+    pub(super) fn changed_path_filter(&self) -> Option<ChangedPathFilter<'a>> {
+        self.source.changed_path_filter(self.local_pos)
+    }
+
     pub fn parents(&self) -> impl ExactSizeIterator<Item = IndexEntry<'a>> {
         let composite = CompositeIndex::new(self.source);
         self.parent_positions()
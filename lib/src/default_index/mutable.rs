@@ -29,6 +29,7 @@ use itertools::Itertools;
 use smallvec::{smallvec, SmallVec};
 use tempfile::NamedTempFile;
 
+use super::changed_path::{ChangedPathFilter, ChangedPathTable};
 use super::composite::{
     AsCompositeIndex, ChangeIdIndexImpl, CompositeIndex, DynIndexSegment, IndexSegment,
 };
@@ -60,6 +61,9 @@ pub(super) struct MutableIndexSegment {
     graph: Vec<MutableGraphEntry>,
     commit_lookup: BTreeMap<CommitId, LocalPosition>,
     change_lookup: BTreeMap<ChangeId, SmallLocalPositionsVec>,
+    /// Filters of the changed paths, indexed by local position. `None` if the
+    /// changed paths aren't indexed.
+    changed_path_filters: Option<Vec<Option<ChangedPathFilter<'static>>>>,
 }
 
 impl MutableIndexSegment {
@@ -72,6 +76,7 @@ impl MutableIndexSegment {
             graph: vec![],
             commit_lookup: BTreeMap::new(),
             change_lookup: BTreeMap::new(),
+            changed_path_filters: None,
         }
     }
 
@@ -79,6 +84,8 @@ impl MutableIndexSegment {
         let num_parent_commits = parent_file.as_composite().num_commits();
         let commit_id_length = parent_file.commit_id_length();
         let change_id_length = parent_file.change_id_length();
+        // Keep indexing changed paths once enabled.
+        let changed_path_filters = parent_file.has_changed_paths().then(Vec::new);
         Self {
             parent_file: Some(parent_file),
             num_parent_commits,
@@ -87,6 +94,14 @@ impl MutableIndexSegment {
             graph: vec![],
             commit_lookup: BTreeMap::new(),
             change_lookup: BTreeMap::new(),
+            changed_path_filters,
+        }
+    }
+
+    /// Starts indexing the paths changed by the commits to be added.
+    pub(super) fn enable_changed_paths(&mut self) {
+        if self.changed_path_filters.is_none() {
+            self.changed_path_filters = Some(vec![None; self.graph.len()]);
         }
     }
 
@@ -95,18 +110,42 @@ impl MutableIndexSegment {
     }
 
     pub(super) fn add_commit(&mut self, commit: &Commit) {
-        self.add_commit_data(
+        if self.as_composite().has_id(commit.id()) {
+            return;
+        }
+        let changed_path_filter = if self.changed_path_filters.is_some() {
+            ChangedPathFilter::from_commit(commit, self.as_composite())
+                .inspect_err(|err| {
+                    tracing::warn!(?err, "failed to index changed paths");
+                })
+                .ok()
+        } else {
+            None
+        };
+        self.add_commit_data_with_changed_paths(
             commit.id().clone(),
             commit.change_id().clone(),
             commit.parent_ids(),
+            changed_path_filter,
         );
     }
 
+    #[cfg(test)]
     pub(super) fn add_commit_data(
         &mut self,
         commit_id: CommitId,
         change_id: ChangeId,
         parent_ids: &[CommitId],
+    ) {
+        self.add_commit_data_with_changed_paths(commit_id, change_id, parent_ids, None);
+    }
+
+    fn add_commit_data_with_changed_paths(
+        &mut self,
+        commit_id: CommitId,
+        change_id: ChangeId,
+        parent_ids: &[CommitId],
+        changed_path_filter: Option<ChangedPathFilter<'static>>,
     ) {
         if self.as_composite().has_id(&commit_id) {
             return;
@@ -137,6 +176,9 @@ impl MutableIndexSegment {
             .and_modify(|positions| positions.push(local_pos))
             .or_insert(smallvec![local_pos]);
         self.graph.push(entry);
+        if let Some(filters) = &mut self.changed_path_filters {
+            filters.push(changed_path_filter);
+        }
     }
 
     pub(super) fn add_commits_from(&mut self, other_segment: &DynIndexSegment) {
@@ -144,7 +186,15 @@ impl MutableIndexSegment {
         for pos in other_segment.num_parent_commits()..other.num_commits() {
             let entry = other.entry_by_pos(IndexPosition(pos));
             let parent_ids = entry.parents().map(|entry| entry.commit_id()).collect_vec();
-            self.add_commit_data(entry.commit_id(), entry.change_id(), &parent_ids);
+            let changed_path_filter = entry
+                .changed_path_filter()
+                .map(ChangedPathFilter::into_owned);
+            self.add_commit_data_with_changed_paths(
+                entry.commit_id(),
+                entry.change_id(),
+                &parent_ids,
+                changed_path_filter,
+            );
         }
     }
 
@@ -323,6 +373,9 @@ impl MutableIndexSegment {
         } else {
             MutableIndexSegment::full(self.commit_id_length, self.change_id_length)
         };
+        if self.changed_path_filters.is_some() {
+            squashed.enable_changed_paths();
+        }
         for parent_file in files_to_squash.iter().rev() {
             squashed.add_commits_from(parent_file.as_ref());
         }
@@ -350,10 +403,27 @@ impl MutableIndexSegment {
         file.write_all(&buf)?;
         persist_content_addressed_temp_file(temp_file, index_file_path)?;
 
+        let changed_paths = if let Some(filters) = &self.changed_path_filters {
+            let table_buf = ChangedPathTable::serialize(filters.iter().map(Option::as_ref));
+            let mut temp_file = NamedTempFile::new_in(dir)?;
+            temp_file.as_file_mut().write_all(&table_buf)?;
+            persist_content_addressed_temp_file(
+                temp_file,
+                ChangedPathTable::file_path(dir, &index_file_id_hex),
+            )?;
+            Some(
+                ChangedPathTable::from_bytes(table_buf)
+                    .expect("in-memory changed-path table should be valid"),
+            )
+        } else {
+            None
+        };
+
         Ok(ReadonlyIndexSegment::load_with_parent_file(
             &mut &buf[local_entries_offset..],
             index_file_id_hex,
             self.parent_file,
+            changed_paths,
             self.commit_id_length,
             self.change_id_length,
         )
@@ -435,6 +505,13 @@ impl IndexSegment for MutableIndexSegment {
     fn parent_positions(&self, local_pos: LocalPosition) -> SmallIndexPositionsVec {
         self.graph[local_pos.0 as usize].parent_positions.clone()
     }
+
+    fn changed_path_filter(&self, local_pos: LocalPosition) -> Option<ChangedPathFilter<'_>> {
+        let filters = self.changed_path_filters.as_ref()?;
+        filters[local_pos.0 as usize]
+            .as_ref()
+            .map(ChangedPathFilter::borrowed)
+    }
 }
 
 /// In-memory mutable records for the on-disk commit index backend.
@@ -461,6 +538,10 @@ impl DefaultMutableIndex {
         self.0.add_commit_data(commit_id, change_id, parent_ids);
     }
 
+    pub(super) fn enable_changed_paths(&mut self) {
+        self.0.enable_changed_paths();
+    }
+
     pub(super) fn squash_and_save_in(self, dir: &Path) -> io::Result<Arc<ReadonlyIndexSegment>> {
         self.0.maybe_squash_with_ancestors().save_in(dir)
     }
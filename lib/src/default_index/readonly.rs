@@ -26,6 +26,7 @@ use std::sync::Arc;
 use smallvec::smallvec;
 use thiserror::Error;
 
+use super::changed_path::{ChangedPathFilter, ChangedPathTable};
 use super::composite::{AsCompositeIndex, ChangeIdIndexImpl, CompositeIndex, IndexSegment};
 use super::entry::{IndexPosition, LocalPosition, SmallIndexPositionsVec, SmallLocalPositionsVec};
 use super::mutable::DefaultMutableIndex;
@@ -198,6 +199,9 @@ impl CommitGraphEntry<'_> {
 ///
 /// Note that u32 fields are 4-byte aligned so long as the parent file name
 /// (which is hexadecimal hash) and commit/change ids aren't of exotic length.
+///
+/// If the changed paths are indexed, their filters are stored in a separate
+/// `ChangedPathTable` file.
 // TODO: replace the table by a trie so we don't have to repeat the full commit
 //       ids
 // TODO: add a fanout table like git's commit graph has?
@@ -218,6 +222,7 @@ pub(super) struct ReadonlyIndexSegment {
     parent_overflow_base: usize,
     change_overflow_base: usize,
     data: Vec<u8>,
+    changed_paths: Option<ChangedPathTable>,
 }
 
 impl Debug for ReadonlyIndexSegment {
@@ -281,10 +286,17 @@ impl ReadonlyIndexSegment {
         } else {
             None
         };
+        // The changed paths are an optional optimization, so the segment can
+        // be used without them.
+        let changed_paths = ChangedPathTable::load(dir, &name).unwrap_or_else(|err| {
+            tracing::warn!(?err, %name, "failed to load changed-path table");
+            None
+        });
         Self::load_with_parent_file(
             file,
             name,
             maybe_parent_file,
+            changed_paths,
             commit_id_length,
             change_id_length,
         )
@@ -296,6 +308,7 @@ impl ReadonlyIndexSegment {
         file: &mut dyn Read,
         name: String,
         parent_file: Option<Arc<ReadonlyIndexSegment>>,
+        changed_paths: Option<ChangedPathTable>,
         commit_id_length: usize,
         change_id_length: usize,
     ) -> Result<Arc<ReadonlyIndexSegment>, ReadonlyIndexLoadError> {
@@ -337,6 +350,8 @@ impl ReadonlyIndexSegment {
                 "unexpected data length",
             ));
         }
+        let changed_paths =
+            changed_paths.filter(|table| table.num_local_commits() == num_local_commits);
 
         Ok(Arc::new(ReadonlyIndexSegment {
             parent_file,
@@ -353,6 +368,7 @@ impl ReadonlyIndexSegment {
             parent_overflow_base,
             change_overflow_base,
             data,
+            changed_paths,
         }))
     }
 
@@ -372,6 +388,10 @@ impl ReadonlyIndexSegment {
         self.change_id_length
     }
 
+    pub(super) fn has_changed_paths(&self) -> bool {
+        self.changed_paths.is_some()
+    }
+
     fn graph_entry(&self, local_pos: LocalPosition) -> CommitGraphEntry {
         let table = &self.data[..self.commit_lookup_base];
         let entry_size = CommitGraphEntry::size(self.commit_id_length);
@@ -567,6 +587,10 @@ impl IndexSegment for ReadonlyIndexSegment {
             self.overflow_parents(overflow_pos, num_parents)
         }
     }
+
+    fn changed_path_filter(&self, local_pos: LocalPosition) -> Option<ChangedPathFilter<'_>> {
+        self.changed_paths.as_ref()?.filter(local_pos.0)
+    }
 }
 
 /// Commit index backend which stores data on local disk.
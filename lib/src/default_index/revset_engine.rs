@@ -17,25 +17,32 @@
 use std::cell::RefCell;
 use std::cmp::{Ordering, Reverse};
 use std::collections::{BTreeSet, BinaryHeap, HashSet};
+use std::io::Read as _;
 use std::ops::Range;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::{fmt, iter};
 
 use itertools::Itertools;
+use pollster::FutureExt as _;
 
+use super::changed_path::filter_keys_for_fileset;
 use super::rev_walk::{EagerRevWalk, PeekableRevWalk, RevWalk, RevWalkBuilder};
 use super::revset_graph_iterator::RevsetGraphWalk;
-use crate::backend::{ChangeId, CommitId, MillisSinceEpoch};
+use crate::backend::{BackendError, BackendResult, ChangeId, CommitId, MillisSinceEpoch};
+use crate::conflicts::{materialize_tree_value, ConflictMarkerStyle, MaterializedTreeValue};
 use crate::default_index::{AsCompositeIndex, CompositeIndex, IndexEntry, IndexPosition};
 use crate::graph::GraphEdge;
 use crate::matchers::{Matcher, Visit};
-use crate::repo_path::RepoPath;
+use crate::merge::MergedTreeValue;
+use crate::repo_path::{RepoPath, RepoPathBuf};
 use crate::revset::{
     ResolvedExpression, ResolvedPredicateExpression, Revset, RevsetEvaluationError,
     RevsetFilterPredicate, GENERATION_RANGE_FULL,
 };
 use crate::store::Store;
+use crate::str_util::StringPattern;
+use crate::trailer::parse_description_trailers;
 use crate::{rewrite, union_find};
 
 type BoxedPredicateFn<'a> = Box<dyn FnMut(&CompositeIndex, IndexPosition) -> bool + 'a>;
@@ -1049,6 +1056,19 @@ fn build_predicate_fn(
                 pattern.matches(commit.description())
             })
         }
+        RevsetFilterPredicate::Trailer { key, value } => {
+            let key = key.clone();
+            let value = value.clone();
+            box_pure_predicate_fn(move |index, pos| {
+                let entry = index.entry_by_pos(pos);
+                let commit = store.get_commit(&entry.commit_id()).unwrap();
+                parse_description_trailers(commit.description())
+                    .iter()
+                    .any(|trailer| {
+                        trailer.key.eq_ignore_ascii_case(&key) && value.matches(&trailer.value)
+                    })
+            })
+        }
         RevsetFilterPredicate::Author(pattern) => {
             let pattern = pattern.clone();
             // TODO: Make these functions that take a needle to search for accept some
@@ -1069,11 +1089,48 @@ fn build_predicate_fn(
                     || pattern.matches(&commit.committer().email)
             })
         }
+        RevsetFilterPredicate::AuthorDate(expression) => {
+            let expression = *expression;
+            box_pure_predicate_fn(move |index, pos| {
+                let entry = index.entry_by_pos(pos);
+                let commit = store.get_commit(&entry.commit_id()).unwrap();
+                let author_date = &commit.author().timestamp;
+                expression.matches(author_date)
+            })
+        }
+        RevsetFilterPredicate::CommitterDate(expression) => {
+            let expression = *expression;
+            box_pure_predicate_fn(move |index, pos| {
+                let entry = index.entry_by_pos(pos);
+                let commit = store.get_commit(&entry.commit_id()).unwrap();
+                let committer_date = &commit.committer().timestamp;
+                expression.matches(committer_date)
+            })
+        }
         RevsetFilterPredicate::File(expr) => {
             let matcher: Rc<dyn Matcher> = expr.to_matcher().into();
+            let filter_keys = filter_keys_for_fileset(expr);
             box_pure_predicate_fn(move |index, pos| {
                 let entry = index.entry_by_pos(pos);
-                has_diff_from_parent(&store, index, &entry, matcher.as_ref())
+                may_have_changed_paths(&entry, &filter_keys)
+                    && has_diff_from_parent(&store, index, &entry, matcher.as_ref())
+            })
+        }
+        RevsetFilterPredicate::DiffContains { text, files } => {
+            let text_pattern = text.clone();
+            let files_matcher: Rc<dyn Matcher> = files.to_matcher().into();
+            let filter_keys = filter_keys_for_fileset(files);
+            box_pure_predicate_fn(move |index, pos| {
+                let entry = index.entry_by_pos(pos);
+                may_have_changed_paths(&entry, &filter_keys)
+                    && matches_diff_from_parent(
+                        &store,
+                        index,
+                        &entry,
+                        &text_pattern,
+                        files_matcher.as_ref(),
+                    )
+                    .unwrap()
             })
         }
         RevsetFilterPredicate::HasConflict => box_pure_predicate_fn(move |index, pos| {
@@ -1081,6 +1138,19 @@ fn build_predicate_fn(
             let commit = store.get_commit(&entry.commit_id()).unwrap();
             commit.has_conflict().unwrap()
         }),
+        RevsetFilterPredicate::Signed => box_pure_predicate_fn(move |index, pos| {
+            let entry = index.entry_by_pos(pos);
+            let commit = store.get_commit(&entry.commit_id()).unwrap();
+            commit.is_signed()
+        }),
+        RevsetFilterPredicate::SignatureStatus(status) => {
+            let status = *status;
+            box_pure_predicate_fn(move |index, pos| {
+                let entry = index.entry_by_pos(pos);
+                let commit = store.get_commit(&entry.commit_id()).unwrap();
+                commit.signature_status() == Some(status)
+            })
+        }
         RevsetFilterPredicate::Extension(ext) => {
             let ext = ext.clone();
             box_pure_predicate_fn(move |index, pos| {
@@ -1092,6 +1162,15 @@ fn build_predicate_fn(
     }
 }
 
+/// Returns false if the changed-path index tells that the commit didn't change
+/// any of the paths under `filter_keys`. Returns true if unknown.
+fn may_have_changed_paths(entry: &IndexEntry<'_>, filter_keys: &[RepoPathBuf]) -> bool {
+    match entry.changed_path_filter() {
+        Some(filter) => filter_keys.iter().any(|path| filter.may_contain(path)),
+        None => true,
+    }
+}
+
 fn has_diff_from_parent(
     store: &Arc<Store>,
     index: &CompositeIndex,
@@ -1114,6 +1193,69 @@ fn has_diff_from_parent(
     from_tree.diff(&to_tree, matcher).next().is_some()
 }
 
+fn matches_diff_from_parent(
+    store: &Arc<Store>,
+    index: &CompositeIndex,
+    entry: &IndexEntry<'_>,
+    text_pattern: &StringPattern,
+    files_matcher: &dyn Matcher,
+) -> BackendResult<bool> {
+    let commit = store.get_commit(&entry.commit_id())?;
+    let parents: Vec<_> = commit.parents().try_collect()?;
+    let from_tree = rewrite::merge_commit_trees_without_repo(store, &index, &parents)?;
+    let to_tree = commit.tree()?;
+    for (path, diff) in from_tree.diff(&to_tree, files_matcher) {
+        let (left_value, right_value) = diff?;
+        let left_content = to_file_content(store, &path, left_value)?;
+        let right_content = to_file_content(store, &path, right_value)?;
+        // Filter lines prior to comparison. This might produce inferior hunks
+        // due to lack of contexts, but is way faster than full diff.
+        let left_lines = match_lines(&left_content, text_pattern);
+        let right_lines = match_lines(&right_content, text_pattern);
+        if left_lines.ne(right_lines) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Iterates over the lines of `text` that match `pattern`.
+fn match_lines<'a: 'b, 'b>(
+    text: &'a [u8],
+    pattern: &'b StringPattern,
+) -> impl Iterator<Item = &'a [u8]> + 'b {
+    // The pattern is matched line by line so that it can be anchored to the
+    // beginning/end of each line.
+    text.split_inclusive(|b| *b == b'\n').filter(|line| {
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        pattern.matches(&String::from_utf8_lossy(line))
+    })
+}
+
+/// Reads the content of a file, or the materialized content of a conflict.
+/// Other kinds of values are treated as empty.
+fn to_file_content(
+    store: &Store,
+    path: &RepoPath,
+    value: MergedTreeValue,
+) -> BackendResult<Vec<u8>> {
+    let mut content = vec![];
+    match materialize_tree_value(store, path, value, ConflictMarkerStyle::default()).block_on()? {
+        MaterializedTreeValue::File { id, mut reader, .. } => {
+            reader
+                .read_to_end(&mut content)
+                .map_err(|err| BackendError::ReadFile {
+                    path: path.to_owned(),
+                    id,
+                    source: err.into(),
+                })?;
+        }
+        MaterializedTreeValue::Conflict { contents, .. } => content = contents,
+        _ => {}
+    }
+    Ok(content)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -37,6 +37,7 @@ use crate::index::{
 use crate::object_id::ObjectId;
 use crate::op_store::{OpStoreError, OperationId};
 use crate::operation::Operation;
+use crate::settings::UserSettings;
 use crate::store::Store;
 
 // BLAKE2b-512 hash length in hex string
@@ -81,6 +82,9 @@ pub enum DefaultIndexStoreError {
 #[derive(Debug)]
 pub struct DefaultIndexStore {
     dir: PathBuf,
+    /// Whether to index the paths changed by each commit when building the
+    /// index from scratch.
+    index_changed_paths: bool,
 }
 
 impl DefaultIndexStore {
@@ -88,17 +92,19 @@ impl DefaultIndexStore {
         "default"
     }
 
-    pub fn init(dir: &Path) -> Result<Self, DefaultIndexStoreInitError> {
+    pub fn init(settings: &UserSettings, dir: &Path) -> Result<Self, DefaultIndexStoreInitError> {
         let store = DefaultIndexStore {
             dir: dir.to_owned(),
+            index_changed_paths: settings.index_changed_paths(),
         };
         store.ensure_base_dirs()?;
         Ok(store)
     }
 
-    pub fn load(dir: &Path) -> DefaultIndexStore {
+    pub fn load(settings: &UserSettings, dir: &Path) -> DefaultIndexStore {
         DefaultIndexStore {
             dir: dir.to_owned(),
+            index_changed_paths: settings.index_changed_paths(),
         }
     }
 
@@ -223,6 +229,9 @@ impl DefaultIndexStore {
                 mutable_index = DefaultMutableIndex::incremental(parent_file)
             }
         }
+        if self.index_changed_paths {
+            mutable_index.enable_changed_paths();
+        }
 
         tracing::info!(
             ?maybe_parent_file,
@@ -16,11 +16,19 @@
 
 use std::path::{Path, PathBuf};
 
-use crate::submodule_store::SubmoduleStore;
+use crate::backend::CommitId;
+#[cfg(feature = "git")]
+use crate::object_id::ObjectId as _;
+use crate::repo_path::RepoPath;
+use crate::submodule_store::{SubmoduleStore, SubmoduleStoreError, SubmoduleStoreResult};
 
+/// Keeps a bare Git repository for each submodule under `repos/`, at the same
+/// relative path as the submodule has in the parent repo. Submodule checkouts
+/// in the working copy borrow objects from these repositories through Git's
+/// alternates mechanism.
 #[derive(Debug)]
 pub struct DefaultSubmoduleStore {
-    #[allow(dead_code)]
+    #[cfg_attr(not(feature = "git"), allow(dead_code))]
     path: PathBuf,
 }
 
@@ -41,10 +49,132 @@ impl DefaultSubmoduleStore {
     pub fn name() -> &'static str {
         "default"
     }
+
+    #[cfg(feature = "git")]
+    fn repo_path(&self, path: &RepoPath) -> PathBuf {
+        path.to_fs_path(&self.path.join("repos"))
+    }
 }
 
 impl SubmoduleStore for DefaultSubmoduleStore {
     fn name(&self) -> &str {
         Self::name()
     }
+
+    #[cfg(feature = "git")]
+    fn fetch(&self, path: &RepoPath, url: &str) -> SubmoduleStoreResult<()> {
+        let repo_path = self.repo_path(path);
+        let repo = if repo_path.exists() {
+            git2::Repository::open_bare(&repo_path)
+        } else {
+            std::fs::create_dir_all(&repo_path).map_err(to_other_err)?;
+            git2::Repository::init_bare(&repo_path)
+        }
+        .map_err(to_other_err)?;
+        let mut remote = repo.remote_anonymous(url).map_err(to_other_err)?;
+        remote
+            .fetch(
+                &["+refs/heads/*:refs/heads/*", "+refs/tags/*:refs/tags/*"],
+                None,
+                None,
+            )
+            .map_err(to_other_err)?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "git"))]
+    fn fetch(&self, _path: &RepoPath, _url: &str) -> SubmoduleStoreResult<()> {
+        Err(SubmoduleStoreError::Unsupported)
+    }
+
+    #[cfg(feature = "git")]
+    fn check_out(
+        &self,
+        path: &RepoPath,
+        disk_path: &Path,
+        id: &CommitId,
+    ) -> SubmoduleStoreResult<()> {
+        let repo_path = self.repo_path(path);
+        if !repo_path.exists() {
+            return Err(SubmoduleStoreError::NotFound {
+                path: path.to_owned(),
+            });
+        }
+        let oid = git2::Oid::from_bytes(id.as_bytes()).map_err(to_other_err)?;
+        let store_repo = git2::Repository::open_bare(&repo_path).map_err(to_other_err)?;
+        if store_repo.find_commit(oid).is_err() {
+            return Err(SubmoduleStoreError::CommitNotFound {
+                path: path.to_owned(),
+                id: id.clone(),
+            });
+        }
+        let objects_path = store_repo.path().join("objects");
+        let mut repo = match git2::Repository::open(disk_path) {
+            Ok(repo) => repo,
+            Err(_) => git2::Repository::init(disk_path).map_err(to_other_err)?,
+        };
+        if repo.find_commit(oid).is_err() {
+            // Let the checkout read objects from the store instead of copying
+            // them, like `git clone --reference` does.
+            let alternates_path = repo.path().join("objects").join("info").join("alternates");
+            let mut alternates = std::fs::read_to_string(&alternates_path).unwrap_or_default();
+            let objects_line = objects_path.display().to_string();
+            if !alternates.lines().any(|line| line == objects_line) {
+                alternates.push_str(&format!("{objects_line}\n"));
+                std::fs::create_dir_all(alternates_path.parent().unwrap()).map_err(to_other_err)?;
+                std::fs::write(&alternates_path, alternates).map_err(to_other_err)?;
+                repo = git2::Repository::open(disk_path).map_err(to_other_err)?;
+            }
+        }
+        let commit = repo.find_commit(oid).map_err(to_other_err)?;
+        let mut checkout_builder = git2::build::CheckoutBuilder::new();
+        checkout_builder.safe();
+        repo.checkout_tree(commit.as_object(), Some(&mut checkout_builder))
+            .map_err(to_other_err)?;
+        repo.set_head_detached(oid).map_err(to_other_err)?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "git"))]
+    fn check_out(
+        &self,
+        path: &RepoPath,
+        _disk_path: &Path,
+        _id: &CommitId,
+    ) -> SubmoduleStoreResult<()> {
+        Err(SubmoduleStoreError::NotFound {
+            path: path.to_owned(),
+        })
+    }
+
+    #[cfg(feature = "git")]
+    fn get_checked_out_commit(
+        &self,
+        _path: &RepoPath,
+        disk_path: &Path,
+    ) -> SubmoduleStoreResult<Option<CommitId>> {
+        let Ok(repo) = git2::Repository::open(disk_path) else {
+            return Ok(None);
+        };
+        let id = repo
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .map(|oid| CommitId::from_bytes(oid.as_bytes()));
+        Ok(id)
+    }
+
+    #[cfg(not(feature = "git"))]
+    fn get_checked_out_commit(
+        &self,
+        _path: &RepoPath,
+        _disk_path: &Path,
+    ) -> SubmoduleStoreResult<Option<CommitId>> {
+        Ok(None)
+    }
+}
+
+#[cfg(feature = "git")]
+fn to_other_err(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> SubmoduleStoreError {
+    SubmoduleStoreError::Other(err.into())
 }
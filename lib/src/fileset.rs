@@ -451,6 +451,16 @@ fn resolve_expression(
     }
 }
 
+/// Parses text into `FilesetExpression` without bare string fallback.
+pub fn parse(
+    text: &str,
+    path_converter: &RepoPathUiConverter,
+) -> FilesetParseResult<FilesetExpression> {
+    let node = fileset_parser::parse_program(text)?;
+    // TODO: add basic tree substitution pass to eliminate redundant expressions
+    resolve_expression(path_converter, &node)
+}
+
 /// Parses text into `FilesetExpression` with bare string fallback.
 ///
 /// If the text can't be parsed as a fileset expression, and if it doesn't
@@ -541,6 +551,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_without_bare_string() {
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: PathBuf::from("/ws/cur"),
+            base: PathBuf::from("/ws"),
+        };
+        assert_eq!(
+            parse("foo", &path_converter).unwrap(),
+            FilesetExpression::prefix_path(repo_path_buf("cur/foo"))
+        );
+        assert_eq!(
+            parse(r#""foo bar""#, &path_converter).unwrap(),
+            FilesetExpression::prefix_path(repo_path_buf("cur/foo bar"))
+        );
+        // Bare strings which aren't valid expressions have to be quoted
+        assert!(parse("foo bar", &path_converter).is_err());
+        assert_eq!(
+            parse_maybe_bare("foo bar", &path_converter).unwrap(),
+            FilesetExpression::prefix_path(repo_path_buf("cur/foo bar"))
+        );
+    }
+
     #[test]
     fn test_parse_glob_pattern() {
         let path_converter = RepoPathUiConverter::Fs {
@@ -39,6 +39,10 @@ pub enum FsmonitorSettings {
     /// The Watchman filesystem monitor (<https://facebook.github.io/watchman/>).
     Watchman(WatchmanConfig),
 
+    /// The builtin inotify filesystem monitor. Requires `jj debug inotify
+    /// daemon` to be running for the working copy.
+    Inotify,
+
     /// Only used in tests.
     Test {
         /// The set of changed files to pretend that the filesystem monitor is
@@ -65,6 +69,7 @@ impl FsmonitorSettings {
                         .optional()?
                         .unwrap_or_default(),
                 })),
+                "inotify" => Ok(Self::Inotify),
                 "test" => Err(ConfigError::Message(
                     "cannot use test fsmonitor in real repository".to_string(),
                 )),
@@ -79,6 +84,10 @@ impl FsmonitorSettings {
     }
 }
 
+/// Filesystem monitor using inotify, without depending on an external tool.
+#[cfg(all(feature = "inotify", target_os = "linux"))]
+pub mod inotify;
+
 /// Filesystem monitor integration using Watchman
 /// (<https://facebook.github.io/watchman/>). Requires `watchman` to already be
 /// installed on the system.
@@ -0,0 +1,931 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A long-lived daemon watches the working copy and records the paths of
+//! changed files in an in-memory journal. Each change bumps the sequence
+//! number of the journal, and a query returns the paths changed since the
+//! clock returned by a previous query. If the daemon was restarted or events
+//! were dropped since then, the query returns no paths and the caller must
+//! crawl the whole working copy.
+//!
+//! Each directory is watched separately, except for `.git` and `.jj`
+//! directories and directories ignored by `.gitignore` files. Ignored
+//! directories are reported as changed by every query, since they may contain
+//! tracked files. A newly watched directory is reported as changed, since files
+//! may have been created in it before it was watched.
+//!
+//! Before answering a query, the daemon creates a cookie file in the `.jj`
+//! directory and waits for its event, so that all the changes made before the
+//! query are in the journal.
+//!
+//! The daemon listens on a Unix socket. A query sends the previous clock (or
+//! an empty line) and receives NUL-terminated records: the new clock, then
+//! either `fresh` or `changed` followed by the changed paths.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::io::{self, BufRead as _, BufReader, Read as _, Write as _};
+use std::os::unix::ffi::{OsStrExt as _, OsStringExt as _};
+use std::os::unix::fs::{DirBuilderExt as _, MetadataExt as _};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::time::{Duration, SystemTime};
+use std::{fs, process, thread};
+
+use blake2::{Blake2b512, Digest};
+use notify::event::ModifyKind;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+use thiserror::Error;
+use tracing::{info, instrument, warn};
+
+use crate::gitignore::GitIgnoreFile;
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+const COOKIE_TIMEOUT: Duration = Duration::from_secs(1);
+const COOKIE_PREFIX: &str = "inotify-cookie-";
+/// The number of recent queries whose clocks are kept in the journal.
+const RECENT_QUERIES: usize = 16;
+/// The maximum length of the path of a Unix socket, excluding the trailing NUL.
+const MAX_SOCKET_PATH_LEN: usize = 107;
+
+/// Returns the path of the socket the daemon for the working copy state
+/// directory `state_path` listens on.
+///
+/// If the path in the state directory is too long for a Unix socket, a path in
+/// the runtime directory is derived from a hash of `state_path` instead. Without
+/// `XDG_RUNTIME_DIR`, a per-user directory in the temporary directory is used,
+/// which the daemon creates private to the current user.
+pub fn socket_path(state_path: &Path) -> PathBuf {
+    let path = state_path.join("inotify.sock");
+    if path.as_os_str().len() <= MAX_SOCKET_PATH_LEN {
+        return path;
+    }
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            let uid = rustix::process::geteuid().as_raw();
+            std::env::temp_dir().join(format!("jj-inotify-{uid}"))
+        });
+    let hash = Blake2b512::digest(state_path.as_os_str().as_bytes());
+    runtime_dir.join(format!("jj-inotify-{}.sock", hex::encode(&hash[..16])))
+}
+
+/// Represents an instance in time from the perspective of the daemon.
+///
+/// The clock identifies the daemon instance, so clocks from a previous run of
+/// the daemon aren't mistaken for clocks of the current one.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Clock {
+    instance_id: String,
+    sequence: u64,
+}
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.instance_id, self.sequence)
+    }
+}
+
+impl FromStr for Clock {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (instance_id, sequence) = s.rsplit_once(':').ok_or(Error::InvalidClock)?;
+        let sequence = sequence.parse().map_err(|_| Error::InvalidClock)?;
+        Ok(Clock {
+            instance_id: instance_id.to_owned(),
+            sequence,
+        })
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Could not connect to the inotify daemon")]
+    ConnectError(#[source] io::Error),
+
+    #[error("Failed to query the inotify daemon")]
+    QueryError(#[source] io::Error),
+
+    #[error("Invalid response from the inotify daemon")]
+    InvalidResponse,
+
+    #[error("Invalid inotify clock")]
+    InvalidClock,
+
+    #[error("The inotify daemon is already running for this working copy")]
+    AlreadyRunning,
+
+    #[error("Could not canonicalize working copy root path")]
+    CanonicalizeRootError(#[source] io::Error),
+
+    #[error("Failed to watch the working copy")]
+    WatchError(#[source] notify::Error),
+
+    #[error("Failed to listen on {}", path.display())]
+    ListenError {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Failed to create socket directory {}", path.display())]
+    SocketDirError {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("{} is not owned by the current user or is writable by others", path.display())]
+    InsecureSocketPath { path: PathBuf },
+}
+
+/// Checks that `path` is owned by the current user, and that none of the
+/// `forbidden_mode` bits are set.
+///
+/// Returns `Ok(false)` if `path` doesn't exist.
+fn check_owned_path(path: &Path, forbidden_mode: u32) -> Result<bool, Error> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(Error::ConnectError(err)),
+    };
+    if metadata.uid() != rustix::process::geteuid().as_raw()
+        || metadata.mode() & forbidden_mode != 0
+    {
+        return Err(Error::InsecureSocketPath {
+            path: path.to_owned(),
+        });
+    }
+    Ok(true)
+}
+
+/// Checks that the socket at `socket_path` can't have been created or replaced
+/// by another user: its directory must be owned by the current user and not
+/// writable by others, and the socket itself must be owned by the current user.
+///
+/// Returns `Ok(false)` if the socket doesn't exist.
+fn check_socket(socket_path: &Path) -> Result<bool, Error> {
+    let dir = socket_path.parent().unwrap_or(Path::new("."));
+    if !check_owned_path(dir, 0o022)? {
+        return Ok(false);
+    }
+    check_owned_path(socket_path, 0)
+}
+
+/// Paths changed in the working copy, keyed by the sequence number of their
+/// last change.
+#[derive(Debug)]
+pub struct Journal {
+    instance_id: String,
+    sequence: u64,
+    changed_paths: HashMap<PathBuf, u64>,
+    /// Directories which aren't watched, and so are always reported as
+    /// changed.
+    unwatched_dirs: BTreeSet<PathBuf>,
+    /// Changes up to this sequence number have been forgotten, either because
+    /// events were dropped or because no recent query needs them.
+    pruned_sequence: u64,
+    /// The sequence numbers of the clocks of the most recent queries.
+    recent_query_sequences: VecDeque<u64>,
+    /// The number of the last cookie file seen.
+    last_cookie: u64,
+}
+
+impl Journal {
+    /// Creates an empty journal for the daemon instance `instance_id`.
+    pub fn new(instance_id: impl Into<String>) -> Self {
+        Journal {
+            instance_id: instance_id.into(),
+            sequence: 0,
+            changed_paths: HashMap::new(),
+            unwatched_dirs: BTreeSet::new(),
+            pruned_sequence: 0,
+            recent_query_sequences: VecDeque::new(),
+            last_cookie: 0,
+        }
+    }
+
+    /// The current clock.
+    pub fn clock(&self) -> Clock {
+        Clock {
+            instance_id: self.instance_id.clone(),
+            sequence: self.sequence,
+        }
+    }
+
+    /// Records changes to `paths`, which are relative to the working copy
+    /// root.
+    pub fn record_changes(&mut self, paths: impl IntoIterator<Item = PathBuf>) {
+        self.sequence += 1;
+        for path in paths {
+            self.changed_paths.insert(path, self.sequence);
+        }
+    }
+
+    /// Records that some events were dropped, so any earlier clock requires a
+    /// full crawl of the working copy.
+    pub fn record_lost_events(&mut self) {
+        self.sequence += 1;
+        self.pruned_sequence = self.sequence;
+        // Paths changed before this point are no longer useful.
+        self.changed_paths.clear();
+    }
+
+    /// Records a query for the changes since `previous_clock`, and forgets the
+    /// changes which none of the recent queries need.
+    pub fn record_query(&mut self, previous_clock: &Clock) {
+        if previous_clock.instance_id != self.instance_id
+            || previous_clock.sequence < self.pruned_sequence
+            || previous_clock.sequence > self.sequence
+        {
+            return;
+        }
+        self.recent_query_sequences
+            .push_back(previous_clock.sequence);
+        if self.recent_query_sequences.len() > RECENT_QUERIES {
+            self.recent_query_sequences.pop_front();
+        }
+        let oldest_sequence = *self.recent_query_sequences.iter().min().unwrap();
+        self.changed_paths
+            .retain(|_, sequence| *sequence > oldest_sequence);
+        self.pruned_sequence = oldest_sequence;
+    }
+
+    /// Returns the paths changed since `previous_clock`, or `None` if they
+    /// aren't known.
+    pub fn changed_since(&self, previous_clock: Option<&Clock>) -> Option<Vec<PathBuf>> {
+        let previous_clock = previous_clock?;
+        if previous_clock.instance_id != self.instance_id
+            || previous_clock.sequence < self.pruned_sequence
+            || previous_clock.sequence > self.sequence
+        {
+            return None;
+        }
+        let mut paths: Vec<_> = self
+            .changed_paths
+            .iter()
+            .filter(|(_, &sequence)| sequence > previous_clock.sequence)
+            .map(|(path, _)| path.clone())
+            .chain(self.unwatched_dirs.iter().cloned())
+            .collect();
+        paths.sort();
+        paths.dedup();
+        Some(paths)
+    }
+}
+
+/// The directories of the working copy being watched, each with the
+/// `.gitignore` rules that apply to its entries.
+struct WatchedDirs {
+    root: PathBuf,
+    watcher: RecommendedWatcher,
+    /// Paths relative to the root. The root itself is the empty path.
+    dirs: BTreeMap<PathBuf, Arc<GitIgnoreFile>>,
+}
+
+impl WatchedDirs {
+    /// Handles an event from the watcher, and notifies `cookie_seen` if it was
+    /// for a cookie file.
+    fn handle_event(
+        &mut self,
+        journal: &mut Journal,
+        cookie_seen: &Condvar,
+        result: notify::Result<notify::Event>,
+    ) {
+        let cookie_dir = self.root.join(".jj");
+        if let Ok(event) = &result {
+            if let Some(cookie) = event
+                .paths
+                .iter()
+                .find_map(|path| parse_cookie_path(&cookie_dir, path))
+            {
+                journal.last_cookie = journal.last_cookie.max(cookie);
+                cookie_seen.notify_all();
+                return;
+            }
+        }
+        match result {
+            Ok(notify::Event { kind, paths, .. })
+                if matches!(kind, EventKind::Access(_)) || paths.is_empty() => {}
+            Ok(event) if event.need_rescan() => {
+                journal.record_lost_events();
+                self.rescan(journal, &mut vec![]);
+            }
+            Ok(event) => {
+                let mut paths: Vec<_> = event
+                    .paths
+                    .iter()
+                    .filter_map(|path| relative_path(&self.root, path))
+                    .collect();
+                if matches!(
+                    event.kind,
+                    EventKind::Create(_)
+                        | EventKind::Remove(_)
+                        | EventKind::Modify(ModifyKind::Name(_))
+                ) {
+                    for path in paths.clone() {
+                        self.update_dir(journal, &path, &mut paths);
+                    }
+                }
+                if paths
+                    .iter()
+                    .any(|path| path.file_name().is_some_and(|name| name == ".gitignore"))
+                {
+                    self.rescan(journal, &mut paths);
+                }
+                journal.record_changes(paths);
+            }
+            Err(err) => {
+                warn!(?err, "Filesystem monitor error");
+                journal.record_lost_events();
+            }
+        }
+    }
+
+    /// Watches `path` if it's a new directory, or stops watching it if it's no
+    /// longer a directory. Newly watched directories are added to `changed`.
+    fn update_dir(&mut self, journal: &mut Journal, path: &Path, changed: &mut Vec<PathBuf>) {
+        let Some(parent) = path.parent() else {
+            return;
+        };
+        let is_dir = self
+            .root
+            .join(path)
+            .symlink_metadata()
+            .is_ok_and(|metadata| metadata.is_dir());
+        if !is_dir {
+            self.forget_dir(journal, path);
+            return;
+        }
+        if self.dirs.contains_key(path) || journal.unwatched_dirs.contains(path) {
+            return;
+        }
+        let Some(git_ignore) = self.dirs.get(parent).cloned() else {
+            return;
+        };
+        let mut dirs = BTreeMap::new();
+        let mut unwatched_dirs = BTreeSet::new();
+        scan_child_dir(
+            &self.root,
+            path,
+            &git_ignore,
+            &mut dirs,
+            &mut unwatched_dirs,
+        );
+        self.watch_dirs(journal, dirs, unwatched_dirs, changed);
+    }
+
+    /// Stops watching `path` and the directories in it.
+    fn forget_dir(&mut self, journal: &mut Journal, path: &Path) {
+        let forgotten = self
+            .dirs
+            .range(path.to_owned()..)
+            .map(|(dir, _)| dir)
+            .take_while(|dir| dir.starts_with(path))
+            .cloned()
+            .collect::<Vec<_>>();
+        for dir in forgotten {
+            // The watch is already gone if the directory was removed.
+            self.watcher.unwatch(&self.root.join(&dir)).ok();
+            self.dirs.remove(&dir);
+        }
+        journal.unwatched_dirs.retain(|dir| !dir.starts_with(path));
+    }
+
+    /// Scans the whole working copy, and updates the watches to match it.
+    /// Newly watched directories are added to `changed`.
+    fn rescan(&mut self, journal: &mut Journal, changed: &mut Vec<PathBuf>) {
+        let mut dirs = BTreeMap::new();
+        let mut unwatched_dirs = BTreeSet::new();
+        scan_dir(
+            &self.root,
+            Path::new(""),
+            &GitIgnoreFile::empty(),
+            &mut dirs,
+            &mut unwatched_dirs,
+        );
+        let removed = self
+            .dirs
+            .keys()
+            .filter(|dir| !dirs.contains_key(*dir))
+            .cloned()
+            .collect::<Vec<_>>();
+        for dir in removed {
+            self.watcher.unwatch(&self.root.join(&dir)).ok();
+            self.dirs.remove(&dir);
+        }
+        journal.unwatched_dirs.clear();
+        self.watch_dirs(journal, dirs, unwatched_dirs, changed);
+    }
+
+    fn watch_dirs(
+        &mut self,
+        journal: &mut Journal,
+        dirs: BTreeMap<PathBuf, Arc<GitIgnoreFile>>,
+        unwatched_dirs: BTreeSet<PathBuf>,
+        changed: &mut Vec<PathBuf>,
+    ) {
+        for (dir, git_ignore) in dirs {
+            if let Some(old_git_ignore) = self.dirs.get_mut(&dir) {
+                *old_git_ignore = git_ignore;
+                continue;
+            }
+            match self
+                .watcher
+                .watch(&self.root.join(&dir), RecursiveMode::NonRecursive)
+            {
+                Ok(()) => {
+                    changed.push(dir.clone());
+                    self.dirs.insert(dir, git_ignore);
+                }
+                Err(err) => {
+                    warn!(?err, ?dir, "Failed to watch directory");
+                    journal.unwatched_dirs.insert(dir);
+                }
+            }
+        }
+        journal.unwatched_dirs.extend(unwatched_dirs);
+    }
+}
+
+/// Collects `dir` and the directories in it which should be watched into
+/// `dirs`, and the ignored directories into `unwatched_dirs`. `git_ignore`
+/// applies to the entries of the parent directory of `dir`.
+fn scan_dir(
+    root: &Path,
+    dir: &Path,
+    git_ignore: &Arc<GitIgnoreFile>,
+    dirs: &mut BTreeMap<PathBuf, Arc<GitIgnoreFile>>,
+    unwatched_dirs: &mut BTreeSet<PathBuf>,
+) {
+    let disk_dir = root.join(dir);
+    // The rules of a `.gitignore` file in a directory with a non-UTF-8 name
+    // can't be matched, so such a directory and its contents are watched.
+    let prefix = dir.to_str().map(|dir| match dir {
+        "" => String::new(),
+        dir => format!("{dir}/"),
+    });
+    let git_ignore = match prefix {
+        Some(prefix) => git_ignore
+            .chain_with_file(&prefix, disk_dir.join(".gitignore"))
+            .unwrap_or_else(|err| {
+                warn!(?err, ?dir, "Failed to read .gitignore file");
+                git_ignore.clone()
+            }),
+        None => git_ignore.clone(),
+    };
+    dirs.insert(dir.to_owned(), git_ignore.clone());
+    let Ok(entries) = disk_dir.read_dir() else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            scan_child_dir(
+                root,
+                &dir.join(entry.file_name()),
+                &git_ignore,
+                dirs,
+                unwatched_dirs,
+            );
+        }
+    }
+}
+
+/// Like `scan_dir()`, but skips `dir` if it's a `.git` or `.jj` directory,
+/// or if it's ignored.
+fn scan_child_dir(
+    root: &Path,
+    dir: &Path,
+    git_ignore: &Arc<GitIgnoreFile>,
+    dirs: &mut BTreeMap<PathBuf, Arc<GitIgnoreFile>>,
+    unwatched_dirs: &mut BTreeSet<PathBuf>,
+) {
+    if dir
+        .file_name()
+        .is_some_and(|name| name == ".git" || name == ".jj")
+    {
+        return;
+    }
+    if dir
+        .to_str()
+        .is_some_and(|dir| git_ignore.matches(&format!("{dir}/")))
+    {
+        unwatched_dirs.insert(dir.to_owned());
+        return;
+    }
+    scan_dir(root, dir, git_ignore, dirs, unwatched_dirs);
+}
+
+/// Daemon which watches a working copy and answers queries for changed files.
+pub struct Daemon {
+    journal: Arc<(Mutex<Journal>, Condvar)>,
+    listener: UnixListener,
+    socket_path: PathBuf,
+    cookie_dir: PathBuf,
+    next_cookie: AtomicU64,
+    // Dropping the watcher stops the thread handling its events.
+    _watched_dirs: Arc<Mutex<WatchedDirs>>,
+}
+
+impl Daemon {
+    /// Starts watching `working_copy_path`, and listens for queries on
+    /// `socket_path`.
+    #[instrument]
+    pub fn start(working_copy_path: &Path, socket_path: &Path) -> Result<Self, Error> {
+        info!("Starting inotify filesystem monitor...");
+        let socket_dir = socket_path.parent().unwrap_or(Path::new("."));
+        match fs::DirBuilder::new().mode(0o700).create(socket_dir) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(source) => {
+                return Err(Error::SocketDirError {
+                    path: socket_dir.to_owned(),
+                    source,
+                })
+            }
+        }
+        if check_socket(socket_path)? && UnixStream::connect(socket_path).is_ok() {
+            return Err(Error::AlreadyRunning);
+        }
+        let root = working_copy_path
+            .canonicalize()
+            .map_err(Error::CanonicalizeRootError)?;
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let instance_id = format!("{:x}-{nanos:x}", process::id());
+        let journal = Arc::new((Mutex::new(Journal::new(instance_id)), Condvar::new()));
+        let cookie_dir = root.join(".jj");
+
+        // Events are handled on a separate thread, since the watcher can't be
+        // updated from its own event handler.
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+                sender.send(result).ok();
+            })
+            .map_err(Error::WatchError)?;
+        watcher
+            .watch(&root, RecursiveMode::NonRecursive)
+            .map_err(Error::WatchError)?;
+        watcher
+            .watch(&cookie_dir, RecursiveMode::NonRecursive)
+            .map_err(Error::WatchError)?;
+        let mut watched_dirs = WatchedDirs {
+            root,
+            watcher,
+            dirs: BTreeMap::from([(PathBuf::new(), GitIgnoreFile::empty())]),
+        };
+        watched_dirs.rescan(&mut journal.0.lock().unwrap(), &mut vec![]);
+        let watched_dirs = Arc::new(Mutex::new(watched_dirs));
+
+        let event_journal = journal.clone();
+        let event_watched_dirs = Arc::downgrade(&watched_dirs);
+        thread::spawn(move || {
+            for result in receiver {
+                let Some(watched_dirs) = event_watched_dirs.upgrade() else {
+                    break;
+                };
+                let (journal, cookie_seen) = &*event_journal;
+                let mut journal = journal.lock().unwrap();
+                watched_dirs
+                    .lock()
+                    .unwrap()
+                    .handle_event(&mut journal, cookie_seen, result);
+            }
+        });
+
+        // Remove the socket left by a daemon which didn't exit cleanly.
+        fs::remove_file(socket_path).ok();
+        let listener = UnixListener::bind(socket_path).map_err(|source| Error::ListenError {
+            path: socket_path.to_owned(),
+            source,
+        })?;
+        Ok(Daemon {
+            journal,
+            listener,
+            socket_path: socket_path.to_owned(),
+            cookie_dir,
+            next_cookie: AtomicU64::new(1),
+            _watched_dirs: watched_dirs,
+        })
+    }
+
+    /// Answers queries until an error occurs.
+    pub fn serve(&self) -> Result<(), Error> {
+        for stream in self.listener.incoming() {
+            let result = stream.and_then(|stream| self.handle_query(stream));
+            if let Err(err) = result {
+                warn!(?err, "Failed to answer filesystem monitor query");
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_query(&self, mut stream: UnixStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(QUERY_TIMEOUT))?;
+        let mut line = String::new();
+        BufReader::new(&mut stream).read_line(&mut line)?;
+        let previous_clock = Clock::from_str(line.trim_end()).ok();
+        let synced = self.sync_events();
+        let (clock, changed_paths) = {
+            let mut journal = self.journal.0.lock().unwrap();
+            let changed_paths = if synced {
+                journal.changed_since(previous_clock.as_ref())
+            } else {
+                None
+            };
+            if let Some(previous_clock) = &previous_clock {
+                journal.record_query(previous_clock);
+            }
+            (journal.clock(), changed_paths)
+        };
+        let mut response = format!("{clock}\0").into_bytes();
+        match changed_paths {
+            None => response.extend_from_slice(b"fresh\0"),
+            Some(paths) => {
+                response.extend_from_slice(b"changed\0");
+                for path in paths {
+                    response.extend_from_slice(path.as_os_str().as_bytes());
+                    response.push(b'\0');
+                }
+            }
+        }
+        stream.write_all(&response)
+    }
+
+    /// Creates a cookie file and waits for its event. Returns `false` if the
+    /// event didn't arrive in time.
+    fn sync_events(&self) -> bool {
+        let cookie = self.next_cookie.fetch_add(1, Ordering::Relaxed);
+        let cookie_path = self.cookie_dir.join(format!("{COOKIE_PREFIX}{cookie}"));
+        if let Err(err) = fs::write(&cookie_path, b"") {
+            warn!(?err, "Failed to create filesystem monitor cookie");
+            return false;
+        }
+        let (journal, cookie_seen) = &*self.journal;
+        let (journal, timeout) = cookie_seen
+            .wait_timeout_while(journal.lock().unwrap(), COOKIE_TIMEOUT, |journal| {
+                journal.last_cookie < cookie
+            })
+            .unwrap();
+        drop(journal);
+        fs::remove_file(&cookie_path).ok();
+        !timeout.timed_out()
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        fs::remove_file(&self.socket_path).ok();
+    }
+}
+
+/// Returns `path` relative to `root`, or `None` if it's in the `.jj` or `.git`
+/// directory.
+fn relative_path(root: &Path, path: &Path) -> Option<PathBuf> {
+    let path = path.strip_prefix(root).ok()?;
+    match path.components().next() {
+        Some(Component::Normal(name)) if name == ".jj" || name == ".git" => None,
+        _ => Some(path.to_owned()),
+    }
+}
+
+/// Returns the number of the cookie if `path` is a cookie file in
+/// `cookie_dir`.
+fn parse_cookie_path(cookie_dir: &Path, path: &Path) -> Option<u64> {
+    let name = path.strip_prefix(cookie_dir).ok()?.to_str()?;
+    name.strip_prefix(COOKIE_PREFIX)?.parse().ok()
+}
+
+/// Handle to the daemon watching a working copy.
+pub struct Fsmonitor {
+    socket_path: PathBuf,
+}
+
+impl Fsmonitor {
+    /// Creates a handle to the daemon listening on `socket_path`.
+    pub fn new(socket_path: &Path) -> Self {
+        Fsmonitor {
+            socket_path: socket_path.to_owned(),
+        }
+    }
+
+    /// Query for changed files since the previous point in time.
+    ///
+    /// The returned list of paths is relative to the working copy root. If it
+    /// is `None`, then the caller must crawl the entire working copy
+    /// themselves.
+    #[instrument(skip(self))]
+    pub fn query_changed_files(
+        &self,
+        previous_clock: Option<Clock>,
+    ) -> Result<(Clock, Option<Vec<PathBuf>>), Error> {
+        info!("Querying inotify daemon for changed files...");
+        check_socket(&self.socket_path)?;
+        let mut stream = UnixStream::connect(&self.socket_path).map_err(Error::ConnectError)?;
+        stream
+            .set_read_timeout(Some(QUERY_TIMEOUT))
+            .map_err(Error::QueryError)?;
+        let request = previous_clock
+            .map(|clock| clock.to_string())
+            .unwrap_or_default();
+        writeln!(stream, "{request}").map_err(Error::QueryError)?;
+        let mut response = vec![];
+        stream
+            .read_to_end(&mut response)
+            .map_err(Error::QueryError)?;
+
+        let mut records = response
+            .strip_suffix(b"\0")
+            .ok_or(Error::InvalidResponse)?
+            .split(|&b| b == b'\0');
+        let clock = records
+            .next()
+            .and_then(|record| std::str::from_utf8(record).ok())
+            .ok_or(Error::InvalidResponse)?
+            .parse()?;
+        match records.next() {
+            Some(b"fresh") => Ok((clock, None)),
+            Some(b"changed") => {
+                let paths = records
+                    .map(|record| PathBuf::from(std::ffi::OsString::from_vec(record.to_vec())))
+                    .collect();
+                Ok((clock, Some(paths)))
+            }
+            _ => Err(Error::InvalidResponse),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[test]
+    fn test_clock_round_trip() {
+        let clock = Journal::new("abc-123").clock();
+        assert_eq!(clock.to_string(), "abc-123:0");
+        assert_eq!(Clock::from_str("abc-123:0").unwrap(), clock);
+        assert!(Clock::from_str("abc").is_err());
+        assert!(Clock::from_str("abc:x").is_err());
+    }
+
+    #[test]
+    fn test_journal_changed_since() {
+        let mut journal = Journal::new("a");
+        assert_eq!(journal.changed_since(None), None);
+
+        let clock0 = journal.clock();
+        journal.record_changes([PathBuf::from("b"), PathBuf::from("a")]);
+        let clock1 = journal.clock();
+        journal.record_changes([PathBuf::from("c"), PathBuf::from("a")]);
+        let clock2 = journal.clock();
+        assert_eq!(
+            journal.changed_since(Some(&clock0)),
+            Some(vec!["a".into(), "b".into(), "c".into()])
+        );
+        assert_eq!(
+            journal.changed_since(Some(&clock1)),
+            Some(vec!["a".into(), "c".into()])
+        );
+        assert_eq!(journal.changed_since(Some(&clock2)), Some(vec![]));
+
+        // Clocks from another instance of the daemon
+        let other_clock = Journal::new("b").clock();
+        assert_eq!(journal.changed_since(Some(&other_clock)), None);
+
+        // Clocks before dropped events
+        journal.record_lost_events();
+        let clock3 = journal.clock();
+        journal.record_changes([PathBuf::from("d")]);
+        assert_eq!(journal.changed_since(Some(&clock2)), None);
+        assert_eq!(journal.changed_since(Some(&clock3)), Some(vec!["d".into()]));
+
+        // Unwatched directories are always reported
+        journal.unwatched_dirs.insert("e".into());
+        assert_eq!(
+            journal.changed_since(Some(&clock3)),
+            Some(vec!["d".into(), "e".into()])
+        );
+        assert_eq!(
+            journal.changed_since(Some(&journal.clock())),
+            Some(vec!["e".into()])
+        );
+    }
+
+    #[test]
+    fn test_journal_record_query() {
+        let mut journal = Journal::new("a");
+        let clock0 = journal.clock();
+        journal.record_changes([PathBuf::from("a")]);
+        let clock1 = journal.clock();
+        journal.record_changes([PathBuf::from("b")]);
+        let clock2 = journal.clock();
+
+        // Changes needed by a recent query are kept
+        journal.record_query(&clock0);
+        journal.record_query(&clock1);
+        assert_eq!(
+            journal.changed_since(Some(&clock0)),
+            Some(vec!["a".into(), "b".into()])
+        );
+
+        // Changes before the oldest recent query are pruned
+        for _ in 0..RECENT_QUERIES {
+            journal.record_query(&clock1);
+        }
+        assert_eq!(journal.changed_since(Some(&clock0)), None);
+        assert_eq!(journal.changed_since(Some(&clock1)), Some(vec!["b".into()]));
+        assert_eq!(journal.changed_since(Some(&clock2)), Some(vec![]));
+        assert_eq!(journal.changed_paths.len(), 1);
+
+        // Queries which can't be answered don't prevent pruning
+        journal.record_query(&clock0);
+        journal.record_query(&Journal::new("b").clock());
+        for _ in 0..RECENT_QUERIES {
+            journal.record_query(&clock2);
+        }
+        assert_eq!(journal.changed_since(Some(&clock1)), None);
+        assert!(journal.changed_paths.is_empty());
+    }
+
+    #[test]
+    fn test_socket_path() {
+        let state_path = Path::new("/repo/.jj/working_copy");
+        assert_eq!(
+            socket_path(state_path),
+            Path::new("/repo/.jj/working_copy/inotify.sock")
+        );
+
+        let long_state_path = Path::new("/repo").join("a".repeat(100));
+        let long_socket_path = socket_path(&long_state_path);
+        let file_name = long_socket_path.file_name().unwrap().to_str().unwrap();
+        assert!(file_name.starts_with("jj-inotify-"));
+        assert!(file_name.ends_with(".sock"));
+        assert_eq!(socket_path(&long_state_path), long_socket_path);
+        assert_ne!(socket_path(&long_state_path.join("b")), long_socket_path);
+    }
+
+    #[test]
+    fn test_check_socket() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let temp_dir = testutils::new_temp_dir();
+        let socket_dir = temp_dir.path().join("sockets");
+        let socket_path = socket_dir.join("inotify.sock");
+        // Missing directory or socket
+        assert!(!check_socket(&socket_path).unwrap());
+        fs::DirBuilder::new()
+            .mode(0o700)
+            .create(&socket_dir)
+            .unwrap();
+        assert!(!check_socket(&socket_path).unwrap());
+
+        let _listener = UnixListener::bind(&socket_path).unwrap();
+        assert!(check_socket(&socket_path).unwrap());
+
+        // Directory writable by others
+        fs::set_permissions(&socket_dir, fs::Permissions::from_mode(0o777)).unwrap();
+        assert_matches!(
+            check_socket(&socket_path),
+            Err(Error::InsecureSocketPath { path }) if path == socket_dir
+        );
+    }
+
+    #[test]
+    fn test_relative_path() {
+        let root = Path::new("/repo");
+        assert_eq!(
+            relative_path(root, Path::new("/repo/dir/file")),
+            Some("dir/file".into())
+        );
+        assert_eq!(relative_path(root, Path::new("/repo/.jj/repo")), None);
+        assert_eq!(relative_path(root, Path::new("/repo/.git")), None);
+        assert_eq!(
+            relative_path(root, Path::new("/repo/dir/.git")),
+            Some("dir/.git".into())
+        );
+        assert_eq!(relative_path(root, Path::new("/other/file")), None);
+    }
+}
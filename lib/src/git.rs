@@ -26,7 +26,7 @@ use itertools::Itertools;
 use tempfile::NamedTempFile;
 use thiserror::Error;
 
-use crate::backend::{BackendError, CommitId};
+use crate::backend::{BackendError, CommitId, Signature, SigningFn};
 use crate::commit::Commit;
 use crate::git_backend::GitBackend;
 use crate::index::Index;
@@ -36,6 +36,7 @@ use crate::refs::{self, BranchPushUpdate};
 use crate::repo::{MutableRepo, Repo};
 use crate::revset::RevsetExpression;
 use crate::settings::GitSettings;
+use crate::signing::SignError;
 use crate::store::Store;
 use crate::str_util::StringPattern;
 use crate::view::View;
@@ -615,6 +616,10 @@ pub enum FailedRefExportReason {
     /// We wanted to modify it, but Git had deleted it
     #[error("Modified ref had been deleted in Git")]
     ModifiedInJjDeletedInGit,
+    /// We wanted to move it, but it's an annotated tag in Git, and moving it
+    /// would drop the tag object
+    #[error("Annotated tag cannot be moved")]
+    AnnotatedTagMoved,
     /// Failed to delete the ref from the Git repo
     #[error("Failed to delete")]
     FailedToDelete(#[source] Box<gix::reference::edit::Error>),
@@ -638,9 +643,9 @@ struct RefsToExport {
 /// repo compared to our last remembered view of the Git repo). These will be
 /// marked conflicted by the next `jj git import`.
 ///
-/// We do not export tags and other refs at the moment, since these aren't
-/// supposed to be modified by JJ. For them, the Git state is considered
-/// authoritative.
+/// Tags are exported the same way as local branches. Other refs aren't
+/// supposed to be modified by JJ, so the Git state is considered authoritative
+/// for them.
 pub fn export_refs(mut_repo: &mut MutableRepo) -> Result<Vec<FailedRefExport>, GitExportError> {
     export_some_refs(mut_repo, |_| true)
 }
@@ -648,6 +653,33 @@ pub fn export_refs(mut_repo: &mut MutableRepo) -> Result<Vec<FailedRefExport>, G
 pub fn export_some_refs(
     mut_repo: &mut MutableRepo,
     git_ref_filter: impl Fn(&RefName) -> bool,
+) -> Result<Vec<FailedRefExport>, GitExportError> {
+    export_refs_with_tag_objects(mut_repo, git_ref_filter, &HashMap::new())
+}
+
+/// Exports the tags created by `create_annotated_tag()`, pointing their Git
+/// refs to the annotated tag objects.
+pub fn export_annotated_tags(
+    mut_repo: &mut MutableRepo,
+    tags: &[AnnotatedTag],
+) -> Result<Vec<FailedRefExport>, GitExportError> {
+    let tag_objects: HashMap<_, _> = tags
+        .iter()
+        .map(|tag| (tag.name.as_str(), tag.object_id))
+        .collect();
+    export_refs_with_tag_objects(
+        mut_repo,
+        |ref_name| matches!(ref_name, RefName::Tag(name) if tag_objects.contains_key(name.as_str())),
+        &tag_objects,
+    )
+}
+
+/// Exports refs like `export_some_refs()`, but points the Git refs of the tags
+/// in `tag_objects` to the given tag objects instead of to the commits.
+fn export_refs_with_tag_objects(
+    mut_repo: &mut MutableRepo,
+    git_ref_filter: impl Fn(&RefName) -> bool,
+    tag_objects: &HashMap<&str, gix::ObjectId>,
 ) -> Result<Vec<FailedRefExport>, GitExportError> {
     let git_repo = get_git_repo(mut_repo.store()).ok_or(GitExportError::UnexpectedBackend)?;
 
@@ -706,7 +738,17 @@ pub fn export_some_refs(
             failed_branches.insert(parsed_ref_name, FailedRefExportReason::InvalidGitName);
             continue;
         };
-        if let Err(reason) = update_git_ref(&git_repo, &git_ref_name, old_oid, new_oid) {
+        let ref_oid = match &parsed_ref_name {
+            RefName::Tag(name) => tag_objects.get(name.as_str()).copied(),
+            _ => None,
+        };
+        if let Err(reason) = update_git_ref(
+            &git_repo,
+            &git_ref_name,
+            old_oid,
+            new_oid,
+            ref_oid.unwrap_or(new_oid),
+        ) {
             failed_branches.insert(parsed_ref_name, reason);
         } else {
             let new_target = RefTarget::normal(CommitId::from_bytes(new_oid.as_bytes()));
@@ -765,7 +807,12 @@ fn diff_refs_to_export(
     // the local branches are considered to be the new "git" remote branches.
     let mut all_branch_targets: HashMap<RefName, (&RefTarget, &RefTarget)> = itertools::chain(
         view.local_branches()
-            .map(|(branch, target)| (RefName::LocalBranch(branch.to_owned()), target)),
+            .map(|(branch, target)| (RefName::LocalBranch(branch.to_owned()), target))
+            .chain(
+                view.tags()
+                    .iter()
+                    .map(|(tag, target)| (RefName::Tag(tag.to_owned()), target)),
+            ),
         view.all_remote_branches()
             .filter(|&((_, remote), _)| remote != REMOTE_NAME_FOR_LOCAL_GIT_REPO)
             .map(|((branch, remote), remote_ref)| {
@@ -787,12 +834,14 @@ fn diff_refs_to_export(
             (ref_name, target)
         })
         .filter(|(ref_name, _)| {
-            // There are two situations where remote-tracking branches get out of sync:
-            // 1. `jj branch forget`
-            // 2. `jj op undo`/`restore` in colocated repo
+            // Known Git refs which no longer exist in the view will be deleted.
+            // There are a few situations where they get out of sync:
+            // 1. `jj branch forget` (remote-tracking branches)
+            // 2. `jj tag delete` (tags)
+            // 3. `jj op undo`/`restore` in colocated repo
             matches!(
                 ref_name,
-                RefName::LocalBranch(..) | RefName::RemoteBranch { .. }
+                RefName::LocalBranch(..) | RefName::RemoteBranch { .. } | RefName::Tag(..)
             )
         })
         .filter(|(ref_name, _)| git_ref_filter(ref_name));
@@ -852,7 +901,7 @@ fn delete_git_ref(
     old_oid: &gix::oid,
 ) -> Result<(), FailedRefExportReason> {
     if let Ok(git_ref) = git_repo.find_reference(git_ref_name) {
-        if git_ref.inner.target.try_id() == Some(old_oid) {
+        if git_ref_points_to(&git_ref, old_oid) {
             // The branch has not been updated by git, so go ahead and delete it
             git_ref
                 .delete()
@@ -867,18 +916,22 @@ fn delete_git_ref(
     Ok(())
 }
 
+/// Updates the Git ref from `old_oid` to `new_oid`. The ref is set to
+/// `ref_oid`, which is either `new_oid` or an annotated tag object pointing to
+/// it.
 fn update_git_ref(
     git_repo: &gix::Repository,
     git_ref_name: &str,
     old_oid: Option<gix::ObjectId>,
     new_oid: gix::ObjectId,
+    ref_oid: gix::ObjectId,
 ) -> Result<(), FailedRefExportReason> {
     match old_oid {
         None => {
             if let Ok(git_repo_ref) = git_repo.find_reference(git_ref_name) {
                 // The branch was added in jj and in git. We're good if and only if git
                 // pointed it to our desired target.
-                if git_repo_ref.inner.target.try_id() != Some(&new_oid) {
+                if !git_ref_points_to(&git_repo_ref, &new_oid) {
                     return Err(FailedRefExportReason::AddedInJjAddedInGit);
                 }
             } else {
@@ -886,7 +939,7 @@ fn update_git_ref(
                 git_repo
                     .reference(
                         git_ref_name,
-                        new_oid,
+                        ref_oid,
                         gix::refs::transaction::PreviousValue::MustNotExist,
                         "export from jj",
                     )
@@ -895,16 +948,25 @@ fn update_git_ref(
         }
         Some(old_oid) => {
             // The branch was modified in jj. We can use gix API for updating under a lock.
+            if let Ok(git_repo_ref) = git_repo.find_reference(git_ref_name) {
+                // Pointing an annotated tag to the new commit would drop the tag
+                // object, including its message and signature.
+                if git_repo_ref.inner.target.try_id() != Some(&old_oid)
+                    && git_ref_points_to(&git_repo_ref, &old_oid)
+                {
+                    return Err(FailedRefExportReason::AnnotatedTagMoved);
+                }
+            }
             if let Err(err) = git_repo.reference(
                 git_ref_name,
-                new_oid,
+                ref_oid,
                 gix::refs::transaction::PreviousValue::MustExistAndMatch(old_oid.into()),
                 "export from jj",
             ) {
                 // The reference was probably updated in git
                 if let Ok(git_repo_ref) = git_repo.find_reference(git_ref_name) {
                     // We still consider this a success if it was updated to our desired target
-                    if git_repo_ref.inner.target.try_id() != Some(&new_oid) {
+                    if !git_ref_points_to(&git_repo_ref, &new_oid) {
                         return Err(FailedRefExportReason::FailedToSet(err.into()));
                     }
                 } else {
@@ -920,6 +982,21 @@ fn update_git_ref(
     Ok(())
 }
 
+/// Returns true if `git_ref` points to `oid`, either directly or through an
+/// annotated tag.
+fn git_ref_points_to(git_ref: &gix::Reference, oid: &gix::oid) -> bool {
+    let raw_ref = &git_ref.inner;
+    if raw_ref.target.try_id() == Some(oid) || raw_ref.peeled.as_deref() == Some(oid) {
+        return true;
+    }
+    git_ref
+        .try_id()
+        .and_then(|id| id.object().ok())
+        .and_then(|object| object.try_into_tag().ok())
+        .and_then(|tag| tag.target_id().ok())
+        .is_some_and(|target_id| *target_id == *oid)
+}
+
 /// Ensures `HEAD@git` is detached and pointing to the `new_oid`. If `new_oid`
 /// is `None` (meaning absent), dummy placeholder ref will be set.
 fn update_git_head(
@@ -1035,6 +1112,87 @@ pub fn reset_head(
     Ok(())
 }
 
+#[derive(Debug, Error)]
+pub enum GitTagError {
+    #[error("Tag name is not allowed in Git: {0}")]
+    InvalidName(String),
+    #[error("Tag already exists in Git: {0}")]
+    AlreadyExists(String),
+    #[error("Failed to sign tag")]
+    Signing(#[from] SignError),
+    #[error("Unexpected git error when creating tag")]
+    InternalGitError(#[from] git2::Error),
+}
+
+/// Annotated tag object written by `create_annotated_tag()`.
+#[derive(Clone, Debug)]
+pub struct AnnotatedTag {
+    name: String,
+    object_id: gix::ObjectId,
+}
+
+/// Creates an annotated tag object pointing to `target` in the backing Git
+/// repo, and points the tag `name` to `target` in the view.
+///
+/// The Git ref isn't updated until the returned tag is passed to
+/// `export_annotated_tags()`. If `sign_with` is specified, the tag object is
+/// signed in the same format as `git tag -s` produces. Fails if the tag
+/// already exists in Git.
+pub fn create_annotated_tag(
+    mut_repo: &mut MutableRepo,
+    git_repo: &git2::Repository,
+    name: &str,
+    target: &CommitId,
+    tagger: &Signature,
+    message: &str,
+    sign_with: Option<&mut SigningFn>,
+) -> Result<AnnotatedTag, GitTagError> {
+    let git_ref_name = format!("refs/tags/{name}");
+    if !git2::Reference::is_valid_name(&git_ref_name) {
+        return Err(GitTagError::InvalidName(name.to_owned()));
+    }
+    if git_repo.find_reference(&git_ref_name).is_ok() {
+        return Err(GitTagError::AlreadyExists(name.to_owned()));
+    }
+    let mut message = message.to_owned();
+    if !message.ends_with('\n') {
+        message.push('\n');
+    }
+    let mut data = format!(
+        "object {target}\ntype commit\ntag {name}\ntagger {tagger}\n\n{message}",
+        target = target.hex(),
+        tagger = format_git_signature(tagger),
+    )
+    .into_bytes();
+    if let Some(sign_with) = sign_with {
+        let signature = sign_with(&data)?;
+        data.extend_from_slice(&signature);
+    }
+    let tag_oid = git_repo.odb()?.write(git2::ObjectType::Tag, &data)?;
+    mut_repo.set_tag_target(name, RefTarget::normal(target.clone()));
+    Ok(AnnotatedTag {
+        name: name.to_owned(),
+        object_id: gix::ObjectId::try_from(tag_oid.as_bytes()).unwrap(),
+    })
+}
+
+/// Formats `signature` as the identity line of a Git object.
+fn format_git_signature(signature: &Signature) -> String {
+    let Signature {
+        name,
+        email,
+        timestamp,
+    } = signature;
+    let seconds = timestamp.timestamp.0.div_euclid(1000);
+    let sign = if timestamp.tz_offset < 0 { '-' } else { '+' };
+    let offset = timestamp.tz_offset.abs();
+    format!(
+        "{name} <{email}> {seconds} {sign}{hours:02}{minutes:02}",
+        hours = offset / 60,
+        minutes = offset % 60
+    )
+}
+
 #[derive(Debug, Error)]
 pub enum GitRemoteManagementError {
     #[error("No git remote named '{0}'")]
@@ -1226,7 +1384,7 @@ pub fn fetch(
     git_repo: &git2::Repository,
     remote_name: &str,
     branch_names: &[StringPattern],
-    callbacks: RemoteCallbacks<'_>,
+    mut callbacks: RemoteCallbacks<'_>,
     git_settings: &GitSettings,
 ) -> Result<GitFetchStats, GitFetchError> {
     // Perform a `git fetch` on the local git repo, updating the remote-tracking
@@ -1238,21 +1396,37 @@ pub fn fetch(
             GitFetchError::InternalGitError(err)
         }
     })?;
-    let mut fetch_options = git2::FetchOptions::new();
-    let mut proxy_options = git2::ProxyOptions::new();
-    proxy_options.auto();
-    fetch_options.proxy_options(proxy_options);
-    let callbacks = callbacks.into_git();
-    fetch_options.remote_callbacks(callbacks);
     // At this point, we are only updating Git's remote tracking branches, not the
-    // local branches.
-    let refspecs: Vec<_> = branch_names
+    // local branches. Patterns that can't be expressed as a refspec glob (such
+    // as regular expressions) are expanded to the names of the matching remote
+    // and remote-tracking branches, so no other refs are updated or pruned.
+    // Exact names are escaped like globs, so a name containing glob meta
+    // characters is rejected below instead of matching other branches.
+    let (mut branch_globs, other_patterns): (Vec<_>, Vec<_>) =
+        branch_names
+            .iter()
+            .partition_map(|pattern| match pattern.to_glob() {
+                Some(glob) => itertools::Either::Left(glob),
+                None => itertools::Either::Right(pattern),
+            });
+    if !other_patterns.is_empty() {
+        let remote_branch_names = list_remote_branch_names(&mut remote, &mut callbacks)?;
+        let tracking_branch_names = list_remote_tracking_branch_names(git_repo, remote_name)?;
+        branch_globs.extend(
+            remote_branch_names
+                .into_iter()
+                .chain(tracking_branch_names)
+                .filter(|name| other_patterns.iter().any(|pattern| pattern.matches(name)))
+                .sorted()
+                .dedup()
+                .map(|name| Cow::Owned(glob::Pattern::escape(&name))),
+        );
+    }
+    let refspecs: Vec<_> = branch_globs
         .iter()
-        .map(|pattern| {
-            pattern
-                .to_glob()
-                .filter(|glob| !glob.contains(INVALID_REFSPEC_CHARS))
-                .map(|glob| format!("+refs/heads/{glob}:refs/remotes/{remote_name}/{glob}"))
+        .map(|glob| {
+            (!glob.contains(INVALID_REFSPEC_CHARS))
+                .then(|| format!("+refs/heads/{glob}:refs/remotes/{remote_name}/{glob}"))
         })
         .collect::<Option<_>>()
         .ok_or(GitFetchError::InvalidBranchPattern)?;
@@ -1261,6 +1435,11 @@ pub fn fetch(
         let stats = GitFetchStats::default();
         return Ok(stats);
     }
+    let mut fetch_options = git2::FetchOptions::new();
+    let mut proxy_options = git2::ProxyOptions::new();
+    proxy_options.auto();
+    fetch_options.proxy_options(proxy_options);
+    fetch_options.remote_callbacks(callbacks.git_callbacks());
     tracing::debug!("remote.download");
     remote.download(&refspecs, Some(&mut fetch_options))?;
     tracing::debug!("remote.prune");
@@ -1299,6 +1478,46 @@ pub fn fetch(
     Ok(stats)
 }
 
+/// Lists the names of the branches on the `remote`.
+fn list_remote_branch_names(
+    remote: &mut git2::Remote,
+    callbacks: &mut RemoteCallbacks,
+) -> Result<Vec<String>, git2::Error> {
+    let mut proxy_options = git2::ProxyOptions::new();
+    proxy_options.auto();
+    tracing::debug!("remote.connect");
+    let connection = remote.connect_auth(
+        git2::Direction::Fetch,
+        Some(callbacks.git_callbacks()),
+        Some(proxy_options),
+    )?;
+    let names = connection
+        .list()?
+        .iter()
+        .filter_map(|head| head.name().strip_prefix("refs/heads/"))
+        .map(|name| name.to_owned())
+        .collect();
+    Ok(names)
+}
+
+/// Lists the names of the remote-tracking branches of the `remote_name`
+/// remote in the Git repo.
+fn list_remote_tracking_branch_names(
+    git_repo: &git2::Repository,
+    remote_name: &str,
+) -> Result<Vec<String>, git2::Error> {
+    let prefix = format!("refs/remotes/{remote_name}/");
+    let mut names = vec![];
+    for git_ref in git_repo.references_glob(&format!("{prefix}*"))? {
+        if let Some(name) = git_ref?.name().and_then(|name| name.strip_prefix(&prefix)) {
+            if name != "HEAD" {
+                names.push(name.to_owned());
+            }
+        }
+    }
+    Ok(names)
+}
+
 #[derive(Error, Debug, PartialEq)]
 pub enum GitPushError {
     #[error("No git remote named '{0}'")]
@@ -1312,6 +1531,8 @@ pub enum GitPushError {
     RefInUnexpectedLocation(Vec<String>),
     #[error("Remote rejected the update of some refs (do you have permission to push to {0:?}?)")]
     RefUpdateRejected(Vec<String>),
+    #[error("Tags already exist on the remote with different targets")]
+    TagAlreadyExists,
     // TODO: I'm sure there are other errors possible, such as transport-level errors,
     // and errors caused by the remote rejecting the push.
     #[error("Unexpected git error when pushing")]
@@ -1407,13 +1628,61 @@ pub fn push_updates(
     )
 }
 
+/// Pushes the specified tags without updating the repo view.
+///
+/// Unlike branches, tags are never force-pushed. The remote rejects a tag which
+/// already exists there with a different target. If the Git ref of a tag points
+/// to an annotated tag object, the tag object is pushed.
+pub fn push_tags(
+    repo: &dyn Repo,
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    tags: &[(String, CommitId)],
+    callbacks: RemoteCallbacks<'_>,
+) -> Result<(), GitPushError> {
+    let qualified_names = tags
+        .iter()
+        .map(|(tag_name, _)| format!("refs/tags/{tag_name}"))
+        .collect_vec();
+    let mut refspecs = vec![];
+    for (qualified_name, (_, target)) in iter::zip(&qualified_names, tags) {
+        let points_to_target = git_repo
+            .find_reference(qualified_name)
+            .and_then(|git_ref| git_ref.peel_to_commit())
+            .is_ok_and(|commit| commit.id().as_bytes() == target.as_bytes());
+        if points_to_target {
+            refspecs.push(format!("{qualified_name}:{qualified_name}"));
+        } else {
+            refspecs.push(format!("{}:{qualified_name}", target.hex()));
+        }
+    }
+    let qualified_remote_refs_expected_locations = qualified_names
+        .iter()
+        .map(|name| (name.as_str(), None))
+        .collect();
+    push_refs(
+        repo,
+        git_repo,
+        remote_name,
+        &qualified_remote_refs_expected_locations,
+        &refspecs,
+        callbacks,
+    )
+    .map_err(|err| match err {
+        GitPushError::InternalGitError(err) if err.code() == git2::ErrorCode::NotFastForward => {
+            GitPushError::TagAlreadyExists
+        }
+        err => err,
+    })
+}
+
 fn push_refs(
     repo: &dyn Repo,
     git_repo: &git2::Repository,
     remote_name: &str,
     qualified_remote_refs_expected_locations: &HashMap<&str, Option<&CommitId>>,
     refspecs: &[String],
-    callbacks: RemoteCallbacks<'_>,
+    mut callbacks: RemoteCallbacks<'_>,
 ) -> Result<(), GitPushError> {
     if remote_name == REMOTE_NAME_FOR_LOCAL_GIT_REPO {
         return Err(GitPushError::RemoteReservedForLocalGitRepo);
@@ -1435,12 +1704,16 @@ fn push_refs(
         let mut proxy_options = git2::ProxyOptions::new();
         proxy_options.auto();
         push_options.proxy_options(proxy_options);
-        let mut callbacks = callbacks.into_git();
+        let mut callbacks = callbacks.git_callbacks();
         callbacks.push_negotiation(|updates| {
             for update in updates {
                 let dst_refname = update
                     .dst_refname()
                     .expect("Expect reference name to be valid UTF-8");
+                if dst_refname.starts_with("refs/tags/") {
+                    // Tags aren't force-pushed, so the remote checks them.
+                    continue;
+                }
                 let expected_remote_location = *qualified_remote_refs_expected_locations
                     .get(dst_refname)
                     .expect("Push is trying to move a ref it wasn't asked to move");
@@ -1603,10 +1876,19 @@ pub struct RemoteCallbacks<'a> {
     pub get_username_password: Option<&'a mut dyn FnMut(&str) -> Option<(String, String)>>,
 }
 
-impl<'a> RemoteCallbacks<'a> {
-    fn into_git(mut self) -> git2::RemoteCallbacks<'a> {
+impl RemoteCallbacks<'_> {
+    /// Creates Git callbacks which borrow these callbacks, so the remote can
+    /// be connected to more than once.
+    fn git_callbacks(&mut self) -> git2::RemoteCallbacks<'_> {
+        let RemoteCallbacks {
+            progress,
+            sideband_progress,
+            get_ssh_keys,
+            get_password,
+            get_username_password,
+        } = self;
         let mut callbacks = git2::RemoteCallbacks::new();
-        if let Some(progress_cb) = self.progress {
+        if let Some(progress_cb) = progress {
             callbacks.transfer_progress(move |progress| {
                 progress_cb(&Progress {
                     bytes_downloaded: (progress.received_objects() < progress.total_objects())
@@ -1617,7 +1899,7 @@ impl<'a> RemoteCallbacks<'a> {
                 true
             });
         }
-        if let Some(sideband_progress_cb) = self.sideband_progress {
+        if let Some(sideband_progress_cb) = sideband_progress {
             callbacks.sideband_progress(move |data| {
                 sideband_progress_cb(data);
                 true
@@ -1651,7 +1933,7 @@ impl<'a> RemoteCallbacks<'a> {
                     }
 
                     let paths = ssh_key_paths_to_try.get_or_insert_with(|| {
-                        if let Some(ref mut cb) = self.get_ssh_keys {
+                        if let Some(cb) = get_ssh_keys {
                             let mut paths = cb(username);
                             paths.reverse();
                             paths
@@ -1669,7 +1951,7 @@ impl<'a> RemoteCallbacks<'a> {
                     }
                 }
                 if allowed_types.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-                    if let Some(ref mut cb) = self.get_password {
+                    if let Some(cb) = get_password {
                         if let Some(pw) = cb(url, username) {
                             tracing::info!(
                                 username,
@@ -1683,7 +1965,7 @@ impl<'a> RemoteCallbacks<'a> {
                     }
                 }
             } else if allowed_types.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-                if let Some(ref mut cb) = self.get_username_password {
+                if let Some(cb) = get_username_password {
                     if let Some((username, pw)) = cb(url) {
                         tracing::info!(username, "using userpass_plaintext");
                         return git2::Cred::userpass_plaintext(&username, &pw).map_err(|err| {
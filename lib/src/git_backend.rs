@@ -20,30 +20,32 @@ use std::fmt::{Debug, Error, Formatter};
 use std::io::{Cursor, Read};
 use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus};
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
 use std::time::SystemTime;
 use std::{fs, io, str};
 
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 use gix::bstr::BString;
 use gix::objs::{CommitRef, CommitRefIter, WriteTo};
 use itertools::Itertools;
+use pollster::FutureExt as _;
 use prost::Message;
 use smallvec::SmallVec;
 use thiserror::Error;
 
 use crate::backend::{
     make_root_commit, Backend, BackendError, BackendInitError, BackendLoadError, BackendResult,
-    ChangeId, Commit, CommitId, Conflict, ConflictId, ConflictTerm, FileId, MergedTreeId,
-    MillisSinceEpoch, SecureSig, Signature, SigningFn, SymlinkId, Timestamp, Tree, TreeId,
-    TreeValue,
+    ChangeId, Commit, CommitId, Conflict, ConflictId, ConflictTerm, CopyRecord, FileId,
+    MergedTreeId, MillisSinceEpoch, SecureSig, Signature, SigningFn, SymlinkId, Timestamp, Tree,
+    TreeId, TreeValue,
 };
 use crate::file_util::{IoResultExt as _, PathError};
 use crate::index::Index;
 use crate::lock::FileLock;
 use crate::merge::{Merge, MergeBuilder};
 use crate::object_id::ObjectId;
-use crate::repo_path::{RepoPath, RepoPathComponentBuf};
+use crate::repo_path::{RepoPath, RepoPathBuf, RepoPathComponentBuf};
 use crate::settings::UserSettings;
 use crate::stacked_table::{
     MutableTable, ReadonlyTable, TableSegment, TableStore, TableStoreError,
@@ -117,6 +119,8 @@ pub struct GitBackend {
     // used in a single-threaded context.
     base_repo: gix::ThreadSafeRepository,
     repo: Mutex<gix::Repository>,
+    // libgit2 instance used for copy detection, opened on first use.
+    git2_repo: OnceLock<Mutex<git2::Repository>>,
     root_commit_id: CommitId,
     root_change_id: ChangeId,
     empty_tree_id: TreeId,
@@ -143,6 +147,7 @@ impl GitBackend {
         GitBackend {
             base_repo,
             repo,
+            git2_repo: OnceLock::new(),
             root_commit_id,
             root_change_id,
             empty_tree_id,
@@ -285,6 +290,20 @@ impl GitBackend {
         self.base_repo.to_thread_local()
     }
 
+    fn lock_git2_repo(&self) -> BackendResult<MutexGuard<'_, git2::Repository>> {
+        if let Some(repo) = self.git2_repo.get() {
+            return Ok(repo.lock().unwrap());
+        }
+        let repo = self
+            .open_git_repo()
+            .map_err(|err| BackendError::Other(err.into()))?;
+        Ok(self
+            .git2_repo
+            .get_or_init(|| Mutex::new(repo))
+            .lock()
+            .unwrap())
+    }
+
     /// Creates new owned git repository instance.
     pub fn open_git_repo(&self) -> Result<git2::Repository, git2::Error> {
         git2::Repository::open(self.git_repo_path())
@@ -380,6 +399,53 @@ impl GitBackend {
         self.save_extra_metadata_table(mut_table, &table_lock)
     }
 
+    /// Returns the root tree of the given commit if it isn't conflicted.
+    fn resolved_root_tree_id(&self, id: &CommitId) -> BackendResult<Option<TreeId>> {
+        let commit = self.read_commit(id).block_on()?;
+        match commit.root_tree {
+            MergedTreeId::Legacy(tree_id) => Ok(Some(tree_id)),
+            MergedTreeId::Merge(tree_ids) => Ok(tree_ids.as_resolved().cloned()),
+        }
+    }
+
+    /// Returns the copies recorded for `head`, which are relative to its
+    /// parent.
+    fn read_recorded_copies(
+        &self,
+        head: &CommitId,
+    ) -> BackendResult<Option<Vec<crate::protos::git_store::commit::Copy>>> {
+        let table = self.cached_extra_metadata_table()?;
+        let Some(extras) = table.get_value(head.as_bytes()) else {
+            return Ok(None);
+        };
+        let proto = crate::protos::git_store::Commit::decode(extras)
+            .map_err(|err| BackendError::Other(err.into()))?;
+        Ok(proto.copies_recorded.then_some(proto.copies))
+    }
+
+    /// Saves the `copies` from the parent of `head` in its extras, so they
+    /// don't have to be detected again.
+    fn record_copies(
+        &self,
+        head: &CommitId,
+        copies: &[crate::protos::git_store::commit::Copy],
+    ) -> BackendResult<()> {
+        let (table, table_lock) = self.read_extra_metadata_table_locked()?;
+        let Some(extras) = table.get_value(head.as_bytes()) else {
+            return Ok(());
+        };
+        let mut proto = crate::protos::git_store::Commit::decode(extras)
+            .map_err(|err| BackendError::Other(err.into()))?;
+        if proto.copies_recorded {
+            return Ok(());
+        }
+        proto.copies_recorded = true;
+        proto.copies = copies.to_vec();
+        let mut mut_table = table.start_mutation();
+        mut_table.add_entry(head.to_bytes(), proto.encode_to_vec());
+        self.save_extra_metadata_table(mut_table, &table_lock)
+    }
+
     fn read_file_sync(&self, id: &FileId) -> BackendResult<Box<dyn Read>> {
         let git_blob_id = validate_git_object_id(id)?;
         let locked_repo = self.lock_git_repo();
@@ -589,6 +655,15 @@ fn serialize_extras(commit: &Commit) -> Vec<u8> {
     proto.encode_to_vec()
 }
 
+/// Returns the serialized `extras` without the copies, which are recorded
+/// after the commit is written and are derived from the commit contents.
+fn strip_extras_copies(extras: &[u8]) -> Vec<u8> {
+    let mut proto = crate::protos::git_store::Commit::decode(extras).unwrap();
+    proto.copies.clear();
+    proto.copies_recorded = false;
+    proto.encode_to_vec()
+}
+
 fn deserialize_extras(commit: &mut Commit, bytes: &[u8]) {
     let proto = crate::protos::git_store::Commit::decode(bytes).unwrap();
     commit.change_id = ChangeId::new(proto.change_id);
@@ -621,6 +696,88 @@ fn deserialize_extras(commit: &mut Commit, bytes: &[u8]) {
     }
 }
 
+/// Infers the files copied or renamed between the `root_tree_id` and
+/// `head_tree_id` trees from the similarity of their contents. If `paths` is
+/// set, only copies from or to these paths are detected.
+fn detect_copies(
+    git_repo: &git2::Repository,
+    root_tree_id: &TreeId,
+    head_tree_id: &TreeId,
+    paths: Option<&[RepoPathBuf]>,
+) -> BackendResult<Vec<crate::protos::git_store::commit::Copy>> {
+    let to_other_err = |err: git2::Error| BackendError::Other(err.into());
+    let find_tree = |id: &TreeId| {
+        let oid = git2::Oid::from_bytes(id.as_bytes()).map_err(to_other_err)?;
+        git_repo.find_tree(oid).map_err(to_other_err)
+    };
+    let root_tree = find_tree(root_tree_id)?;
+    let head_tree = find_tree(head_tree_id)?;
+    let is_requested = |path: &[u8]| {
+        paths.map_or(true, |paths| {
+            paths
+                .iter()
+                .any(|p| p.as_internal_file_string().as_bytes() == path)
+        })
+    };
+
+    // Find the candidate sources and targets without comparing the contents,
+    // which only visits the subtrees that changed.
+    let changes = git_repo
+        .diff_tree_to_tree(Some(&root_tree), Some(&head_tree), None)
+        .map_err(to_other_err)?;
+    let mut sources = vec![];
+    let mut targets = vec![];
+    for delta in changes.deltas() {
+        match delta.status() {
+            git2::Delta::Added => targets.extend(delta.new_file().path_bytes()),
+            git2::Delta::Deleted | git2::Delta::Modified => {
+                sources.extend(delta.old_file().path_bytes());
+            }
+            _ => {}
+        }
+    }
+    if !targets.iter().any(|path| is_requested(path)) {
+        sources.retain(|path| is_requested(path));
+    }
+    if sources.is_empty() || targets.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // Compare the contents of the candidates only
+    let mut options = git2::DiffOptions::new();
+    options.disable_pathspec_match(true);
+    for path in sources.iter().chain(&targets) {
+        options.pathspec(path.to_vec());
+    }
+    let mut diff = git_repo
+        .diff_tree_to_tree(Some(&root_tree), Some(&head_tree), Some(&mut options))
+        .map_err(to_other_err)?;
+    diff.find_similar(Some(
+        git2::DiffFindOptions::new().renames(true).copies(true),
+    ))
+    .map_err(to_other_err)?;
+    let to_path_string = |file: &git2::DiffFile| {
+        let path = str::from_utf8(file.path_bytes()?).ok()?;
+        Some(path.to_owned())
+    };
+    let copies = diff
+        .deltas()
+        .filter(|delta| matches!(delta.status(), git2::Delta::Renamed | git2::Delta::Copied))
+        .filter(|delta| {
+            delta.old_file().path_bytes().is_some_and(is_requested)
+                || delta.new_file().path_bytes().is_some_and(is_requested)
+        })
+        .filter_map(|delta| {
+            Some(crate::protos::git_store::commit::Copy {
+                source: to_path_string(&delta.old_file())?,
+                target: to_path_string(&delta.new_file())?,
+                source_file: delta.old_file().id().as_bytes().to_vec(),
+            })
+        })
+        .collect();
+    Ok(copies)
+}
+
 /// Returns `RefEdit` that will create a ref in `refs/jj/keep` if not exist.
 /// Used for preventing GC of commits we create.
 fn to_no_gc_ref_update(id: &CommitId) -> gix::refs::transaction::RefEdit {
@@ -1142,6 +1299,7 @@ impl Backend for GitBackend {
             }
         }
         let extras = serialize_extras(&contents);
+        let stripped_extras = strip_extras_copies(&extras);
 
         // If two writers write commits of the same id with different metadata, they
         // will both succeed and the metadata entries will be "merged" later. Since
@@ -1185,7 +1343,9 @@ impl Backend for GitBackend {
                     })?;
 
             match table.get_value(git_id.as_bytes()) {
-                Some(existing_extras) if existing_extras != extras => {
+                Some(existing_extras)
+                    if strip_extras_copies(existing_extras) != stripped_extras =>
+                {
                     // It's possible a commit already exists with the same commit id but different
                     // change id. Adjust the timestamp until this is no longer the case.
                     committer.time.seconds -= 1;
@@ -1209,6 +1369,62 @@ impl Backend for GitBackend {
         Ok((id, contents))
     }
 
+    fn get_copy_records(
+        &self,
+        paths: Option<&[RepoPathBuf]>,
+        root: &CommitId,
+        head: &CommitId,
+    ) -> BackendResult<BoxStream<'_, BackendResult<CopyRecord>>> {
+        // Reading the commit also imports its extras if they're missing
+        let is_parent = self.read_commit(head).block_on()?.parents == [root.clone()];
+        let recorded_copies = if is_parent {
+            self.read_recorded_copies(head)?
+        } else {
+            None
+        };
+        let copies = if let Some(copies) = recorded_copies {
+            copies
+        } else {
+            // Git doesn't record copies, so they're inferred from the similarity
+            // of the files added and removed between the two trees.
+            let (Some(root_tree_id), Some(head_tree_id)) = (
+                self.resolved_root_tree_id(root)?,
+                self.resolved_root_tree_id(head)?,
+            ) else {
+                // Conflicted trees aren't stored as plain Git trees
+                return Ok(Box::pin(futures::stream::empty()));
+            };
+            // The copies from the parent are detected once for all paths and
+            // recorded in the extras.
+            let detect_paths = if is_parent { None } else { paths };
+            let copies = {
+                let git_repo = self.lock_git2_repo()?;
+                detect_copies(&git_repo, &root_tree_id, &head_tree_id, detect_paths)?
+            };
+            if is_parent {
+                self.record_copies(head, &copies)?;
+            }
+            copies
+        };
+        let records = copies
+            .into_iter()
+            .map(|copy| CopyRecord {
+                target: RepoPathBuf::from_internal_string(copy.target),
+                target_commit: head.clone(),
+                source: RepoPathBuf::from_internal_string(copy.source),
+                source_file: FileId::new(copy.source_file),
+                source_commit: root.clone(),
+            })
+            .filter(|record| {
+                paths.map_or(true, |paths| {
+                    paths.contains(&record.source) || paths.contains(&record.target)
+                })
+            })
+            .map(Ok)
+            .collect_vec();
+        Ok(Box::pin(futures::stream::iter(records)))
+    }
+
     #[tracing::instrument(skip(self, index))]
     fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
         let git_repo = self.lock_git_repo();
@@ -1361,6 +1577,7 @@ fn bytes_vec_from_json(value: &serde_json::Value) -> Vec<u8> {
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
+    use futures::StreamExt as _;
     use git2::Oid;
     use hex::ToHex;
     use pollster::FutureExt;
@@ -1881,6 +2098,112 @@ mod tests {
             .any(|git_ref| git_ref.target().unwrap() == git_id(&commit_id)));
     }
 
+    #[test]
+    fn get_copy_records_saves_parent_copies() {
+        let settings = user_settings();
+        let temp_dir = testutils::new_temp_dir();
+        let backend = GitBackend::init_internal(&settings, temp_dir.path()).unwrap();
+        let git_repo = backend.open_git_repo().unwrap();
+        let blob_id = git_repo.blob(b"1\n2\n3\n4\n5\n6\n7\n8\n").unwrap();
+        let write_tree = |name: &str| {
+            let mut builder = git_repo.treebuilder(None).unwrap();
+            builder.insert(name, blob_id, 0o100644).unwrap();
+            TreeId::from_bytes(builder.write().unwrap().as_bytes())
+        };
+        let signature = Signature {
+            name: "Someone".to_string(),
+            email: "someone@example.com".to_string(),
+            timestamp: Timestamp {
+                timestamp: MillisSinceEpoch(0),
+                tz_offset: 0,
+            },
+        };
+        let commit1 = Commit {
+            parents: vec![backend.root_commit_id().clone()],
+            predecessors: vec![],
+            root_tree: MergedTreeId::resolved(write_tree("a")),
+            change_id: ChangeId::from_hex("abc123"),
+            description: "".to_string(),
+            author: signature.clone(),
+            committer: signature,
+            secure_sig: None,
+        };
+        let commit1_id = backend.write_commit(commit1.clone(), None).unwrap().0;
+        let commit2 = Commit {
+            parents: vec![commit1_id.clone()],
+            root_tree: MergedTreeId::resolved(write_tree("b")),
+            change_id: ChangeId::from_hex("def456"),
+            ..commit1
+        };
+        let commit2_id = backend.write_commit(commit2, None).unwrap().0;
+
+        let read_extras = |id: &CommitId| {
+            let table = backend.cached_extra_metadata_table().unwrap();
+            let extras = table.get_value(id.as_bytes()).unwrap();
+            crate::protos::git_store::Commit::decode(extras).unwrap()
+        };
+        // Copies aren't detected when the commit is written
+        assert!(!read_extras(&commit2_id).copies_recorded);
+        let records: Vec<_> = backend
+            .get_copy_records(None, &commit1_id, &commit2_id)
+            .unwrap()
+            .collect::<Vec<_>>()
+            .block_on();
+        assert_eq!(records.len(), 1);
+        // The copies from the parent are recorded once queried
+        let extras = read_extras(&commit2_id);
+        assert!(extras.copies_recorded);
+        assert_eq!(
+            extras.copies,
+            vec![crate::protos::git_store::commit::Copy {
+                source: "a".to_owned(),
+                target: "b".to_owned(),
+                source_file: blob_id.as_bytes().to_vec(),
+            }]
+        );
+        let records: Vec<_> = backend
+            .get_copy_records(None, &commit1_id, &commit2_id)
+            .unwrap()
+            .collect::<Vec<_>>()
+            .block_on();
+        assert_eq!(records.len(), 1);
+        // Copies between unrelated commits aren't recorded
+        let records: Vec<_> = backend
+            .get_copy_records(None, backend.root_commit_id(), &commit2_id)
+            .unwrap()
+            .collect::<Vec<_>>()
+            .block_on();
+        assert_eq!(records.len(), 0);
+
+        // Copies of an imported commit are inferred when queried
+        let git_signature = git2::Signature::now("Someone", "someone@example.com").unwrap();
+        let git_parent = git_repo.find_commit(git_id(&commit1_id)).unwrap();
+        let git_tree_id = Oid::from_bytes(write_tree("c").as_bytes()).unwrap();
+        let git_tree = git_repo.find_tree(git_tree_id).unwrap();
+        let git_commit_id = git_repo
+            .commit(
+                None,
+                &git_signature,
+                &git_signature,
+                "git commit message",
+                &git_tree,
+                &[&git_parent],
+            )
+            .unwrap();
+        let commit3_id = CommitId::from_bytes(git_commit_id.as_bytes());
+        backend.import_head_commits([&commit3_id]).unwrap();
+        assert!(!read_extras(&commit3_id).copies_recorded);
+        let records: Vec<_> = backend
+            .get_copy_records(None, &commit1_id, &commit3_id)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .block_on();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].target.as_internal_file_string(), "c");
+        assert!(read_extras(&commit3_id).copies_recorded);
+    }
+
     #[test]
     fn import_head_commits_duplicates() {
         let settings = user_settings();
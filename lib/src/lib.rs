@@ -28,10 +28,13 @@ extern crate self as jj_lib;
 #[macro_use]
 pub mod content_hash;
 
+pub mod absorb;
+pub mod annotate;
 pub mod backend;
 pub mod commit;
 pub mod commit_builder;
 pub mod conflicts;
+pub mod copies;
 pub mod dag_walk;
 pub mod default_index;
 pub mod default_submodule_store;
@@ -59,6 +62,7 @@ pub mod local_working_copy;
 pub mod lock;
 pub mod matchers;
 pub mod merge;
+pub mod merge_drivers;
 pub mod merged_tree;
 pub mod object_id;
 pub mod op_heads_store;
@@ -84,6 +88,10 @@ pub mod stacked_table;
 pub mod store;
 pub mod str_util;
 pub mod submodule_store;
+#[cfg(feature = "testing")]
+pub mod test_signing_backend;
+pub mod time_util;
+pub mod trailer;
 pub mod transaction;
 pub mod tree;
 pub mod tree_builder;
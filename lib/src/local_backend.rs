@@ -15,6 +15,7 @@
 #![allow(missing_docs)]
 
 use std::any::Any;
+use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::fs;
 use std::fs::File;
@@ -24,20 +25,23 @@ use std::time::SystemTime;
 
 use async_trait::async_trait;
 use blake2::{Blake2b512, Digest};
+use futures::stream::BoxStream;
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
 use prost::Message;
 use tempfile::NamedTempFile;
 
 use crate::backend::{
     make_root_commit, Backend, BackendError, BackendResult, ChangeId, Commit, CommitId, Conflict,
-    ConflictId, ConflictTerm, FileId, MergedTreeId, MillisSinceEpoch, SecureSig, Signature,
-    SigningFn, SymlinkId, Timestamp, Tree, TreeId, TreeValue,
+    ConflictId, ConflictTerm, CopyRecord, FileId, MergedTreeId, MillisSinceEpoch, SecureSig,
+    Signature, SigningFn, SymlinkId, Timestamp, Tree, TreeId, TreeValue,
 };
 use crate::content_hash::blake2b_hash;
 use crate::file_util::persist_content_addressed_temp_file;
 use crate::index::Index;
 use crate::merge::MergeBuilder;
 use crate::object_id::ObjectId;
-use crate::repo_path::{RepoPath, RepoPathComponentBuf};
+use crate::repo_path::{RepoPath, RepoPathBuf, RepoPathComponentBuf};
 
 const COMMIT_ID_LENGTH: usize = 64;
 const CHANGE_ID_LENGTH: usize = 16;
@@ -120,6 +124,110 @@ impl LocalBackend {
     fn conflict_path(&self, id: &ConflictId) -> PathBuf {
         self.path.join("conflicts").join(id.hex())
     }
+
+    fn read_commit_proto(
+        &self,
+        id: &CommitId,
+    ) -> BackendResult<crate::protos::local_store::Commit> {
+        let path = self.commit_path(id);
+        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
+        crate::protos::local_store::Commit::decode(&*buf).map_err(to_other_err)
+    }
+
+    fn resolved_root_tree_id(&self, id: &CommitId) -> BackendResult<Option<TreeId>> {
+        let commit = self.read_commit(id).block_on()?;
+        match commit.root_tree {
+            MergedTreeId::Legacy(tree_id) => Ok(Some(tree_id)),
+            MergedTreeId::Merge(tree_ids) => Ok(tree_ids.into_resolved().ok()),
+        }
+    }
+
+    /// Detects the files copied or renamed between the `root_tree_id` and
+    /// `head_tree_id` trees. Since there's no similarity detection, a file is
+    /// considered a copy only if its contents are identical to the old contents
+    /// of a removed or modified file.
+    fn detect_copies(
+        &self,
+        root_tree_id: &TreeId,
+        head_tree_id: &TreeId,
+    ) -> BackendResult<Vec<crate::protos::local_store::commit::Copy>> {
+        let mut sources = vec![];
+        let mut targets = vec![];
+        self.collect_changed_files(
+            RepoPath::root(),
+            root_tree_id,
+            head_tree_id,
+            &mut sources,
+            &mut targets,
+        )?;
+        let copies = targets
+            .into_iter()
+            .filter_map(|(target, id)| {
+                let (source, _) = sources.iter().find(|(_, source_id)| *source_id == id)?;
+                Some(crate::protos::local_store::commit::Copy {
+                    source: source.as_internal_file_string().to_owned(),
+                    target: target.into_internal_string(),
+                    source_file: id.to_bytes(),
+                })
+            })
+            .collect();
+        Ok(copies)
+    }
+
+    /// Collects the files removed or modified in `right_id` as `sources`, and
+    /// the files added in `right_id` as `targets`. Only the subtrees that
+    /// differ are visited.
+    fn collect_changed_files(
+        &self,
+        dir: &RepoPath,
+        left_id: &TreeId,
+        right_id: &TreeId,
+        sources: &mut Vec<(RepoPathBuf, FileId)>,
+        targets: &mut Vec<(RepoPathBuf, FileId)>,
+    ) -> BackendResult<()> {
+        if left_id == right_id {
+            return Ok(());
+        }
+        let left = self.read_tree(dir, left_id).block_on()?;
+        let right = self.read_tree(dir, right_id).block_on()?;
+        let names: BTreeSet<_> = left
+            .entries()
+            .chain(right.entries())
+            .map(|entry| entry.name())
+            .collect();
+        for name in names {
+            let path = dir.join(name);
+            let left_value = left.value(name);
+            let right_value = right.value(name);
+            if left_value == right_value {
+                continue;
+            }
+            let to_tree_id = |value: Option<&TreeValue>| match value {
+                Some(TreeValue::Tree(id)) => Some(id.clone()),
+                _ => None,
+            };
+            let left_tree_id = to_tree_id(left_value);
+            let right_tree_id = to_tree_id(right_value);
+            if left_tree_id.is_some() || right_tree_id.is_some() {
+                self.collect_changed_files(
+                    &path,
+                    left_tree_id.as_ref().unwrap_or(&self.empty_tree_id),
+                    right_tree_id.as_ref().unwrap_or(&self.empty_tree_id),
+                    sources,
+                    targets,
+                )?;
+            }
+            if let Some(TreeValue::File { id, .. }) = left_value {
+                sources.push((path.clone(), id.clone()));
+            }
+            if let Some(TreeValue::File { id, .. }) = right_value {
+                if left_value.is_none() {
+                    targets.push((path, id.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -260,10 +368,7 @@ impl Backend for LocalBackend {
             ));
         }
 
-        let path = self.commit_path(id);
-        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
-
-        let proto = crate::protos::local_store::Commit::decode(&*buf).map_err(to_other_err)?;
+        let proto = self.read_commit_proto(id)?;
         Ok(commit_from_proto(proto))
     }
 
@@ -282,6 +387,15 @@ impl Backend for LocalBackend {
         let temp_file = NamedTempFile::new_in(&self.path).map_err(to_other_err)?;
 
         let mut proto = commit_to_proto(&commit);
+        let tree_id = match &commit.root_tree {
+            MergedTreeId::Legacy(tree_id) => Some(tree_id),
+            MergedTreeId::Merge(tree_ids) => tree_ids.as_resolved(),
+        };
+        if let ([parent_id], Some(tree_id)) = (commit.parents.as_slice(), tree_id) {
+            if let Some(parent_tree_id) = self.resolved_root_tree_id(parent_id)? {
+                proto.copies = self.detect_copies(&parent_tree_id, tree_id)?;
+            }
+        }
         if let Some(sign) = sign_with {
             let data = proto.encode_to_vec();
             let sig = sign(&data).map_err(to_other_err)?;
@@ -301,6 +415,49 @@ impl Backend for LocalBackend {
         Ok((id, commit))
     }
 
+    fn get_copy_records(
+        &self,
+        paths: Option<&[RepoPathBuf]>,
+        root: &CommitId,
+        head: &CommitId,
+    ) -> BackendResult<BoxStream<'_, BackendResult<CopyRecord>>> {
+        let head_proto = if *head == self.root_commit_id {
+            None
+        } else {
+            Some(self.read_commit_proto(head)?)
+        };
+        let copies = match head_proto {
+            // The copies from the parent were recorded when the commit was written
+            Some(proto) if proto.parents == [root.to_bytes()] => proto.copies,
+            _ => {
+                let (Some(root_tree_id), Some(head_tree_id)) = (
+                    self.resolved_root_tree_id(root)?,
+                    self.resolved_root_tree_id(head)?,
+                ) else {
+                    return Ok(Box::pin(futures::stream::empty()));
+                };
+                self.detect_copies(&root_tree_id, &head_tree_id)?
+            }
+        };
+        let records = copies
+            .into_iter()
+            .map(|copy| CopyRecord {
+                target: RepoPathBuf::from_internal_string(copy.target),
+                target_commit: head.clone(),
+                source: RepoPathBuf::from_internal_string(copy.source),
+                source_file: FileId::new(copy.source_file),
+                source_commit: root.clone(),
+            })
+            .filter(|record| {
+                paths.map_or(true, |paths| {
+                    paths.contains(&record.source) || paths.contains(&record.target)
+                })
+            })
+            .map(Ok)
+            .collect_vec();
+        Ok(Box::pin(futures::stream::iter(records)))
+    }
+
     fn gc(&self, _index: &dyn Index, _keep_newer: SystemTime) -> BackendResult<()> {
         Ok(())
     }
@@ -495,7 +652,7 @@ fn conflict_term_to_proto(part: &ConflictTerm) -> crate::protos::local_store::co
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
-    use pollster::FutureExt;
+    use futures::StreamExt as _;
 
     use super::*;
 
@@ -549,6 +706,79 @@ mod tests {
         assert_eq!(root_merge_commit, commit);
     }
 
+    #[test]
+    fn write_commit_records_copies() {
+        let temp_dir = testutils::new_temp_dir();
+        let backend = LocalBackend::init(temp_dir.path());
+        let write_file = |contents: &str| {
+            let file_id = backend
+                .write_file(RepoPath::root(), &mut contents.as_bytes())
+                .unwrap();
+            TreeValue::File {
+                id: file_id,
+                executable: false,
+            }
+        };
+        let write_tree = |entries: Vec<(&str, TreeValue)>| {
+            let mut tree = Tree::default();
+            for (name, value) in entries {
+                tree.set(RepoPathComponentBuf::from(name), value);
+            }
+            backend.write_tree(RepoPath::root(), &tree).unwrap()
+        };
+        let dir_id = write_tree(vec![("a", write_file("a\n"))]);
+        let tree1_id = write_tree(vec![
+            ("dir", TreeValue::Tree(dir_id)),
+            ("c", write_file("c\n")),
+        ]);
+        // `dir/a` is renamed to `b`, `c` is modified and copied to `d`
+        let tree2_id = write_tree(vec![
+            ("b", write_file("a\n")),
+            ("c", write_file("modified\n")),
+            ("d", write_file("c\n")),
+        ]);
+        let commit1 = Commit {
+            parents: vec![backend.root_commit_id().clone()],
+            predecessors: vec![],
+            root_tree: MergedTreeId::resolved(tree1_id),
+            change_id: ChangeId::from_hex("abc123"),
+            description: "".to_string(),
+            author: create_signature(),
+            committer: create_signature(),
+            secure_sig: None,
+        };
+        let commit1_id = backend.write_commit(commit1.clone(), None).unwrap().0;
+        let commit2 = Commit {
+            parents: vec![commit1_id.clone()],
+            root_tree: MergedTreeId::resolved(tree2_id),
+            ..commit1
+        };
+        let commit2_id = backend.write_commit(commit2, None).unwrap().0;
+
+        let proto = backend.read_commit_proto(&commit2_id).unwrap();
+        let copies = proto
+            .copies
+            .iter()
+            .map(|copy| (copy.source.as_str(), copy.target.as_str()))
+            .collect_vec();
+        assert_eq!(copies, vec![("dir/a", "b"), ("c", "d")]);
+
+        let get_records = |paths: Option<&[RepoPathBuf]>| {
+            backend
+                .get_copy_records(paths, &commit1_id, &commit2_id)
+                .unwrap()
+                .map(Result::unwrap)
+                .collect::<Vec<_>>()
+                .block_on()
+        };
+        assert_eq!(get_records(None).len(), 2);
+        let records = get_records(Some(&[RepoPathBuf::from_internal_string("d")]));
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].source.as_internal_file_string(), "c");
+        assert_eq!(records[0].source_commit, commit1_id);
+        assert_eq!(records[0].target_commit, commit2_id);
+    }
+
     fn create_signature() -> Signature {
         Signature {
             name: "Someone".to_string(),
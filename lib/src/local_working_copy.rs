@@ -41,12 +41,14 @@ use thiserror::Error;
 use tracing::{instrument, trace_span};
 
 use crate::backend::{
-    BackendError, BackendResult, FileId, MergedTreeId, MillisSinceEpoch, SymlinkId, TreeId,
-    TreeValue,
+    BackendError, BackendResult, CommitId, FileId, MergedTreeId, MillisSinceEpoch, SymlinkId,
+    TreeId, TreeValue,
 };
 use crate::commit::Commit;
-use crate::conflicts::{self, materialize_tree_value, MaterializedTreeValue};
+use crate::conflicts::{self, materialize_tree_value, ConflictMarkerStyle, MaterializedTreeValue};
 use crate::file_util::{check_symlink_support, try_symlink};
+#[cfg(all(feature = "inotify", target_os = "linux"))]
+use crate::fsmonitor::inotify;
 use crate::fsmonitor::FsmonitorSettings;
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::{watchman, WatchmanConfig};
@@ -62,10 +64,11 @@ use crate::op_store::{OperationId, WorkspaceId};
 use crate::repo_path::{RepoPath, RepoPathBuf, RepoPathComponent};
 use crate::settings::HumanByteSize;
 use crate::store::Store;
+use crate::submodule_store::{SubmoduleStore, SubmoduleStoreError};
 use crate::tree::Tree;
 use crate::working_copy::{
-    CheckoutError, CheckoutStats, LockedWorkingCopy, ResetError, SnapshotError, SnapshotOptions,
-    SnapshotProgress, WorkingCopy, WorkingCopyFactory, WorkingCopyStateError,
+    CheckoutError, CheckoutOptions, CheckoutStats, LockedWorkingCopy, ResetError, SnapshotError,
+    SnapshotOptions, SnapshotProgress, WorkingCopy, WorkingCopyFactory, WorkingCopyStateError,
 };
 
 #[cfg(unix)]
@@ -303,6 +306,8 @@ pub struct TreeState {
     /// the repo is configured to use the Watchman filesystem monitor and
     /// Watchman has been queried at least once.
     watchman_clock: Option<crate::protos::working_copy::WatchmanClock>,
+    /// The most recent clock value returned by the inotify daemon.
+    inotify_clock: Option<String>,
 }
 
 fn file_state_from_proto(proto: &crate::protos::working_copy::FileState) -> FileState {
@@ -475,6 +480,7 @@ fn file_state(metadata: &Metadata) -> Option<FileState> {
 struct FsmonitorMatcher {
     matcher: Option<Box<dyn Matcher>>,
     watchman_clock: Option<crate::protos::working_copy::WatchmanClock>,
+    inotify_clock: Option<String>,
 }
 
 struct DirectoryToVisit<'a> {
@@ -484,6 +490,98 @@ struct DirectoryToVisit<'a> {
     file_states: FileStates<'a>,
 }
 
+/// Tree value to be checked out by `TreeState::update()`. Unlike
+/// `MaterializedTreeValue`, a file is represented by its id, and its contents
+/// are streamed from the backend by the worker thread which writes it.
+enum CheckoutValue {
+    Absent,
+    AccessDenied,
+    File { id: FileId, executable: bool },
+    Symlink { target: String },
+    Conflict { contents: Vec<u8>, executable: bool },
+    GitSubmodule(CommitId),
+}
+
+impl CheckoutValue {
+    async fn materialize(
+        store: &Store,
+        path: &RepoPath,
+        value: MergedTreeValue,
+        conflict_marker_style: ConflictMarkerStyle,
+    ) -> BackendResult<Self> {
+        if let Some(Some(TreeValue::File { id, executable })) = value.as_resolved() {
+            return Ok(CheckoutValue::File {
+                id: id.clone(),
+                executable: *executable,
+            });
+        }
+        match materialize_tree_value(store, path, value, conflict_marker_style).await? {
+            MaterializedTreeValue::Absent => Ok(CheckoutValue::Absent),
+            MaterializedTreeValue::AccessDenied(_) => Ok(CheckoutValue::AccessDenied),
+            MaterializedTreeValue::File { id, executable, .. } => {
+                Ok(CheckoutValue::File { id, executable })
+            }
+            MaterializedTreeValue::Symlink { id: _, target } => {
+                Ok(CheckoutValue::Symlink { target })
+            }
+            MaterializedTreeValue::Conflict {
+                id: _,
+                contents,
+                executable,
+            } => Ok(CheckoutValue::Conflict {
+                contents,
+                executable,
+            }),
+            MaterializedTreeValue::GitSubmodule(id) => Ok(CheckoutValue::GitSubmodule(id)),
+            MaterializedTreeValue::Tree(_) => {
+                panic!("unexpected tree entry in diff at {path:?}");
+            }
+        }
+    }
+
+    fn is_absent(&self) -> bool {
+        matches!(self, CheckoutValue::Absent)
+    }
+
+    fn is_present(&self) -> bool {
+        !self.is_absent()
+    }
+
+    /// Size of the contents held in memory.
+    fn size(&self) -> usize {
+        match self {
+            CheckoutValue::Conflict { contents, .. } => contents.len(),
+            CheckoutValue::Symlink { target } => target.len(),
+            CheckoutValue::Absent
+            | CheckoutValue::AccessDenied
+            | CheckoutValue::File { .. }
+            | CheckoutValue::GitSubmodule(_) => 0,
+        }
+    }
+}
+
+/// Maximum size of the file contents to buffer before writing them out.
+const MAX_PENDING_CHECKOUT_SIZE: usize = 64 << 20;
+
+/// Filesystem updates queued by `TreeState::update()` to be done in a batch.
+#[derive(Default)]
+struct PendingCheckout {
+    /// Files to write, with their index into the changed file states.
+    writes: Vec<(usize, PathBuf, CheckoutValue)>,
+    /// Total size of the contents to write.
+    size: usize,
+    /// Removed files whose parent directories should be deleted if empty.
+    /// This has to be done after the writes since a directory may become
+    /// non-empty again.
+    removed_files: Vec<PathBuf>,
+}
+
+impl PendingCheckout {
+    fn is_empty(&self) -> bool {
+        self.writes.is_empty() && self.removed_files.is_empty()
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum TreeStateError {
     #[error("Reading tree state from {path}")]
@@ -555,6 +653,7 @@ impl TreeState {
             own_mtime: MillisSinceEpoch(0),
             symlink_support: check_symlink_support().unwrap_or(false),
             watchman_clock: None,
+            inotify_clock: None,
         }
     }
 
@@ -618,6 +717,7 @@ impl TreeState {
             FileStatesMap::from_proto(proto.file_states, proto.is_file_states_sorted);
         self.sparse_patterns = sparse_patterns_from_proto(proto.sparse_patterns.as_ref());
         self.watchman_clock = proto.watchman_clock;
+        self.inotify_clock = Some(proto.inotify_clock).filter(|clock| !clock.is_empty());
         Ok(())
     }
 
@@ -645,6 +745,7 @@ impl TreeState {
         }
         proto.sparse_patterns = Some(sparse_patterns);
         proto.watchman_clock = self.watchman_clock.clone();
+        proto.inotify_clock = self.inotify_clock.clone().unwrap_or_default();
 
         let mut temp_file = NamedTempFile::new_in(&self.state_path).unwrap();
         temp_file
@@ -755,6 +856,19 @@ impl TreeState {
             .map_err(|err| TreeStateError::Fsmonitor(Box::new(err)))
     }
 
+    #[cfg(all(feature = "inotify", target_os = "linux"))]
+    #[instrument(skip(self))]
+    pub fn query_inotify(&self) -> Result<(inotify::Clock, Option<Vec<PathBuf>>), TreeStateError> {
+        let fsmonitor = inotify::Fsmonitor::new(&inotify::socket_path(&self.state_path));
+        let previous_clock = self
+            .inotify_clock
+            .as_deref()
+            .and_then(|clock| clock.parse().ok());
+        fsmonitor
+            .query_changed_files(previous_clock)
+            .map_err(|err| TreeStateError::Fsmonitor(Box::new(err)))
+    }
+
     /// Look for changes to the working copy. If there are any changes, create
     /// a new tree from it and return it, and also update the dirstate on disk.
     #[instrument(skip_all)]
@@ -764,6 +878,8 @@ impl TreeState {
             fsmonitor_settings,
             progress,
             max_new_file_size,
+            conflict_marker_style,
+            submodule_store,
         } = options;
 
         let sparse_matcher = self.sparse_matcher();
@@ -773,6 +889,7 @@ impl TreeState {
         let FsmonitorMatcher {
             matcher: fsmonitor_matcher,
             watchman_clock,
+            inotify_clock,
         } = self.make_fsmonitor_matcher(fsmonitor_settings)?;
         let fsmonitor_matcher = match fsmonitor_matcher.as_ref() {
             None => &EverythingMatcher,
@@ -783,6 +900,7 @@ impl TreeState {
         if matcher.visit(RepoPath::root()).is_nothing() {
             // No need to iterate file states to build empty deleted_files.
             self.watchman_clock = watchman_clock;
+            self.inotify_clock = inotify_clock;
             return Ok(is_dirty);
         }
 
@@ -807,6 +925,8 @@ impl TreeState {
                 directory_to_visit,
                 progress,
                 max_new_file_size,
+                conflict_marker_style,
+                submodule_store.as_deref(),
             )
         })?;
 
@@ -866,6 +986,7 @@ impl TreeState {
             assert_eq!(state_paths, tree_paths);
         }
         self.watchman_clock = watchman_clock;
+        self.inotify_clock = inotify_clock;
         Ok(is_dirty)
     }
 
@@ -880,6 +1001,8 @@ impl TreeState {
         directory_to_visit: DirectoryToVisit,
         progress: Option<&SnapshotProgress>,
         max_new_file_size: u64,
+        conflict_marker_style: ConflictMarkerStyle,
+        submodule_store: Option<&dyn SubmoduleStore>,
     ) -> Result<(), SnapshotError> {
         let DirectoryToVisit {
             dir,
@@ -923,12 +1046,29 @@ impl TreeState {
                 let maybe_current_file_state = file_states.get(&path);
                 if let Some(file_state) = &maybe_current_file_state {
                     if file_state.file_type == FileType::GitSubmodule {
+                        if let Some(submodule_store) = submodule_store {
+                            let update = self.get_updated_submodule_value(
+                                submodule_store,
+                                &path,
+                                &entry.path(),
+                                current_tree,
+                            )?;
+                            if let Some(tree_value) = update {
+                                tree_entries_tx.send((path, tree_value)).ok();
+                            }
+                        }
                         return Ok(());
                     }
                 }
 
                 if file_type.is_dir() {
                     let file_states = file_states.prefixed(&path);
+                    if file_states.is_empty() && entry.path().join(".git").exists() {
+                        // Don't track the contents of an untracked nested
+                        // repository, such as the checkout left behind by a
+                        // submodule that was removed from the tree.
+                        return Ok(());
+                    }
                     if git_ignore.matches(&path.to_internal_dir_string()) {
                         // If the whole directory is ignored, visit only paths we're already
                         // tracking.
@@ -960,6 +1100,7 @@ impl TreeState {
                                     Some(&current_file_state),
                                     current_tree,
                                     &new_file_state,
+                                    conflict_marker_style,
                                 )?;
                                 if let Some(tree_value) = update {
                                     tree_entries_tx
@@ -989,6 +1130,8 @@ impl TreeState {
                             directory_to_visit,
                             progress,
                             max_new_file_size,
+                            conflict_marker_style,
+                            submodule_store,
                         )?;
                     }
                 } else if matcher.matches(&path) {
@@ -1022,6 +1165,7 @@ impl TreeState {
                                 maybe_current_file_state.as_ref(),
                                 current_tree,
                                 &new_file_state,
+                                conflict_marker_style,
                             )?;
                             if let Some(tree_value) = update {
                                 tree_entries_tx.send((path.clone(), tree_value)).ok();
@@ -1043,15 +1187,17 @@ impl TreeState {
         &self,
         fsmonitor_settings: FsmonitorSettings,
     ) -> Result<FsmonitorMatcher, SnapshotError> {
-        let (watchman_clock, changed_files) = match fsmonitor_settings {
-            FsmonitorSettings::None => (None, None),
-            FsmonitorSettings::Test { changed_files } => (None, Some(changed_files)),
+        let (watchman_clock, inotify_clock, changed_files) = match fsmonitor_settings {
+            FsmonitorSettings::None => (None, None, None),
+            FsmonitorSettings::Test { changed_files } => (None, None, Some(changed_files)),
             #[cfg(feature = "watchman")]
             FsmonitorSettings::Watchman(config) => match self.query_watchman(&config) {
-                Ok((watchman_clock, changed_files)) => (Some(watchman_clock.into()), changed_files),
+                Ok((watchman_clock, changed_files)) => {
+                    (Some(watchman_clock.into()), None, changed_files)
+                }
                 Err(err) => {
                     tracing::warn!(?err, "Failed to query filesystem monitor");
-                    (None, None)
+                    (None, None, None)
                 }
             },
             #[cfg(not(feature = "watchman"))]
@@ -1063,6 +1209,26 @@ impl TreeState {
                         .into(),
                 });
             }
+            #[cfg(all(feature = "inotify", target_os = "linux"))]
+            FsmonitorSettings::Inotify => match self.query_inotify() {
+                Ok((inotify_clock, changed_files)) => {
+                    (None, Some(inotify_clock.to_string()), changed_files)
+                }
+                Err(err) => {
+                    tracing::warn!(?err, "Failed to query filesystem monitor");
+                    (None, None, None)
+                }
+            },
+            #[cfg(not(all(feature = "inotify", target_os = "linux")))]
+            FsmonitorSettings::Inotify => {
+                return Err(SnapshotError::Other {
+                    message: "Failed to query the filesystem monitor".to_string(),
+                    err: "Cannot query the inotify daemon because jj was not compiled with the \
+                          `inotify` feature or isn't running on Linux (consider disabling \
+                          `core.fsmonitor`)"
+                        .into(),
+                });
+            }
         };
         let matcher: Option<Box<dyn Matcher>> = match changed_files {
             None => None,
@@ -1074,15 +1240,43 @@ impl TreeState {
                         .collect_vec()
                 });
 
-                Some(Box::new(FilesMatcher::new(repo_paths)))
+                if inotify_clock.is_some() {
+                    // inotify reports a renamed or created directory without
+                    // its contents.
+                    Some(Box::new(PrefixMatcher::new(repo_paths)))
+                } else {
+                    Some(Box::new(FilesMatcher::new(repo_paths)))
+                }
             }
         };
         Ok(FsmonitorMatcher {
             matcher,
             watchman_clock,
+            inotify_clock,
         })
     }
 
+    fn get_updated_submodule_value(
+        &self,
+        submodule_store: &dyn SubmoduleStore,
+        repo_path: &RepoPath,
+        disk_path: &Path,
+        current_tree: &MergedTree,
+    ) -> Result<Option<MergedTreeValue>, SnapshotError> {
+        let checked_out_id = submodule_store
+            .get_checked_out_commit(repo_path, disk_path)
+            .map_err(|err| SnapshotError::Other {
+                message: format!("Failed to read submodule {}", disk_path.display()),
+                err: err.into(),
+            })?;
+        let Some(id) = checked_out_id else {
+            return Ok(None);
+        };
+        let new_tree_values = Merge::normal(TreeValue::GitSubmodule(id));
+        let current_tree_values = current_tree.path_value(repo_path)?;
+        Ok((new_tree_values != current_tree_values).then_some(new_tree_values))
+    }
+
     fn get_updated_tree_value(
         &self,
         repo_path: &RepoPath,
@@ -1090,6 +1284,7 @@ impl TreeState {
         maybe_current_file_state: Option<&FileState>,
         current_tree: &MergedTree,
         new_file_state: &FileState,
+        conflict_marker_style: ConflictMarkerStyle,
     ) -> Result<Option<MergedTreeValue>, SnapshotError> {
         let clean = match maybe_current_file_state {
             None => {
@@ -1123,6 +1318,7 @@ impl TreeState {
                     &disk_path,
                     &current_tree_values,
                     executable,
+                    conflict_marker_style,
                 )?,
                 FileType::Symlink => {
                     let id = self.write_symlink_to_store(repo_path, &disk_path)?;
@@ -1144,6 +1340,7 @@ impl TreeState {
         disk_path: &Path,
         current_tree_values: &MergedTreeValue,
         executable: FileExecutableFlag,
+        conflict_marker_style: ConflictMarkerStyle,
     ) -> Result<MergedTreeValue, SnapshotError> {
         // If the file contained a conflict before and is now a normal file on disk, we
         // try to parse any conflict markers in the file into a conflict.
@@ -1172,6 +1369,7 @@ impl TreeState {
                 self.store.as_ref(),
                 repo_path,
                 &content,
+                conflict_marker_style,
             )
             .block_on()?;
             match new_file_ids.into_resolved() {
@@ -1277,6 +1475,28 @@ impl TreeState {
     }
 
     #[cfg_attr(windows, allow(unused_variables))]
+    fn write_submodule(
+        &self,
+        submodule_store: &dyn SubmoduleStore,
+        path: &RepoPath,
+        disk_path: &Path,
+        id: &CommitId,
+    ) -> Result<(), CheckoutError> {
+        fs::create_dir_all(disk_path).map_err(|err| CheckoutError::Other {
+            message: format!("Failed to create directory {}", disk_path.display()),
+            err: err.into(),
+        })?;
+        match submodule_store.check_out(path, disk_path, id) {
+            // Like Git, leave an empty directory behind if the submodule
+            // hasn't been fetched yet.
+            Ok(()) | Err(SubmoduleStoreError::NotFound { .. }) => Ok(()),
+            Err(err) => Err(CheckoutError::Other {
+                message: format!("Failed to check out submodule {}", disk_path.display()),
+                err: err.into(),
+            }),
+        }
+    }
+
     fn set_executable(&self, disk_path: &Path, executable: bool) -> Result<(), CheckoutError> {
         #[cfg(unix)]
         {
@@ -1287,7 +1507,11 @@ impl TreeState {
         Ok(())
     }
 
-    pub fn check_out(&mut self, new_tree: &MergedTree) -> Result<CheckoutStats, CheckoutError> {
+    pub fn check_out(
+        &mut self,
+        new_tree: &MergedTree,
+        options: &CheckoutOptions,
+    ) -> Result<CheckoutStats, CheckoutError> {
         let old_tree = self.current_tree().map_err(|err| match err {
             err @ BackendError::ObjectNotFound { .. } => CheckoutError::SourceNotFound {
                 source: Box::new(err),
@@ -1295,7 +1519,7 @@ impl TreeState {
             other => CheckoutError::InternalBackendError(other),
         })?;
         let stats = self
-            .update(&old_tree, new_tree, self.sparse_matcher().as_ref())
+            .update(&old_tree, new_tree, self.sparse_matcher().as_ref(), options)
             .block_on()?;
         self.tree_id = new_tree.id();
         Ok(stats)
@@ -1304,6 +1528,7 @@ impl TreeState {
     pub fn set_sparse_patterns(
         &mut self,
         sparse_patterns: Vec<RepoPathBuf>,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         let tree = self.current_tree().map_err(|err| match err {
             err @ BackendError::ObjectNotFound { .. } => CheckoutError::SourceNotFound {
@@ -1316,9 +1541,11 @@ impl TreeState {
         let added_matcher = DifferenceMatcher::new(&new_matcher, &old_matcher);
         let removed_matcher = DifferenceMatcher::new(&old_matcher, &new_matcher);
         let empty_tree = MergedTree::resolved(Tree::null(self.store.clone(), RepoPathBuf::root()));
-        let added_stats = self.update(&empty_tree, &tree, &added_matcher).block_on()?;
+        let added_stats = self
+            .update(&empty_tree, &tree, &added_matcher, options)
+            .block_on()?;
         let removed_stats = self
-            .update(&tree, &empty_tree, &removed_matcher)
+            .update(&tree, &empty_tree, &removed_matcher, options)
             .block_on()?;
         self.sparse_patterns = sparse_patterns;
         assert_eq!(added_stats.updated_files, 0);
@@ -1339,6 +1566,7 @@ impl TreeState {
         old_tree: &MergedTree,
         new_tree: &MergedTree,
         matcher: &dyn Matcher,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: maybe it's better not include the skipped counts in the "intended"
         // counts
@@ -1350,13 +1578,20 @@ impl TreeState {
         };
         let mut changed_file_states = Vec::new();
         let mut deleted_files = HashSet::new();
+        let mut pending = PendingCheckout::default();
         let mut diff_stream = Box::pin(
             old_tree
                 .diff_stream(new_tree, matcher)
                 .map(|(path, diff)| async {
                     match diff {
                         Ok((before, after)) => {
-                            let result = materialize_tree_value(&self.store, &path, after).await;
+                            let result = CheckoutValue::materialize(
+                                &self.store,
+                                &path,
+                                after,
+                                options.conflict_marker_style,
+                            )
+                            .await;
                             (path, result.map(|value| (before.is_present(), value)))
                         }
                         Err(err) => (path, Err(err)),
@@ -1375,12 +1610,26 @@ impl TreeState {
             }
             let disk_path = path.to_fs_path(&self.working_copy_path);
 
+            let is_submodule = matches!(after, CheckoutValue::GitSubmodule(_));
+            let is_occupied =
+                |disk_path: &Path| disk_path.exists() && !(is_submodule && disk_path.is_dir());
             if present_before {
                 fs::remove_file(&disk_path).ok();
-            } else if disk_path.exists() {
-                changed_file_states.push((path, FileState::placeholder()));
-                stats.skipped_files += 1;
-                continue;
+            } else if is_occupied(&disk_path) {
+                // The path may be a directory left by the removed files, which
+                // will be deleted once the pending removals are done.
+                if !pending.removed_files.is_empty() {
+                    self.write_pending_checkout(
+                        &mut pending,
+                        &mut changed_file_states,
+                        &mut deleted_files,
+                    )?;
+                }
+                if is_occupied(&disk_path) {
+                    changed_file_states.push((path, FileState::placeholder()));
+                    stats.skipped_files += 1;
+                    continue;
+                }
             }
             if after.is_present() {
                 let skip = create_parent_dirs(&self.working_copy_path, &path)?;
@@ -1391,50 +1640,117 @@ impl TreeState {
                 }
             }
             // TODO: Check that the file has not changed before overwriting/removing it.
-            let file_state = match after {
-                MaterializedTreeValue::Absent | MaterializedTreeValue::AccessDenied(_) => {
-                    let mut parent_dir = disk_path.parent().unwrap();
-                    loop {
-                        if fs::remove_dir(parent_dir).is_err() {
-                            break;
-                        }
-                        parent_dir = parent_dir.parent().unwrap();
-                    }
+            match after {
+                CheckoutValue::Absent | CheckoutValue::AccessDenied => {
+                    pending.removed_files.push(disk_path);
                     deleted_files.insert(path);
-                    continue;
                 }
-                MaterializedTreeValue::File {
-                    executable,
-                    mut reader,
-                    ..
-                } => self.write_file(&disk_path, &mut reader, executable)?,
-                MaterializedTreeValue::Symlink { id: _, target } => {
-                    if self.symlink_support {
-                        self.write_symlink(&disk_path, target)?
-                    } else {
-                        self.write_file(&disk_path, &mut target.as_bytes(), false)?
+                CheckoutValue::GitSubmodule(id) => {
+                    if let Some(submodule_store) = &options.submodule_store {
+                        self.write_submodule(submodule_store.as_ref(), &path, &disk_path, &id)?;
                     }
+                    changed_file_states.push((path, FileState::for_gitsubmodule()));
                 }
-                MaterializedTreeValue::GitSubmodule(_) => {
-                    println!("ignoring git submodule at {path:?}");
-                    FileState::for_gitsubmodule()
-                }
-                MaterializedTreeValue::Tree(_) => {
-                    panic!("unexpected tree entry in diff at {path:?}");
+                CheckoutValue::File { .. }
+                | CheckoutValue::Symlink { .. }
+                | CheckoutValue::Conflict { .. } => {
+                    // The file state will be filled in once the file is written.
+                    pending.size += after.size();
+                    pending
+                        .writes
+                        .push((changed_file_states.len(), disk_path, after));
+                    changed_file_states.push((path, FileState::placeholder()));
+                    if pending.size >= MAX_PENDING_CHECKOUT_SIZE {
+                        self.write_pending_checkout(
+                            &mut pending,
+                            &mut changed_file_states,
+                            &mut deleted_files,
+                        )?;
+                    }
                 }
-                MaterializedTreeValue::Conflict {
-                    id: _,
-                    contents,
-                    executable,
-                } => self.write_conflict(&disk_path, contents, executable)?,
-            };
-            changed_file_states.push((path, file_state));
+            }
+        }
+        if !pending.is_empty() {
+            self.write_pending_checkout(
+                &mut pending,
+                &mut changed_file_states,
+                &mut deleted_files,
+            )?;
         }
+        // Files which turned out to be inaccessible when writing them were
+        // deleted instead.
+        changed_file_states.retain(|(path, _)| !deleted_files.contains(path));
         self.file_states
             .merge_in(changed_file_states, &deleted_files);
         Ok(stats)
     }
 
+    /// Writes the queued files in parallel, and then deletes the directories
+    /// left empty by the removed files.
+    fn write_pending_checkout(
+        &self,
+        pending: &mut PendingCheckout,
+        changed_file_states: &mut [(RepoPathBuf, FileState)],
+        deleted_files: &mut HashSet<RepoPathBuf>,
+    ) -> Result<(), CheckoutError> {
+        let paths = &*changed_file_states;
+        let written_file_states: Vec<_> = mem::take(&mut pending.writes)
+            .into_par_iter()
+            .map(|(index, disk_path, value)| {
+                let file_state = match value {
+                    CheckoutValue::File { id, executable } => {
+                        let path = &paths[index].0;
+                        let mut reader = match self.store.read_file(path, &id) {
+                            Ok(reader) => reader,
+                            Err(BackendError::ReadAccessDenied { .. }) => {
+                                return Ok((index, disk_path, None));
+                            }
+                            Err(err) => return Err(err.into()),
+                        };
+                        self.write_file(&disk_path, &mut reader, executable)?
+                    }
+                    CheckoutValue::Symlink { target } => {
+                        if self.symlink_support {
+                            self.write_symlink(&disk_path, target)?
+                        } else {
+                            self.write_file(&disk_path, &mut target.as_bytes(), false)?
+                        }
+                    }
+                    CheckoutValue::Conflict {
+                        contents,
+                        executable,
+                    } => self.write_conflict(&disk_path, contents, executable)?,
+                    CheckoutValue::Absent
+                    | CheckoutValue::AccessDenied
+                    | CheckoutValue::GitSubmodule(_) => {
+                        panic!("unexpected value to write at {}", disk_path.display());
+                    }
+                };
+                Ok((index, disk_path, Some(file_state)))
+            })
+            .collect::<Result<_, CheckoutError>>()?;
+        for (index, disk_path, file_state) in written_file_states {
+            match file_state {
+                Some(file_state) => changed_file_states[index].1 = file_state,
+                None => {
+                    deleted_files.insert(changed_file_states[index].0.clone());
+                    pending.removed_files.push(disk_path);
+                }
+            }
+        }
+        pending.size = 0;
+        for disk_path in mem::take(&mut pending.removed_files) {
+            let mut parent_dir = disk_path.parent().unwrap();
+            loop {
+                if fs::remove_dir(parent_dir).is_err() {
+                    break;
+                }
+                parent_dir = parent_dir.parent().unwrap();
+            }
+        }
+        Ok(())
+    }
+
     pub async fn reset(&mut self, new_tree: &MergedTree) -> Result<(), ResetError> {
         let old_tree = self.current_tree().map_err(|err| match err {
             err @ BackendError::ObjectNotFound { .. } => ResetError::SourceNotFound {
@@ -1462,10 +1778,7 @@ impl TreeState {
                         TreeValue::Conflict(_id) => {
                             panic!("unexpected conflict entry in diff at {path:?}");
                         }
-                        TreeValue::GitSubmodule(_id) => {
-                            println!("ignoring git submodule at {path:?}");
-                            FileType::GitSubmodule
-                        }
+                        TreeValue::GitSubmodule(_id) => FileType::GitSubmodule,
                         TreeValue::Tree(_id) => {
                             panic!("unexpected tree entry in diff at {path:?}");
                         }
@@ -1725,6 +2038,24 @@ impl LocalWorkingCopy {
                 err: err.into(),
             })
     }
+
+    /// Path to the socket the inotify daemon listens on for this working copy.
+    #[cfg(all(feature = "inotify", target_os = "linux"))]
+    pub fn inotify_socket_path(&self) -> PathBuf {
+        inotify::socket_path(&self.state_path)
+    }
+
+    #[cfg(all(feature = "inotify", target_os = "linux"))]
+    pub fn query_inotify(
+        &self,
+    ) -> Result<(inotify::Clock, Option<Vec<PathBuf>>), WorkingCopyStateError> {
+        self.tree_state()?
+            .query_inotify()
+            .map_err(|err| WorkingCopyStateError {
+                message: "Failed to query the inotify daemon".to_string(),
+                err: err.into(),
+            })
+    }
 }
 
 pub struct LocalWorkingCopyFactory {}
@@ -1801,7 +2132,11 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
         Ok(tree_state.current_tree_id().clone())
     }
 
-    fn check_out(&mut self, commit: &Commit) -> Result<CheckoutStats, CheckoutError> {
+    fn check_out(
+        &mut self,
+        commit: &Commit,
+        options: &CheckoutOptions,
+    ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: Write a "pending_checkout" file with the new TreeId so we can
         // continue an interrupted update if we find such a file.
         let new_tree = commit.tree()?;
@@ -1812,7 +2147,7 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
                 message: "Failed to load the working copy state".to_string(),
                 err: err.into(),
             })?
-            .check_out(&new_tree)?;
+            .check_out(&new_tree, options)?;
         self.tree_state_dirty = true;
         Ok(stats)
     }
@@ -1852,6 +2187,7 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
     fn set_sparse_patterns(
         &mut self,
         new_sparse_patterns: Vec<RepoPathBuf>,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: Write a "pending_checkout" file with new sparse patterns so we can
         // continue an interrupted update if we find such a file.
@@ -1862,7 +2198,7 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
                 message: "Failed to load the working copy state".to_string(),
                 err: err.into(),
             })?
-            .set_sparse_patterns(new_sparse_patterns)?;
+            .set_sparse_patterns(new_sparse_patterns, options)?;
         self.tree_state_dirty = true;
         Ok(stats)
     }
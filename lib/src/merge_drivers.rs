@@ -0,0 +1,301 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-file merge drivers which resolve content conflicts before falling back
+//! to the line-based merge.
+//!
+//! Merge drivers are configured in the `merge-drivers` table. Each driver
+//! applies to the files matched by a fileset expression, and either uses a
+//! builtin strategy or runs an external command:
+//!
+//! ```toml
+//! [merge-drivers.lockfiles]
+//! files = 'glob:"**/Cargo.lock"'
+//! builtin = "theirs"
+//!
+//! [merge-drivers.json]
+//! files = 'glob:"**/*.json"'
+//! command = ["json-merge", "%base", "%left", "%right", "-o", "%output"]
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use config::ConfigError;
+use itertools::Itertools as _;
+use thiserror::Error;
+
+use crate::files::{self, ContentHunk, MergeResult};
+use crate::fileset::{self, FilesetExpression, FilesetParseError};
+use crate::merge::Merge;
+use crate::repo_path::{RepoPath, RepoPathUiConverter};
+use crate::settings::{ConfigResultExt as _, UserSettings};
+
+/// Error while loading the merge drivers from the config.
+#[derive(Debug, Error)]
+pub enum MergeDriverConfigError {
+    /// The config couldn't be read.
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    /// The `files` expression of a driver couldn't be parsed.
+    #[error("Invalid fileset for merge driver '{name}'")]
+    Fileset {
+        /// Name of the driver.
+        name: String,
+        /// Underlying error.
+        source: FilesetParseError,
+    },
+    /// The driver isn't configured correctly.
+    #[error("Invalid merge driver '{name}': {message}")]
+    InvalidDriver {
+        /// Name of the driver.
+        name: String,
+        /// Description of the problem.
+        message: String,
+    },
+}
+
+/// Error while running a merge driver.
+#[derive(Debug, Error)]
+#[error("Failed to run merge driver '{name}'")]
+pub struct MergeDriverError {
+    /// Name of the driver.
+    pub name: String,
+    /// Underlying error.
+    pub source: std::io::Error,
+}
+
+/// Builtin merge strategies.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuiltinMergeStrategy {
+    /// Keep the lines of all sides where they conflict, like Git's `union`
+    /// merge driver.
+    Union,
+    /// Keep the content of the first side.
+    Ours,
+    /// Keep the content of the last side.
+    Theirs,
+}
+
+/// How a merge driver resolves conflicts.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MergeDriverKind {
+    /// Use a builtin strategy.
+    Builtin(BuiltinMergeStrategy),
+    /// Run an external command. The `%base`, `%left`, `%right` and `%output`
+    /// arguments are replaced with paths to temporary files.
+    Command(Vec<String>),
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MergeDriverConfig {
+    files: String,
+    builtin: Option<BuiltinMergeStrategy>,
+    command: Option<Vec<String>>,
+}
+
+/// Merge driver for the files matching a fileset.
+#[derive(Clone, Debug)]
+pub struct MergeDriver {
+    name: String,
+    files: FilesetExpression,
+    kind: MergeDriverKind,
+}
+
+impl MergeDriver {
+    /// Creates a driver applying to the files matched by `files`.
+    pub fn new(name: impl Into<String>, files: FilesetExpression, kind: MergeDriverKind) -> Self {
+        MergeDriver {
+            name: name.into(),
+            files,
+            kind,
+        }
+    }
+
+    /// Name of the driver in the config.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How the driver resolves conflicts.
+    pub fn kind(&self) -> &MergeDriverKind {
+        &self.kind
+    }
+
+    /// Merges the `contents` of a file. Returns `None` if the driver couldn't
+    /// resolve the conflict, in which case the caller should fall back to the
+    /// line-based merge.
+    ///
+    /// Conflicts with more than two sides are merged one 3-way merge at a
+    /// time, each merging the previous result with the next side.
+    pub fn merge(&self, contents: &Merge<&[u8]>) -> Result<Option<Vec<u8>>, MergeDriverError> {
+        match &self.kind {
+            MergeDriverKind::Builtin(BuiltinMergeStrategy::Ours) => {
+                Ok(Some(contents.first().to_vec()))
+            }
+            MergeDriverKind::Builtin(BuiltinMergeStrategy::Theirs) => {
+                Ok(Some(contents.adds().last().unwrap().to_vec()))
+            }
+            MergeDriverKind::Builtin(BuiltinMergeStrategy::Union) => {
+                Ok(Some(union_merge(contents)))
+            }
+            MergeDriverKind::Command(command) => {
+                let mut merged = contents.first().to_vec();
+                for (base, right) in contents.removes().zip(contents.adds().skip(1)) {
+                    let Some(content) = self.run_command(command, base, &merged, right)? else {
+                        return Ok(None);
+                    };
+                    merged = content;
+                }
+                Ok(Some(merged))
+            }
+        }
+    }
+
+    fn run_command(
+        &self,
+        command: &[String],
+        base: &[u8],
+        left: &[u8],
+        right: &[u8],
+    ) -> Result<Option<Vec<u8>>, MergeDriverError> {
+        let to_err = |source| MergeDriverError {
+            name: self.name.clone(),
+            source,
+        };
+        let temp_dir = tempfile::Builder::new()
+            .prefix("jj-merge-")
+            .tempdir()
+            .map_err(to_err)?;
+        let write_file = |name: &str, content: &[u8]| -> std::io::Result<PathBuf> {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, content)?;
+            Ok(path)
+        };
+        let base_path = write_file("base", base).map_err(to_err)?;
+        let left_path = write_file("left", left).map_err(to_err)?;
+        let right_path = write_file("right", right).map_err(to_err)?;
+        let output_path = write_file("output", b"").map_err(to_err)?;
+        let args = command[1..].iter().map(|arg| match arg.as_str() {
+            "%base" => base_path.as_os_str().to_owned(),
+            "%left" => left_path.as_os_str().to_owned(),
+            "%right" => right_path.as_os_str().to_owned(),
+            "%output" => output_path.as_os_str().to_owned(),
+            _ => arg.into(),
+        });
+        let status = Command::new(&command[0])
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .status()
+            .map_err(to_err)?;
+        if !status.success() {
+            return Ok(None);
+        }
+        fs::read(&output_path).map(Some).map_err(to_err)
+    }
+}
+
+/// Merges `contents` line by line, keeping the lines of all sides in order
+/// where they conflict.
+fn union_merge(contents: &Merge<&[u8]>) -> Vec<u8> {
+    match files::merge(contents) {
+        MergeResult::Resolved(ContentHunk(content)) => content,
+        MergeResult::Conflict(hunks) => {
+            let mut content = vec![];
+            for hunk in &hunks {
+                if let Some(ContentHunk(resolved)) = hunk.as_resolved() {
+                    content.extend_from_slice(resolved);
+                } else {
+                    for ContentHunk(side) in hunk.adds() {
+                        content.extend_from_slice(side);
+                    }
+                }
+            }
+            content
+        }
+    }
+}
+
+/// Merge drivers configured for a repo.
+#[derive(Debug, Default)]
+pub struct MergeDrivers {
+    drivers: Vec<MergeDriver>,
+}
+
+impl MergeDrivers {
+    /// Creates a set of drivers which are tried in order.
+    pub fn new(drivers: Vec<MergeDriver>) -> Self {
+        MergeDrivers { drivers }
+    }
+
+    /// Loads the drivers from the `merge-drivers` table of the config. The
+    /// drivers are tried in the alphabetical order of their names.
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, MergeDriverConfigError> {
+        let configs: BTreeMap<String, MergeDriverConfig> = settings
+            .config()
+            .get("merge-drivers")
+            .optional()?
+            .unwrap_or_default();
+        // The file patterns are relative to the repo root.
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: PathBuf::new(),
+            base: PathBuf::new(),
+        };
+        let drivers = configs
+            .into_iter()
+            .map(|(name, config)| {
+                let files = fileset::parse_maybe_bare(&config.files, &path_converter).map_err(
+                    |source| MergeDriverConfigError::Fileset {
+                        name: name.clone(),
+                        source,
+                    },
+                )?;
+                let invalid = |message: &str| MergeDriverConfigError::InvalidDriver {
+                    name: name.clone(),
+                    message: message.to_owned(),
+                };
+                let kind = match (config.builtin, config.command) {
+                    (Some(strategy), None) => MergeDriverKind::Builtin(strategy),
+                    (None, Some(command)) => {
+                        if command.is_empty() {
+                            return Err(invalid("The command is empty"));
+                        }
+                        if !command.iter().any(|arg| arg == "%output") {
+                            return Err(invalid("The command doesn't have an %output argument"));
+                        }
+                        MergeDriverKind::Command(command)
+                    }
+                    (None, None) => return Err(invalid("Either builtin or command must be set")),
+                    (Some(_), Some(_)) => {
+                        return Err(invalid("Only one of builtin or command can be set"));
+                    }
+                };
+                Ok(MergeDriver::new(name, files, kind))
+            })
+            .try_collect()?;
+        Ok(MergeDrivers { drivers })
+    }
+
+    /// Returns the first driver which applies to `path`.
+    pub fn find(&self, path: &RepoPath) -> Option<&MergeDriver> {
+        self.drivers
+            .iter()
+            .find(|driver| driver.files.to_matcher().matches(path))
+    }
+}
@@ -29,11 +29,13 @@ use itertools::Itertools;
 
 use crate::backend;
 use crate::backend::{BackendResult, ConflictId, MergedTreeId, TreeId, TreeValue};
+use crate::copies::{copies_tree_diff_stream, CopiesTreeDiffStream, CopyRecords};
 use crate::matchers::{EverythingMatcher, Matcher};
 use crate::merge::{Merge, MergeBuilder, MergedTreeValue};
+use crate::merge_drivers::MergeDrivers;
 use crate::repo_path::{RepoPath, RepoPathBuf, RepoPathComponent};
 use crate::store::Store;
-use crate::tree::{try_resolve_file_conflict, Tree};
+use crate::tree::{try_resolve_file_conflict_with_drivers, Tree};
 use crate::tree_builder::TreeBuilder;
 
 /// Presents a view of a merged set of trees.
@@ -185,10 +187,14 @@ impl MergedTree {
     /// Tries to resolve any conflicts, resolving any conflicts that can be
     /// automatically resolved and leaving the rest unresolved.
     pub fn resolve(&self) -> BackendResult<MergedTree> {
+        self.resolve_with_drivers(&MergeDrivers::default())
+    }
+
+    fn resolve_with_drivers(&self, merge_drivers: &MergeDrivers) -> BackendResult<MergedTree> {
         match self {
             MergedTree::Legacy(_) => panic!("Cannot resolve conflicts in legacy tree"),
             MergedTree::Merge(trees) => {
-                let merged = merge_trees(trees)?;
+                let merged = merge_trees(trees, merge_drivers)?;
                 // If the result can be resolved, then `merge_trees()` above would have returned
                 // a resolved merge. However, that function will always preserve the arity of
                 // conflicts it cannot resolve. So we simplify the conflict again
@@ -198,7 +204,7 @@ impl MergedTree {
                 // particular,  that this last simplification doesn't enable further automatic
                 // resolutions
                 if cfg!(debug_assertions) {
-                    let re_merged = merge_trees(&simplified).unwrap();
+                    let re_merged = merge_trees(&simplified, &MergeDrivers::default()).unwrap();
                     debug_assert_eq!(re_merged, simplified);
                 }
                 Ok(MergedTree::Merge(simplified))
@@ -354,6 +360,24 @@ impl MergedTree {
         }
     }
 
+    /// Stream of the differences between this tree and another tree, where
+    /// files that were copied or renamed according to `copy_records` are
+    /// reported as a single entry with their source path.
+    pub fn diff_stream_with_copies<'a>(
+        &self,
+        other: &MergedTree,
+        matcher: &'a dyn Matcher,
+        copy_records: &'a CopyRecords,
+    ) -> CopiesTreeDiffStream<'a> {
+        copies_tree_diff_stream(
+            self.diff_stream(other, matcher),
+            self.clone(),
+            other.clone(),
+            matcher,
+            copy_records,
+        )
+    }
+
     /// Collects lists of modified, added, and removed files between this tree
     /// and another tree.
     pub fn diff_summary(
@@ -386,6 +410,20 @@ impl MergedTree {
 
     /// Merges this tree with `other`, using `base` as base.
     pub fn merge(&self, base: &MergedTree, other: &MergedTree) -> BackendResult<MergedTree> {
+        self.merge_with_drivers(base, other, &MergeDrivers::default())
+    }
+
+    /// Merges this tree with `other`, using `base` as base, and resolves file
+    /// conflicts with the matching `merge_drivers`.
+    ///
+    /// Merge drivers may run external commands, so this should only be used
+    /// when the merged tree is written as part of a rewrite.
+    pub fn merge_with_drivers(
+        &self,
+        base: &MergedTree,
+        other: &MergedTree,
+        merge_drivers: &MergeDrivers,
+    ) -> BackendResult<MergedTree> {
         // Convert legacy trees to merged trees and unwrap to `Merge<Tree>`
         let to_merge = |tree: &MergedTree| -> BackendResult<Merge<Tree>> {
             match tree {
@@ -401,7 +439,7 @@ impl MergedTree {
         };
         let nested = Merge::from_vec(vec![to_merge(self)?, to_merge(base)?, to_merge(other)?]);
         let flattened = MergedTree::Merge(nested.flatten().simplify());
-        flattened.resolve()
+        flattened.resolve_with_drivers(merge_drivers)
     }
 }
 
@@ -474,7 +512,7 @@ fn trees_value<'a>(trees: &'a Merge<Tree>, basename: &RepoPathComponent) -> Merg
 
 /// The returned conflict will either be resolved or have the same number of
 /// sides as the input.
-fn merge_trees(merge: &Merge<Tree>) -> BackendResult<Merge<Tree>> {
+fn merge_trees(merge: &Merge<Tree>, merge_drivers: &MergeDrivers) -> BackendResult<Merge<Tree>> {
     if let Some(tree) = merge.resolve_trivial() {
         return Ok(Merge::resolved(tree.clone()));
     }
@@ -490,7 +528,7 @@ fn merge_trees(merge: &Merge<Tree>) -> BackendResult<Merge<Tree>> {
     for basename in all_tree_basenames(merge) {
         let path_merge = merge.map(|tree| tree.value(basename).cloned());
         let path = dir.join(basename);
-        let path_merge = merge_tree_values(store, &path, path_merge)?;
+        let path_merge = merge_tree_values(store, &path, path_merge, merge_drivers)?;
         match path_merge.into_resolved() {
             Ok(value) => {
                 new_tree.set_or_remove(basename, value);
@@ -528,6 +566,7 @@ fn merge_tree_values(
     store: &Arc<Store>,
     path: &RepoPath,
     values: MergedTreeValue,
+    merge_drivers: &MergeDrivers,
 ) -> BackendResult<MergedTreeValue> {
     if let Some(resolved) = values.resolve_trivial() {
         return Ok(Merge::resolved(resolved.clone()));
@@ -537,7 +576,7 @@ fn merge_tree_values(
         // If all sides are trees or missing, merge the trees recursively, treating
         // missing trees as empty.
         let empty_tree_id = store.empty_tree_id();
-        let merged_tree = merge_trees(&trees)?;
+        let merged_tree = merge_trees(&trees, merge_drivers)?;
         Ok(merged_tree
             .map(|tree| (tree.id() != empty_tree_id).then(|| TreeValue::Tree(tree.id().clone()))))
     } else {
@@ -547,7 +586,9 @@ fn merge_tree_values(
         let simplified = values.clone().simplify();
         // No fast path for simplified.is_resolved(). If it could be resolved, it would
         // have been caught by values.resolve_trivial() above.
-        if let Some(resolved) = try_resolve_file_conflict(store, path, &simplified)? {
+        if let Some(resolved) =
+            try_resolve_file_conflict_with_drivers(store, path, &simplified, merge_drivers)?
+        {
             Ok(Merge::normal(resolved))
         } else {
             // Failed to merge the files, or the paths are not files
@@ -13,6 +13,13 @@ pub struct Commit {
     /// TODO(#1624): delete when we assume that all commits use this format
     #[prost(bool, tag = "10")]
     pub uses_tree_conflict_format: bool,
+    /// Files copied or renamed from the parent commit.
+    #[prost(message, repeated, tag = "11")]
+    pub copies: ::prost::alloc::vec::Vec<commit::Copy>,
+    /// Whether `copies` was computed. Copies are inferred when first queried
+    /// and recorded afterwards.
+    #[prost(bool, tag = "12")]
+    pub copies_recorded: bool,
     #[deprecated]
     #[prost(bool, tag = "8")]
     pub is_open: bool,
@@ -20,3 +27,16 @@ pub struct Commit {
     #[prost(bool, tag = "9")]
     pub is_pruned: bool,
 }
+/// Nested message and enum types in `Commit`.
+pub mod commit {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Copy {
+        #[prost(string, tag = "1")]
+        pub source: ::prost::alloc::string::String,
+        #[prost(string, tag = "2")]
+        pub target: ::prost::alloc::string::String,
+        #[prost(bytes = "vec", tag = "3")]
+        pub source_file: ::prost::alloc::vec::Vec<u8>,
+    }
+}
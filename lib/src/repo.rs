@@ -39,6 +39,7 @@ use crate::default_submodule_store::DefaultSubmoduleStore;
 use crate::file_util::{IoResultExt as _, PathError};
 use crate::index::{ChangeIdIndex, Index, IndexStore, MutableIndex, ReadonlyIndex};
 use crate::local_backend::LocalBackend;
+use crate::merge_drivers::{MergeDriverConfigError, MergeDrivers};
 use crate::object_id::{HexPrefix, ObjectId, PrefixResolution};
 use crate::op_heads_store::{self, OpHeadResolutionError, OpHeadsStore};
 use crate::op_store::{
@@ -116,6 +117,8 @@ pub enum RepoInitError {
     Backend(#[from] BackendInitError),
     #[error(transparent)]
     Path(#[from] PathError),
+    #[error(transparent)]
+    MergeDrivers(#[from] MergeDriverConfigError),
 }
 
 impl ReadonlyRepo {
@@ -131,7 +134,7 @@ impl ReadonlyRepo {
     }
 
     pub fn default_index_store_initializer() -> &'static IndexStoreInitializer<'static> {
-        &|_settings, store_path| Ok(Box::new(DefaultIndexStore::init(store_path)?))
+        &|settings, store_path| Ok(Box::new(DefaultIndexStore::init(settings, store_path)?))
     }
 
     pub fn default_submodule_store_initializer() -> &'static SubmoduleStoreInitializer<'static> {
@@ -156,7 +159,12 @@ impl ReadonlyRepo {
         let backend = backend_initializer(user_settings, &store_path)?;
         let backend_path = store_path.join("type");
         fs::write(&backend_path, backend.name()).context(&backend_path)?;
-        let store = Store::new(backend, signer, user_settings.use_tree_conflict_format());
+        let store = Store::new(
+            backend,
+            signer,
+            user_settings.use_tree_conflict_format(),
+            MergeDrivers::from_settings(user_settings)?,
+        );
         let repo_settings = user_settings.with_repo(&repo_path).unwrap();
 
         let op_store_path = repo_path.join("op_store");
@@ -411,7 +419,9 @@ impl Default for StoreFactories {
         // Index
         factories.add_index_store(
             DefaultIndexStore::name(),
-            Box::new(|_settings, store_path| Ok(Box::new(DefaultIndexStore::load(store_path)))),
+            Box::new(|settings, store_path| {
+                Ok(Box::new(DefaultIndexStore::load(settings, store_path)))
+            }),
         );
 
         // SubmoduleStores
@@ -440,6 +450,8 @@ pub enum StoreLoadError {
     Backend(#[from] BackendLoadError),
     #[error(transparent)]
     Signing(#[from] SignInitError),
+    #[error(transparent)]
+    MergeDrivers(#[from] MergeDriverConfigError),
 }
 
 impl StoreFactories {
@@ -615,6 +627,7 @@ impl RepoLoader {
             store_factories.load_backend(user_settings, &repo_path.join("store"))?,
             Signer::from_settings(user_settings)?,
             user_settings.use_tree_conflict_format(),
+            MergeDrivers::from_settings(user_settings)?,
         );
         let repo_settings = user_settings.with_repo(repo_path).unwrap();
         let op_store =
@@ -1548,14 +1561,21 @@ impl MutableRepo {
         // merging the view. Merging in base_repo's index isn't typically
         // necessary, but it can be if base_repo is ahead of either self or other_repo
         // (e.g. because we're undoing an operation that hasn't been published).
-        self.index.merge_in(base_repo.readonly_index());
-        self.index.merge_in(other_repo.readonly_index());
+        self.merge_index(base_repo);
+        self.merge_index(other_repo);
 
         self.view.ensure_clean(|v| self.enforce_view_invariants(v));
         self.merge_view(&base_repo.view, &other_repo.view);
         self.view.mark_dirty();
     }
 
+    /// Merges the index of `other_repo` into this repo's index, so the commits
+    /// in `other_repo` can be looked up even if they aren't visible in this
+    /// repo's view.
+    pub fn merge_index(&mut self, other_repo: &ReadonlyRepo) {
+        self.index.merge_in(other_repo.readonly_index());
+    }
+
     fn merge_view(&mut self, base: &View, other: &View) {
         // Merge working-copy commits. If there's a conflict, we keep the self side.
         for (workspace_id, base_wc_commit) in base.wc_commit_ids() {
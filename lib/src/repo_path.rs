@@ -502,6 +502,48 @@ impl RepoPathUiConverter {
         }
     }
 
+    /// Format a copy from `source` to `target` for display in the UI, with
+    /// the directories the paths have in common outside of the braces (e.g.
+    /// "dir/{old => new}/file").
+    pub fn format_copied_path(&self, source: &RepoPath, target: &RepoPath) -> String {
+        let source = self.format_file_path(source);
+        let target = self.format_file_path(target);
+        let separator = std::path::MAIN_SEPARATOR_STR;
+        let source_parts: Vec<&str> = source.split(separator).collect();
+        let target_parts: Vec<&str> = target.split(separator).collect();
+        let max_common = source_parts.len().min(target_parts.len()) - 1;
+        let prefix_len = source_parts
+            .iter()
+            .zip(&target_parts)
+            .take_while(|(s, t)| s == t)
+            .count()
+            .min(max_common);
+        let suffix_len = source_parts
+            .iter()
+            .rev()
+            .zip(target_parts.iter().rev())
+            .take_while(|(s, t)| s == t)
+            .count()
+            .min(max_common - prefix_len);
+        let mut formatted = String::new();
+        for part in &source_parts[..prefix_len] {
+            formatted.push_str(part);
+            formatted.push_str(separator);
+        }
+        formatted.push('{');
+        formatted
+            .push_str(&source_parts[prefix_len..source_parts.len() - suffix_len].join(separator));
+        formatted.push_str(" => ");
+        formatted
+            .push_str(&target_parts[prefix_len..target_parts.len() - suffix_len].join(separator));
+        formatted.push('}');
+        for part in &source_parts[source_parts.len() - suffix_len..] {
+            formatted.push_str(separator);
+            formatted.push_str(part);
+        }
+        formatted
+    }
+
     /// Parses a path from the UI.
     ///
     /// It's up to the implementation whether absolute paths are allowed, and
@@ -858,4 +900,29 @@ mod tests {
             Ok(repo_path("dir/file"))
         );
     }
+
+    #[test]
+    fn test_format_copied_path() {
+        let converter = RepoPathUiConverter::Fs {
+            cwd: PathBuf::from("/repo"),
+            base: PathBuf::from("/repo"),
+        };
+        let format = |source: &str, target: &str| {
+            converter
+                .format_copied_path(repo_path(source), repo_path(target))
+                .replace(std::path::MAIN_SEPARATOR, "/")
+        };
+        assert_eq!(format("one", "two"), "{one => two}");
+        assert_eq!(format("one/two", "one/three"), "one/{two => three}");
+        assert_eq!(format("one/two", "three/two"), "{one => three}/two");
+        assert_eq!(
+            format("one/two/three", "one/four/three"),
+            "one/{two => four}/three"
+        );
+        assert_eq!(
+            format("one/two/three", "one/three"),
+            "one/{two/three => three}"
+        );
+        assert_eq!(format("one", "two/one"), "{one => two/one}");
+    }
 }
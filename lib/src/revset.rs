@@ -41,8 +41,10 @@ pub use crate::revset_parser::{
     expect_literal, BinaryOp, ExpressionKind, ExpressionNode, FunctionCallNode, RevsetAliasesMap,
     RevsetParseError, RevsetParseErrorKind, UnaryOp,
 };
+use crate::signing::SigStatus;
 use crate::store::Store;
 use crate::str_util::StringPattern;
+use crate::time_util::{DatePattern, DatePatternContext};
 use crate::{dsl_util, revset_parser};
 
 /// Error occurred during symbol resolution.
@@ -127,14 +129,30 @@ pub enum RevsetFilterPredicate {
     ParentCount(Range<u32>),
     /// Commits with description containing the needle.
     Description(StringPattern),
+    /// Commits with a trailer of the given key (case-insensitive) whose value
+    /// matches the pattern.
+    Trailer { key: String, value: StringPattern },
     /// Commits with author's name or email containing the needle.
     Author(StringPattern),
     /// Commits with committer's name or email containing the needle.
     Committer(StringPattern),
+    /// Commits with author dates matching the given date pattern.
+    AuthorDate(DatePattern),
+    /// Commits with committer dates matching the given date pattern.
+    CommitterDate(DatePattern),
     /// Commits modifying the paths specified by the fileset.
     File(FilesetExpression),
+    /// Commits containing diffs matching the `text` pattern within the `files`.
+    DiffContains {
+        text: StringPattern,
+        files: FilesetExpression,
+    },
     /// Commits with conflicts
     HasConflict,
+    /// Commits with a cryptographic signature
+    Signed,
+    /// Commits with a signature of the given verification status
+    SignatureStatus(SigStatus),
     /// Custom predicates provided by extensions
     Extension(Rc<dyn RevsetFilterExtension>),
 }
@@ -678,6 +696,19 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
             RevsetFilterPredicate::Description(pattern),
         ))
     });
+    map.insert("trailer", |function, _context| {
+        let ([key_arg], [value_opt_arg]) = function.expect_arguments()?;
+        let key = revset_parser::expect_literal("string", key_arg)?;
+        let value = if let Some(value_arg) = value_opt_arg {
+            expect_string_pattern(value_arg)?
+        } else {
+            StringPattern::everything()
+        };
+        Ok(RevsetExpression::filter(RevsetFilterPredicate::Trailer {
+            key,
+            value,
+        }))
+    });
     map.insert("author", |function, _context| {
         let [arg] = function.expect_exact_arguments()?;
         let pattern = expect_string_pattern(arg)?;
@@ -698,6 +729,20 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
             pattern,
         )))
     });
+    map.insert("author_date", |function, context| {
+        let [arg] = function.expect_exact_arguments()?;
+        let pattern = expect_date_pattern(arg, context.date_pattern_context())?;
+        Ok(RevsetExpression::filter(RevsetFilterPredicate::AuthorDate(
+            pattern,
+        )))
+    });
+    map.insert("committer_date", |function, context| {
+        let [arg] = function.expect_exact_arguments()?;
+        let pattern = expect_date_pattern(arg, context.date_pattern_context())?;
+        Ok(RevsetExpression::filter(
+            RevsetFilterPredicate::CommitterDate(pattern),
+        ))
+    });
     map.insert("empty", |function, _context| {
         function.expect_no_arguments()?;
         Ok(RevsetExpression::is_empty())
@@ -718,10 +763,39 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
             ))
         }
     });
+    map.insert("diff_contains", |function, context| {
+        let ([text_arg], [files_opt_arg]) = function.expect_arguments()?;
+        let text = expect_string_pattern(text_arg)?;
+        let files = if let Some(files_arg) = files_opt_arg {
+            let ctx = context.workspace.as_ref().ok_or_else(|| {
+                RevsetParseError::with_span(
+                    RevsetParseErrorKind::FsPathWithoutWorkspace,
+                    files_arg.span,
+                )
+            })?;
+            FilesetExpression::pattern(expect_file_pattern(files_arg, ctx.path_converter)?)
+        } else {
+            FilesetExpression::all()
+        };
+        Ok(RevsetExpression::filter(
+            RevsetFilterPredicate::DiffContains { text, files },
+        ))
+    });
     map.insert("conflict", |function, _context| {
         function.expect_no_arguments()?;
         Ok(RevsetExpression::filter(RevsetFilterPredicate::HasConflict))
     });
+    map.insert("signed", |function, _context| {
+        function.expect_no_arguments()?;
+        Ok(RevsetExpression::filter(RevsetFilterPredicate::Signed))
+    });
+    map.insert("signature_status", |function, _context| {
+        let [arg] = function.expect_exact_arguments()?;
+        let status = revset_parser::expect_literal("signature status", arg)?;
+        Ok(RevsetExpression::filter(
+            RevsetFilterPredicate::SignatureStatus(status),
+        ))
+    });
     map.insert("present", |function, context| {
         let [arg] = function.expect_exact_arguments()?;
         let expression = lower_expression(arg, context)?;
@@ -749,6 +823,22 @@ pub fn expect_string_pattern(node: &ExpressionNode) -> Result<StringPattern, Rev
     revset_parser::expect_pattern_with("string pattern", node, parse_pattern)
 }
 
+pub fn expect_date_pattern(
+    node: &ExpressionNode,
+    context: &DatePatternContext,
+) -> Result<DatePattern, RevsetParseError> {
+    let parse_pattern =
+        |value: &str, kind: Option<&str>| -> Result<_, Box<dyn std::error::Error + Send + Sync>> {
+            match kind {
+                None => Err(
+                    format!("Date pattern must specify 'after' or 'before' in {value:?}").into(),
+                ),
+                Some(kind) => Ok(context.parse_relative(value, kind)?),
+            }
+        };
+    revset_parser::expect_pattern_with("date pattern", node, parse_pattern)
+}
+
 /// Resolves function call by using the given function map.
 fn lower_function_call(
     function: &FunctionCallNode,
@@ -1977,6 +2067,7 @@ impl RevsetExtensions {
 pub struct RevsetParseContext<'a> {
     aliases_map: &'a RevsetAliasesMap,
     user_email: String,
+    date_pattern_context: DatePatternContext,
     extensions: &'a RevsetExtensions,
     workspace: Option<RevsetWorkspaceContext<'a>>,
 }
@@ -1985,12 +2076,14 @@ impl<'a> RevsetParseContext<'a> {
     pub fn new(
         aliases_map: &'a RevsetAliasesMap,
         user_email: String,
+        date_pattern_context: DatePatternContext,
         extensions: &'a RevsetExtensions,
         workspace: Option<RevsetWorkspaceContext<'a>>,
     ) -> Self {
         Self {
             aliases_map,
             user_email,
+            date_pattern_context,
             extensions,
             workspace,
         }
@@ -2004,6 +2097,10 @@ impl<'a> RevsetParseContext<'a> {
         &self.user_email
     }
 
+    pub fn date_pattern_context(&self) -> &DatePatternContext {
+        &self.date_pattern_context
+    }
+
     pub fn symbol_resolvers(&self) -> &[impl AsRef<dyn SymbolResolverExtension>] {
         self.extensions.symbol_resolvers()
     }
@@ -2047,6 +2144,7 @@ mod tests {
         let context = RevsetParseContext::new(
             &aliases_map,
             "test.user@example.com".to_string(),
+            chrono::Utc::now().fixed_offset().into(),
             &extensions,
             None,
         );
@@ -2076,6 +2174,7 @@ mod tests {
         let context = RevsetParseContext::new(
             &aliases_map,
             "test.user@example.com".to_string(),
+            chrono::Utc::now().fixed_offset().into(),
             &extensions,
             Some(workspace_ctx),
         );
@@ -2101,6 +2200,7 @@ mod tests {
         let context = RevsetParseContext::new(
             &aliases_map,
             "test.user@example.com".to_string(),
+            chrono::Utc::now().fixed_offset().into(),
             &extensions,
             None,
         );
@@ -2518,6 +2618,27 @@ mod tests {
         insta::assert_debug_snapshot!(
             parse("description(\"(foo)\")").unwrap(),
             @r###"Filter(Description(Substring("(foo)")))"###);
+        insta::assert_debug_snapshot!(
+            parse("trailer(Change-Id)").unwrap(),
+            @r###"
+        Filter(
+            Trailer {
+                key: "Change-Id",
+                value: Substring(""),
+            },
+        )
+        "###);
+        insta::assert_debug_snapshot!(
+            parse(r#"trailer("Signed-off-by", regex:"^Test")"#).unwrap(),
+            @r###"
+        Filter(
+            Trailer {
+                key: "Signed-off-by",
+                value: Regex(Regex("^Test")),
+            },
+        )
+        "###);
+        assert!(parse("trailer()").is_err());
         assert!(parse("mine(foo)").is_err());
         insta::assert_debug_snapshot!(
             parse("mine()").unwrap(),
@@ -2547,6 +2668,27 @@ mod tests {
             ),
         )
         "###);
+        insta::assert_debug_snapshot!(
+            parse("diff_contains(foo)").unwrap(), @r###"
+        Filter(
+            DiffContains {
+                text: Substring("foo"),
+                files: All,
+            },
+        )
+        "###);
+        insta::assert_debug_snapshot!(
+            parse_with_workspace("diff_contains(exact:foo, file:bar)", &WorkspaceId::default())
+                .unwrap(), @r###"
+        Filter(
+            DiffContains {
+                text: Exact("foo"),
+                files: Pattern(FilePath("bar")),
+            },
+        )
+        "###);
+        assert!(parse("diff_contains(foo, bar)").is_err());
+        assert!(parse("diff_contains()").is_err());
     }
 
     #[test]
@@ -25,12 +25,15 @@ use tracing::instrument;
 use crate::backend::{BackendError, BackendResult, CommitId, MergedTreeId};
 use crate::commit::Commit;
 use crate::commit_builder::CommitBuilder;
+use crate::copies::CopyRecords;
 use crate::index::Index;
-use crate::matchers::{Matcher, Visit};
+use crate::matchers::{EverythingMatcher, Matcher, Visit};
+use crate::merge::Merge;
 use crate::merged_tree::{MergedTree, MergedTreeBuilder};
 use crate::object_id::ObjectId;
 use crate::repo::{MutableRepo, Repo};
 use crate::repo_path::RepoPath;
+use crate::revset::{RevsetEvaluationError, RevsetExpression, RevsetIteratorExt};
 use crate::settings::UserSettings;
 use crate::store::Store;
 
@@ -226,9 +229,24 @@ impl<'repo> CommitRewriter<'repo> {
             let old_base_tree = merge_commit_trees(self.mut_repo, &old_parents)?;
             let new_base_tree = merge_commit_trees(self.mut_repo, &new_parents)?;
             let old_tree = self.old_commit.tree()?;
+            let was_empty = old_base_tree.id() == *self.old_commit.tree_id();
+            let (old_base_tree, old_tree) = match (&old_parents[..], &new_parents[..]) {
+                ([old_parent], [new_parent]) => follow_renames(
+                    self.mut_repo.store(),
+                    old_parent,
+                    new_parent,
+                    old_base_tree,
+                    old_tree,
+                    &new_base_tree,
+                )?,
+                _ => (old_base_tree, old_tree),
+            };
+            let merge_drivers = self.mut_repo.store().merge_drivers();
             (
-                old_base_tree.id() == *self.old_commit.tree_id(),
-                new_base_tree.merge(&old_base_tree, &old_tree)?.id(),
+                was_empty,
+                new_base_tree
+                    .merge_with_drivers(&old_base_tree, &old_tree, merge_drivers)?
+                    .id(),
             )
         };
         // Ensure we don't abandon commits with multiple parents (merge commits), even
@@ -304,6 +322,70 @@ pub fn rebase_commit_with_options(
     }
 }
 
+/// Moves the files that were renamed between `old_parent` and `new_parent`
+/// to their new paths in `old_base_tree` and `old_tree`, so that the changes
+/// made to the source of a rename are applied to its target when the trees are
+/// merged onto `new_base_tree`.
+fn follow_renames(
+    store: &Arc<Store>,
+    old_parent: &Commit,
+    new_parent: &Commit,
+    old_base_tree: MergedTree,
+    old_tree: MergedTree,
+    new_base_tree: &MergedTree,
+) -> BackendResult<(MergedTree, MergedTree)> {
+    if old_base_tree.id() == new_base_tree.id() {
+        return Ok((old_base_tree, old_tree));
+    }
+    // A rename only matters if the commit changed a file that no longer exists
+    // in the new base, so copy detection is limited to these files.
+    let mut sources = vec![];
+    for (path, diff) in old_base_tree.diff(&old_tree, &EverythingMatcher) {
+        let (before, _) = diff?;
+        if before.is_present() && new_base_tree.path_value(&path)?.is_absent() {
+            sources.push(path);
+        }
+    }
+    if sources.is_empty() {
+        return Ok((old_base_tree, old_tree));
+    }
+    let mut copy_records = CopyRecords::default();
+    copy_records.add_records(
+        store
+            .get_copy_records(Some(&sources), old_parent.id(), new_parent.id())?
+            .collect::<Vec<_>>()
+            .block_on(),
+    )?;
+    let sources: HashSet<_> = sources.into_iter().collect();
+    let mut base_builder = MergedTreeBuilder::new(old_base_tree.id());
+    let mut tree_builder = MergedTreeBuilder::new(old_tree.id());
+    let mut has_renames = false;
+    for record in copy_records.iter() {
+        if !sources.contains(&record.source)
+            || old_base_tree.path_value(&record.target)?.is_present()
+            || old_tree.path_value(&record.target)?.is_present()
+        {
+            continue;
+        }
+        let base_value = old_base_tree.path_value(&record.source)?;
+        if base_value.is_absent() {
+            continue;
+        }
+        let value = old_tree.path_value(&record.source)?;
+        base_builder.set_or_remove(record.target.clone(), base_value);
+        base_builder.set_or_remove(record.source.clone(), Merge::absent());
+        tree_builder.set_or_remove(record.target.clone(), value);
+        tree_builder.set_or_remove(record.source.clone(), Merge::absent());
+        has_renames = true;
+    }
+    if !has_renames {
+        return Ok((old_base_tree, old_tree));
+    }
+    let old_base_tree = store.get_root_tree(&base_builder.write_tree(store)?)?;
+    let old_tree = store.get_root_tree(&tree_builder.write_tree(store)?)?;
+    Ok((old_base_tree, old_tree))
+}
+
 pub fn rebase_to_dest_parent(
     repo: &dyn Repo,
     source: &Commit,
@@ -341,6 +423,152 @@ pub fn back_out_commit(
         .write()
 }
 
+/// Result of `duplicate_commits()`.
+pub struct DuplicateCommitsStats {
+    /// Pairs of original commit ID and the duplicated commit, in topological
+    /// order.
+    pub duplicated_commits: Vec<(CommitId, Commit)>,
+    /// The number of descendant commits which were rebased onto the duplicated
+    /// commits.
+    pub num_rebased: u32,
+}
+
+/// Duplicates the `target_commits` onto a new location in the graph.
+///
+/// The roots of `target_commits` are duplicated on top of `parent_commit_ids`,
+/// and the other commits are duplicated on top of the duplicates of their
+/// parents in the set, so the topology within the set is preserved. The
+/// `children_commit_ids` are then rebased onto the heads of the duplicated
+/// commits, replacing any of their parents in `parent_commit_ids`.
+///
+/// `target_commits` should be in reverse topological order.
+pub fn duplicate_commits(
+    settings: &UserSettings,
+    mut_repo: &mut MutableRepo,
+    target_commits: &[CommitId],
+    parent_commit_ids: &[CommitId],
+    children_commit_ids: &[CommitId],
+) -> BackendResult<DuplicateCommitsStats> {
+    let target_commit_ids: HashSet<_> = target_commits.iter().cloned().collect();
+    let connected_target_commits: Vec<_> = RevsetExpression::commits(target_commits.to_vec())
+        .connected()
+        .evaluate_programmatic(mut_repo)
+        .map_err(|err| match err {
+            RevsetEvaluationError::StoreError(err) => err,
+            RevsetEvaluationError::Other(_) => panic!("Unexpected revset error: {err}"),
+        })?
+        .iter()
+        .commits(mut_repo.store())
+        .try_collect()?;
+
+    // Commits in the target set should only have other commits in the set as
+    // parents. If a commit has a parent outside the set but an ancestor in the
+    // set, then that ancestor becomes the parent. The roots of the set have no
+    // such parents.
+    let mut internal_parents: HashMap<CommitId, Vec<CommitId>> = HashMap::new();
+    for commit in connected_target_commits.iter().rev() {
+        let mut new_parents = vec![];
+        for old_parent in commit.parent_ids() {
+            if target_commit_ids.contains(old_parent) {
+                new_parents.push(old_parent.clone());
+            } else if let Some(parents) = internal_parents.get(old_parent) {
+                new_parents.extend(parents.iter().cloned());
+            }
+        }
+        internal_parents.insert(commit.id().clone(), new_parents);
+    }
+
+    let mut duplicated_old_to_new: HashMap<CommitId, Commit> = HashMap::new();
+    let mut duplicated_commits = vec![];
+    // Topological order ensures that the parents of a commit in the set were
+    // already duplicated.
+    for original_commit_id in target_commits.iter().rev() {
+        let original_commit = mut_repo.store().get_commit(original_commit_id)?;
+        let new_parent_ids = match internal_parents[original_commit_id].as_slice() {
+            [] => parent_commit_ids.to_vec(),
+            parent_ids => parent_ids
+                .iter()
+                .map(|id| duplicated_old_to_new[id].id().clone())
+                .collect(),
+        };
+        let new_commit = CommitRewriter::new(mut_repo, original_commit, new_parent_ids)
+            .rebase(settings)?
+            .generate_new_change_id()
+            .write()?;
+        duplicated_old_to_new.insert(original_commit_id.clone(), new_commit.clone());
+        duplicated_commits.push((original_commit_id.clone(), new_commit));
+    }
+
+    // The heads of the duplicated set become the parents of the new children.
+    let internal_parent_ids: HashSet<&CommitId> = internal_parents
+        .iter()
+        .filter(|(id, _)| target_commit_ids.contains(*id))
+        .flat_map(|(_, parents)| parents)
+        .collect();
+    let target_head_ids = duplicated_commits
+        .iter()
+        .filter(|(old_id, _)| !internal_parent_ids.contains(old_id))
+        .map(|(_, new_commit)| new_commit.id().clone())
+        .collect_vec();
+
+    let children_commit_ids_set: HashSet<&CommitId> = children_commit_ids.iter().collect();
+    let mut num_rebased = 0;
+    mut_repo.transform_descendants(settings, children_commit_ids.to_vec(), |mut rewriter| {
+        if children_commit_ids_set.contains(rewriter.old_commit().id()) {
+            let mut new_parent_ids = vec![];
+            for old_parent_id in rewriter.old_commit().parent_ids() {
+                // The duplicated commits are inserted between the new parents
+                // and the new children.
+                if !parent_commit_ids.contains(old_parent_id) {
+                    new_parent_ids.push(old_parent_id.clone());
+                }
+            }
+            new_parent_ids.extend(target_head_ids.iter().cloned());
+            rewriter.set_new_rewritten_parents(new_parent_ids);
+        }
+        num_rebased += 1;
+        rewriter.rebase(settings)?.write()?;
+        Ok(())
+    })?;
+
+    Ok(DuplicateCommitsStats {
+        duplicated_commits,
+        num_rebased,
+    })
+}
+
+/// Duplicates the `target_commits` on top of their original parents, or on top
+/// of the duplicates of their parents if those are in the set too.
+///
+/// `target_commits` should be in reverse topological order. Returns pairs of
+/// original commit ID and the duplicated commit, in topological order.
+pub fn duplicate_commits_onto_parents(
+    settings: &UserSettings,
+    mut_repo: &mut MutableRepo,
+    target_commits: &[CommitId],
+) -> BackendResult<Vec<(CommitId, Commit)>> {
+    let mut duplicated_old_to_new: HashMap<CommitId, CommitId> = HashMap::new();
+    let mut duplicated_commits = vec![];
+    // Topological order ensures that any parents of the original commit are
+    // either not in `target_commits` or were already duplicated.
+    for original_commit_id in target_commits.iter().rev() {
+        let original_commit = mut_repo.store().get_commit(original_commit_id)?;
+        let new_parent_ids = original_commit
+            .parent_ids()
+            .iter()
+            .map(|id| duplicated_old_to_new.get(id).unwrap_or(id).clone())
+            .collect();
+        let new_commit = mut_repo
+            .rewrite_commit(settings, &original_commit)
+            .generate_new_change_id()
+            .set_parents(new_parent_ids)
+            .write()?;
+        duplicated_old_to_new.insert(original_commit_id.clone(), new_commit.id().clone());
+        duplicated_commits.push((original_commit_id.clone(), new_commit));
+    }
+    Ok(duplicated_commits)
+}
+
 #[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
 pub enum EmptyBehaviour {
     /// Always keep empty commits
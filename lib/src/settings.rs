@@ -22,6 +22,7 @@ use rand::prelude::*;
 use rand_chacha::ChaCha20Rng;
 
 use crate::backend::{ChangeId, Commit, Signature, Timestamp};
+use crate::conflicts::ConflictMarkerStyle;
 use crate::fmt_util::binary_prefix;
 use crate::fsmonitor::FsmonitorSettings;
 use crate::signing::SignBehavior;
@@ -153,6 +154,10 @@ impl UserSettings {
             .unwrap_or(false)
     }
 
+    pub fn index_changed_paths(&self) -> bool {
+        self.config.get_bool("index.changed-paths").unwrap_or(false)
+    }
+
     pub fn user_name(&self) -> String {
         self.config.get_string("user.name").unwrap_or_default()
     }
@@ -172,6 +177,11 @@ impl UserSettings {
     // address
     pub const USER_EMAIL_PLACEHOLDER: &'static str = "(no email configured)";
 
+    /// Returns the timestamp configured by `debug.commit-timestamp`, if any.
+    pub fn commit_timestamp(&self) -> Option<Timestamp> {
+        self.timestamp.clone()
+    }
+
     pub fn operation_timestamp(&self) -> Option<Timestamp> {
         get_timestamp_config(&self.config, "debug.operation-timestamp")
     }
@@ -262,6 +272,14 @@ impl UserSettings {
         }
     }
 
+    pub fn conflict_marker_style(&self) -> Result<ConflictMarkerStyle, config::ConfigError> {
+        Ok(self
+            .config
+            .get("ui.conflict-marker-style")
+            .optional()?
+            .unwrap_or_default())
+    }
+
     // separate from sign_settings as those two are needed in pretty different
     // places
     pub fn signing_backend(&self) -> Option<String> {
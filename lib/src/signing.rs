@@ -16,7 +16,8 @@
 //! various backends.
 
 use std::collections::HashMap;
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
+use std::str::FromStr;
 use std::sync::RwLock;
 
 use thiserror::Error;
@@ -37,6 +38,41 @@ pub enum SigStatus {
     Bad,
 }
 
+impl SigStatus {
+    /// Returns the lowercase name of the status.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SigStatus::Good => "good",
+            SigStatus::Unknown => "unknown",
+            SigStatus::Bad => "bad",
+        }
+    }
+}
+
+impl fmt::Display for SigStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SigStatus {
+    type Err = SigStatusParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "good" => Ok(SigStatus::Good),
+            "unknown" => Ok(SigStatus::Unknown),
+            "bad" => Ok(SigStatus::Bad),
+            _ => Err(SigStatusParseError(s.to_owned())),
+        }
+    }
+}
+
+/// Error occurred when parsing an unknown [`SigStatus`] name.
+#[derive(Debug, Error)]
+#[error("Invalid signature status: {0}")]
+pub struct SigStatusParseError(String);
+
 /// The result of a signature verification.
 /// Key and display are optional additional info that backends can or can not
 /// provide to add additional information for the templater to potentially show.
@@ -140,7 +176,7 @@ pub enum SignBehavior {
     /// others. This is what jj does when configured to always sign.
     Own,
     /// Always sign commits, regardless of who authored or signed them before.
-    /// This is what jj does on `jj sign -f`.
+    /// This is what jj does on `jj sign`.
     Force,
 }
 
@@ -166,6 +202,8 @@ impl Signer {
             Box::new(SshBackend::from_config(settings.config())) as Box<dyn SigningBackend>,
             // Box::new(X509Backend::from_settings(settings)?) as Box<dyn SigningBackend>,
         ];
+        #[cfg(feature = "testing")]
+        backends.push(Box::new(crate::test_signing_backend::TestSigningBackend));
 
         let main_backend = settings
             .signing_backend()
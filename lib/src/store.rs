@@ -21,15 +21,17 @@ use std::io::Read;
 use std::sync::{Arc, RwLock};
 use std::time::SystemTime;
 
+use futures::stream::BoxStream;
 use pollster::FutureExt;
 
 use crate::backend::{
-    self, Backend, BackendResult, ChangeId, CommitId, ConflictId, FileId, MergedTreeId, SigningFn,
-    SymlinkId, TreeId,
+    self, Backend, BackendResult, ChangeId, CommitId, ConflictId, CopyRecord, FileId, MergedTreeId,
+    SigningFn, SymlinkId, TreeId,
 };
 use crate::commit::Commit;
 use crate::index::Index;
 use crate::merge::{Merge, MergedTreeValue};
+use crate::merge_drivers::MergeDrivers;
 use crate::merged_tree::MergedTree;
 use crate::repo_path::{RepoPath, RepoPathBuf};
 use crate::signing::Signer;
@@ -44,6 +46,7 @@ pub struct Store {
     commit_cache: RwLock<HashMap<CommitId, Arc<backend::Commit>>>,
     tree_cache: RwLock<HashMap<(RepoPathBuf, TreeId), Arc<backend::Tree>>>,
     use_tree_conflict_format: bool,
+    merge_drivers: MergeDrivers,
 }
 
 impl Debug for Store {
@@ -59,6 +62,7 @@ impl Store {
         backend: Box<dyn Backend>,
         signer: Signer,
         use_tree_conflict_format: bool,
+        merge_drivers: MergeDrivers,
     ) -> Arc<Self> {
         Arc::new(Store {
             backend,
@@ -66,6 +70,7 @@ impl Store {
             commit_cache: Default::default(),
             tree_cache: Default::default(),
             use_tree_conflict_format,
+            merge_drivers,
         })
     }
 
@@ -82,6 +87,12 @@ impl Store {
         self.use_tree_conflict_format
     }
 
+    /// Merge drivers to resolve file conflicts with before falling back to
+    /// the line-based merge.
+    pub fn merge_drivers(&self) -> &MergeDrivers {
+        &self.merge_drivers
+    }
+
     pub fn commit_id_length(&self) -> usize {
         self.backend.commit_id_length()
     }
@@ -268,6 +279,15 @@ impl Store {
         TreeBuilder::new(self.clone(), base_tree_id)
     }
 
+    pub fn get_copy_records(
+        &self,
+        paths: Option<&[RepoPathBuf]>,
+        root: &CommitId,
+        head: &CommitId,
+    ) -> BackendResult<BoxStream<'_, BackendResult<CopyRecord>>> {
+        self.backend.get_copy_records(paths, root, head)
+    }
+
     pub fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
         self.backend.gc(index, keep_newer)
     }
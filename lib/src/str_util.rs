@@ -30,20 +30,45 @@ pub enum StringPatternParseError {
     /// Failed to parse glob pattern.
     #[error(transparent)]
     GlobPattern(glob::PatternError),
+    /// Failed to parse regular expression.
+    #[error(transparent)]
+    Regex(regex::Error),
 }
 
 /// Pattern to be tested against string property like commit description or
 /// branch name.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug)]
 pub enum StringPattern {
     /// Matches strings exactly equal to `string`.
     Exact(String),
-    /// Unix-style shell wildcard pattern.
-    Glob(glob::Pattern),
+    /// Matches strings that are case-insensitively equal to `string`.
+    ExactI(String),
     /// Matches strings that contain `substring`.
     Substring(String),
+    /// Matches strings that case-insensitively contain `substring`.
+    SubstringI(String),
+    /// Unix-style shell wildcard pattern.
+    Glob(glob::Pattern),
+    /// Unix-style shell wildcard pattern, matched case-insensitively. The
+    /// pattern is compiled from the lowercase source string.
+    GlobI(glob::Pattern),
+    /// Regular expression. Matches if the expression is found anywhere in
+    /// the string, unless it's anchored.
+    Regex(regex::Regex),
+    /// Regular expression, matched case-insensitively.
+    RegexI(regex::Regex),
 }
 
+impl PartialEq for StringPattern {
+    fn eq(&self, other: &Self) -> bool {
+        // Compiled patterns are compared by their source strings.
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+            && self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for StringPattern {}
+
 impl StringPattern {
     /// Pattern that matches any string.
     pub const fn everything() -> Self {
@@ -52,8 +77,9 @@ impl StringPattern {
 
     /// Parses the given string as a `StringPattern`. Everything before the
     /// first ":" is considered the string's prefix. If the prefix is "exact:",
-    /// "glob:", or "substring:", a pattern of the specified kind is returned.
-    /// Returns an error if the string has an unrecognized prefix. Otherwise, a
+    /// "glob:", "regex:", or "substring:" (or one of their case-insensitive
+    /// "-i" variants), a pattern of the specified kind is returned. Returns an
+    /// error if the string has an unrecognized prefix. Otherwise, a
     /// `StringPattern::Exact` is returned.
     pub fn parse(src: &str) -> Result<StringPattern, StringPatternParseError> {
         if let Some((kind, pat)) = src.split_once(':') {
@@ -77,12 +103,41 @@ impl StringPattern {
         Ok(StringPattern::Glob(pattern))
     }
 
+    /// Parses the given string as a case-insensitive glob pattern.
+    pub fn glob_i(src: &str) -> Result<Self, StringPatternParseError> {
+        // The glob crate only folds the case of ASCII characters, so both the
+        // pattern and the haystack are lowercased instead.
+        let pattern = glob::Pattern::new(&src.to_lowercase())
+            .map_err(StringPatternParseError::GlobPattern)?;
+        Ok(StringPattern::GlobI(pattern))
+    }
+
+    /// Parses the given string as a regular expression.
+    pub fn regex(src: &str) -> Result<Self, StringPatternParseError> {
+        let pattern = regex::Regex::new(src).map_err(StringPatternParseError::Regex)?;
+        Ok(StringPattern::Regex(pattern))
+    }
+
+    /// Parses the given string as a case-insensitive regular expression.
+    pub fn regex_i(src: &str) -> Result<Self, StringPatternParseError> {
+        let pattern = regex::RegexBuilder::new(src)
+            .case_insensitive(true)
+            .build()
+            .map_err(StringPatternParseError::Regex)?;
+        Ok(StringPattern::RegexI(pattern))
+    }
+
     /// Parses the given string as pattern of the specified `kind`.
     pub fn from_str_kind(src: &str, kind: &str) -> Result<Self, StringPatternParseError> {
         match kind {
             "exact" => Ok(StringPattern::exact(src)),
-            "glob" => StringPattern::glob(src),
+            "exact-i" => Ok(StringPattern::ExactI(src.to_owned())),
             "substring" => Ok(StringPattern::Substring(src.to_owned())),
+            "substring-i" => Ok(StringPattern::SubstringI(src.to_owned())),
+            "glob" => StringPattern::glob(src),
+            "glob-i" => StringPattern::glob_i(src),
+            "regex" => StringPattern::regex(src),
+            "regex-i" => StringPattern::regex_i(src),
             _ => Err(StringPatternParseError::InvalidKind(kind.to_owned())),
         }
     }
@@ -98,7 +153,7 @@ impl StringPattern {
     pub fn as_exact(&self) -> Option<&str> {
         match self {
             StringPattern::Exact(literal) => Some(literal),
-            StringPattern::Glob(_) | StringPattern::Substring(_) => None,
+            _ => None,
         }
     }
 
@@ -106,15 +161,19 @@ impl StringPattern {
     pub fn as_str(&self) -> &str {
         match self {
             StringPattern::Exact(literal) => literal,
-            StringPattern::Glob(pattern) => pattern.as_str(),
+            StringPattern::ExactI(literal) => literal,
             StringPattern::Substring(needle) => needle,
+            StringPattern::SubstringI(needle) => needle,
+            StringPattern::Glob(pattern) => pattern.as_str(),
+            StringPattern::GlobI(pattern) => pattern.as_str(),
+            StringPattern::Regex(pattern) => pattern.as_str(),
+            StringPattern::RegexI(pattern) => pattern.as_str(),
         }
     }
 
     /// Converts this pattern to a glob string. Returns `None` if the pattern
     /// can't be represented as a glob.
     pub fn to_glob(&self) -> Option<Cow<'_, str>> {
-        // TODO: If we add Regex pattern, it will return None.
         match self {
             StringPattern::Exact(literal) => Some(glob::Pattern::escape(literal).into()),
             StringPattern::Glob(pattern) => Some(pattern.as_str().into()),
@@ -122,15 +181,30 @@ impl StringPattern {
             StringPattern::Substring(needle) => {
                 Some(format!("*{}*", glob::Pattern::escape(needle)).into())
             }
+            StringPattern::ExactI(_)
+            | StringPattern::SubstringI(_)
+            | StringPattern::GlobI(_)
+            | StringPattern::Regex(_)
+            | StringPattern::RegexI(_) => None,
         }
     }
 
     /// Returns true if this pattern matches the `haystack`.
+    ///
+    /// Case-insensitive patterns compare the Unicode lowercase forms of the
+    /// strings.
     pub fn matches(&self, haystack: &str) -> bool {
         match self {
             StringPattern::Exact(literal) => haystack == literal,
-            StringPattern::Glob(pattern) => pattern.matches(haystack),
+            StringPattern::ExactI(literal) => haystack.to_lowercase() == literal.to_lowercase(),
             StringPattern::Substring(needle) => haystack.contains(needle),
+            StringPattern::SubstringI(needle) => {
+                haystack.to_lowercase().contains(&needle.to_lowercase())
+            }
+            StringPattern::Glob(pattern) => pattern.matches(haystack),
+            StringPattern::GlobI(pattern) => pattern.matches(&haystack.to_lowercase()),
+            StringPattern::Regex(pattern) => pattern.is_match(haystack),
+            StringPattern::RegexI(pattern) => pattern.is_match(haystack),
         }
     }
 
@@ -177,6 +251,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_string_pattern_matches() {
+        let pattern = StringPattern::parse("exact-i:Foo").unwrap();
+        assert!(pattern.matches("foo"));
+        assert!(pattern.matches("FOO"));
+        assert!(!pattern.matches("foobar"));
+
+        let pattern = StringPattern::parse("substring-i:Foo").unwrap();
+        assert!(pattern.matches("a FOO b"));
+        assert!(!pattern.matches("fo"));
+
+        let pattern = StringPattern::parse("glob-i:Foo*").unwrap();
+        assert!(pattern.matches("foobar"));
+        assert!(pattern.matches("FOOBAR"));
+        assert!(!pattern.matches("barfoo"));
+
+        // Case-insensitive patterns aren't limited to ASCII
+        assert!(StringPattern::parse("exact-i:Ärger")
+            .unwrap()
+            .matches("äRGER"));
+        assert!(StringPattern::parse("substring-i:ß")
+            .unwrap()
+            .matches("Straße"));
+        assert!(StringPattern::parse("glob-i:Ä*").unwrap().matches("äpfel"));
+        assert!(StringPattern::parse("regex-i:^ä").unwrap().matches("Äpfel"));
+
+        let pattern = StringPattern::parse(r"regex:^release-\d+$").unwrap();
+        assert!(pattern.matches("release-12"));
+        assert!(!pattern.matches("release-12a"));
+        assert!(!pattern.matches("Release-12"));
+        // Unanchored expressions match anywhere in the string.
+        assert!(StringPattern::parse("regex:o+").unwrap().matches("foo"));
+
+        let pattern = StringPattern::parse(r"regex-i:^release-\d+$").unwrap();
+        assert!(pattern.matches("RELEASE-12"));
+    }
+
+    #[test]
+    fn test_string_pattern_to_glob_unsupported() {
+        assert_eq!(StringPattern::ExactI("a".into()).to_glob(), None);
+        assert_eq!(StringPattern::regex("a").unwrap().to_glob(), None);
+    }
+
     #[test]
     fn test_parse() {
         // Parse specific pattern kinds.
@@ -205,6 +322,24 @@ mod tests {
             StringPattern::from_str_kind("foo", "exact").unwrap()
         );
 
+        // Parse case-insensitive and regex pattern kinds.
+        assert_eq!(
+            StringPattern::parse("regex:^foo$").unwrap(),
+            StringPattern::regex("^foo$").unwrap()
+        );
+        assert_eq!(
+            StringPattern::parse("glob-i:foo*").unwrap(),
+            StringPattern::glob_i("foo*").unwrap()
+        );
+        assert_ne!(
+            StringPattern::parse("glob-i:foo*").unwrap(),
+            StringPattern::glob("foo*").unwrap()
+        );
+        assert!(matches! {
+            StringPattern::parse("regex:("),
+            Err(StringPatternParseError::Regex(_))
+        });
+
         // Parsing an unknown prefix results in an error.
         assert!(matches! {
             StringPattern::parse("unknown-prefix:foo"),
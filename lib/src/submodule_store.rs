@@ -15,7 +15,56 @@
 #![allow(missing_docs)]
 
 use std::fmt::Debug;
+use std::path::Path;
 
+use thiserror::Error;
+
+use crate::backend::CommitId;
+use crate::object_id::ObjectId as _;
+use crate::repo_path::{RepoPath, RepoPathBuf};
+
+#[derive(Debug, Error)]
+pub enum SubmoduleStoreError {
+    #[error("Submodule {} has not been fetched", path.as_internal_file_string())]
+    NotFound { path: RepoPathBuf },
+    #[error(
+        "Submodule {} does not contain commit {}",
+        path.as_internal_file_string(),
+        id.hex()
+    )]
+    CommitNotFound { path: RepoPathBuf, id: CommitId },
+    #[error("Submodules are not supported by this store")]
+    Unsupported,
+    #[error(transparent)]
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+pub type SubmoduleStoreResult<T> = Result<T, SubmoduleStoreError>;
+
+/// Stores the repositories of the submodules used in a repo, keyed by the path
+/// of the submodule in the parent repo.
 pub trait SubmoduleStore: Send + Sync + Debug {
     fn name(&self) -> &str;
+
+    /// Fetches the submodule at `path` from `url`, adding it to the store if
+    /// it's not there yet.
+    fn fetch(&self, path: &RepoPath, url: &str) -> SubmoduleStoreResult<()>;
+
+    /// Makes the directory at `disk_path` a checkout of commit `id` of the
+    /// submodule at `path`. Returns `SubmoduleStoreError::NotFound` if the
+    /// submodule hasn't been fetched.
+    fn check_out(
+        &self,
+        path: &RepoPath,
+        disk_path: &Path,
+        id: &CommitId,
+    ) -> SubmoduleStoreResult<()>;
+
+    /// Returns the commit checked out in the submodule checkout at
+    /// `disk_path`, or `None` if there's no checkout there.
+    fn get_checked_out_commit(
+        &self,
+        path: &RepoPath,
+        disk_path: &Path,
+    ) -> SubmoduleStoreResult<Option<CommitId>>;
 }
@@ -1,7 +1,26 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides a fake signing backend for testing
+
 use hex::ToHex;
-use jj_lib::content_hash::blake2b_hash;
-use jj_lib::signing::{SigStatus, SignError, SignResult, SigningBackend, Verification};
 
+use crate::content_hash::blake2b_hash;
+use crate::signing::{SigStatus, SignError, SignResult, SigningBackend, Verification};
+
+/// A signing backend which "signs" data with a hash of the data and the key.
+/// It's selected by `signing.backend = "test"`.
 #[derive(Debug)]
 pub struct TestSigningBackend;
 
@@ -0,0 +1,222 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing and formatting of commit message trailers such as
+//! `Signed-off-by: Name <email>`.
+
+use itertools::Itertools as _;
+
+/// A `key: value` line in the last paragraph of a commit description.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Trailer {
+    /// Trailer key, e.g. `Signed-off-by`.
+    pub key: String,
+    /// Trailer value with continuation lines joined by a space.
+    pub value: String,
+}
+
+impl Trailer {
+    /// Parses a single `key: value` line. Returns `None` if the line isn't a
+    /// trailer.
+    pub fn parse(line: &str) -> Option<Self> {
+        let (key, value) = line.split_once(':')?;
+        let is_valid_key = !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        is_valid_key.then(|| Trailer {
+            key: key.to_owned(),
+            value: value.trim().to_owned(),
+        })
+    }
+}
+
+/// Parses the trailers from the last paragraph of the `description`.
+///
+/// The trailers are recognized only if every line of the last paragraph is a
+/// trailer or a continuation line starting with whitespace, and if the
+/// paragraph isn't the subject of the description. If the first line of the
+/// description is blank, the subject is empty.
+pub fn parse_description_trailers(description: &str) -> Vec<Trailer> {
+    let has_subject = description
+        .lines()
+        .next()
+        .is_some_and(|line| !line.trim().is_empty());
+    let paragraphs = split_paragraphs(description);
+    let last_paragraph = match paragraphs.as_slice() {
+        [_, .., last_paragraph] => last_paragraph,
+        [last_paragraph] if !has_subject => last_paragraph,
+        _ => return vec![],
+    };
+    parse_trailer_lines(last_paragraph).unwrap_or_default()
+}
+
+/// Appends the `trailers` to the `description`, skipping the ones already
+/// present. The trailers are added to the existing trailer paragraph if any.
+/// If the `description` is empty, the trailers follow an empty subject line.
+pub fn append_trailers(description: &str, trailers: &[Trailer]) -> String {
+    let existing_trailers = parse_description_trailers(description);
+    let mut new_trailers: Vec<&Trailer> = vec![];
+    for trailer in trailers {
+        if !existing_trailers.contains(trailer) && !new_trailers.contains(&trailer) {
+            new_trailers.push(trailer);
+        }
+    }
+    if new_trailers.is_empty() {
+        return description.to_owned();
+    }
+    let mut new_description = description.trim_end().to_owned();
+    new_description.push('\n');
+    if !new_description.trim().is_empty() && existing_trailers.is_empty() {
+        new_description.push('\n');
+    }
+    for trailer in new_trailers {
+        new_description.push_str(&format!("{}: {}\n", trailer.key, trailer.value));
+    }
+    new_description
+}
+
+fn split_paragraphs(description: &str) -> Vec<Vec<&str>> {
+    description
+        .lines()
+        .group_by(|line| line.trim().is_empty())
+        .into_iter()
+        .filter(|(is_blank, _)| !is_blank)
+        .map(|(_, lines)| lines.collect())
+        .collect()
+}
+
+fn parse_trailer_lines(lines: &[&str]) -> Option<Vec<Trailer>> {
+    let mut trailers: Vec<Trailer> = vec![];
+    for line in lines {
+        if line.starts_with(char::is_whitespace) {
+            let trailer = trailers.last_mut()?;
+            if !trailer.value.is_empty() {
+                trailer.value.push(' ');
+            }
+            trailer.value.push_str(line.trim());
+        } else {
+            trailers.push(Trailer::parse(line)?);
+        }
+    }
+    Some(trailers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trailer(key: &str, value: &str) -> Trailer {
+        Trailer {
+            key: key.to_owned(),
+            value: value.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_parse_trailer() {
+        assert_eq!(
+            Trailer::parse("Signed-off-by: Test User <test@example.com>"),
+            Some(trailer("Signed-off-by", "Test User <test@example.com>"))
+        );
+        assert_eq!(Trailer::parse("Key:value "), Some(trailer("Key", "value")));
+        assert_eq!(Trailer::parse("Key:"), Some(trailer("Key", "")));
+        assert_eq!(Trailer::parse("Not a trailer: text"), None);
+        assert_eq!(Trailer::parse(": no key"), None);
+        assert_eq!(Trailer::parse("no colon"), None);
+    }
+
+    #[test]
+    fn test_parse_description_trailers() {
+        assert_eq!(parse_description_trailers(""), vec![]);
+        // The subject can't be a trailer paragraph
+        assert_eq!(parse_description_trailers("Key: value\n"), vec![]);
+        assert_eq!(
+            parse_description_trailers("\nKey: value\n"),
+            vec![trailer("Key", "value")]
+        );
+        assert_eq!(parse_description_trailers("subject\n\nbody\n"), vec![]);
+        assert_eq!(
+            parse_description_trailers(
+                "subject\n\nbody\n\nChange-Id: I1234\nSigned-off-by: Test User\n"
+            ),
+            vec![
+                trailer("Change-Id", "I1234"),
+                trailer("Signed-off-by", "Test User"),
+            ]
+        );
+        // Continuation lines are joined
+        assert_eq!(
+            parse_description_trailers("subject\n\nKey: first\n  second\n\n\n"),
+            vec![trailer("Key", "first second")]
+        );
+        // Only the last paragraph is parsed
+        assert_eq!(
+            parse_description_trailers("subject\n\nKey: value\n\nbody\n"),
+            vec![]
+        );
+        // All lines must be trailers
+        assert_eq!(
+            parse_description_trailers("subject\n\nKey: value\nnot a trailer\n"),
+            vec![]
+        );
+        assert_eq!(
+            parse_description_trailers("subject\n\n  Key: value\n"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_append_trailers() {
+        let sign_off = trailer("Signed-off-by", "Test User");
+        assert_eq!(
+            append_trailers("", std::slice::from_ref(&sign_off)),
+            "\nSigned-off-by: Test User\n"
+        );
+        assert_eq!(
+            append_trailers(
+                "\nSigned-off-by: Test User\n",
+                std::slice::from_ref(&sign_off)
+            ),
+            "\nSigned-off-by: Test User\n"
+        );
+        assert_eq!(
+            append_trailers("\nChange-Id: I1234\n", std::slice::from_ref(&sign_off)),
+            "\nChange-Id: I1234\nSigned-off-by: Test User\n"
+        );
+        assert_eq!(
+            append_trailers("subject", std::slice::from_ref(&sign_off)),
+            "subject\n\nSigned-off-by: Test User\n"
+        );
+        assert_eq!(
+            append_trailers(
+                "subject\n\nChange-Id: I1234\n",
+                std::slice::from_ref(&sign_off)
+            ),
+            "subject\n\nChange-Id: I1234\nSigned-off-by: Test User\n"
+        );
+        // Existing and duplicated trailers are skipped
+        assert_eq!(
+            append_trailers(
+                "subject\n\nSigned-off-by: Test User\n",
+                &[sign_off.clone(), trailer("Key", "value"), sign_off.clone()]
+            ),
+            "subject\n\nSigned-off-by: Test User\nKey: value\n"
+        );
+        assert_eq!(
+            append_trailers("subject\n\nSigned-off-by: Test User\n", &[sign_off]),
+            "subject\n\nSigned-off-by: Test User\n"
+        );
+    }
+}
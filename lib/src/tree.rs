@@ -29,6 +29,7 @@ use crate::backend::{
 use crate::files::MergeResult;
 use crate::matchers::{EverythingMatcher, Matcher};
 use crate::merge::{trivial_merge, Merge, MergedTreeValue};
+use crate::merge_drivers::MergeDrivers;
 use crate::object_id::ObjectId;
 use crate::repo_path::{RepoPath, RepoPathBuf, RepoPathComponent};
 use crate::store::Store;
@@ -403,6 +404,17 @@ pub fn try_resolve_file_conflict(
     store: &Store,
     filename: &RepoPath,
     conflict: &MergedTreeValue,
+) -> BackendResult<Option<TreeValue>> {
+    try_resolve_file_conflict_with_drivers(store, filename, conflict, &MergeDrivers::default())
+}
+
+/// Like `try_resolve_file_conflict()`, but tries the matching merge driver
+/// before merging content hunks.
+pub(crate) fn try_resolve_file_conflict_with_drivers(
+    store: &Store,
+    filename: &RepoPath,
+    conflict: &MergedTreeValue,
+    merge_drivers: &MergeDrivers,
 ) -> BackendResult<Option<TreeValue>> {
     // If there are any non-file or any missing parts in the conflict, we can't
     // merge it. We check early so we don't waste time reading file contents if
@@ -455,6 +467,15 @@ pub fn try_resolve_file_conflict(
             Ok(content)
         })?;
     let slices = contents.map(|content| content.as_slice());
+    if let Some(driver) = merge_drivers.find(filename) {
+        let merged = driver
+            .merge(&slices)
+            .map_err(|err| BackendError::Other(err.into()))?;
+        if let Some(merged_content) = merged {
+            let id = store.write_file(filename, &mut merged_content.as_slice())?;
+            return Ok(Some(TreeValue::File { id, executable }));
+        }
+    }
     let merge_result = files::merge(&slices);
     match merge_result {
         MergeResult::Resolved(merged_content) => {
@@ -24,12 +24,14 @@ use thiserror::Error;
 
 use crate::backend::{BackendError, MergedTreeId};
 use crate::commit::Commit;
+use crate::conflicts::ConflictMarkerStyle;
 use crate::fsmonitor::FsmonitorSettings;
 use crate::gitignore::{GitIgnoreError, GitIgnoreFile};
 use crate::op_store::{OperationId, WorkspaceId};
 use crate::repo_path::{RepoPath, RepoPathBuf};
 use crate::settings::HumanByteSize;
 use crate::store::Store;
+use crate::submodule_store::SubmoduleStore;
 
 /// The trait all working-copy implementations must implement.
 pub trait WorkingCopy: Send {
@@ -102,7 +104,11 @@ pub trait LockedWorkingCopy {
     fn snapshot(&mut self, options: SnapshotOptions) -> Result<MergedTreeId, SnapshotError>;
 
     /// Check out the specified commit in the working copy.
-    fn check_out(&mut self, commit: &Commit) -> Result<CheckoutStats, CheckoutError>;
+    fn check_out(
+        &mut self,
+        commit: &Commit,
+        options: &CheckoutOptions,
+    ) -> Result<CheckoutStats, CheckoutError>;
 
     /// Update to another commit without touching the files in the working copy.
     fn reset(&mut self, commit: &Commit) -> Result<(), ResetError>;
@@ -123,6 +129,7 @@ pub trait LockedWorkingCopy {
     fn set_sparse_patterns(
         &mut self,
         new_sparse_patterns: Vec<RepoPathBuf>,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError>;
 
     /// Finish the modifications to the working copy by writing the updated
@@ -198,6 +205,12 @@ pub struct SnapshotOptions<'a> {
     /// (depending on implementation)
     /// return `SnapshotError::NewFileTooLarge`.
     pub max_new_file_size: u64,
+    /// The style of the conflict markers that were written to conflicted files,
+    /// used to tell whether a conflicted file was modified.
+    pub conflict_marker_style: ConflictMarkerStyle,
+    /// The store to read the checked-out commit of Git submodules from. If
+    /// `None`, submodules are left unchanged.
+    pub submodule_store: Option<Arc<dyn SubmoduleStore>>,
 }
 
 impl SnapshotOptions<'_> {
@@ -208,6 +221,28 @@ impl SnapshotOptions<'_> {
             fsmonitor_settings: FsmonitorSettings::None,
             progress: None,
             max_new_file_size: u64::MAX,
+            conflict_marker_style: ConflictMarkerStyle::default(),
+            submodule_store: None,
+        }
+    }
+}
+
+/// Options used when checking out a tree in the working copy.
+#[derive(Clone, Debug)]
+pub struct CheckoutOptions {
+    /// The style of the conflict markers to write to conflicted files.
+    pub conflict_marker_style: ConflictMarkerStyle,
+    /// The store to check out Git submodules from. If `None`, submodules are
+    /// not checked out.
+    pub submodule_store: Option<Arc<dyn SubmoduleStore>>,
+}
+
+impl CheckoutOptions {
+    /// Create an instance for use in tests.
+    pub fn empty_for_test() -> Self {
+        CheckoutOptions {
+            conflict_marker_style: ConflictMarkerStyle::default(),
+            submodule_store: None,
         }
     }
 }
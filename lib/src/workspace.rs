@@ -28,6 +28,7 @@ use crate::commit::Commit;
 use crate::file_util::{IoResultExt as _, PathError};
 use crate::local_backend::LocalBackend;
 use crate::local_working_copy::{LocalWorkingCopy, LocalWorkingCopyFactory};
+use crate::merge_drivers::MergeDriverConfigError;
 use crate::op_store::{OperationId, WorkspaceId};
 use crate::repo::{
     read_store_type, BackendInitializer, CheckOutCommitError, IndexStoreInitializer,
@@ -38,8 +39,8 @@ use crate::settings::UserSettings;
 use crate::signing::{SignInitError, Signer};
 use crate::store::Store;
 use crate::working_copy::{
-    CheckoutError, CheckoutStats, LockedWorkingCopy, WorkingCopy, WorkingCopyFactory,
-    WorkingCopyStateError,
+    CheckoutError, CheckoutOptions, CheckoutStats, LockedWorkingCopy, WorkingCopy,
+    WorkingCopyFactory, WorkingCopyStateError,
 };
 
 #[derive(Error, Debug)]
@@ -58,6 +59,8 @@ pub enum WorkspaceInitError {
     Backend(#[from] BackendInitError),
     #[error(transparent)]
     SignInit(#[from] SignInitError),
+    #[error(transparent)]
+    MergeDrivers(#[from] MergeDriverConfigError),
 }
 
 #[derive(Error, Debug)]
@@ -270,6 +273,7 @@ impl Workspace {
             .map_err(|repo_init_err| match repo_init_err {
                 RepoInitError::Backend(err) => WorkspaceInitError::Backend(err),
                 RepoInitError::Path(err) => WorkspaceInitError::Path(err),
+                RepoInitError::MergeDrivers(err) => WorkspaceInitError::MergeDrivers(err),
             })?;
             let (working_copy, repo) = init_working_copy(
                 user_settings,
@@ -387,6 +391,7 @@ impl Workspace {
         operation_id: OperationId,
         old_tree_id: Option<&MergedTreeId>,
         commit: &Commit,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         let mut locked_ws =
             self.start_working_copy_mutation()
@@ -403,7 +408,7 @@ impl Workspace {
                 return Err(CheckoutError::ConcurrentCheckout);
             }
         }
-        let stats = locked_ws.locked_wc().check_out(commit)?;
+        let stats = locked_ws.locked_wc().check_out(commit, options)?;
         locked_ws
             .finish(operation_id)
             .map_err(|err| CheckoutError::Other {
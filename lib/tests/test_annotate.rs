@@ -0,0 +1,206 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+
+use jj_lib::annotate::{get_annotation_for_file, get_annotation_with_domain};
+use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
+use jj_lib::repo::{MutableRepo, Repo};
+use jj_lib::repo_path::RepoPath;
+use jj_lib::settings::UserSettings;
+use testutils::{create_tree, TestRepo};
+
+fn create_commit(
+    mut_repo: &mut MutableRepo,
+    settings: &UserSettings,
+    repo: &TestRepo,
+    parents: &[&Commit],
+    content: &str,
+) -> Commit {
+    let file_path = RepoPath::from_internal_string("file");
+    let tree = create_tree(&repo.repo, &[(file_path, content)]);
+    let parent_ids = if parents.is_empty() {
+        vec![repo.repo.store().root_commit_id().clone()]
+    } else {
+        parents.iter().map(|commit| commit.id().clone()).collect()
+    };
+    mut_repo
+        .new_commit(settings, parent_ids, tree.id())
+        .write()
+        .unwrap()
+}
+
+/// Formats the annotation of `file` in `commit` as lines of
+/// `<commit name>: <line>`.
+fn annotate(commit: &Commit, names: &HashMap<&CommitId, &str>) -> String {
+    let file_path = RepoPath::from_internal_string("file");
+    let results = get_annotation_for_file(commit, file_path).unwrap();
+    results
+        .file_annotations
+        .iter()
+        .map(|(commit_id, line)| {
+            let name = names.get(commit_id).copied().unwrap_or("?");
+            format!("{name}: {}", String::from_utf8_lossy(line))
+        })
+        .collect()
+}
+
+#[test]
+fn test_annotate_linear() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.mut_repo();
+    let commit1 = create_commit(mut_repo, &settings, &test_repo, &[], "1\n");
+    let commit2 = create_commit(mut_repo, &settings, &test_repo, &[&commit1], "1\n2\n");
+    let commit3 = create_commit(
+        mut_repo,
+        &settings,
+        &test_repo,
+        &[&commit2],
+        "0\n1\n2 modified",
+    );
+    let names = HashMap::from([
+        (commit1.id(), "commit1"),
+        (commit2.id(), "commit2"),
+        (commit3.id(), "commit3"),
+    ]);
+
+    insta::assert_snapshot!(annotate(&commit1, &names), @"commit1: 1");
+    insta::assert_snapshot!(annotate(&commit2, &names), @r###"
+    commit1: 1
+    commit2: 2
+    "###);
+    insta::assert_snapshot!(annotate(&commit3, &names), @r###"
+    commit3: 0
+    commit1: 1
+    commit3: 2 modified
+    "###);
+}
+
+#[test]
+fn test_annotate_merge() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.mut_repo();
+    let commit1 = create_commit(mut_repo, &settings, &test_repo, &[], "1\n");
+    let commit2 = create_commit(mut_repo, &settings, &test_repo, &[&commit1], "1\n2\n");
+    let commit3 = create_commit(mut_repo, &settings, &test_repo, &[&commit1], "1\n3\n");
+    let commit4 = create_commit(
+        mut_repo,
+        &settings,
+        &test_repo,
+        &[&commit2, &commit3],
+        "1\n2\n3\n4\n",
+    );
+    let names = HashMap::from([
+        (commit1.id(), "commit1"),
+        (commit2.id(), "commit2"),
+        (commit3.id(), "commit3"),
+        (commit4.id(), "commit4"),
+    ]);
+
+    insta::assert_snapshot!(annotate(&commit4, &names), @r###"
+    commit1: 1
+    commit2: 2
+    commit3: 3
+    commit4: 4
+    "###);
+}
+
+#[test]
+fn test_annotate_file_added_later() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.mut_repo();
+    let empty_tree_id = repo.store().empty_merged_tree_id();
+    let commit1 = mut_repo
+        .new_commit(
+            &settings,
+            vec![repo.store().root_commit_id().clone()],
+            empty_tree_id,
+        )
+        .write()
+        .unwrap();
+    let commit2 = create_commit(mut_repo, &settings, &test_repo, &[&commit1], "1\n");
+    let names = HashMap::from([(commit1.id(), "commit1"), (commit2.id(), "commit2")]);
+
+    insta::assert_snapshot!(annotate(&commit1, &names), @"");
+    insta::assert_snapshot!(annotate(&commit2, &names), @"commit2: 1");
+}
+
+#[test]
+fn test_annotate_with_domain() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.mut_repo();
+    let commit1 = create_commit(mut_repo, &settings, &test_repo, &[], "1\n");
+    let commit2 = create_commit(mut_repo, &settings, &test_repo, &[&commit1], "1\n2\n");
+    let commit3 = create_commit(mut_repo, &settings, &test_repo, &[&commit2], "1\n2\n3\n");
+    let names = HashMap::from([
+        (commit1.id(), "commit1"),
+        (commit2.id(), "commit2"),
+        (commit3.id(), "commit3"),
+    ]);
+
+    // Annotates content on top of commit3 without leaving the domain. Lines
+    // introduced by commits outside the domain, and new lines, are left
+    // unattributed.
+    let annotate_with_domain = |domain: &[&Commit]| {
+        let domain: HashSet<_> = domain.iter().map(|commit| commit.id().clone()).collect();
+        let file_path = RepoPath::from_internal_string("file");
+        let content = b"1\n2\n3\n4\n";
+        let line_origins =
+            get_annotation_with_domain(content, std::slice::from_ref(&commit3), file_path, &domain)
+                .unwrap();
+        line_origins
+            .iter()
+            .zip(content.split_inclusive(|b| *b == b'\n'))
+            .map(|(commit_id, line)| {
+                let name = commit_id.as_ref().map_or("-", |id| names[id]);
+                format!("{name}: {}", String::from_utf8_lossy(line))
+            })
+            .collect::<String>()
+    };
+    insta::assert_snapshot!(annotate_with_domain(&[&commit1, &commit2, &commit3]), @r###"
+    commit1: 1
+    commit2: 2
+    commit3: 3
+    -: 4
+    "###);
+    insta::assert_snapshot!(annotate_with_domain(&[&commit2, &commit3]), @r###"
+    -: 1
+    commit2: 2
+    commit3: 3
+    -: 4
+    "###);
+    insta::assert_snapshot!(annotate_with_domain(&[&commit2]), @r###"
+    -: 1
+    -: 2
+    -: 3
+    -: 4
+    "###);
+}
@@ -321,6 +321,56 @@ fn test_rewrite_resets_author_timestamp(backend: TestRepoBackend) {
     assert_eq!(rewritten_commit_2.committer().timestamp, new_timestamp_2);
 }
 
+#[test_case(TestRepoBackend::Local ; "local backend")]
+#[test_case(TestRepoBackend::Git ; "git backend")]
+fn test_write_hidden(backend: TestRepoBackend) {
+    // Pin the committer timestamp so rewriting twice produces the same commit
+    let config = testutils::base_config()
+        .set_override("debug.commit-timestamp", "2001-02-03T04:05:06+07:00")
+        .unwrap()
+        .build()
+        .unwrap();
+    let settings = UserSettings::from_config(config);
+    let test_repo = TestRepo::init_with_backend(backend);
+    let repo = &test_repo.repo;
+    let store = repo.store().clone();
+
+    let mut tx = repo.start_transaction(&settings);
+    let initial_commit = tx
+        .mut_repo()
+        .new_commit(
+            &settings,
+            vec![store.root_commit_id().clone()],
+            store.empty_merged_tree_id(),
+        )
+        .write()
+        .unwrap();
+    let repo = tx.commit("test");
+
+    let mut tx = repo.start_transaction(&settings);
+    let hidden_commit = tx
+        .mut_repo()
+        .rewrite_commit(&settings, &initial_commit)
+        .set_description("description")
+        .write_hidden()
+        .unwrap();
+    assert_eq!(hidden_commit.description(), "description");
+    assert_eq!(hidden_commit.change_id(), initial_commit.change_id());
+    assert_eq!(store.get_commit(hidden_commit.id()).unwrap(), hidden_commit);
+    // The hidden commit isn't added to the repo
+    assert!(!tx.mut_repo().view().heads().contains(hidden_commit.id()));
+    assert!(!tx.mut_repo().has_changes());
+
+    // Writing the same commit results in the same commit
+    let commit = tx
+        .mut_repo()
+        .rewrite_commit(&settings, &initial_commit)
+        .set_description("description")
+        .write()
+        .unwrap();
+    assert_eq!(commit, hidden_commit);
+}
+
 #[test_case(TestRepoBackend::Local ; "local backend")]
 // #[test_case(TestRepoBackend::Git ; "git backend")]
 fn test_commit_builder_descendants(backend: TestRepoBackend) {
@@ -16,6 +16,7 @@ use indoc::indoc;
 use jj_lib::backend::FileId;
 use jj_lib::conflicts::{
     extract_as_single_hunk, materialize_merge_result, parse_conflict, update_from_content,
+    ConflictMarkerStyle,
 };
 use jj_lib::merge::Merge;
 use jj_lib::repo::Repo;
@@ -116,6 +117,97 @@ fn test_materialize_conflict_basic() {
     );
 }
 
+#[test]
+fn test_materialize_conflict_marker_styles() {
+    let test_repo = TestRepo::init();
+    let store = test_repo.repo.store();
+
+    let path = RepoPath::from_internal_string("file");
+    let base_id = testutils::write_file(store, path, "line 1\nline 2\nline 3\n");
+    let left_id = testutils::write_file(store, path, "line 1\nleft 2\nline 3\n");
+    let right_id = testutils::write_file(store, path, "line 1\nright 2\nline 3\n");
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_id.clone())],
+        vec![Some(left_id.clone()), Some(right_id.clone())],
+    );
+
+    insta::assert_snapshot!(
+        &materialize_conflict_string_with_style(
+            store,
+            path,
+            &conflict,
+            ConflictMarkerStyle::Snapshot
+        ),
+        @r###"
+    line 1
+    <<<<<<< Conflict 1 of 1
+    +++++++ Contents of side #1
+    left 2
+    ------- Contents of base
+    line 2
+    +++++++ Contents of side #2
+    right 2
+    >>>>>>> Conflict 1 of 1 ends
+    line 3
+    "###
+    );
+    insta::assert_snapshot!(
+        &materialize_conflict_string_with_style(
+            store,
+            path,
+            &conflict,
+            ConflictMarkerStyle::Git
+        ),
+        @r###"
+    line 1
+    <<<<<<< Side #1 (Conflict 1 of 1)
+    left 2
+    ||||||| Base
+    line 2
+    =======
+    right 2
+    >>>>>>> Side #2 (Conflict 1 of 1 ends)
+    line 3
+    "###
+    );
+
+    // Git-style markers can only represent 2-sided conflicts, so conflicts with
+    // more sides fall back to snapshot-style markers.
+    let other_id = testutils::write_file(store, path, "line 1\nother 2\nline 3\n");
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_id.clone()), Some(base_id.clone())],
+        vec![
+            Some(left_id.clone()),
+            Some(right_id.clone()),
+            Some(other_id),
+        ],
+    );
+    insta::assert_snapshot!(
+        &materialize_conflict_string_with_style(
+            store,
+            path,
+            &conflict,
+            ConflictMarkerStyle::Git
+        ),
+        @r###"
+    line 1
+    <<<<<<< Conflict 1 of 1
+    +++++++ Contents of side #1
+    left 2
+    ------- Contents of base #1
+    line 2
+    +++++++ Contents of side #2
+    right 2
+    ------- Contents of base #2
+    line 2
+    +++++++ Contents of side #3
+    other 2
+    >>>>>>> Conflict 1 of 1 ends
+    line 3
+    "###
+    );
+}
+
 #[test]
 fn test_materialize_conflict_multi_rebase_conflicts() {
     let test_repo = TestRepo::init();
@@ -583,6 +675,59 @@ fn test_parse_conflict_simple() {
     )
 }
 
+#[test]
+fn test_parse_conflict_git_style() {
+    insta::assert_debug_snapshot!(
+        parse_conflict(indoc! {b"
+            line 1
+            <<<<<<< Side #1
+            left
+            ||||||| Base
+            line 2
+            =======
+            right
+            >>>>>>> Side #2
+            line 3
+            "},
+            2
+        ),
+        @r###"
+    Some(
+        [
+            Resolved(
+                "line 1\n",
+            ),
+            Conflicted(
+                [
+                    "left\n",
+                    "line 2\n",
+                    "right\n",
+                ],
+            ),
+            Resolved(
+                "line 3\n",
+            ),
+        ],
+    )
+    "###
+    );
+    // A Git-style conflict without a base section isn't recognized
+    insta::assert_debug_snapshot!(
+        parse_conflict(indoc! {b"
+            line 1
+            <<<<<<< Side #1
+            left
+            =======
+            right
+            >>>>>>> Side #2
+            line 3
+            "},
+            2
+        ),
+        @"None"
+    );
+}
+
 #[test]
 fn test_parse_conflict_multi_way() {
     insta::assert_debug_snapshot!(
@@ -762,7 +907,7 @@ fn test_update_conflict_from_content() {
     // old conflict id back.
     let materialized = materialize_conflict_string(store, path, &conflict);
     let parse = |content| {
-        update_from_content(&conflict, store, path, content)
+        update_from_content(&conflict, store, path, content, ConflictMarkerStyle::Diff)
             .block_on()
             .unwrap()
     };
@@ -794,6 +939,32 @@ fn test_update_conflict_from_content() {
             ]
         )
     );
+
+    // Git-style conflict markers are recognized regardless of the configured
+    // style, and an unchanged Git-style conflict gives the old conflict back.
+    let materialized =
+        materialize_conflict_string_with_style(store, path, &conflict, ConflictMarkerStyle::Git);
+    assert_eq!(
+        update_from_content(
+            &conflict,
+            store,
+            path,
+            materialized.as_bytes(),
+            ConflictMarkerStyle::Git
+        )
+        .block_on()
+        .unwrap(),
+        conflict
+    );
+    let new_conflict =
+        parse(b"resolved 1\nline 2\n<<<<<<<\nleft 3\n|||||||\nline 3\n=======\nright 3\n>>>>>>>\n");
+    assert_eq!(
+        new_conflict,
+        Merge::from_removes_adds(
+            vec![Some(new_base_file_id)],
+            vec![Some(new_left_file_id), Some(new_right_file_id)]
+        )
+    );
 }
 
 #[test]
@@ -811,7 +982,7 @@ fn test_update_conflict_from_content_modify_delete() {
     // old conflict id back.
     let materialized = materialize_conflict_string(store, path, &conflict);
     let parse = |content| {
-        update_from_content(&conflict, store, path, content)
+        update_from_content(&conflict, store, path, content, ConflictMarkerStyle::Diff)
             .block_on()
             .unwrap()
     };
@@ -865,7 +1036,7 @@ fn test_update_conflict_from_content_simplified_conflict() {
     let materialized = materialize_conflict_string(store, path, &conflict);
     let materialized_simplified = materialize_conflict_string(store, path, &simplified_conflict);
     let parse = |content| {
-        update_from_content(&conflict, store, path, content)
+        update_from_content(&conflict, store, path, content, ConflictMarkerStyle::Diff)
             .block_on()
             .unwrap()
     };
@@ -989,11 +1160,20 @@ fn materialize_conflict_string(
     store: &Store,
     path: &RepoPath,
     conflict: &Merge<Option<FileId>>,
+) -> String {
+    materialize_conflict_string_with_style(store, path, conflict, ConflictMarkerStyle::Diff)
+}
+
+fn materialize_conflict_string_with_style(
+    store: &Store,
+    path: &RepoPath,
+    conflict: &Merge<Option<FileId>>,
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> String {
     let mut result: Vec<u8> = vec![];
     let contents = extract_as_single_hunk(conflict, store, path)
         .block_on()
         .unwrap();
-    materialize_merge_result(&contents, &mut result).unwrap();
+    materialize_merge_result(&contents, conflict_marker_style, &mut result).unwrap();
     String::from_utf8(result).unwrap()
 }
@@ -1514,6 +1514,155 @@ fn test_export_refs_branch_changed() {
     assert_eq!(git_repo.head().unwrap().name(), Some("refs/heads/feature"));
 }
 
+#[test]
+fn test_export_refs_tag_changed() {
+    // Tags are exported like local branches, but an annotated tag object created
+    // by Git isn't replaced by a lightweight tag
+    let test_data = GitRepoData::create();
+    let git_settings = GitSettings::default();
+    let git_repo = test_data.git_repo;
+    let commit = empty_git_commit(&git_repo, "refs/heads/main", &[]);
+    let signature = git2::Signature::now("Someone", "someone@example.com").unwrap();
+    let annotated_tag_oid = git_repo
+        .tag("v1", commit.as_object(), &signature, "Release 1", false)
+        .unwrap();
+    git_repo
+        .reference("refs/tags/v3", commit.id(), false, "test")
+        .unwrap();
+
+    let mut tx = test_data.repo.start_transaction(&test_data.settings);
+    let mut_repo = tx.mut_repo();
+    git::import_refs(mut_repo, &git_settings).unwrap();
+    assert!(git::export_refs(mut_repo).unwrap().is_empty());
+    assert_eq!(mut_repo.get_tag("v1"), RefTarget::normal(jj_id(&commit)));
+
+    let new_commit = create_random_commit(mut_repo, &test_data.settings)
+        .set_parents(vec![jj_id(&commit)])
+        .write()
+        .unwrap();
+    mut_repo.set_tag_target("v1", RefTarget::normal(new_commit.id().clone()));
+    mut_repo.set_tag_target("v2", RefTarget::normal(new_commit.id().clone()));
+    let failed = git::export_refs(mut_repo).unwrap();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].name, RefName::Tag("v1".to_string()));
+    assert_matches!(failed[0].reason, FailedRefExportReason::AnnotatedTagMoved);
+    assert_eq!(
+        mut_repo.get_git_ref("refs/tags/v1"),
+        RefTarget::normal(jj_id(&commit))
+    );
+    assert_eq!(
+        git_repo.find_reference("refs/tags/v1").unwrap().target(),
+        Some(annotated_tag_oid)
+    );
+    assert_eq!(
+        mut_repo.get_git_ref("refs/tags/v2"),
+        RefTarget::normal(new_commit.id().clone())
+    );
+    assert_eq!(
+        git_repo.find_reference("refs/tags/v2").unwrap().target(),
+        Some(git_id(&new_commit))
+    );
+
+    // Deleting a tag deletes the Git ref, and the untouched tag stays
+    mut_repo.set_tag_target("v2", RefTarget::absent());
+    assert_eq!(git::export_refs(mut_repo).unwrap().len(), 1);
+    assert!(mut_repo.get_git_ref("refs/tags/v2").is_absent());
+    assert!(git_repo.find_reference("refs/tags/v2").is_err());
+    assert_eq!(
+        mut_repo.get_git_ref("refs/tags/v3"),
+        RefTarget::normal(jj_id(&commit))
+    );
+    assert_eq!(
+        git_repo.find_reference("refs/tags/v3").unwrap().target(),
+        Some(commit.id())
+    );
+}
+
+#[test]
+fn test_create_annotated_tag() {
+    let test_data = GitRepoData::create();
+    let git_repo = test_data.git_repo;
+    let commit = empty_git_commit(&git_repo, "refs/heads/main", &[]);
+    let tagger = Signature {
+        name: "Test User".to_owned(),
+        email: "test.user@example.com".to_owned(),
+        timestamp: Timestamp {
+            timestamp: MillisSinceEpoch(1_000_000_000_000),
+            tz_offset: -90,
+        },
+    };
+
+    let mut tx = test_data.repo.start_transaction(&test_data.settings);
+    let mut_repo = tx.mut_repo();
+    let mut sign_fn = |data: &[u8]| {
+        assert!(data.ends_with(b"Release 1\n"));
+        Ok(b"-----BEGIN TEST SIGNATURE-----\n".to_vec())
+    };
+    let tag = git::create_annotated_tag(
+        mut_repo,
+        &git_repo,
+        "v1",
+        &jj_id(&commit),
+        &tagger,
+        "Release 1",
+        Some(&mut sign_fn),
+    )
+    .unwrap();
+    assert_eq!(mut_repo.get_tag("v1"), RefTarget::normal(jj_id(&commit)));
+    // The Git ref isn't written until the tag is exported
+    assert!(mut_repo.get_git_ref("refs/tags/v1").is_absent());
+    assert!(git_repo.find_reference("refs/tags/v1").is_err());
+
+    assert!(git::export_annotated_tags(mut_repo, &[tag])
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        mut_repo.get_git_ref("refs/tags/v1"),
+        RefTarget::normal(jj_id(&commit))
+    );
+    let git_tag = git_repo
+        .find_reference("refs/tags/v1")
+        .unwrap()
+        .peel_to_tag()
+        .unwrap();
+    assert_eq!(git_tag.target_id(), commit.id());
+    assert_eq!(
+        git_tag.message(),
+        Some("Release 1\n-----BEGIN TEST SIGNATURE-----\n")
+    );
+    let git_tagger = git_tag.tagger().unwrap();
+    assert_eq!(git_tagger.name(), Some("Test User"));
+    assert_eq!(git_tagger.when().seconds(), 1_000_000_000);
+    assert_eq!(git_tagger.when().offset_minutes(), -90);
+    // Nothing to export since the Git ref is already up to date
+    assert!(git::export_refs(mut_repo).unwrap().is_empty());
+
+    assert_matches!(
+        git::create_annotated_tag(
+            mut_repo,
+            &git_repo,
+            "v1",
+            &jj_id(&commit),
+            &tagger,
+            "",
+            None,
+        ),
+        Err(git::GitTagError::AlreadyExists(_))
+    );
+    assert_matches!(
+        git::create_annotated_tag(
+            mut_repo,
+            &git_repo,
+            "bad..name",
+            &jj_id(&commit),
+            &tagger,
+            "",
+            None,
+        ),
+        Err(git::GitTagError::InvalidName(_))
+    );
+}
+
 #[test]
 fn test_export_refs_current_branch_changed() {
     // If we update a branch that is checked out in the git repo, HEAD gets detached
@@ -18,11 +18,14 @@ use std::process::Command;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 
-use jj_lib::backend::CommitId;
+use futures::StreamExt as _;
+use jj_lib::backend::{CommitId, CopyRecord, TreeValue};
 use jj_lib::git_backend::GitBackend;
 use jj_lib::repo::{ReadonlyRepo, Repo};
+use jj_lib::repo_path::{RepoPath, RepoPathBuf};
 use maplit::hashset;
-use testutils::{create_random_commit, CommitGraphBuilder, TestRepo, TestRepoBackend};
+use pollster::FutureExt as _;
+use testutils::{create_random_commit, create_tree, CommitGraphBuilder, TestRepo, TestRepoBackend};
 
 fn get_git_backend(repo: &Arc<ReadonlyRepo>) -> &GitBackend {
     repo.store()
@@ -191,3 +194,75 @@ fn test_gc() {
     repo.store().gc(base_index.as_index(), now()).unwrap();
     assert_eq!(collect_no_gc_refs(git_repo_path), hashset! {});
 }
+
+#[test]
+fn test_copy_detection() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+
+    let contents = "1\n2\n3\n4\n5\n6\n7\n8\n";
+    let path_a = RepoPath::from_internal_string("a");
+    let path_b = RepoPath::from_internal_string("dir/b");
+    let path_c = RepoPath::from_internal_string("c");
+    let tree1 = create_tree(repo, &[(path_a, contents), (path_c, "c\n")]);
+    let tree2 = create_tree(repo, &[(path_b, contents), (path_c, "c\n")]);
+    let mut tx = repo.start_transaction(&settings);
+    let commit1 = tx
+        .mut_repo()
+        .new_commit(
+            &settings,
+            vec![repo.store().root_commit_id().clone()],
+            tree1.id(),
+        )
+        .write()
+        .unwrap();
+    let commit2 = tx
+        .mut_repo()
+        .new_commit(&settings, vec![commit1.id().clone()], tree2.id())
+        .write()
+        .unwrap();
+
+    let get_records = |paths: Option<&[RepoPathBuf]>| -> Vec<CopyRecord> {
+        repo.store()
+            .get_copy_records(paths, commit1.id(), commit2.id())
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .block_on()
+    };
+    let records = get_records(None);
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].source, path_a.to_owned());
+    assert_eq!(records[0].target, path_b.to_owned());
+    assert_eq!(records[0].source_commit, *commit1.id());
+    assert_eq!(records[0].target_commit, *commit2.id());
+    let Ok(Some(TreeValue::File { id, .. })) = tree1.path_value(path_a).unwrap().into_resolved()
+    else {
+        panic!("expected a file");
+    };
+    assert_eq!(records[0].source_file, id);
+
+    // Filtered by source or target path
+    assert_eq!(get_records(Some(&[path_a.to_owned()])).len(), 1);
+    assert_eq!(get_records(Some(&[path_b.to_owned()])).len(), 1);
+    assert_eq!(get_records(Some(&[path_c.to_owned()])).len(), 0);
+
+    // Copies between commits that aren't parent and child are inferred
+    let tree3 = create_tree(repo, &[(path_b, contents), (path_c, "modified\n")]);
+    let commit3 = tx
+        .mut_repo()
+        .new_commit(&settings, vec![commit2.id().clone()], tree3.id())
+        .write()
+        .unwrap();
+    let records: Vec<_> = repo
+        .store()
+        .get_copy_records(Some(&[path_a.to_owned()]), commit1.id(), commit3.id())
+        .unwrap()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>()
+        .block_on();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].target, path_b.to_owned());
+    assert_eq!(records[0].target_commit, *commit3.id());
+}
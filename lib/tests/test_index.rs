@@ -24,16 +24,20 @@ use jj_lib::default_index::{
     AsCompositeIndex as _, CompositeIndex, DefaultIndexStore, DefaultIndexStoreError,
     DefaultMutableIndex, DefaultReadonlyIndex,
 };
+use jj_lib::fileset::FilesetExpression;
 use jj_lib::index::Index as _;
 use jj_lib::object_id::{HexPrefix, ObjectId as _, PrefixResolution};
 use jj_lib::op_store::{RefTarget, RemoteRef};
 use jj_lib::repo::{MutableRepo, ReadonlyRepo, Repo};
-use jj_lib::revset::{ResolvedExpression, GENERATION_RANGE_FULL};
+use jj_lib::repo_path::RepoPath;
+use jj_lib::revset::{
+    ResolvedExpression, RevsetExpression, RevsetFilterPredicate, GENERATION_RANGE_FULL,
+};
 use jj_lib::settings::UserSettings;
 use maplit::hashset;
 use testutils::test_backend::TestBackend;
 use testutils::{
-    commit_transactions, create_random_commit, load_repo_at_head, write_random_commit,
+    commit_transactions, create_random_commit, create_tree, load_repo_at_head, write_random_commit,
     CommitGraphBuilder, TestRepo,
 };
 
@@ -727,6 +731,108 @@ fn test_reindex_missing_commit() {
     assert_matches!(err, DefaultIndexStoreError::IndexCommits { op_id, .. } if op_id == *bad_op_id);
 }
 
+#[test]
+fn test_index_changed_paths() {
+    let settings = UserSettings::from_config(
+        testutils::base_config()
+            .add_source(config::File::from_str(
+                "index.changed-paths = true",
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap(),
+    );
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let repo = &test_repo.repo;
+
+    let dir_file = RepoPath::from_internal_string("dir/file");
+    let other_file = RepoPath::from_internal_string("other");
+    let tree1 = create_tree(repo, &[(dir_file, "1")]);
+    let tree2 = create_tree(repo, &[(dir_file, "1"), (other_file, "1")]);
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.mut_repo();
+    let commit1 = mut_repo
+        .new_commit(
+            &settings,
+            vec![repo.store().root_commit_id().clone()],
+            tree1.id(),
+        )
+        .write()
+        .unwrap();
+    let commit2 = mut_repo
+        .new_commit(&settings, vec![commit1.id().clone()], tree2.id())
+        .write()
+        .unwrap();
+    let commit3 = mut_repo
+        .new_commit(&settings, vec![commit2.id().clone()], tree2.id())
+        .write()
+        .unwrap();
+    let commit4 = mut_repo
+        .new_commit(
+            &settings,
+            vec![commit1.id().clone(), commit3.id().clone()],
+            tree1.id(),
+        )
+        .write()
+        .unwrap();
+    let repo = tx.commit("test");
+
+    let resolve = |repo: &Arc<ReadonlyRepo>, expression: FilesetExpression| -> Vec<CommitId> {
+        let expression = RevsetExpression::filter(RevsetFilterPredicate::File(expression));
+        let revset = expression.evaluate_programmatic(repo.as_ref()).unwrap();
+        revset.iter().collect()
+    };
+    let assert_resolved = |repo: &Arc<ReadonlyRepo>| {
+        assert_eq!(
+            resolve(
+                repo,
+                FilesetExpression::prefix_path(dir_file.parent().unwrap().to_owned())
+            ),
+            vec![commit1.id().clone()]
+        );
+        assert_eq!(
+            resolve(repo, FilesetExpression::file_path(other_file.to_owned())),
+            vec![commit4.id().clone(), commit2.id().clone()]
+        );
+        assert_eq!(
+            resolve(repo, FilesetExpression::all()),
+            vec![
+                commit4.id().clone(),
+                commit2.id().clone(),
+                commit1.id().clone()
+            ]
+        );
+    };
+
+    // Every segment should have a changed-path table
+    let segments_dir = repo.repo_path().join("index").join("segments");
+    let segment_paths: Vec<_> = segments_dir
+        .read_dir()
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_none())
+        .collect();
+    assert!(!segment_paths.is_empty());
+    for path in &segment_paths {
+        assert!(path.with_extension("changed_paths").is_file());
+    }
+    assert_resolved(&repo);
+
+    // Reload from the readonly segments
+    let repo = load_repo_at_head(&settings, repo.repo_path());
+    assert_resolved(&repo);
+
+    // Rebuild the index from scratch
+    let default_index_store: &DefaultIndexStore =
+        repo.index_store().as_any().downcast_ref().unwrap();
+    default_index_store.reinit().unwrap();
+    default_index_store
+        .build_index_at_operation(repo.operation(), repo.store())
+        .unwrap();
+    let repo = load_repo_at_head(&settings, repo.repo_path());
+    assert_resolved(&repo);
+}
+
 /// Test that .jj/repo/index/type is created when the repo is created.
 #[test]
 fn test_index_store_type() {
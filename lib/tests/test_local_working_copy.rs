@@ -21,7 +21,7 @@ use std::sync::Arc;
 
 use indoc::indoc;
 use itertools::Itertools;
-use jj_lib::backend::{MergedTreeId, TreeId, TreeValue};
+use jj_lib::backend::{CommitId, MergedTreeId, TreeId, TreeValue};
 use jj_lib::file_util::{check_symlink_support, try_symlink};
 use jj_lib::fsmonitor::FsmonitorSettings;
 use jj_lib::local_working_copy::LocalWorkingCopy;
@@ -32,7 +32,7 @@ use jj_lib::repo::{ReadonlyRepo, Repo};
 use jj_lib::repo_path::{RepoPath, RepoPathBuf, RepoPathComponent};
 use jj_lib::secret_backend::SecretBackend;
 use jj_lib::settings::UserSettings;
-use jj_lib::working_copy::{CheckoutStats, SnapshotError, SnapshotOptions};
+use jj_lib::working_copy::{CheckoutOptions, CheckoutStats, SnapshotError, SnapshotOptions};
 use jj_lib::workspace::{default_working_copy_factories, LockedWorkspace, Workspace};
 use test_case::test_case;
 use testutils::{
@@ -226,10 +226,20 @@ fn test_checkout_file_transitions(backend: TestRepoBackend) {
     let right_commit = commit_with_tree(&store, right_tree_id.clone());
 
     let ws = &mut test_workspace.workspace;
-    ws.check_out(repo.op_id().clone(), None, &left_commit)
-        .unwrap();
-    ws.check_out(repo.op_id().clone(), None, &right_commit)
-        .unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &left_commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &right_commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     // Check that the working copy is clean.
     let new_tree = test_workspace.snapshot().unwrap();
@@ -325,9 +335,20 @@ fn test_conflict_subdirectory() {
     let merged_commit = commit_with_tree(repo.store(), merged_tree.id());
     let repo = &test_workspace.repo;
     let ws = &mut test_workspace.workspace;
-    ws.check_out(repo.op_id().clone(), None, &commit1).unwrap();
-    ws.check_out(repo.op_id().clone(), None, &merged_commit)
-        .unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit1,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &merged_commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 }
 
 #[test]
@@ -371,13 +392,25 @@ fn test_acl() {
         &default_working_copy_factories(),
     )
     .unwrap();
-    ws.check_out(repo.op_id().clone(), None, &commit1).unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit1,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
     assert!(!secret_modified_path.to_fs_path(&workspace_root).is_file());
     assert!(!secret_added_path.to_fs_path(&workspace_root).is_file());
     assert!(!secret_deleted_path.to_fs_path(&workspace_root).is_file());
     assert!(became_secret_path.to_fs_path(&workspace_root).is_file());
     assert!(!became_public_path.to_fs_path(&workspace_root).is_file());
-    ws.check_out(repo.op_id().clone(), None, &commit2).unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit2,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
     assert!(!secret_modified_path.to_fs_path(&workspace_root).is_file());
     assert!(!secret_added_path.to_fs_path(&workspace_root).is_file());
     assert!(!secret_deleted_path.to_fs_path(&workspace_root).is_file());
@@ -396,7 +429,13 @@ fn test_tree_builder_file_directory_transition() {
     let mut check_out_tree = |tree_id: &TreeId| {
         let tree = repo.store().get_tree(RepoPath::root(), tree_id).unwrap();
         let commit = commit_with_tree(repo.store(), MergedTreeId::Legacy(tree.id().clone()));
-        ws.check_out(repo.op_id().clone(), None, &commit).unwrap();
+        ws.check_out(
+            repo.op_id().clone(),
+            None,
+            &commit,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
     };
 
     let parent_path = RepoPath::from_internal_string("foo/bar");
@@ -473,7 +512,14 @@ fn test_conflicting_changes_on_disk() {
     )
     .unwrap();
 
-    let stats = ws.check_out(repo.op_id().clone(), None, &commit).unwrap();
+    let stats = ws
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
     assert_eq!(
         stats,
         CheckoutStats {
@@ -519,7 +565,13 @@ fn test_reset() {
 
     let ws = &mut test_workspace.workspace;
     let commit = commit_with_tree(repo.store(), tree_with_file.id());
-    ws.check_out(repo.op_id().clone(), None, &commit).unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     // Test the setup: the file should exist on disk and in the tree state.
     assert!(ignored_path.to_fs_path(&workspace_root).is_file());
@@ -571,7 +623,13 @@ fn test_checkout_discard() {
     let commit2 = commit_with_tree(repo.store(), tree2.id());
 
     let ws = &mut test_workspace.workspace;
-    ws.check_out(repo.op_id().clone(), None, &commit1).unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit1,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
     let wc: &LocalWorkingCopy = ws.working_copy().as_any().downcast_ref().unwrap();
     let state_path = wc.state_path().to_path_buf();
 
@@ -582,7 +640,10 @@ fn test_checkout_discard() {
 
     // Start a checkout
     let mut locked_ws = ws.start_working_copy_mutation().unwrap();
-    locked_ws.locked_wc().check_out(&commit2).unwrap();
+    locked_ws
+        .locked_wc()
+        .check_out(&commit2, &CheckoutOptions::empty_for_test())
+        .unwrap();
     // The change should be reflected in the working copy but not saved
     assert!(!file1_path.to_fs_path(&workspace_root).is_file());
     assert!(file2_path.to_fs_path(&workspace_root).is_file());
@@ -603,6 +664,124 @@ fn test_checkout_discard() {
     assert!(!reloaded_wc.file_states().unwrap().contains_path(file2_path));
 }
 
+#[test]
+fn test_checkout_many_files() {
+    // Test that checking out many files in parallel updates the files on disk
+    // and the file states consistently.
+    let settings = testutils::user_settings();
+    let mut test_workspace = TestWorkspace::init(&settings);
+    let repo = test_workspace.repo.clone();
+    let workspace_root = test_workspace.workspace.workspace_root().clone();
+
+    let file_path = |dir: usize, file: usize| {
+        RepoPathBuf::from_internal_string(format!("dir{dir}/sub/file{file}"))
+    };
+    let paths1 = (0..4)
+        .cartesian_product(0..100)
+        .map(|(dir, file)| file_path(dir, file))
+        .collect_vec();
+    let contents1 = paths1
+        .iter()
+        .map(|path| format!("{path:?}\n"))
+        .collect_vec();
+    let tree1 = create_tree(
+        &repo,
+        &paths1
+            .iter()
+            .zip(&contents1)
+            .map(|(path, contents)| (path.as_ref(), contents.as_str()))
+            .collect_vec(),
+    );
+    // In the second tree, "dir0" is removed, "dir1" becomes a file, the files
+    // in "dir2" are modified, and "dir3" is kept as is.
+    let dir1_path = RepoPath::from_internal_string("dir1");
+    let paths2 = (2..4)
+        .cartesian_product(0..100)
+        .map(|(dir, file)| file_path(dir, file))
+        .collect_vec();
+    let contents2 = paths2
+        .iter()
+        .map(|path| {
+            if path.as_internal_file_string().starts_with("dir2/") {
+                format!("modified {path:?}\n")
+            } else {
+                format!("{path:?}\n")
+            }
+        })
+        .collect_vec();
+    let tree2 = create_tree(
+        &repo,
+        &paths2
+            .iter()
+            .zip(&contents2)
+            .map(|(path, contents)| (path.as_ref(), contents.as_str()))
+            .chain([(dir1_path, "dir1 is a file\n")])
+            .collect_vec(),
+    );
+    let commit1 = commit_with_tree(repo.store(), tree1.id());
+    let commit2 = commit_with_tree(repo.store(), tree2.id());
+
+    let ws = &mut test_workspace.workspace;
+    let stats = ws
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit1,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
+    assert_eq!(
+        stats,
+        CheckoutStats {
+            updated_files: 0,
+            added_files: 400,
+            removed_files: 0,
+            skipped_files: 0,
+        }
+    );
+    for (path, contents) in paths1.iter().zip(&contents1) {
+        let disk_path = path.to_fs_path(&workspace_root);
+        assert_eq!(std::fs::read_to_string(disk_path).unwrap(), *contents);
+    }
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), tree1.id());
+
+    let ws = &mut test_workspace.workspace;
+    let stats = ws
+        .check_out(
+            repo.op_id().clone(),
+            Some(&tree1.id()),
+            &commit2,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
+    assert_eq!(
+        stats,
+        CheckoutStats {
+            updated_files: 100,
+            added_files: 1,
+            removed_files: 200,
+            skipped_files: 0,
+        }
+    );
+    assert!(!workspace_root.join("dir0").exists());
+    assert_eq!(
+        std::fs::read_to_string(dir1_path.to_fs_path(&workspace_root)).unwrap(),
+        "dir1 is a file\n"
+    );
+    for (path, contents) in paths2.iter().zip(&contents2) {
+        let disk_path = path.to_fs_path(&workspace_root);
+        assert_eq!(std::fs::read_to_string(disk_path).unwrap(), *contents);
+    }
+    let wc: &LocalWorkingCopy = ws.working_copy().as_any().downcast_ref().unwrap();
+    let file_states = wc.file_states().unwrap();
+    assert_eq!(file_states.into_iter().count(), 201);
+    assert!(file_states.contains_path(dir1_path));
+    assert!(!file_states.contains_path(&file_path(0, 0)));
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), tree2.id());
+}
+
 #[test]
 fn test_materialize_snapshot_conflicted_files() {
     let settings = testutils::user_settings();
@@ -629,7 +808,14 @@ fn test_materialize_snapshot_conflicted_files() {
         .unwrap();
     let commit = commit_with_tree(repo.store(), merged_tree.id());
 
-    let stats = ws.check_out(repo.op_id().clone(), None, &commit).unwrap();
+    let stats = ws
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
     assert_eq!(
         stats,
         CheckoutStats {
@@ -876,7 +1062,13 @@ fn test_gitignores_in_ignored_dir() {
     let tree1 = create_tree(&test_workspace.repo, &[(gitignore_path, "ignored\n")]);
     let commit1 = commit_with_tree(test_workspace.repo.store(), tree1.id());
     let ws = &mut test_workspace.workspace;
-    ws.check_out(op_id.clone(), None, &commit1).unwrap();
+    ws.check_out(
+        op_id.clone(),
+        None,
+        &commit1,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     testutils::write_working_copy_file(&workspace_root, nested_gitignore_path, "!file\n");
     testutils::write_working_copy_file(&workspace_root, ignored_path, "contents");
@@ -928,7 +1120,14 @@ fn test_gitignores_checkout_never_overwrites_ignored() {
     // "contents". The exiting contents ("garbage") shouldn't be replaced in the
     // working copy.
     let ws = &mut test_workspace.workspace;
-    assert!(ws.check_out(repo.op_id().clone(), None, &commit).is_ok());
+    assert!(ws
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit,
+            &CheckoutOptions::empty_for_test()
+        )
+        .is_ok());
 
     // Check that the old contents are in the working copy
     let path = workspace_root.join("modified");
@@ -998,7 +1197,13 @@ fn test_gitignores_ignored_directory_already_tracked() {
 
     // Check out the tree with the files in `ignored/`
     let ws = &mut test_workspace.workspace;
-    ws.check_out(repo.op_id().clone(), None, &commit).unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     // Make some changes inside the ignored directory and check that they are
     // detected when we snapshot. The files that are still there should not be
@@ -1103,7 +1308,13 @@ fn test_gitsubmodule() {
     let tree = store.get_root_tree(&tree_id).unwrap();
     let commit = commit_with_tree(repo.store(), tree.id());
     let ws = &mut test_workspace.workspace;
-    ws.check_out(repo.op_id().clone(), None, &commit).unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     std::fs::create_dir(submodule_path.to_fs_path(&workspace_root)).unwrap();
 
@@ -1126,6 +1337,110 @@ fn test_gitsubmodule() {
     );
 }
 
+#[test]
+fn test_gitsubmodule_check_out_and_snapshot() {
+    // Tests that git submodules are checked out from the submodule store, and
+    // that moving HEAD of a submodule checkout is snapshotted.
+    let settings = testutils::user_settings();
+    let mut test_workspace = TestWorkspace::init_with_backend(&settings, TestRepoBackend::Git);
+    let repo = test_workspace.repo.clone();
+    let store = repo.store().clone();
+    let submodule_store = repo.submodule_store().clone();
+    let workspace_root = test_workspace.workspace.workspace_root().clone();
+
+    // Create a Git repo with two commits and fetch it into the store
+    let origin_dir = testutils::new_temp_dir();
+    let origin_repo = git2::Repository::init_bare(origin_dir.path()).unwrap();
+    let signature = git2::Signature::now("Someone", "someone@example.com").unwrap();
+    let blob = origin_repo.blob(b"contents\n").unwrap();
+    let mut origin_tree_builder = origin_repo.treebuilder(None).unwrap();
+    origin_tree_builder.insert("file", blob, 0o100644).unwrap();
+    let origin_tree = origin_repo
+        .find_tree(origin_tree_builder.write().unwrap())
+        .unwrap();
+    let oid1 = origin_repo
+        .commit(None, &signature, &signature, "1", &origin_tree, &[])
+        .unwrap();
+    let oid2 = origin_repo
+        .commit(
+            Some("refs/heads/main"),
+            &signature,
+            &signature,
+            "2",
+            &origin_tree,
+            &[&origin_repo.find_commit(oid1).unwrap()],
+        )
+        .unwrap();
+    let submodule_path = RepoPath::from_internal_string("submodule");
+    submodule_store
+        .fetch(submodule_path, origin_dir.path().to_str().unwrap())
+        .unwrap();
+
+    let mut tree_builder = store.tree_builder(store.empty_tree_id().clone());
+    tree_builder.set(
+        submodule_path.to_owned(),
+        TreeValue::GitSubmodule(CommitId::from_bytes(oid1.as_bytes())),
+    );
+    let tree_id = MergedTreeId::Legacy(tree_builder.write_tree().unwrap());
+    let commit = commit_with_tree(&store, tree_id.clone());
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit,
+        &CheckoutOptions {
+            submodule_store: Some(submodule_store.clone()),
+            ..CheckoutOptions::empty_for_test()
+        },
+    )
+    .unwrap();
+    let submodule_disk_path = submodule_path.to_fs_path(&workspace_root);
+    assert!(submodule_disk_path.join("file").is_file());
+
+    // Snapshotting an unchanged submodule doesn't change the tree
+    let snapshot_options = || SnapshotOptions {
+        submodule_store: Some(submodule_store.clone()),
+        ..SnapshotOptions::empty_for_test()
+    };
+    let mut locked_ws = ws.start_working_copy_mutation().unwrap();
+    let new_tree_id = locked_ws.locked_wc().snapshot(snapshot_options()).unwrap();
+    locked_ws.finish(repo.op_id().clone()).unwrap();
+    assert_eq!(new_tree_id, tree_id);
+
+    // Moving HEAD of the submodule is recorded in the tree
+    let submodule_repo = git2::Repository::open(&submodule_disk_path).unwrap();
+    submodule_repo.set_head_detached(oid2).unwrap();
+    let mut locked_ws = ws.start_working_copy_mutation().unwrap();
+    let new_tree_id = locked_ws.locked_wc().snapshot(snapshot_options()).unwrap();
+    locked_ws.finish(repo.op_id().clone()).unwrap();
+    let new_tree = store.get_root_tree(&new_tree_id).unwrap();
+    assert_eq!(
+        new_tree.path_value(submodule_path).unwrap(),
+        Merge::normal(TreeValue::GitSubmodule(CommitId::from_bytes(
+            oid2.as_bytes()
+        )))
+    );
+
+    // The checkout left behind by a removed submodule isn't snapshotted
+    let commit = commit_with_tree(&store, store.empty_merged_tree_id());
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit,
+        &CheckoutOptions {
+            submodule_store: Some(submodule_store.clone()),
+            ..CheckoutOptions::empty_for_test()
+        },
+    )
+    .unwrap();
+    assert!(submodule_disk_path.join("file").is_file());
+    let mut locked_ws = ws.start_working_copy_mutation().unwrap();
+    let new_tree_id = locked_ws.locked_wc().snapshot(snapshot_options()).unwrap();
+    locked_ws.finish(repo.op_id().clone()).unwrap();
+    assert_eq!(new_tree_id, store.empty_merged_tree_id());
+}
+
 #[test]
 fn test_existing_directory_symlink() {
     let settings = testutils::user_settings();
@@ -1147,7 +1462,14 @@ fn test_existing_directory_symlink() {
 
     // Checkout should fail because "parent" already exists and is a symlink.
     let ws = &mut test_workspace.workspace;
-    assert!(ws.check_out(repo.op_id().clone(), None, &commit).is_err());
+    assert!(ws
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit,
+            &CheckoutOptions::empty_for_test()
+        )
+        .is_err());
 
     // Therefore, "../escaped" shouldn't be created.
     assert!(!workspace_root.parent().unwrap().join("escaped").exists());
@@ -18,7 +18,7 @@ use jj_lib::repo::Repo;
 use jj_lib::repo_path::{RepoPath, RepoPathComponent};
 use jj_lib::rewrite::rebase_commit;
 use jj_lib::tree::{merge_trees, Tree};
-use testutils::{create_single_tree, create_tree, TestRepo};
+use testutils::{create_single_tree, create_tree, TestRepo, TestRepoBackend};
 
 #[test]
 fn test_same_type() {
@@ -641,3 +641,69 @@ fn test_simplify_conflict_after_resolving_parent() {
 
 // TODO: Add tests for simplification of multi-way conflicts. Both the content
 // and the executable bit need testing.
+
+#[test]
+fn test_rebase_follows_rename() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+
+    // Set up a repo like this:
+    // C
+    // | B
+    // |/
+    // A
+    //
+    // Commit A has a file with several lines. B modifies a line of the file,
+    // and C renames the file. Rebasing B onto C should apply the modification
+    // to the renamed file.
+    let old_path = RepoPath::from_internal_string("old");
+    let new_path = RepoPath::from_internal_string("dir/new");
+    let mut tx = repo.start_transaction(&settings);
+    let tree_a = create_tree(repo, &[(old_path, "a\nb\nc\nd\ne\nf\ng\nh\n")]);
+    let commit_a = tx
+        .mut_repo()
+        .new_commit(
+            &settings,
+            vec![repo.store().root_commit_id().clone()],
+            tree_a.id(),
+        )
+        .write()
+        .unwrap();
+    let tree_b = create_tree(repo, &[(old_path, "a\nb\nc\nD\ne\nf\ng\nh\n")]);
+    let commit_b = tx
+        .mut_repo()
+        .new_commit(&settings, vec![commit_a.id().clone()], tree_b.id())
+        .write()
+        .unwrap();
+    let tree_c = create_tree(repo, &[(new_path, "a\nb\nc\nd\ne\nf\ng\nh\n")]);
+    let commit_c = tx
+        .mut_repo()
+        .new_commit(&settings, vec![commit_a.id().clone()], tree_c.id())
+        .write()
+        .unwrap();
+
+    let commit_b2 = rebase_commit(
+        &settings,
+        tx.mut_repo(),
+        commit_b,
+        vec![commit_c.id().clone()],
+    )
+    .unwrap();
+    let tree_b2 = commit_b2.tree().unwrap();
+    assert!(tree_b2.path_value(old_path).unwrap().is_absent());
+    match tree_b2.path_value(new_path).unwrap().into_resolved() {
+        Ok(Some(TreeValue::File {
+            id,
+            executable: false,
+        })) => {
+            assert_eq!(
+                testutils::read_file(repo.store(), new_path, &id),
+                b"a\nb\nc\nD\ne\nf\ng\nh\n"
+            );
+        }
+        other => {
+            panic!("unexpected value: {other:#?}");
+        }
+    }
+}
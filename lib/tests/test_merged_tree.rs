@@ -15,7 +15,8 @@
 use futures::executor::block_on;
 use futures::StreamExt;
 use itertools::Itertools;
-use jj_lib::backend::{FileId, MergedTreeId, TreeValue};
+use jj_lib::backend::{CommitId, CopyRecord, FileId, MergedTreeId, TreeValue};
+use jj_lib::copies::{CopyOperation, CopyRecords};
 use jj_lib::files::MergeResult;
 use jj_lib::matchers::{EverythingMatcher, FilesMatcher, Matcher, PrefixMatcher};
 use jj_lib::merge::{Merge, MergeBuilder};
@@ -24,6 +25,7 @@ use jj_lib::merged_tree::{
 };
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::{RepoPath, RepoPathBuf, RepoPathComponent};
+use jj_lib::settings::UserSettings;
 use jj_lib::tree::merge_trees;
 use pretty_assertions::assert_eq;
 use testutils::{create_single_tree, write_file, TestRepo};
@@ -815,6 +817,103 @@ fn test_diff_resolved() {
     diff_stream_equals_iter(&before_merged, &after_merged, &EverythingMatcher);
 }
 
+/// Diff two resolved trees, with copy records
+#[test]
+fn test_diff_with_copies() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let copy_source_path = RepoPath::from_internal_string("dir1/file");
+    let copy_target_path = RepoPath::from_internal_string("dir1/copy");
+    let rename_source_path = RepoPath::from_internal_string("dir2/file");
+    let rename_target_path = RepoPath::from_internal_string("dir3/file");
+    let before = create_single_tree(
+        repo,
+        &[
+            (copy_source_path, "copied"),
+            (rename_source_path, "renamed"),
+        ],
+    );
+    let after = create_single_tree(
+        repo,
+        &[
+            (copy_source_path, "copied"),
+            (copy_target_path, "copied"),
+            (rename_target_path, "renamed and modified"),
+        ],
+    );
+    let before_merged = MergedTree::new(Merge::resolved(before.clone()));
+    let after_merged = MergedTree::new(Merge::resolved(after.clone()));
+
+    let copy_record = |source: &RepoPath, target: &RepoPath| {
+        let Some(TreeValue::File { id, .. }) = before.path_value(source).unwrap() else {
+            panic!("expected a file");
+        };
+        Ok(CopyRecord {
+            target: target.to_owned(),
+            target_commit: CommitId::from_hex("02"),
+            source: source.to_owned(),
+            source_file: id,
+            source_commit: CommitId::from_hex("01"),
+        })
+    };
+    let mut copy_records = CopyRecords::default();
+    copy_records
+        .add_records([
+            copy_record(copy_source_path, copy_target_path),
+            copy_record(rename_source_path, rename_target_path),
+        ])
+        .unwrap();
+
+    let diff_with_copies = |matcher: &dyn Matcher| {
+        block_on(
+            before_merged
+                .diff_stream_with_copies(&after_merged, matcher, &copy_records)
+                .map(|entry| {
+                    let (before, after) = entry.value.unwrap();
+                    (
+                        entry.source,
+                        entry.target,
+                        entry.copy_operation,
+                        before.is_present(),
+                        after.is_present(),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        )
+    };
+    assert_eq!(
+        diff_with_copies(&EverythingMatcher),
+        vec![
+            (
+                copy_source_path.to_owned(),
+                copy_target_path.to_owned(),
+                Some(CopyOperation::Copy),
+                true,
+                true
+            ),
+            (
+                rename_source_path.to_owned(),
+                rename_target_path.to_owned(),
+                Some(CopyOperation::Rename),
+                true,
+                true
+            ),
+        ]
+    );
+    // The removal of the source is reported if the target isn't matched
+    assert_eq!(
+        diff_with_copies(&FilesMatcher::new([rename_source_path])),
+        vec![(
+            rename_source_path.to_owned(),
+            rename_source_path.to_owned(),
+            None,
+            true,
+            false
+        )]
+    );
+}
+
 /// Diff two conflicted trees
 #[test]
 fn test_diff_conflicted() {
@@ -1473,3 +1572,84 @@ fn test_merge_simplify_file_conflict_with_absent() {
     let merged = child1_merged.merge(&parent_merged, &child2_merged).unwrap();
     assert_eq!(merged, expected_merged);
 }
+
+#[test]
+fn test_merge_with_merge_drivers() {
+    let settings = UserSettings::from_config(
+        testutils::base_config()
+            .add_source(config::File::from_str(
+                r#"
+                [merge-drivers.ours]
+                files = 'glob:"*.lock"'
+                builtin = "ours"
+                [merge-drivers.theirs]
+                files = 'glob:"dir/*.lock"'
+                builtin = "theirs"
+                [merge-drivers.union]
+                files = '"list"'
+                builtin = "union"
+                "#,
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .unwrap(),
+    );
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let repo = &test_repo.repo;
+
+    let ours_path = RepoPath::from_internal_string("a.lock");
+    let theirs_path = RepoPath::from_internal_string("dir/b.lock");
+    let union_path = RepoPath::from_internal_string("list");
+    let other_path = RepoPath::from_internal_string("other");
+    let base = create_single_tree(
+        repo,
+        &[
+            (ours_path, "base\n"),
+            (theirs_path, "base\n"),
+            (union_path, "a\nbase\nc\n"),
+            (other_path, "base\n"),
+        ],
+    );
+    let side1 = create_single_tree(
+        repo,
+        &[
+            (ours_path, "side1\n"),
+            (theirs_path, "side1\n"),
+            (union_path, "a\nside1\nc\n"),
+            (other_path, "side1\n"),
+        ],
+    );
+    let side2 = create_single_tree(
+        repo,
+        &[
+            (ours_path, "side2\n"),
+            (theirs_path, "side2\n"),
+            (union_path, "a\nside2\nc\n"),
+            (other_path, "side2\n"),
+        ],
+    );
+    let base_merged = MergedTree::resolved(base);
+    let side1_merged = MergedTree::resolved(side1);
+    let side2_merged = MergedTree::resolved(side2);
+
+    // The drivers aren't used unless requested
+    let merged = side1_merged.merge(&base_merged, &side2_merged).unwrap();
+    assert!(!merged.path_value(ours_path).unwrap().is_resolved());
+
+    let merged = side1_merged
+        .merge_with_drivers(&base_merged, &side2_merged, repo.store().merge_drivers())
+        .unwrap();
+    let read_resolved = |path: &RepoPath| {
+        let value = merged.path_value(path).unwrap();
+        let Some(Some(TreeValue::File { id, .. })) = value.as_resolved() else {
+            panic!("unexpected value at {path:?}: {value:?}");
+        };
+        testutils::read_file(repo.store(), path, id)
+    };
+    // The "ours" pattern doesn't match dir/b.lock since "*" doesn't match "/"
+    assert_eq!(read_resolved(ours_path), b"side1\n");
+    assert_eq!(read_resolved(theirs_path), b"side2\n");
+    assert_eq!(read_resolved(union_path), b"a\nside1\nside2\nc\n");
+    // Files not matched by any driver are merged line by line as usual
+    assert!(!merged.path_value(other_path).unwrap().is_resolved());
+}
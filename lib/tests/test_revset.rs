@@ -32,6 +32,8 @@ use jj_lib::revset::{
     RevsetParseContext, RevsetResolutionError, RevsetWorkspaceContext, SymbolResolverExtension,
 };
 use jj_lib::settings::GitSettings;
+use jj_lib::signing::{SignBehavior, Signer};
+use jj_lib::test_signing_backend::TestSigningBackend;
 use jj_lib::workspace::Workspace;
 use test_case::test_case;
 use testutils::{
@@ -45,7 +47,13 @@ fn resolve_symbol_with_extensions(
     symbol: &str,
 ) -> Result<Vec<CommitId>, RevsetResolutionError> {
     let aliases_map = RevsetAliasesMap::default();
-    let context = RevsetParseContext::new(&aliases_map, String::new(), extensions, None);
+    let context = RevsetParseContext::new(
+        &aliases_map,
+        String::new(),
+        chrono::Local::now().into(),
+        extensions,
+        None,
+    );
     let expression = parse(symbol, &context).unwrap();
     assert_matches!(*expression, RevsetExpression::CommitRef(_));
     let symbol_resolver = DefaultSymbolResolver::new(repo, extensions.symbol_resolvers());
@@ -179,7 +187,13 @@ fn test_resolve_symbol_commit_id() {
     );
     let aliases_map = RevsetAliasesMap::default();
     let extensions = RevsetExtensions::default();
-    let context = RevsetParseContext::new(&aliases_map, settings.user_email(), &extensions, None);
+    let context = RevsetParseContext::new(
+        &aliases_map,
+        settings.user_email(),
+        chrono::Local::now().into(),
+        &extensions,
+        None,
+    );
     assert_matches!(
         optimize(parse("present(04)", &context).unwrap()).resolve_user_expression(repo.as_ref(), &symbol_resolver),
         Err(RevsetResolutionError::AmbiguousCommitIdPrefix(s)) if s == "04"
@@ -837,6 +851,7 @@ fn resolve_commit_ids(repo: &dyn Repo, revset_str: &str) -> Vec<CommitId> {
     let context = RevsetParseContext::new(
         &aliases_map,
         settings.user_email(),
+        chrono::Local::now().into(),
         &revset_extensions,
         None,
     );
@@ -868,6 +883,7 @@ fn resolve_commit_ids_in_workspace(
     let context = RevsetParseContext::new(
         &aliases_map,
         settings.user_email(),
+        chrono::Local::now().into(),
         &extensions,
         Some(workspace_ctx),
     );
@@ -2340,6 +2356,59 @@ fn test_evaluate_expression_description() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_trailer() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.mut_repo();
+
+    let commit1 = create_random_commit(mut_repo, &settings)
+        .set_description("commit 1\n\nChange-Id: I1111\nSigned-off-by: Alice\n")
+        .write()
+        .unwrap();
+    let commit2 = create_random_commit(mut_repo, &settings)
+        .set_parents(vec![commit1.id().clone()])
+        .set_description("commit 2\n\nsigned-off-by: Bob\n")
+        .write()
+        .unwrap();
+    let commit3 = create_random_commit(mut_repo, &settings)
+        .set_parents(vec![commit2.id().clone()])
+        .set_description("commit 3\n\nSigned-off-by: Carol\n\nnot a trailer paragraph\n")
+        .write()
+        .unwrap();
+
+    // Keys are matched case-insensitively
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "trailer(Signed-off-by)"),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "trailer(change-id)"),
+        vec![commit1.id().clone()]
+    );
+    // Values are matched by the pattern
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "trailer(Signed-off-by, Bob)"),
+        vec![commit2.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "trailer(Signed-off-by, exact:Al)"),
+        vec![]
+    );
+    // Only the trailers in the last paragraph are considered
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "trailer(Signed-off-by, Carol)"),
+        vec![]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "description(Carol)"),
+        vec![commit3.id().clone()]
+    );
+}
+
 #[test]
 fn test_evaluate_expression_author() {
     let settings = testutils::user_settings();
@@ -2546,6 +2615,165 @@ fn test_evaluate_expression_committer() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_author_date() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.mut_repo();
+
+    let timestamp1 = Timestamp::from_datetime(
+        chrono::DateTime::parse_from_rfc3339("2023-03-25T11:30:00Z").unwrap(),
+    );
+    let timestamp2 = Timestamp::from_datetime(
+        chrono::DateTime::parse_from_rfc3339("2023-03-25T12:30:00Z").unwrap(),
+    );
+    let timestamp3 = Timestamp::from_datetime(
+        chrono::DateTime::parse_from_rfc3339("2023-03-25T13:30:00Z").unwrap(),
+    );
+
+    let root_commit = repo.store().root_commit();
+    let commit1 = create_random_commit(mut_repo, &settings)
+        .set_author(Signature {
+            name: "name1".to_string(),
+            email: "email1".to_string(),
+            timestamp: timestamp1.clone(),
+        })
+        .set_committer(Signature {
+            name: "name1".to_string(),
+            email: "email1".to_string(),
+            timestamp: timestamp2.clone(),
+        })
+        .write()
+        .unwrap();
+    let commit2 = create_random_commit(mut_repo, &settings)
+        .set_parents(vec![commit1.id().clone()])
+        .set_author(Signature {
+            name: "name2".to_string(),
+            email: "email2".to_string(),
+            timestamp: timestamp2.clone(),
+        })
+        .set_committer(Signature {
+            name: "name1".to_string(),
+            email: "email1".to_string(),
+            timestamp: timestamp2.clone(),
+        })
+        .write()
+        .unwrap();
+    let commit3 = create_random_commit(mut_repo, &settings)
+        .set_parents(vec![commit2.id().clone()])
+        .set_author(Signature {
+            name: "name3".to_string(),
+            email: "email3".to_string(),
+            timestamp: timestamp3,
+        })
+        .set_committer(Signature {
+            name: "name1".to_string(),
+            email: "email1".to_string(),
+            timestamp: timestamp2.clone(),
+        })
+        .write()
+        .unwrap();
+
+    // Can find multiple matches
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "author_date(after:'2023-03-25T12:00:00Z')"),
+        vec![commit3.id().clone(), commit2.id().clone()]
+    );
+    // The lower bound is inclusive and the upper bound is exclusive
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "author_date(after:'2023-03-25T12:30:00Z')"),
+        vec![commit3.id().clone(), commit2.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "author_date(before:'2023-03-25T12:30:00Z')"),
+        vec![commit1.id().clone(), root_commit.id().clone()]
+    );
+    // Can be combined to match a range
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            "author_date(after:'2023-03-25T12:00:00Z') & author_date(before:'2023-03-25T13:00:00Z')"
+        ),
+        vec![commit2.id().clone()]
+    );
+}
+
+#[test]
+fn test_evaluate_expression_committer_date() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.mut_repo();
+
+    let timestamp1 = Timestamp::from_datetime(
+        chrono::DateTime::parse_from_rfc3339("2023-03-25T11:30:00Z").unwrap(),
+    );
+    let timestamp2 = Timestamp::from_datetime(
+        chrono::DateTime::parse_from_rfc3339("2023-03-25T12:30:00Z").unwrap(),
+    );
+    let timestamp3 = Timestamp::from_datetime(
+        chrono::DateTime::parse_from_rfc3339("2023-03-25T13:30:00Z").unwrap(),
+    );
+
+    let root_commit = repo.store().root_commit();
+    let commit1 = create_random_commit(mut_repo, &settings)
+        .set_author(Signature {
+            name: "name1".to_string(),
+            email: "email1".to_string(),
+            timestamp: timestamp2.clone(),
+        })
+        .set_committer(Signature {
+            name: "name1".to_string(),
+            email: "email1".to_string(),
+            timestamp: timestamp1.clone(),
+        })
+        .write()
+        .unwrap();
+    let commit2 = create_random_commit(mut_repo, &settings)
+        .set_parents(vec![commit1.id().clone()])
+        .set_author(Signature {
+            name: "name1".to_string(),
+            email: "email1".to_string(),
+            timestamp: timestamp2.clone(),
+        })
+        .set_committer(Signature {
+            name: "name2".to_string(),
+            email: "email2".to_string(),
+            timestamp: timestamp2.clone(),
+        })
+        .write()
+        .unwrap();
+    let commit3 = create_random_commit(mut_repo, &settings)
+        .set_parents(vec![commit2.id().clone()])
+        .set_author(Signature {
+            name: "name1".to_string(),
+            email: "email1".to_string(),
+            timestamp: timestamp2.clone(),
+        })
+        .set_committer(Signature {
+            name: "name3".to_string(),
+            email: "email3".to_string(),
+            timestamp: timestamp3,
+        })
+        .write()
+        .unwrap();
+
+    // Can find multiple matches
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "committer_date(after:'2023-03-25T12:00:00Z')"),
+        vec![commit3.id().clone(), commit2.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "committer_date(before:'2023-03-25T12:00:00Z')"),
+        vec![commit1.id().clone(), root_commit.id().clone()]
+    );
+}
+
 #[test]
 fn test_evaluate_expression_union() {
     let settings = testutils::user_settings();
@@ -2913,6 +3141,148 @@ fn test_evaluate_expression_file() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_diff_contains() {
+    let settings = testutils::user_settings();
+    let test_workspace = TestWorkspace::init(&settings);
+    let repo = &test_workspace.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.mut_repo();
+
+    let empty_clean_inserted_deleted =
+        RepoPath::from_internal_string("empty_clean_inserted_deleted");
+    let blank_clean_inserted_clean = RepoPath::from_internal_string("blank_clean_inserted_clean");
+    let noeol_modified_modified_clean =
+        RepoPath::from_internal_string("noeol_modified_modified_clean");
+    let tree1 = create_tree(
+        repo,
+        &[
+            (empty_clean_inserted_deleted, ""),
+            (blank_clean_inserted_clean, "\n"),
+            (noeol_modified_modified_clean, "1"),
+        ],
+    );
+    let tree2 = create_tree(
+        repo,
+        &[
+            (empty_clean_inserted_deleted, ""),
+            (blank_clean_inserted_clean, "\n"),
+            (noeol_modified_modified_clean, "2"),
+        ],
+    );
+    let tree3 = create_tree(
+        repo,
+        &[
+            (empty_clean_inserted_deleted, "3"),
+            (blank_clean_inserted_clean, "\n3\n"),
+            (noeol_modified_modified_clean, "2 3"),
+        ],
+    );
+    let tree4 = create_tree(
+        repo,
+        &[
+            (empty_clean_inserted_deleted, ""),
+            (blank_clean_inserted_clean, "\n3\n"),
+            (noeol_modified_modified_clean, "2 3"),
+        ],
+    );
+    let commit1 = mut_repo
+        .new_commit(
+            &settings,
+            vec![repo.store().root_commit_id().clone()],
+            tree1.id(),
+        )
+        .write()
+        .unwrap();
+    let commit2 = mut_repo
+        .new_commit(&settings, vec![commit1.id().clone()], tree2.id())
+        .write()
+        .unwrap();
+    let commit3 = mut_repo
+        .new_commit(&settings, vec![commit2.id().clone()], tree3.id())
+        .write()
+        .unwrap();
+    let commit4 = mut_repo
+        .new_commit(&settings, vec![commit3.id().clone()], tree4.id())
+        .write()
+        .unwrap();
+
+    let query = |revset_str: &str| {
+        resolve_commit_ids_in_workspace(
+            mut_repo,
+            revset_str,
+            &test_workspace.workspace,
+            Some(test_workspace.workspace.workspace_root()),
+        )
+    };
+
+    // should match both inserted and deleted lines
+    assert_eq!(
+        query("diff_contains('2')"),
+        vec![commit3.id().clone(), commit2.id().clone()]
+    );
+    assert_eq!(
+        query("diff_contains('3')"),
+        vec![commit4.id().clone(), commit3.id().clone()]
+    );
+    assert_eq!(query("diff_contains('2 3')"), vec![commit3.id().clone()]);
+    assert_eq!(
+        query("diff_contains('1')"),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+    // an empty pattern matches any modified line, but not unmodified ones
+    assert_eq!(
+        query("diff_contains('')"),
+        vec![
+            commit4.id().clone(),
+            commit3.id().clone(),
+            commit2.id().clone(),
+            commit1.id().clone()
+        ]
+    );
+
+    // should match line with eol
+    assert_eq!(
+        query(&format!(
+            "diff_contains(exact:'3', {})",
+            blank_clean_inserted_clean.as_internal_file_string()
+        )),
+        vec![commit3.id().clone()]
+    );
+
+    // should match line without eol
+    assert_eq!(
+        query(&format!(
+            "diff_contains(exact:'1', {})",
+            noeol_modified_modified_clean.as_internal_file_string()
+        )),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+
+    // exact:'' should match blank line
+    assert_eq!(
+        query(&format!(
+            "diff_contains(exact:'', {})",
+            empty_clean_inserted_deleted.as_internal_file_string()
+        )),
+        vec![]
+    );
+    assert_eq!(
+        query(&format!(
+            "diff_contains(exact:'', {})",
+            blank_clean_inserted_clean.as_internal_file_string()
+        )),
+        vec![commit1.id().clone()]
+    );
+
+    // should be combined with other filters
+    assert_eq!(
+        query(&format!("{}:: & diff_contains('3')", commit4.id().hex())),
+        vec![commit4.id().clone()]
+    );
+}
+
 #[test]
 fn test_evaluate_expression_conflict() {
     let settings = testutils::user_settings();
@@ -2948,6 +3318,62 @@ fn test_evaluate_expression_conflict() {
     );
 }
 
+#[test_case(TestRepoBackend::Local ; "local backend")]
+#[test_case(TestRepoBackend::Git ; "git backend")]
+fn test_evaluate_expression_signed(backend: TestRepoBackend) {
+    let settings = testutils::user_settings();
+    let signer = Signer::new(Some(Box::new(TestSigningBackend)), vec![]);
+    let test_workspace = TestWorkspace::init_with_backend_and_signer(&settings, backend, signer);
+    let repo = &test_workspace.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.mut_repo();
+    let commit1 = create_random_commit(mut_repo, &settings)
+        .set_sign_behavior(SignBehavior::Force)
+        .write()
+        .unwrap();
+    let commit2 = write_random_commit(mut_repo, &settings);
+    // A signature which doesn't match the signed data
+    let mut bad_data = commit1.store_commit().clone();
+    bad_data.description = "tampered".to_owned();
+    bad_data.secure_sig = None;
+    let mut sign_with = |_: &[u8]| Ok(b"--- JJ-TEST-SIGNATURE ---\nKEY: \nbogus".to_vec());
+    let commit3 = repo
+        .store()
+        .write_commit(bad_data, Some(&mut sign_with))
+        .unwrap();
+    mut_repo.add_head(&commit3).unwrap();
+
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signed()"),
+        vec![commit3.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!(
+                "~signed() & ({} | {} | {})",
+                commit1.id().hex(),
+                commit2.id().hex(),
+                commit3.id().hex()
+            )
+        ),
+        vec![commit2.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signature_status(good)"),
+        vec![commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signature_status(bad)"),
+        vec![commit3.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signature_status(unknown)"),
+        vec![]
+    );
+}
+
 #[test]
 fn test_reverse_graph_iterator() {
     let settings = testutils::user_settings();